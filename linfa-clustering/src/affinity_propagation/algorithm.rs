@@ -0,0 +1,407 @@
+use crate::affinity_propagation::hyperparameters::AffinityPropagationHyperParams;
+use linfa_kernel::Kernel;
+use ndarray::{Array1, Array2, ArrayBase, Data, Ix2};
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Affinity Propagation clusters points by choosing a subset of them as
+/// exemplars, every other point is assigned to its most similar exemplar.
+/// The exemplars are found by passing messages between all pairs of points,
+/// the number of clusters is not specified but controlled by the `preference`.
+///
+/// The algorithm only needs the pairwise similarities of the points, it can be
+/// trained on feature vectors (using the negative squared euclidean distance),
+/// a precomputed similarity matrix or a [kernel](../linfa_kernel/struct.Kernel.html).
+///
+/// ## The algorithm
+///
+/// Two kinds of messages are exchanged until the exemplars no longer change:
+/// - The responsibility `r(i, k)` sent from point `i` to candidate exemplar `k`
+///   reflects how well suited `k` is as exemplar for `i`, compared to all other
+///   candidates
+/// - The availability `a(i, k)` sent from candidate `k` to point `i` reflects
+///   how appropriate it is for `i` to choose `k`, given the support `k`
+///   receives from other points
+///
+/// Points with `r(k, k) + a(k, k) > 0` are exemplars. More details can be found in
+/// [Frey and Dueck (2007)](https://doi.org/10.1126/science.1136800).
+///
+/// Each iteration is `O(N^2)` in time and memory.
+///
+/// ## Tutorial
+///
+/// ```
+/// use linfa_clustering::{AffinityPropagationHyperParams, AffinityPropagation, generate_blobs};
+/// use ndarray::array;
+/// use ndarray_rand::rand::SeedableRng;
+/// use rand_isaac::Isaac64Rng;
+///
+/// let mut rng = Isaac64Rng::seed_from_u64(42);
+/// let expected_centroids = array![[0., 1.], [-10., 20.], [-1., 10.]];
+/// let observations = generate_blobs(20, &expected_centroids, &mut rng);
+///
+/// // A smaller preference results in fewer exemplars
+/// let hyperparams = AffinityPropagationHyperParams::new()
+///     .preference(-50.)
+///     .damping(0.7)
+///     .build();
+/// let model = AffinityPropagation::fit(hyperparams, &observations);
+///
+/// // The indices of the observations chosen as exemplars
+/// let exemplars = model.exemplars();
+/// // `None` only if the algorithm found no exemplars at all
+/// let labels = model.labels();
+/// ```
+pub struct AffinityPropagation {
+    hyperparameters: AffinityPropagationHyperParams,
+    exemplars: Vec<usize>,
+    labels: Array1<Option<usize>>,
+    n_iterations: u64,
+    converged: bool,
+}
+
+impl AffinityPropagation {
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `fit` finds exemplars using the negative squared euclidean distance as similarity.
+    pub fn fit(
+        hyperparameters: AffinityPropagationHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Self {
+        let n_observations = observations.nrows();
+        let similarity = Array2::from_shape_fn((n_observations, n_observations), |(i, j)| {
+            -observations
+                .row(i)
+                .sq_l2_dist(&observations.row(j))
+                .unwrap()
+        });
+
+        Self::propagate(hyperparameters, similarity)
+    }
+
+    /// Find exemplars given the square matrix of pairwise similarities with shape
+    /// `(n_observations, n_observations)`, larger values mean more similar points.
+    ///
+    /// The similarities do not have to be symmetric, the diagonal is replaced by the
+    /// preference.
+    ///
+    /// **Panics** if the similarity matrix is not square.
+    pub fn fit_similarity(
+        hyperparameters: AffinityPropagationHyperParams,
+        similarity: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Self {
+        assert_eq!(
+            similarity.nrows(),
+            similarity.ncols(),
+            "the similarity matrix must be square"
+        );
+
+        Self::propagate(hyperparameters, similarity.to_owned())
+    }
+
+    /// Find exemplars using the values of a kernel matrix as similarities, for example a
+    /// gaussian kernel.
+    pub fn fit_kernel<D: Data<Elem = f64>>(
+        hyperparameters: AffinityPropagationHyperParams,
+        kernel: &Kernel<'_, f64, D>,
+    ) -> Self {
+        let n_observations = kernel.size();
+        let mut similarity = Array2::zeros((n_observations, n_observations));
+        for (j, mut column) in similarity.gencolumns_mut().into_iter().enumerate() {
+            column.assign(&Array1::from(kernel.column(j)));
+        }
+
+        Self::propagate(hyperparameters, similarity)
+    }
+
+    /// Return the indices of the observations chosen as exemplars, in increasing order.
+    pub fn exemplars(&self) -> &[usize] {
+        &self.exemplars
+    }
+
+    /// Return, for each observation, the index of its exemplar in
+    /// [`exemplars`](#method.exemplars). All labels are `None` if no exemplars were found.
+    pub fn labels(&self) -> &Array1<Option<usize>> {
+        &self.labels
+    }
+
+    /// Return the number of message passing iterations performed.
+    pub fn n_iterations(&self) -> u64 {
+        self.n_iterations
+    }
+
+    /// Return whether the exemplars were stable for `convergence_iterations` iterations before
+    /// `max_n_iterations` was reached.
+    pub fn converged(&self) -> bool {
+        self.converged
+    }
+
+    /// Return the hyperparameters used to train this Affinity Propagation model instance.
+    pub fn hyperparameters(&self) -> &AffinityPropagationHyperParams {
+        &self.hyperparameters
+    }
+
+    fn propagate(
+        hyperparameters: AffinityPropagationHyperParams,
+        mut similarity: Array2<f64>,
+    ) -> Self {
+        let n = similarity.nrows();
+        let preference = hyperparameters
+            .preference()
+            .unwrap_or_else(|| median_off_diagonal(&similarity));
+        similarity.diag_mut().fill(preference);
+
+        let damping = hyperparameters.damping();
+        let mut responsibility = Array2::<f64>::zeros((n, n));
+        let mut availability = Array2::<f64>::zeros((n, n));
+
+        let mut exemplars = Vec::new();
+        let mut n_stable = 0;
+        let mut n_iterations = 0;
+        let mut converged = false;
+        while n_iterations < hyperparameters.max_n_iterations() {
+            n_iterations += 1;
+
+            // r(i, k) = s(i, k) - max_{k' != k} (a(i, k') + s(i, k'))
+            for i in 0..n {
+                let (mut first, mut second, mut first_index) =
+                    (f64::NEG_INFINITY, f64::NEG_INFINITY, 0);
+                for k in 0..n {
+                    let value = availability[(i, k)] + similarity[(i, k)];
+                    if value > first {
+                        second = first;
+                        first = value;
+                        first_index = k;
+                    } else if value > second {
+                        second = value;
+                    }
+                }
+                for k in 0..n {
+                    let competitor = if k == first_index { second } else { first };
+                    let update = similarity[(i, k)] - competitor;
+                    responsibility[(i, k)] =
+                        damping * responsibility[(i, k)] + (1. - damping) * update;
+                }
+            }
+
+            // a(i, k) = min(0, r(k, k) + sum_{i' not in {i, k}} max(0, r(i', k)))
+            // a(k, k) = sum_{i' != k} max(0, r(i', k))
+            for k in 0..n {
+                let support = (0..n)
+                    .filter(|i| *i != k)
+                    .map(|i| responsibility[(i, k)].max(0.))
+                    .sum::<f64>();
+                for i in 0..n {
+                    let update = if i == k {
+                        support
+                    } else {
+                        (responsibility[(k, k)] + support - responsibility[(i, k)].max(0.)).min(0.)
+                    };
+                    availability[(i, k)] = damping * availability[(i, k)] + (1. - damping) * update;
+                }
+            }
+
+            let current = (0..n)
+                .filter(|k| responsibility[(*k, *k)] + availability[(*k, *k)] > 0.)
+                .collect::<Vec<_>>();
+            if !current.is_empty() && current == exemplars {
+                n_stable += 1;
+            } else {
+                n_stable = 0;
+            }
+            exemplars = current;
+
+            if n_stable >= hyperparameters.convergence_iterations() {
+                converged = true;
+                break;
+            }
+        }
+
+        let labels = if exemplars.is_empty() {
+            Array1::from(vec![None; n])
+        } else {
+            // within each cluster choose the member most similar to all other members
+            let labels = assign_to_exemplars(&similarity, &exemplars);
+            for (cluster, exemplar) in exemplars.iter_mut().enumerate() {
+                let members = (0..n).filter(|i| labels[*i] == cluster).collect::<Vec<_>>();
+                let total_similarity = |candidate: usize| {
+                    members
+                        .iter()
+                        .filter(|i| **i != candidate)
+                        .map(|i| similarity[(*i, candidate)])
+                        .sum::<f64>()
+                };
+                *exemplar = members
+                    .iter()
+                    .copied()
+                    .max_by(|a, b| {
+                        total_similarity(*a)
+                            .partial_cmp(&total_similarity(*b))
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap();
+            }
+            exemplars.sort_unstable();
+
+            assign_to_exemplars(&similarity, &exemplars).mapv(Some)
+        };
+
+        AffinityPropagation {
+            hyperparameters,
+            exemplars,
+            labels,
+            n_iterations,
+            converged,
+        }
+    }
+}
+
+/// Assign each point to its most similar exemplar, exemplars are assigned to themselves
+fn assign_to_exemplars(similarity: &Array2<f64>, exemplars: &[usize]) -> Array1<usize> {
+    (0..similarity.nrows())
+        .map(
+            |i| match exemplars.iter().position(|exemplar| *exemplar == i) {
+                Some(cluster) => cluster,
+                None => exemplars
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| {
+                        similarity[(i, **a)]
+                            .partial_cmp(&similarity[(i, **b)])
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .map(|(cluster, _)| cluster)
+                    .unwrap(),
+            },
+        )
+        .collect()
+}
+
+/// The median of the similarities between different points, 0 for a single point
+fn median_off_diagonal(similarity: &Array2<f64>) -> f64 {
+    let mut values = similarity
+        .indexed_iter()
+        .filter(|((i, j), _)| i != j)
+        .map(|(_, value)| *value)
+        .collect::<Vec<_>>();
+    if values.is_empty() {
+        return 0.;
+    }
+    values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.
+    } else {
+        values[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_blobs, AffinityPropagationHyperParams};
+    use ndarray::{array, Array2};
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    /// Three groups of four points around (0, 0), (5, 0) and (0, 5)
+    fn groups() -> Array2<f64> {
+        array![
+            [-0.16, 0.03],
+            [-0.08, 0.06],
+            [0.08, -0.26],
+            [-0.29, 0.2],
+            [4.86, -0.16],
+            [5.3, -0.02],
+            [5.2, -0.01],
+            [5.08, -0.21],
+            [0.08, 5.22],
+            [0.01, 5.14],
+            [0.1, 4.74],
+            [0.15, 5.05]
+        ]
+    }
+
+    fn check_groups(model: &AffinityPropagation) {
+        assert!(model.converged());
+        assert_eq!(model.exemplars().len(), 3);
+
+        // one exemplar per group and all members of a group share it
+        for (group, exemplar) in model.exemplars().iter().enumerate() {
+            assert_eq!(exemplar / 4, group);
+            for i in group * 4..(group + 1) * 4 {
+                assert_eq!(model.labels()[i], Some(group));
+            }
+        }
+    }
+
+    #[test]
+    fn finds_exemplar_per_group() {
+        let params = AffinityPropagationHyperParams::new().build();
+        let model = AffinityPropagation::fit(params, &groups());
+        check_groups(&model);
+    }
+
+    #[test]
+    fn number_of_blobs() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [10., 10.], [-10., 10.]];
+        let observations = generate_blobs(20, &centroids, &mut rng);
+
+        // a strong damping avoids oscillations between equally good exemplars
+        let params = AffinityPropagationHyperParams::new().damping(0.9).build();
+        let model = AffinityPropagation::fit(params, &observations);
+
+        assert!(model.converged());
+        assert!(model.n_iterations() < 200);
+        assert_eq!(model.exemplars().len(), 3);
+        for (blob, exemplar) in model.exemplars().iter().enumerate() {
+            assert_eq!(exemplar / 20, blob);
+        }
+        for (i, label) in model.labels().iter().enumerate() {
+            assert_eq!(*label, Some(i / 20));
+        }
+    }
+
+    #[test]
+    fn lower_preference_fewer_clusters() {
+        let observations = groups();
+
+        let n_clusters = [-10., -100., -1000.]
+            .iter()
+            .map(|preference| {
+                let params = AffinityPropagationHyperParams::new()
+                    .preference(*preference)
+                    .build();
+                AffinityPropagation::fit(params, &observations)
+                    .exemplars()
+                    .len()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(n_clusters, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn kernel_and_precomputed_similarity() {
+        let observations = groups();
+        let kernel = Kernel::gaussian(&observations, 1.);
+
+        let params = AffinityPropagationHyperParams::new().build();
+        let model = AffinityPropagation::fit_kernel(params.clone(), &kernel);
+        check_groups(&model);
+
+        let similarity = Array2::from_shape_fn((12, 12), |(i, j)| kernel.column(j)[i]);
+        let precomputed = AffinityPropagation::fit_similarity(params, &similarity);
+        assert_eq!(model.exemplars(), precomputed.exemplars());
+        assert_eq!(model.labels(), precomputed.labels());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_damping() {
+        AffinityPropagationHyperParams::new().damping(1.).build();
+    }
+}
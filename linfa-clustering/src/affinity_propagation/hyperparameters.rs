@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The set of hyperparameters that can be specified for the execution of
+/// the [Affinity Propagation algorithm](struct.AffinityPropagation.html).
+pub struct AffinityPropagationHyperParams {
+    /// How suitable each point is as exemplar, the median similarity if not
+    /// specified.
+    preference: Option<f64>,
+    /// Fraction of the previous messages kept in each update.
+    damping: f64,
+    /// We exit the message passing when the number of iterations exceeds
+    /// `max_n_iterations` even if the exemplars have not converged.
+    max_n_iterations: u64,
+    /// The exemplars are considered converged if they did not change for
+    /// `convergence_iterations` iterations.
+    convergence_iterations: u64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters](struct.AffinityPropagationHyperParams.html) for
+/// the [Affinity Propagation algorithm](struct.AffinityPropagation.html) (using the builder pattern).
+pub struct AffinityPropagationHyperParamsBuilder {
+    preference: Option<f64>,
+    damping: f64,
+    max_n_iterations: u64,
+    convergence_iterations: u64,
+}
+
+impl AffinityPropagationHyperParamsBuilder {
+    /// Set the value of `preference`.
+    ///
+    /// The preference replaces the self-similarity of each point and controls how
+    /// likely points are to become exemplars: smaller values result in fewer clusters.
+    /// By default the median of the similarities between different points is used.
+    pub fn preference(mut self, preference: f64) -> Self {
+        self.preference = Some(preference);
+        self
+    }
+
+    /// Set the value of `damping`, in the interval `[0.5, 1)`.
+    ///
+    /// Each update keeps a fraction `damping` of the previous messages, which avoids
+    /// oscillations.
+    pub fn damping(mut self, damping: f64) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// We exit the message passing when the number of iterations exceeds
+    /// `max_n_iterations` even if the exemplars have not converged.
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Set the value of `convergence_iterations`.
+    ///
+    /// The exemplars are considered converged if they did not change for
+    /// `convergence_iterations` iterations.
+    pub fn convergence_iterations(mut self, convergence_iterations: u64) -> Self {
+        self.convergence_iterations = convergence_iterations;
+        self
+    }
+
+    /// Return an instance of `AffinityPropagationHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> AffinityPropagationHyperParams {
+        AffinityPropagationHyperParams::build(
+            self.preference,
+            self.damping,
+            self.max_n_iterations,
+            self.convergence_iterations,
+        )
+    }
+}
+
+impl AffinityPropagationHyperParams {
+    /// `new` lets us configure our training algorithm parameters, all of them are optional.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `preference` is the median similarity;
+    /// * `damping = 0.5`;
+    /// * `max_n_iterations = 200`;
+    /// * `convergence_iterations = 15`.
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> AffinityPropagationHyperParamsBuilder {
+        AffinityPropagationHyperParamsBuilder {
+            preference: None,
+            damping: 0.5,
+            max_n_iterations: 200,
+            convergence_iterations: 15,
+        }
+    }
+
+    /// How suitable each point is as exemplar, `None` if the median similarity is used.
+    pub fn preference(&self) -> Option<f64> {
+        self.preference
+    }
+
+    /// Fraction of the previous messages kept in each update.
+    pub fn damping(&self) -> f64 {
+        self.damping
+    }
+
+    /// We exit the message passing when the number of iterations exceeds
+    /// `max_n_iterations` even if the exemplars have not converged.
+    pub fn max_n_iterations(&self) -> u64 {
+        self.max_n_iterations
+    }
+
+    /// The exemplars are considered converged if they did not change for
+    /// `convergence_iterations` iterations.
+    pub fn convergence_iterations(&self) -> u64 {
+        self.convergence_iterations
+    }
+
+    fn build(
+        preference: Option<f64>,
+        damping: f64,
+        max_n_iterations: u64,
+        convergence_iterations: u64,
+    ) -> Self {
+        if !(0.5..1.).contains(&damping) {
+            panic!("`damping` must be in the interval [0.5, 1)!");
+        }
+        if max_n_iterations == 0 {
+            panic!("`max_n_iterations` cannot be 0!");
+        }
+        if convergence_iterations == 0 {
+            panic!("`convergence_iterations` cannot be 0!");
+        }
+        if let Some(preference) = preference {
+            if !preference.is_finite() {
+                panic!("`preference` must be finite!");
+            }
+        }
+        AffinityPropagationHyperParams {
+            preference,
+            damping,
+            max_n_iterations,
+            convergence_iterations,
+        }
+    }
+}
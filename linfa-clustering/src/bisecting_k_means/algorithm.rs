@@ -0,0 +1,406 @@
+use crate::bisecting_k_means::hyperparameters::{BisectStrategy, BisectingKMeansHyperParams};
+use crate::k_means::{KMeans, KMeansHyperParams};
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2};
+use ndarray_rand::rand::Rng;
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+
+/// How often a split is attempted before the cluster is considered indivisible
+const MAX_SPLIT_ATTEMPTS: usize = 10;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Bisecting K-means is a divisive hierarchical clustering algorithm: starting
+/// with all observations in a single cluster, it repeatedly splits one cluster
+/// in two with [K-means](struct.KMeans.html) until `n_clusters` clusters are found.
+///
+/// The splits form a binary tree, whose leaves are the clusters. The tree can be
+/// traversed to inspect the hierarchy without computing a full dendrogram, each
+/// split is as cheap as a K-means run on the observations of a single cluster.
+///
+/// ## The algorithm
+///
+/// - The root of the tree contains all observations
+/// - The leaf with the largest inertia (sum of squared distances to its centroid)
+///   or the most observations, depending on the
+///   [`BisectStrategy`](enum.BisectStrategy.html), is split with 2-means
+/// - Leaves which cannot be split, because all of their observations are identical,
+///   are skipped. If a split leaves one side empty, because both initial centroids
+///   coincide, it is retried with a different initialisation
+///
+/// The clusters are numbered in depth-first order of the tree, visiting the first
+/// child of each split before the second one.
+///
+/// ## Tutorial
+///
+/// ```
+/// use linfa_clustering::{generate_blobs, BisectStrategy};
+/// use linfa_clustering::{BisectingKMeans, BisectingKMeansHyperParams};
+/// use ndarray::array;
+/// use ndarray_rand::rand::SeedableRng;
+/// use rand_isaac::Isaac64Rng;
+///
+/// let mut rng = Isaac64Rng::seed_from_u64(42);
+/// let expected_centroids = array![[0., 1.], [-10., 20.], [-1., 10.]];
+/// let observations = generate_blobs(100, &expected_centroids, &mut rng);
+///
+/// let hyperparams = BisectingKMeansHyperParams::new(3)
+///     .bisecting_strategy(BisectStrategy::LargestCluster)
+///     .build();
+/// let model = BisectingKMeans::fit(hyperparams, &observations, &mut rng);
+///
+/// // The flat clustering
+/// let labels = model.labels();
+/// let new_labels = model.predict(&array![[-9., 20.5]]);
+///
+/// // The hierarchy, starting from the root which contains all observations
+/// let root = &model.nodes()[0];
+/// if let Some((first, second)) = root.children() {
+///     let first_child = &model.nodes()[first];
+/// }
+/// ```
+pub struct BisectingKMeans {
+    hyperparameters: BisectingKMeansHyperParams,
+    nodes: Vec<BisectingNode>,
+    centroids: Array2<f64>,
+    labels: Array1<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// A node of the binary tree built by [Bisecting K-means](struct.BisectingKMeans.html),
+/// a cluster at some level of the hierarchy.
+pub struct BisectingNode {
+    centroid: Array1<f64>,
+    n_observations: usize,
+    inertia: f64,
+    children: Option<(usize, usize)>,
+    cluster: Option<usize>,
+}
+
+impl BisectingNode {
+    fn new(observations: &ArrayBase<impl Data<Elem = f64>, Ix2>, members: &[usize]) -> Self {
+        let members = observations.select(Axis(0), members);
+        let centroid = members.mean_axis(Axis(0)).unwrap();
+        let inertia = members
+            .genrows()
+            .into_iter()
+            .map(|observation| observation.sq_l2_dist(&centroid).unwrap())
+            .sum();
+
+        BisectingNode {
+            centroid,
+            n_observations: members.nrows(),
+            inertia,
+            children: None,
+            cluster: None,
+        }
+    }
+
+    /// Return the mean of the observations in this node.
+    pub fn centroid(&self) -> &Array1<f64> {
+        &self.centroid
+    }
+
+    /// Return the number of training observations in this node.
+    pub fn n_observations(&self) -> usize {
+        self.n_observations
+    }
+
+    /// Return the sum of squared distances of the observations to the centroid.
+    pub fn inertia(&self) -> f64 {
+        self.inertia
+    }
+
+    /// Return the indices of the two children in
+    /// [`nodes`](struct.BisectingKMeans.html#method.nodes), `None` for leaves.
+    pub fn children(&self) -> Option<(usize, usize)> {
+        self.children
+    }
+
+    /// Return the cluster index of a leaf, `None` for inner nodes.
+    pub fn cluster(&self) -> Option<usize> {
+        self.cluster
+    }
+}
+
+impl BisectingKMeans {
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `fit` splits the observations into `n_clusters` clusters.
+    ///
+    /// Fewer clusters are returned if no cluster can be split anymore, because all of
+    /// their observations are identical.
+    ///
+    /// **Panics** if there are fewer observations than `n_clusters`.
+    pub fn fit(
+        hyperparameters: BisectingKMeansHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let n_observations = observations.nrows();
+        assert!(
+            hyperparameters.n_clusters() <= n_observations,
+            "`n_clusters` cannot be larger than the number of observations"
+        );
+
+        let split_params = KMeansHyperParams::new(2)
+            .tolerance(hyperparameters.tolerance())
+            .max_n_iterations(hyperparameters.max_n_iterations())
+            .build();
+
+        // the observations of each node, only kept for leaves
+        let mut members = vec![(0..n_observations).collect::<Vec<_>>()];
+        let mut nodes = vec![BisectingNode::new(observations, &members[0])];
+        let mut splittable = vec![nodes[0].inertia > 0.];
+        let mut n_leaves = 1;
+        while n_leaves < hyperparameters.n_clusters() {
+            let score = |node: &BisectingNode| match hyperparameters.bisecting_strategy() {
+                BisectStrategy::BiggestInertia => node.inertia,
+                BisectStrategy::LargestCluster => node.n_observations as f64,
+            };
+            // the first leaf with the highest score
+            let parent = (0..nodes.len())
+                .filter(|i| nodes[*i].children.is_none() && splittable[*i])
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(best) if score(&nodes[best]) >= score(&nodes[i]) => Some(best),
+                    _ => Some(i),
+                });
+            let parent = match parent {
+                Some(parent) => parent,
+                None => break,
+            };
+
+            let (first, second) = match bisect(observations, &members[parent], &split_params, rng) {
+                Some(split) => split,
+                None => {
+                    splittable[parent] = false;
+                    continue;
+                }
+            };
+
+            nodes[parent].children = Some((nodes.len(), nodes.len() + 1));
+            members[parent].clear();
+            for child in &[&first, &second] {
+                nodes.push(BisectingNode::new(observations, child));
+                splittable.push(nodes.last().unwrap().inertia > 0.);
+            }
+            members.push(first);
+            members.push(second);
+            n_leaves += 1;
+        }
+
+        // number the leaves in depth-first order
+        let mut labels = Array1::zeros(n_observations);
+        let mut leaves = Vec::with_capacity(n_leaves);
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            match nodes[node].children {
+                Some((first, second)) => {
+                    stack.push(second);
+                    stack.push(first);
+                }
+                None => {
+                    nodes[node].cluster = Some(leaves.len());
+                    for &i in &members[node] {
+                        labels[i] = leaves.len();
+                    }
+                    leaves.push(node);
+                }
+            }
+        }
+
+        let mut centroids = Array2::zeros((leaves.len(), observations.ncols()));
+        for (mut row, leaf) in centroids.genrows_mut().into_iter().zip(&leaves) {
+            row.assign(&nodes[*leaf].centroid);
+        }
+
+        Self {
+            hyperparameters,
+            nodes,
+            centroids,
+            labels,
+        }
+    }
+
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `predict` returns, for each observation, the index of its cluster.
+    ///
+    /// Each observation descends the tree from the root, moving to the child with the
+    /// closest centroid, until it reaches a leaf.
+    pub fn predict(&self, observations: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> Array1<usize> {
+        observations.map_axis(Axis(1), |observation| self.descend(&observation))
+    }
+
+    fn descend(&self, observation: &ArrayBase<impl Data<Elem = f64>, Ix1>) -> usize {
+        let mut node = &self.nodes[0];
+        while let Some((first, second)) = node.children {
+            let (first, second) = (&self.nodes[first], &self.nodes[second]);
+            node = if first.centroid.sq_l2_dist(observation).unwrap()
+                <= second.centroid.sq_l2_dist(observation).unwrap()
+            {
+                first
+            } else {
+                second
+            };
+        }
+        node.cluster.unwrap()
+    }
+
+    /// Return the nodes of the tree, the root containing all training observations is the
+    /// first node.
+    pub fn nodes(&self) -> &[BisectingNode] {
+        &self.nodes
+    }
+
+    /// Return the centroids of the clusters, the leaves of the tree, as a 2-dimensional
+    /// matrix with shape `(n_clusters, n_features)`.
+    pub fn centroids(&self) -> &Array2<f64> {
+        &self.centroids
+    }
+
+    /// Return the index of the cluster of each training observation.
+    pub fn labels(&self) -> &Array1<usize> {
+        &self.labels
+    }
+
+    /// Return the hyperparameters used to train this Bisecting K-means model instance.
+    pub fn hyperparameters(&self) -> &BisectingKMeansHyperParams {
+        &self.hyperparameters
+    }
+}
+
+/// Split the members in two with 2-means, `None` if one side stays empty in all attempts
+fn bisect(
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    members: &[usize],
+    params: &KMeansHyperParams,
+    rng: &mut impl Rng,
+) -> Option<(Vec<usize>, Vec<usize>)> {
+    let subset = observations.select(Axis(0), members);
+    for _ in 0..MAX_SPLIT_ATTEMPTS {
+        let model = KMeans::fit(params.clone(), &subset, rng);
+        let pred = model.predict(&subset);
+        let (first, second): (Vec<_>, Vec<_>) = members
+            .iter()
+            .zip(pred.iter())
+            .partition(|(_, cluster)| **cluster == 0);
+        if !first.is_empty() && !second.is_empty() {
+            return Some((
+                first.into_iter().map(|(i, _)| *i).collect(),
+                second.into_iter().map(|(i, _)| *i).collect(),
+            ));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_blobs;
+    use ndarray::{array, s, stack};
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn splits_hierarchy_of_blobs() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // the first split separates the distant blob from the two close ones
+        let centroids = array![[0., 0.], [0., 10.], [60., 0.]];
+        let observations = generate_blobs(100, &centroids, &mut rng);
+
+        let params = BisectingKMeansHyperParams::new(3).build();
+        let model = BisectingKMeans::fit(params, &observations, &mut rng);
+
+        let nodes = model.nodes();
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(nodes[0].n_observations(), 300);
+        let (first, second) = nodes[0].children().unwrap();
+        let (close, distant) = if nodes[first].n_observations() == 200 {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        assert_eq!(nodes[distant].n_observations(), 100);
+        assert!(nodes[distant].centroid()[0] > 50.);
+        assert!(nodes[close].children().is_some());
+
+        // every inner node is the union of its children
+        for node in nodes {
+            match node.children() {
+                Some((first, second)) => {
+                    assert!(node.cluster().is_none());
+                    assert_eq!(
+                        node.n_observations(),
+                        nodes[first].n_observations() + nodes[second].n_observations()
+                    );
+                }
+                None => assert!(node.cluster().is_some()),
+            }
+        }
+
+        // every blob is a cluster
+        let labels = model.labels();
+        for i in 0..3 {
+            let blob = labels.slice(s![i * 100..(i + 1) * 100]);
+            assert!(blob.iter().all(|x| *x == blob[0]));
+        }
+        let mut clusters = (0..3).map(|i| labels[i * 100]).collect::<Vec<_>>();
+        clusters.sort_unstable();
+        assert_eq!(clusters, vec![0, 1, 2]);
+
+        assert_eq!(&model.predict(&observations), labels);
+        assert_eq!(model.centroids().nrows(), 3);
+    }
+
+    #[test]
+    fn bisecting_strategies() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // a large dense blob and a small spread out blob far away
+        let dense = generate_blobs(200, &array![[0., 0.]], &mut rng) * 0.01;
+        let sparse = generate_blobs(20, &array![[100., 0.]], &mut rng);
+        let observations = stack(Axis(0), &[dense.view(), sparse.view()]).unwrap();
+
+        // the second split is on the dense blob with most observations, or on the sparse
+        // blob with the largest inertia
+        for (strategy, expected) in &[
+            (BisectStrategy::LargestCluster, 200),
+            (BisectStrategy::BiggestInertia, 20),
+        ] {
+            let params = BisectingKMeansHyperParams::new(3)
+                .bisecting_strategy(*strategy)
+                .build();
+            // both strategies start from the same seed
+            let mut rng = Isaac64Rng::seed_from_u64(42);
+            let model = BisectingKMeans::fit(params, &observations, &mut rng);
+
+            let (first, second) = model.nodes()[0].children().unwrap();
+            let split = match (
+                model.nodes()[first].children(),
+                model.nodes()[second].children(),
+            ) {
+                (Some(_), None) => first,
+                (None, Some(_)) => second,
+                _ => panic!("exactly one child of the root must be split"),
+            };
+            assert_eq!(model.nodes()[split].n_observations(), *expected);
+        }
+    }
+
+    #[test]
+    fn identical_observations_are_not_split() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let observations = array![[1., 1.], [1., 1.], [10., 10.]];
+
+        let params = BisectingKMeansHyperParams::new(3).build();
+        let model = BisectingKMeans::fit(params, &observations, &mut rng);
+
+        assert_eq!(model.centroids().nrows(), 2);
+        assert_eq!(model.labels()[0], model.labels()[1]);
+        assert_ne!(model.labels()[0], model.labels()[2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn n_clusters_cannot_be_zero() {
+        BisectingKMeansHyperParams::new(0).build();
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// How the next cluster to split is chosen
+pub enum BisectStrategy {
+    /// Split the cluster with the largest sum of squared distances to its centroid
+    BiggestInertia,
+    /// Split the cluster with the most observations
+    LargestCluster,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The set of hyperparameters that can be specified for the execution of
+/// the [Bisecting K-means algorithm](struct.BisectingKMeans.html).
+pub struct BisectingKMeansHyperParams {
+    /// The number of clusters we will be looking for in the training dataset.
+    n_clusters: usize,
+    /// How the next cluster to split is chosen.
+    bisecting_strategy: BisectStrategy,
+    /// The convergence tolerance of each 2-means split.
+    tolerance: f64,
+    /// The maximum number of iterations of each 2-means split.
+    max_n_iterations: u64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters](struct.BisectingKMeansHyperParams.html) for
+/// the [Bisecting K-means algorithm](struct.BisectingKMeans.html) (using the builder pattern).
+pub struct BisectingKMeansHyperParamsBuilder {
+    n_clusters: usize,
+    bisecting_strategy: BisectStrategy,
+    tolerance: f64,
+    max_n_iterations: u64,
+}
+
+impl BisectingKMeansHyperParamsBuilder {
+    /// Set the strategy used to choose the next cluster to split.
+    pub fn bisecting_strategy(mut self, bisecting_strategy: BisectStrategy) -> Self {
+        self.bisecting_strategy = bisecting_strategy;
+        self
+    }
+
+    /// Set the value of `tolerance`.
+    ///
+    /// Each split is a run of [K-means](struct.KMeans.html) with two clusters, which
+    /// is considered complete if the euclidean distance between the old and the new
+    /// centroids is lower or equal than `tolerance`.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// The maximum number of iterations of each 2-means split.
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Return an instance of `BisectingKMeansHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> BisectingKMeansHyperParams {
+        BisectingKMeansHyperParams::build(
+            self.n_clusters,
+            self.bisecting_strategy,
+            self.tolerance,
+            self.max_n_iterations,
+        )
+    }
+}
+
+impl BisectingKMeansHyperParams {
+    /// `new` lets us configure our training algorithm parameters:
+    /// * we will be looking for `n_clusters` in the training dataset;
+    /// * the `bisecting_strategy` decides which cluster is split next;
+    /// * `tolerance` and `max_n_iterations` control each 2-means split.
+    ///
+    /// `n_clusters` is mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `bisecting_strategy = BisectStrategy::BiggestInertia`;
+    /// * `tolerance = 1e-4`;
+    /// * `max_n_iterations = 300`.
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n_clusters: usize) -> BisectingKMeansHyperParamsBuilder {
+        BisectingKMeansHyperParamsBuilder {
+            n_clusters,
+            bisecting_strategy: BisectStrategy::BiggestInertia,
+            tolerance: 1e-4,
+            max_n_iterations: 300,
+        }
+    }
+
+    /// The number of clusters we will be looking for in the training dataset.
+    pub fn n_clusters(&self) -> usize {
+        self.n_clusters
+    }
+
+    /// How the next cluster to split is chosen.
+    pub fn bisecting_strategy(&self) -> BisectStrategy {
+        self.bisecting_strategy
+    }
+
+    /// The convergence tolerance of each 2-means split.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// The maximum number of iterations of each 2-means split.
+    pub fn max_n_iterations(&self) -> u64 {
+        self.max_n_iterations
+    }
+
+    fn build(
+        n_clusters: usize,
+        bisecting_strategy: BisectStrategy,
+        tolerance: f64,
+        max_n_iterations: u64,
+    ) -> Self {
+        if max_n_iterations == 0 {
+            panic!("`max_n_iterations` cannot be 0!");
+        }
+        if tolerance <= 0. {
+            panic!("`tolerance` must be greater than 0!");
+        }
+        if n_clusters == 0 {
+            panic!("`n_clusters` cannot be 0!");
+        }
+        BisectingKMeansHyperParams {
+            n_clusters,
+            bisecting_strategy,
+            tolerance,
+            max_n_iterations,
+        }
+    }
+}
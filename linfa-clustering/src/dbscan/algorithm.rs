@@ -1,5 +1,5 @@
-use crate::dbscan::hyperparameters::DbscanHyperParams;
-use ndarray::{Array1, ArrayBase, ArrayView, Axis, Data, Ix1, Ix2};
+use crate::dbscan::hyperparameters::{DbscanHyperParams, Metric};
+use ndarray::{Array1, ArrayBase, ArrayView1, Data, Ix1, Ix2};
 use ndarray_stats::DeviationExt;
 use serde::{Deserialize, Serialize};
 
@@ -69,67 +69,142 @@ use serde::{Deserialize, Serialize};
 pub struct Dbscan;
 
 impl Dbscan {
+    /// Cluster the observations and return the cluster of each observation, `None` for noise
+    ///
+    /// For the `Euclidean` metric the observations have shape `(n_features, n_observations)`,
+    /// for the `Precomputed` metric `observations` is the square matrix of pairwise distances
+    /// with shape `(n_observations, n_observations)`.
+    ///
+    /// **Panics** if a precomputed distance matrix is not square.
     pub fn predict(
         hyperparameters: &DbscanHyperParams,
         observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
     ) -> Array1<Option<usize>> {
-        let mut cluster_memberships = Array1::from_elem(observations.dim().1, None);
-        let mut current_cluster_id = 0;
-        for (i, obs) in observations.axis_iter(Axis(1)).enumerate() {
-            if cluster_memberships[i].is_some() {
-                continue;
-            }
-            let (neighbor_count, mut search_queue) = find_neighbors(
-                &obs,
-                observations,
-                hyperparameters.tolerance(),
-                &cluster_memberships,
-            );
-            if neighbor_count < hyperparameters.minimum_points() {
-                continue;
-            }
-            // Now go over the neighbours adding them to the cluster
-            cluster_memberships[i] = Some(current_cluster_id);
+        Dbscan::predict_with_weights(hyperparameters, observations, None)
+    }
+
+    /// Cluster weighted observations and return the cluster of each observation, `None` for
+    /// noise
+    ///
+    /// An observation is a core point if the summed weights of the observations in its
+    /// neighborhood (including itself) reach `min_points`. This is useful if each observation
+    /// represents a group of observations, an observation with weight 2 is then equivalent to a
+    /// duplicated observation with weight 1.
+    ///
+    /// **Panics** if the number of weights differs from the number of observations or any
+    /// weight is negative.
+    pub fn predict_weighted(
+        hyperparameters: &DbscanHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        weights: &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> Array1<Option<usize>> {
+        assert!(
+            weights.iter().all(|w| *w >= 0.),
+            "weights must be non-negative"
+        );
 
-            while !search_queue.is_empty() {
-                let candidate = search_queue.remove(0);
+        Dbscan::predict_with_weights(hyperparameters, observations, Some(weights.view()))
+    }
 
-                let (neighbor_count, mut neighbors) = find_neighbors(
-                    &candidate.1,
-                    observations,
-                    hyperparameters.tolerance(),
-                    &cluster_memberships,
+    fn predict_with_weights(
+        hyperparameters: &DbscanHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        weights: Option<ArrayView1<f64>>,
+    ) -> Array1<Option<usize>> {
+        match hyperparameters.metric() {
+            Metric::Euclidean => {
+                let distance = |i: usize, j: usize| {
+                    observations
+                        .column(i)
+                        .l2_dist(&observations.column(j))
+                        .unwrap()
+                };
+                expand_clusters(hyperparameters, observations.ncols(), distance, weights)
+            }
+            Metric::Precomputed => {
+                assert_eq!(
+                    observations.nrows(),
+                    observations.ncols(),
+                    "a precomputed distance matrix must be square"
                 );
-                if neighbor_count >= hyperparameters.minimum_points() {
-                    cluster_memberships[candidate.0] = Some(current_cluster_id);
-                    search_queue.append(&mut neighbors);
-                }
+                // region queries are simple lookups in the rows of the matrix
+                let distance = |i: usize, j: usize| observations[(i, j)];
+                expand_clusters(hyperparameters, observations.nrows(), distance, weights)
             }
-            current_cluster_id += 1;
         }
-        cluster_memberships
     }
 }
 
-type Neighbors<'a> = Vec<(usize, ArrayView<'a, f64, Ix1>)>;
+fn expand_clusters(
+    hyperparameters: &DbscanHyperParams,
+    n_observations: usize,
+    distance: impl Fn(usize, usize) -> f64,
+    weights: Option<ArrayView1<f64>>,
+) -> Array1<Option<usize>> {
+    if let Some(weights) = &weights {
+        assert_eq!(
+            weights.len(),
+            n_observations,
+            "there must be one weight per observation"
+        );
+    }
+    let min_points = hyperparameters.minimum_points() as f64;
+
+    let mut cluster_memberships = Array1::from_elem(n_observations, None);
+    let mut current_cluster_id = 0;
+    for i in 0..n_observations {
+        if cluster_memberships[i].is_some() {
+            continue;
+        }
+        let (neighbor_weight, mut search_queue) = find_neighbors(
+            i,
+            &distance,
+            weights.as_ref(),
+            hyperparameters.tolerance(),
+            &cluster_memberships,
+        );
+        if neighbor_weight < min_points {
+            continue;
+        }
+        // Now go over the neighbours adding them to the cluster
+        cluster_memberships[i] = Some(current_cluster_id);
+
+        while !search_queue.is_empty() {
+            let candidate = search_queue.remove(0);
+
+            let (neighbor_weight, mut neighbors) = find_neighbors(
+                candidate,
+                &distance,
+                weights.as_ref(),
+                hyperparameters.tolerance(),
+                &cluster_memberships,
+            );
+            if neighbor_weight >= min_points {
+                cluster_memberships[candidate] = Some(current_cluster_id);
+                search_queue.append(&mut neighbors);
+            }
+        }
+        current_cluster_id += 1;
+    }
+    cluster_memberships
+}
 
-fn find_neighbors<'a>(
-    candidate: &ArrayBase<impl Data<Elem = f64>, Ix1>,
-    observations: &'a ArrayBase<impl Data<Elem = f64>, Ix2>,
+/// Sum the weights of the observations in the neighborhood of `candidate` (each observation
+/// counts as one without weights) and return the ones which are not yet assigned to a cluster
+fn find_neighbors(
+    candidate: usize,
+    distance: &impl Fn(usize, usize) -> f64,
+    weights: Option<&ArrayView1<f64>>,
     eps: f64,
     clusters: &Array1<Option<usize>>,
-) -> (usize, Neighbors<'a>) {
+) -> (f64, Vec<usize>) {
     let mut res = vec![];
-    let mut count = 0;
-    for (i, (obs, cluster)) in observations
-        .axis_iter(Axis(1))
-        .zip(clusters.iter())
-        .enumerate()
-    {
-        if candidate.l2_dist(&obs).unwrap() < eps {
-            count += 1;
+    let mut count = 0.;
+    for (i, cluster) in clusters.iter().enumerate() {
+        if distance(candidate, i) < eps {
+            count += weights.map_or(1., |weights| weights[i]);
             if cluster.is_none() {
-                res.push((i, obs));
+                res.push(i);
             }
         }
     }
@@ -139,7 +214,7 @@ fn find_neighbors<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ndarray::{arr1, s, Array2};
+    use ndarray::{arr1, array, s, Array2};
 
     #[test]
     fn nested_clusters() {
@@ -178,6 +253,25 @@ mod tests {
         assert_eq!(labels, expected);
     }
 
+    #[test]
+    fn sparse_points_are_noise() {
+        // two dense groups and three isolated points between them
+        let mut data: Array2<f64> = Array2::zeros((2, 23));
+        data.slice_mut(s![0, 0..10])
+            .assign(&Array1::linspace(0.0, 0.9, 10));
+        data.slice_mut(s![0, 10..20])
+            .assign(&Array1::linspace(20.0, 20.9, 10));
+        data.slice_mut(s![.., 20..])
+            .assign(&array![[5.0, 10.0, 15.0], [3.0, -3.0, 3.0]]);
+
+        let params = DbscanHyperParams::new(3).tolerance(0.5).build();
+        let labels = Dbscan::predict(&params, &data);
+
+        assert!(labels.slice(s![..10]).iter().all(|x| x == &Some(0)));
+        assert!(labels.slice(s![10..20]).iter().all(|x| x == &Some(1)));
+        assert!(labels.slice(s![20..]).iter().all(|x| x.is_none()));
+    }
+
     #[test]
     fn dataset_too_small() {
         let params = DbscanHyperParams::new(4).build();
@@ -187,4 +281,69 @@ mod tests {
         let labels = Dbscan::predict(&params, &data);
         assert!(labels.iter().all(|x| x.is_none()));
     }
+
+    #[test]
+    fn precomputed_matches_euclidean() {
+        let mut data: Array2<f64> = Array2::zeros((2, 30));
+        let rising = Array1::linspace(0.0, 4.5, 10);
+        data.slice_mut(s![0, 0..10]).assign(&rising);
+        data.slice_mut(s![0, 10..20]).assign(&rising);
+        data.slice_mut(s![1, 10..20]).fill(20.0);
+        data.slice_mut(s![.., 20..29]).fill(10.0);
+        data.slice_mut(s![.., 29]).fill(-10.0);
+
+        let n = data.ncols();
+        let distances = Array2::from_shape_fn((n, n), |(i, j)| {
+            data.column(i).l2_dist(&data.column(j)).unwrap()
+        });
+
+        let params = DbscanHyperParams::new(3).tolerance(1.0).build();
+        let labels = Dbscan::predict(&params, &data);
+
+        let params = DbscanHyperParams::new(3)
+            .tolerance(1.0)
+            .metric(Metric::Precomputed)
+            .build();
+        let labels_precomputed = Dbscan::predict(&params, &distances);
+
+        assert_eq!(labels, labels_precomputed);
+        assert_eq!(labels[1], Some(0));
+        assert_eq!(labels[11], Some(1));
+        assert_eq!(labels[20], Some(2));
+        assert_eq!(labels[29], None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn precomputed_not_square() {
+        let params = DbscanHyperParams::new(2)
+            .metric(Metric::Precomputed)
+            .build();
+        Dbscan::predict(&params, &Array2::<f64>::zeros((2, 3)));
+    }
+
+    #[test]
+    fn duplicate_equals_doubled_weight() {
+        // three points on a line, only the middle one is dense enough with a duplicate
+        let data = array![[0.0, 0.8, 1.6], [0.0, 0.0, 0.0]];
+        let duplicated = array![[0.0, 0.8, 0.8, 1.6], [0.0, 0.0, 0.0, 0.0]];
+        let params = DbscanHyperParams::new(4).tolerance(1.0).build();
+
+        // without weights no point has enough neighbors
+        let labels = Dbscan::predict(&params, &data);
+        assert!(labels.iter().all(|x| x.is_none()));
+
+        let labels = Dbscan::predict_weighted(&params, &data, &arr1(&[1.0, 2.0, 1.0]));
+        let labels_duplicated = Dbscan::predict(&params, &duplicated);
+
+        assert_eq!(labels, arr1(&[None, Some(0), None]));
+        assert_eq!(labels_duplicated, arr1(&[None, Some(0), Some(0), None]));
+
+        // a uniform weight of one is the same as no weights
+        let data = Array2::<f64>::zeros((2, 5));
+        assert_eq!(
+            Dbscan::predict_weighted(&params, &data, &Array1::ones(5)),
+            Dbscan::predict(&params, &data)
+        );
+    }
 }
@@ -1,5 +1,15 @@
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// How the distance between two observations is determined
+pub enum Metric {
+    /// Euclidean distance between the feature vectors of the observations
+    Euclidean,
+    /// The observations are a square matrix of pairwise distances, this allows
+    /// clustering of data which is not represented by feature vectors
+    Precomputed,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// The set of hyperparameters that can be specified for the execution of
 /// the [DBSCAN algorithm](struct.Dbscan.html).
@@ -9,12 +19,15 @@ pub struct DbscanHyperParams {
     /// Minimum number of neighboring points a point needs to have to be a core
     /// point and not a noise point.
     min_points: usize,
+    /// How the distance between observations is determined
+    metric: Metric,
 }
 
 /// Helper struct used to construct a set of hyperparameters for
 pub struct DbscanHyperParamsBuilder {
     tolerance: f64,
     min_points: usize,
+    metric: Metric,
 }
 
 impl DbscanHyperParamsBuilder {
@@ -24,12 +37,19 @@ impl DbscanHyperParamsBuilder {
         self
     }
 
+    /// How the distance between observations is determined, use `Metric::Precomputed` to
+    /// pass a distance matrix instead of feature vectors.
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+
     /// Return an instance of `DbscanHyperParams` after having performed
     /// validation checks on all hyperparameters.
     ///
     /// **Panics** if any of the validation checks fail.
     pub fn build(self) -> DbscanHyperParams {
-        DbscanHyperParams::build(self.tolerance, self.min_points)
+        DbscanHyperParams::build(self.tolerance, self.min_points, self.metric)
     }
 }
 
@@ -39,12 +59,14 @@ impl DbscanHyperParams {
     ///
     /// Defaults are provided if the optional parameters are not specified:
     /// * `tolerance = 1e-4`
+    /// * `metric = Metric::Euclidean`
     // Violates the convention that new should return a value of type `Self`
     #[allow(clippy::new_ret_no_self)]
     pub fn new(min_points: usize) -> DbscanHyperParamsBuilder {
         DbscanHyperParamsBuilder {
             min_points,
             tolerance: 1e-4,
+            metric: Metric::Euclidean,
         }
     }
 
@@ -60,7 +82,12 @@ impl DbscanHyperParams {
         self.min_points
     }
 
-    fn build(tolerance: f64, min_points: usize) -> Self {
+    /// How the distance between observations is determined
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    fn build(tolerance: f64, min_points: usize, metric: Metric) -> Self {
         if tolerance <= 0. {
             panic!("`tolerance` must be greater than 0!");
         }
@@ -71,6 +98,7 @@ impl DbscanHyperParams {
         Self {
             tolerance,
             min_points,
+            metric,
         }
     }
 }
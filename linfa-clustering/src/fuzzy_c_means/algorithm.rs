@@ -0,0 +1,295 @@
+use crate::fuzzy_c_means::hyperparameters::FuzzyCMeansHyperParams;
+use ndarray::{Array2, ArrayBase, Axis, Data, Ix2, Zip};
+use ndarray_rand::rand::Rng;
+use ndarray_rand::rand_distr::Uniform;
+use ndarray_rand::RandomExt;
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Fuzzy C-means is a soft version of [K-means](struct.KMeans.html): instead of
+/// assigning each observation to a single cluster, it computes a degree of membership
+/// to every cluster. The memberships of an observation are non-negative and sum up to one.
+///
+/// This is useful when the boundaries between clusters are unclear, observations between
+/// two clusters are shared by both instead of being forced into one of them.
+///
+/// ## The algorithm
+///
+/// The memberships `u_ij` and the cluster centers `c_j` minimize
+/// ```ignore
+/// sum_i sum_j u_ij^m * ||x_i - c_j||^2
+/// ```
+/// where `m > 1` is the `fuzziness`. Starting from random memberships two steps are
+/// alternated until no membership changes by more than `tolerance`:
+/// - each center is the mean of all observations weighted by `u_ij^m`;
+/// - each membership is updated to `u_ij = 1 / sum_k (d_ij / d_ik)^(2 / (m - 1))`,
+///   where `d_ij` is the euclidean distance between observation `i` and center `j`.
+///
+/// As `m` approaches 1 the memberships become hard assignments and the algorithm
+/// reduces to K-means, large values of `m` spread the memberships evenly.
+///
+/// ## Tutorial
+///
+/// ```
+/// use linfa_clustering::{generate_blobs, FuzzyCMeans, FuzzyCMeansHyperParams};
+/// use ndarray::{array, Axis};
+/// use ndarray_rand::rand::SeedableRng;
+/// use rand_isaac::Isaac64Rng;
+///
+/// let mut rng = Isaac64Rng::seed_from_u64(42);
+/// let expected_centroids = array![[0., 1.], [-10., 20.], [-1., 10.]];
+/// let observations = generate_blobs(100, &expected_centroids, &mut rng);
+///
+/// let hyperparams = FuzzyCMeansHyperParams::new(3).fuzziness(1.5).build();
+/// let model = FuzzyCMeans::fit(hyperparams, &observations, &mut rng);
+///
+/// // One row of memberships per observation, one column per cluster
+/// let memberships = model.membership_matrix();
+/// assert_eq!(memberships.dim(), (300, 3));
+/// let centers = model.cluster_centers();
+///
+/// // New observations are assigned memberships to the fitted centers
+/// let new_memberships = model.predict(&array![[-9., 20.5]]);
+/// ```
+pub struct FuzzyCMeans {
+    hyperparameters: FuzzyCMeansHyperParams,
+    cluster_centers: Array2<f64>,
+    membership_matrix: Array2<f64>,
+    n_iterations: u64,
+}
+
+impl FuzzyCMeans {
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `fit` computes `n_clusters` centers and the membership of each observation
+    /// to every cluster.
+    ///
+    /// **Panics** if there are fewer observations than `n_clusters`.
+    pub fn fit(
+        hyperparameters: FuzzyCMeansHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let n_clusters = hyperparameters.n_clusters();
+        assert!(
+            n_clusters <= observations.nrows(),
+            "`n_clusters` cannot be larger than the number of observations!"
+        );
+        let fuzziness = hyperparameters.fuzziness();
+
+        let mut membership_matrix = Array2::random_using(
+            (observations.nrows(), n_clusters),
+            Uniform::new(0., 1.),
+            rng,
+        );
+        let row_sums = membership_matrix.sum_axis(Axis(1)).insert_axis(Axis(1));
+        membership_matrix /= &row_sums;
+
+        let mut n_iterations = 0;
+        let cluster_centers = loop {
+            let cluster_centers =
+                compute_cluster_centers(&membership_matrix, observations, fuzziness);
+            let new_membership_matrix =
+                compute_memberships(&cluster_centers, observations, fuzziness);
+
+            let change = (&new_membership_matrix - &membership_matrix)
+                .fold(0., |max: f64, delta| max.max(delta.abs()));
+            membership_matrix = new_membership_matrix;
+            n_iterations += 1;
+
+            // the centers are returned together with the memberships computed from them
+            if change <= hyperparameters.tolerance()
+                || n_iterations >= hyperparameters.max_n_iterations()
+            {
+                break cluster_centers;
+            }
+        };
+
+        FuzzyCMeans {
+            hyperparameters,
+            cluster_centers,
+            membership_matrix,
+            n_iterations,
+        }
+    }
+
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `predict` returns the membership of each observation to every cluster, with shape
+    /// `(n_observations, n_clusters)`.
+    pub fn predict(&self, observations: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> Array2<f64> {
+        compute_memberships(
+            &self.cluster_centers,
+            observations,
+            self.hyperparameters.fuzziness(),
+        )
+    }
+
+    /// Return the membership of each training observation to every cluster, with shape
+    /// `(n_observations, n_clusters)`. Each row sums up to one.
+    pub fn membership_matrix(&self) -> &Array2<f64> {
+        &self.membership_matrix
+    }
+
+    /// Return the cluster centers as a 2-dimensional matrix with shape
+    /// `(n_clusters, n_features)`.
+    pub fn cluster_centers(&self) -> &Array2<f64> {
+        &self.cluster_centers
+    }
+
+    /// Return the number of training iterations performed.
+    pub fn n_iterations(&self) -> u64 {
+        self.n_iterations
+    }
+
+    /// Return the hyperparameters used to train this Fuzzy C-means model instance.
+    pub fn hyperparameters(&self) -> &FuzzyCMeansHyperParams {
+        &self.hyperparameters
+    }
+}
+
+/// Each center is the mean of all observations, weighted by their memberships raised
+/// to the power `fuzziness`.
+fn compute_cluster_centers(
+    // (n_observations, n_clusters)
+    membership_matrix: &Array2<f64>,
+    // (n_observations, n_features)
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    fuzziness: f64,
+) -> Array2<f64> {
+    let weights = membership_matrix.mapv(|u| u.powf(fuzziness));
+    let totals = weights.sum_axis(Axis(0)).insert_axis(Axis(1));
+    weights.t().dot(observations) / &totals
+}
+
+/// Compute the membership of each observation to every cluster given the centers.
+///
+/// An observation coinciding with one or more centers is shared equally between them.
+fn compute_memberships(
+    // (n_clusters, n_features)
+    cluster_centers: &Array2<f64>,
+    // (n_observations, n_features)
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    fuzziness: f64,
+) -> Array2<f64> {
+    let exponent = 1. / (fuzziness - 1.);
+    let mut membership_matrix = Array2::zeros((observations.nrows(), cluster_centers.nrows()));
+
+    Zip::from(membership_matrix.genrows_mut())
+        .and(observations.genrows())
+        .apply(|mut memberships, observation| {
+            let distances = cluster_centers.map_axis(Axis(1), |center| {
+                center
+                    .sq_l2_dist(&observation)
+                    .expect("Failed to compute distance")
+            });
+            let closest = distances.fold(f64::INFINITY, |min, d| min.min(*d));
+
+            // ratios to the closest center avoid overflows for tiny distances
+            if closest > 0. {
+                memberships.assign(&distances.mapv(|d| (closest / d).powf(exponent)));
+            } else {
+                memberships.assign(&distances.mapv(|d| if d > 0. { 0. } else { 1. }));
+            }
+            let total = memberships.sum();
+            memberships /= total;
+        });
+    membership_matrix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_blobs;
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, Array1};
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    fn most_likely_clusters(membership_matrix: &Array2<f64>) -> Array1<usize> {
+        membership_matrix.map_axis(Axis(1), |memberships| {
+            (0..memberships.len())
+                .max_by(|a, b| memberships[*a].partial_cmp(&memberships[*b]).unwrap())
+                .unwrap()
+        })
+    }
+
+    #[test]
+    fn recovers_blobs() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let expected_centers = array![[0., 0.], [10., 10.], [-10., 10.]];
+        let observations = generate_blobs(50, &expected_centers, &mut rng);
+
+        let params = FuzzyCMeansHyperParams::new(3).build();
+        let model = FuzzyCMeans::fit(params, &observations, &mut rng);
+        assert!(model.n_iterations() < 300);
+
+        let memberships = model.membership_matrix();
+        assert_eq!(memberships.dim(), (150, 3));
+        assert_abs_diff_eq!(
+            memberships.sum_axis(Axis(1)),
+            Array1::ones(150),
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(&model.predict(&observations), memberships, epsilon = 1e-10);
+
+        // every blob is dominated by a different cluster, whose center is close to the blob's
+        let clusters = most_likely_clusters(memberships);
+        for blob in 0..3 {
+            let cluster = clusters[blob * 50];
+            assert!(clusters
+                .iter()
+                .skip(blob * 50)
+                .take(50)
+                .all(|c| *c == cluster));
+            for other in 0..blob {
+                assert_ne!(clusters[other * 50], cluster);
+            }
+            let center = model.cluster_centers().row(cluster);
+            assert!(center.sq_l2_dist(&expected_centers.row(blob)).unwrap() < 1.);
+        }
+    }
+
+    #[test]
+    fn fuzziness_controls_softness() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let expected_centers = array![[0., 0.], [10., 10.], [-10., 10.]];
+        let observations = generate_blobs(50, &expected_centers, &mut rng);
+
+        let mean_max_membership = |fuzziness: f64, rng: &mut Isaac64Rng| {
+            let params = FuzzyCMeansHyperParams::new(3).fuzziness(fuzziness).build();
+            let model = FuzzyCMeans::fit(params, &observations, rng);
+            model
+                .membership_matrix()
+                .map_axis(Axis(1), |memberships| {
+                    memberships.fold(0., |max: f64, u| max.max(*u))
+                })
+                .mean()
+                .unwrap()
+        };
+
+        let hard = mean_max_membership(1.2, &mut rng);
+        let medium = mean_max_membership(2., &mut rng);
+        let soft = mean_max_membership(3., &mut rng);
+        assert!(hard > 0.99);
+        assert!(hard > medium && medium > soft);
+    }
+
+    #[test]
+    fn observation_on_center() {
+        let centers = array![[0., 0.], [2., 0.], [2., 0.]];
+        let observations = array![[0., 0.], [2., 0.], [1., 0.]];
+
+        let memberships = compute_memberships(&centers, &observations, 2.);
+        assert_abs_diff_eq!(
+            memberships,
+            array![[1., 0., 0.], [0., 0.5, 0.5], [1. / 3., 1. / 3., 1. / 3.]],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn fuzziness_must_exceed_one() {
+        FuzzyCMeansHyperParams::new(3).fuzziness(1.).build();
+    }
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The set of hyperparameters that can be specified for the execution of
+/// the [Fuzzy C-means algorithm](struct.FuzzyCMeans.html).
+pub struct FuzzyCMeansHyperParams {
+    /// The number of clusters we will be looking for in the training dataset.
+    n_clusters: usize,
+    /// The exponent `m > 1` applied to the memberships, larger values give softer memberships.
+    fuzziness: f64,
+    /// The training is considered complete if no membership changes by more than `tolerance`
+    /// in a training iteration.
+    tolerance: f64,
+    /// We exit the training loop when the number of training iterations
+    /// exceeds `max_n_iterations` even if the `tolerance` convergence
+    /// condition has not been met.
+    max_n_iterations: u64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters](struct.FuzzyCMeansHyperParams.html) for
+/// the [Fuzzy C-means algorithm](struct.FuzzyCMeans.html) (using the builder pattern).
+pub struct FuzzyCMeansHyperParamsBuilder {
+    n_clusters: usize,
+    fuzziness: f64,
+    tolerance: f64,
+    max_n_iterations: u64,
+}
+
+impl FuzzyCMeansHyperParamsBuilder {
+    /// Set the value of `fuzziness`.
+    ///
+    /// The memberships are raised to the power `fuzziness > 1` when computing the
+    /// cluster centers. Values close to 1 approach the hard assignments of K-means,
+    /// larger values spread the memberships more evenly across the clusters.
+    pub fn fuzziness(mut self, fuzziness: f64) -> Self {
+        self.fuzziness = fuzziness;
+        self
+    }
+
+    /// Set the value of `tolerance`.
+    ///
+    /// The training is considered complete if no membership changes by more than
+    /// `tolerance` in a training iteration.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// We exit the training loop when the number of training iterations
+    /// exceeds `max_n_iterations` even if the `tolerance` convergence
+    /// condition has not been met.
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Return an instance of `FuzzyCMeansHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> FuzzyCMeansHyperParams {
+        FuzzyCMeansHyperParams::build(
+            self.n_clusters,
+            self.fuzziness,
+            self.tolerance,
+            self.max_n_iterations,
+        )
+    }
+}
+
+impl FuzzyCMeansHyperParams {
+    /// `new` lets us configure our training algorithm parameters:
+    /// * we will be looking for `n_clusters` in the training dataset;
+    /// * the memberships are raised to the power `fuzziness` when computing the centers;
+    /// * the training is considered complete if no membership changes by more than
+    ///   `tolerance` in a training iteration;
+    /// * we exit the training loop when the number of training iterations
+    ///   exceeds `max_n_iterations` even if the `tolerance` convergence
+    ///   condition has not been met.
+    ///
+    /// `n_clusters` is mandatory.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `fuzziness = 2.0`;
+    /// * `tolerance = 1e-4`;
+    /// * `max_n_iterations = 300`.
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(n_clusters: usize) -> FuzzyCMeansHyperParamsBuilder {
+        FuzzyCMeansHyperParamsBuilder {
+            n_clusters,
+            fuzziness: 2.,
+            tolerance: 1e-4,
+            max_n_iterations: 300,
+        }
+    }
+
+    /// The number of clusters we will be looking for in the training dataset.
+    pub fn n_clusters(&self) -> usize {
+        self.n_clusters
+    }
+
+    /// The exponent `m > 1` applied to the memberships, larger values give softer memberships.
+    pub fn fuzziness(&self) -> f64 {
+        self.fuzziness
+    }
+
+    /// The training is considered complete if no membership changes by more than `tolerance`
+    /// in a training iteration.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// We exit the training loop when the number of training iterations
+    /// exceeds `max_n_iterations` even if the `tolerance` convergence
+    /// condition has not been met.
+    pub fn max_n_iterations(&self) -> u64 {
+        self.max_n_iterations
+    }
+
+    fn build(n_clusters: usize, fuzziness: f64, tolerance: f64, max_n_iterations: u64) -> Self {
+        if max_n_iterations == 0 {
+            panic!("`max_n_iterations` cannot be 0!");
+        }
+        if tolerance <= 0. {
+            panic!("`tolerance` must be greater than 0!");
+        }
+        if n_clusters == 0 {
+            panic!("`n_clusters` cannot be 0!");
+        }
+        if fuzziness.is_nan() || fuzziness <= 1. {
+            panic!("`fuzziness` must be greater than 1!");
+        }
+        FuzzyCMeansHyperParams {
+            n_clusters,
+            fuzziness,
+            tolerance,
+            max_n_iterations,
+        }
+    }
+}
@@ -0,0 +1,536 @@
+use crate::hdbscan::hyperparameters::HdbscanHyperParams;
+use ndarray::{Array1, ArrayBase, Data, Ix2};
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// HDBSCAN (Hierarchical Density-Based Spatial Clustering of Applications
+/// with Noise) extends DBSCAN to a whole hierarchy of density levels and
+/// extracts the most stable clusters from it. In contrast to DBSCAN there is
+/// no global distance `tolerance`, which allows the algorithm to find clusters
+/// of varying density.
+///
+/// As in DBSCAN the number of clusters is determined by the dataset, points
+/// which do not belong to any cluster are labelled as noise. Additionally an
+/// outlier score is computed for each point.
+///
+/// We provide an implementation of the O(N^2) algorithm based on a minimum
+/// spanning tree, more details can be found in the next section or in
+/// [Campello et al. (2013)](https://doi.org/10.1007/978-3-642-37456-2_14).
+///
+/// ## The algorithm
+///
+/// - The core distance of each point is the distance to its `min_samples`-th
+///   nearest neighbor (counting the point itself), a measure of the density
+///   around it
+/// - The mutual reachability distance between two points is the maximum of
+///   their distance and both core distances, it pushes sparse points away from
+///   all other points
+/// - A minimum spanning tree over the mutual reachability distances gives the
+///   single-linkage hierarchy of all points
+/// - The hierarchy is condensed: walking from the root towards the leaves a
+///   split only creates new clusters if both parts contain at least
+///   `min_cluster_size` points, otherwise the smaller part falls out of the
+///   cluster as noise
+/// - Clusters are selected by their stability, the persistence of their
+///   points over the density levels, such that no selected cluster is contained
+///   in another one
+///
+/// ## Tutorial
+///
+/// Let's do a walkthrough of an example running HDBSCAN on some data.
+///
+/// ```
+/// use linfa_clustering::{HdbscanHyperParams, Hdbscan, generate_blobs};
+/// use ndarray::array;
+/// use ndarray_rand::rand::SeedableRng;
+/// use rand_isaac::Isaac64Rng;
+///
+/// // Our random number generator, seeded for reproducibility
+/// let seed = 42;
+/// let mut rng = Isaac64Rng::seed_from_u64(seed);
+///
+/// // `expected_centroids` has shape `(n_centroids, n_features)`
+/// // i.e. three points in the 2-dimensional plane
+/// let expected_centroids = array![[0., 1.], [-10., 20.], [-1., 10.]];
+/// // Let's generate a synthetic dataset: three blobs of observations
+/// // (100 points each) centered around our `expected_centroids`
+/// let observations = generate_blobs(100, &expected_centroids, &mut rng);
+///
+/// // Let's configure and run our HDBSCAN algorithm
+/// // We use the builder pattern to specify the hyperparameters
+/// // `min_cluster_size` is the only mandatory parameter.
+/// let min_cluster_size = 10;
+/// let hyperparams = HdbscanHyperParams::new(min_cluster_size)
+///     .min_samples(5)
+///     .build();
+/// // Let's run the algorithm!
+/// let result = Hdbscan::predict(&hyperparams, &observations);
+/// // Points are `None` if noise `Some(id)` if belonging to a cluster.
+/// let labels = result.labels();
+/// ```
+///
+pub struct Hdbscan;
+
+impl Hdbscan {
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// cluster the observations and return the cluster of each observation together with
+    /// its outlier score.
+    pub fn predict(
+        hyperparameters: &HdbscanHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> HdbscanResult {
+        let n_observations = observations.nrows();
+        if n_observations == 0 {
+            return HdbscanResult {
+                labels: Array1::from(vec![]),
+                outlier_scores: Array1::zeros(0),
+                n_clusters: 0,
+            };
+        }
+
+        let distance =
+            |i: usize, j: usize| observations.row(i).l2_dist(&observations.row(j)).unwrap();
+        let core_distances = core_distances(
+            n_observations,
+            hyperparameters.min_samples().min(n_observations),
+            &distance,
+        );
+        let mutual_reachability =
+            |i: usize, j: usize| distance(i, j).max(core_distances[i]).max(core_distances[j]);
+
+        let edges = minimum_spanning_tree(n_observations, mutual_reachability);
+        let dendrogram = Dendrogram::single_linkage(n_observations, edges);
+        let tree = CondensedTree::new(&dendrogram, hyperparameters.min_cluster_size());
+
+        let selected = tree.select_clusters();
+        let (labels, n_clusters) = tree.labels(&selected);
+
+        HdbscanResult {
+            labels,
+            outlier_scores: tree.outlier_scores(),
+            n_clusters,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The clustering found by [HDBSCAN](struct.Hdbscan.html)
+pub struct HdbscanResult {
+    labels: Array1<Option<usize>>,
+    outlier_scores: Array1<f64>,
+    n_clusters: usize,
+}
+
+impl HdbscanResult {
+    /// Return the cluster of each observation, `None` for noise
+    pub fn labels(&self) -> &Array1<Option<usize>> {
+        &self.labels
+    }
+
+    /// Return the GLOSH outlier score of each observation
+    ///
+    /// The score lies in `[0, 1]` and compares the density at which an observation
+    /// leaves its cluster with the highest density found in that cluster. Points in the
+    /// core of a cluster have a score close to 0, outliers a score close to 1.
+    pub fn outlier_scores(&self) -> &Array1<f64> {
+        &self.outlier_scores
+    }
+
+    /// Return the number of clusters found
+    pub fn n_clusters(&self) -> usize {
+        self.n_clusters
+    }
+}
+
+/// Distance of each point to its `min_samples`-th nearest neighbor, the point itself included
+fn core_distances(
+    n_observations: usize,
+    min_samples: usize,
+    distance: &impl Fn(usize, usize) -> f64,
+) -> Vec<f64> {
+    (0..n_observations)
+        .map(|i| {
+            let mut distances = (0..n_observations)
+                .map(|j| distance(i, j))
+                .collect::<Vec<_>>();
+            distances.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            distances[min_samples - 1]
+        })
+        .collect()
+}
+
+/// Prim's algorithm on the complete graph, returns the edges as `(weight, from, to)`
+fn minimum_spanning_tree(
+    n_observations: usize,
+    distance: impl Fn(usize, usize) -> f64,
+) -> Vec<(f64, usize, usize)> {
+    let mut in_tree = vec![false; n_observations];
+    let mut best = vec![f64::INFINITY; n_observations];
+    let mut from = vec![0; n_observations];
+    let mut edges = Vec::with_capacity(n_observations - 1);
+
+    let mut current = 0;
+    in_tree[current] = true;
+    for _ in 1..n_observations {
+        let mut next = None;
+        for (j, &done) in in_tree.iter().enumerate() {
+            if done {
+                continue;
+            }
+            let dist = distance(current, j);
+            if dist < best[j] {
+                best[j] = dist;
+                from[j] = current;
+            }
+            let closer = match next {
+                Some(next) => best[j] < best[next],
+                None => true,
+            };
+            if closer {
+                next = Some(j);
+            }
+        }
+
+        let next = next.unwrap();
+        in_tree[next] = true;
+        edges.push((best[next], from[next], next));
+        current = next;
+    }
+
+    edges
+}
+
+/// Single-linkage hierarchy, the leaves `0..n` are the observations and the merge `k`
+/// creates the node `n + k`
+struct Dendrogram {
+    n_observations: usize,
+    children: Vec<(usize, usize)>,
+    distances: Vec<f64>,
+    sizes: Vec<usize>,
+}
+
+impl Dendrogram {
+    fn single_linkage(n_observations: usize, mut edges: Vec<(f64, usize, usize)>) -> Self {
+        edges.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+        // union-find over all nodes, each component points to its topmost node
+        let mut parents = (0..2 * n_observations - 1).collect::<Vec<_>>();
+        let find = |parents: &mut Vec<usize>, mut node: usize| {
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+            node
+        };
+
+        let mut children = Vec::with_capacity(edges.len());
+        let mut distances = Vec::with_capacity(edges.len());
+        let mut sizes = vec![1; 2 * n_observations - 1];
+        for (k, (distance, a, b)) in edges.into_iter().enumerate() {
+            let (a, b) = (find(&mut parents, a), find(&mut parents, b));
+            let node = n_observations + k;
+            parents[a] = node;
+            parents[b] = node;
+            sizes[node] = sizes[a] + sizes[b];
+            children.push((a, b));
+            distances.push(distance);
+        }
+
+        Dendrogram {
+            n_observations,
+            children,
+            distances,
+            sizes,
+        }
+    }
+
+    fn root(&self) -> usize {
+        2 * self.n_observations - 2
+    }
+
+    /// Collect the observations below a node
+    fn leaves(&self, node: usize) -> Vec<usize> {
+        let mut stack = vec![node];
+        let mut leaves = vec![];
+        while let Some(node) = stack.pop() {
+            if node < self.n_observations {
+                leaves.push(node);
+            } else {
+                let (left, right) = self.children[node - self.n_observations];
+                stack.push(left);
+                stack.push(right);
+            }
+        }
+        leaves
+    }
+}
+
+/// Hierarchy of clusters with at least `min_cluster_size` points, the densities are
+/// expressed as `lambda = 1 / distance`
+struct CondensedTree {
+    /// The cluster out of which each observation falls
+    point_parents: Vec<usize>,
+    /// The density at which each observation falls out of its cluster
+    point_lambdas: Vec<f64>,
+    /// The parent of each cluster, the root cluster `0` has none
+    cluster_parents: Vec<Option<usize>>,
+    /// The density at which each cluster is split off its parent
+    cluster_births: Vec<f64>,
+    /// The number of observations of each cluster at its birth
+    cluster_sizes: Vec<usize>,
+}
+
+impl CondensedTree {
+    fn new(dendrogram: &Dendrogram, min_cluster_size: usize) -> Self {
+        let n_observations = dendrogram.n_observations;
+        let mut tree = CondensedTree {
+            point_parents: vec![0; n_observations],
+            point_lambdas: vec![0.; n_observations],
+            cluster_parents: vec![None],
+            cluster_births: vec![0.],
+            cluster_sizes: vec![n_observations],
+        };
+
+        let mut stack = vec![(dendrogram.root(), 0)];
+        while let Some((node, cluster)) = stack.pop() {
+            if node < n_observations {
+                // only reached for a single observation
+                tree.point_parents[node] = cluster;
+                tree.point_lambdas[node] = tree.cluster_births[cluster];
+                continue;
+            }
+
+            let (left, right) = dendrogram.children[node - n_observations];
+            let lambda = 1. / dendrogram.distances[node - n_observations];
+            let (left_size, right_size) = (dendrogram.sizes[left], dendrogram.sizes[right]);
+
+            if left_size >= min_cluster_size && right_size >= min_cluster_size {
+                // a true split, both parts become new clusters
+                for &child in &[left, right] {
+                    tree.cluster_parents.push(Some(cluster));
+                    tree.cluster_births.push(lambda);
+                    tree.cluster_sizes.push(dendrogram.sizes[child]);
+                    stack.push((child, tree.cluster_births.len() - 1));
+                }
+            } else {
+                // the cluster shrinks, parts which are too small fall out of it
+                for &child in &[left, right] {
+                    if dendrogram.sizes[child] >= min_cluster_size {
+                        stack.push((child, cluster));
+                    } else {
+                        for point in dendrogram.leaves(child) {
+                            tree.point_parents[point] = cluster;
+                            tree.point_lambdas[point] = lambda;
+                        }
+                    }
+                }
+            }
+        }
+
+        tree
+    }
+
+    fn n_clusters(&self) -> usize {
+        self.cluster_parents.len()
+    }
+
+    /// Select the clusters with the excess of mass method, the root cluster is never selected
+    ///
+    /// Children are always created after their parents, iterating in reverse visits the
+    /// hierarchy bottom-up.
+    fn select_clusters(&self) -> Vec<bool> {
+        let n_clusters = self.n_clusters();
+        let mut children = vec![vec![]; n_clusters];
+        let mut stability = vec![0.; n_clusters];
+
+        for (&cluster, &lambda) in self.point_parents.iter().zip(self.point_lambdas.iter()) {
+            stability[cluster] += lambda - self.cluster_births[cluster];
+        }
+        for cluster in 1..n_clusters {
+            let parent = self.cluster_parents[cluster].unwrap();
+            stability[parent] += (self.cluster_births[cluster] - self.cluster_births[parent])
+                * self.cluster_sizes[cluster] as f64;
+            children[parent].push(cluster);
+        }
+
+        let mut selected = vec![false; n_clusters];
+        for cluster in (1..n_clusters).rev() {
+            let children_stability: f64 = children[cluster].iter().map(|&c| stability[c]).sum();
+            if children_stability > stability[cluster] {
+                stability[cluster] = children_stability;
+            } else {
+                selected[cluster] = true;
+                let mut descendants = children[cluster].clone();
+                while let Some(descendant) = descendants.pop() {
+                    selected[descendant] = false;
+                    descendants.extend(&children[descendant]);
+                }
+            }
+        }
+
+        selected
+    }
+
+    /// Assign each observation to the selected cluster it belongs to
+    fn labels(&self, selected: &[bool]) -> (Array1<Option<usize>>, usize) {
+        let mut ids = vec![None; self.n_clusters()];
+        let mut n_clusters = 0;
+        for (cluster, _) in selected.iter().enumerate().filter(|(_, s)| **s) {
+            ids[cluster] = Some(n_clusters);
+            n_clusters += 1;
+        }
+
+        let labels = self
+            .point_parents
+            .iter()
+            .map(|&cluster| {
+                let mut cluster = Some(cluster);
+                while let Some(c) = cluster {
+                    if selected[c] {
+                        return ids[c];
+                    }
+                    cluster = self.cluster_parents[c];
+                }
+                None
+            })
+            .collect();
+
+        (labels, n_clusters)
+    }
+
+    /// GLOSH outlier scores, comparing the density at which an observation falls out of its
+    /// cluster with the highest density of any observation in that cluster
+    fn outlier_scores(&self) -> Array1<f64> {
+        let mut max_lambdas = vec![0f64; self.n_clusters()];
+        for (&cluster, &lambda) in self.point_parents.iter().zip(self.point_lambdas.iter()) {
+            max_lambdas[cluster] = max_lambdas[cluster].max(lambda);
+        }
+        for cluster in (1..self.n_clusters()).rev() {
+            let parent = self.cluster_parents[cluster].unwrap();
+            max_lambdas[parent] = max_lambdas[parent].max(max_lambdas[cluster]);
+        }
+
+        self.point_parents
+            .iter()
+            .zip(self.point_lambdas.iter())
+            .map(|(&cluster, &lambda)| {
+                let max_lambda = max_lambdas[cluster];
+                if max_lambda <= 0. || lambda >= max_lambda {
+                    0.
+                } else if max_lambda.is_infinite() {
+                    1.
+                } else {
+                    (max_lambda - lambda) / max_lambda
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Dbscan, DbscanHyperParams};
+    use ndarray::{arr1, s, stack, Array2, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+    use std::collections::HashMap;
+
+    /// Two dense blobs close to each other and a sparse blob, 100 observations each
+    fn varying_density() -> Array2<f64> {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let blobs = [([0., 0.], 0.1), ([1., 0.], 0.1), ([10., 0.], 2.)]
+            .iter()
+            .map(|(center, scale)| {
+                Array2::<f64>::random_using((100, 2), StandardNormal, &mut rng) * *scale
+                    + &arr1(center)
+            })
+            .collect::<Vec<_>>();
+
+        stack(
+            Axis(0),
+            &blobs.iter().map(|blob| blob.view()).collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    /// The most frequent cluster among the observations of a blob, if it contains at least
+    /// the given fraction of the blob
+    fn majority(labels: &Array1<Option<usize>>, blob: usize, fraction: f64) -> Option<usize> {
+        let mut counts = HashMap::new();
+        for label in labels
+            .slice(s![blob * 100..(blob + 1) * 100])
+            .iter()
+            .flatten()
+        {
+            *counts.entry(*label).or_insert(0) += 1;
+        }
+        counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .filter(|(_, count)| *count as f64 >= fraction * 100.)
+            .map(|(label, _)| label)
+    }
+
+    fn separates_blobs(labels: &Array1<Option<usize>>, fraction: f64) -> bool {
+        let majorities = (0..3)
+            .map(|blob| majority(labels, blob, fraction))
+            .collect::<Vec<_>>();
+
+        majorities.iter().all(|x| x.is_some())
+            && majorities[0] != majorities[1]
+            && majorities[0] != majorities[2]
+            && majorities[1] != majorities[2]
+    }
+
+    #[test]
+    fn clusters_of_varying_density() {
+        let observations = varying_density();
+
+        let params = HdbscanHyperParams::new(10).min_samples(5).build();
+        let result = Hdbscan::predict(&params, &observations);
+        assert_eq!(result.n_clusters(), 3);
+        assert!(separates_blobs(result.labels(), 0.9));
+
+        // a tolerance small enough to split the dense blobs leaves the sparse blob as noise
+        for i in 1..=60 {
+            let params = DbscanHyperParams::new(5).tolerance(0.05 * i as f64).build();
+            let labels = Dbscan::predict(&params, &observations.t());
+            assert!(!separates_blobs(&labels, 0.5));
+        }
+    }
+
+    #[test]
+    fn outlier_is_noise() {
+        let mut observations = varying_density();
+        observations.row_mut(0).assign(&arr1(&[30., 30.]));
+
+        let params = HdbscanHyperParams::new(10).min_samples(5).build();
+        let result = Hdbscan::predict(&params, &observations);
+
+        assert_eq!(result.labels()[0], None);
+        let scores = result.outlier_scores();
+        assert!(scores.iter().all(|x| *x >= 0. && *x <= 1.));
+        assert!(scores.iter().skip(1).all(|x| *x < scores[0]));
+        assert!(scores[0] > 0.9);
+    }
+
+    #[test]
+    fn single_observation_is_noise() {
+        let params = HdbscanHyperParams::new(2).build();
+        let result = Hdbscan::predict(&params, &Array2::<f64>::zeros((1, 2)));
+
+        assert_eq!(result.labels(), &arr1(&[None]));
+        assert_eq!(result.n_clusters(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn min_cluster_size_too_small() {
+        HdbscanHyperParams::new(1).build();
+    }
+}
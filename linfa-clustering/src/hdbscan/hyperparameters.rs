@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The set of hyperparameters that can be specified for the execution of
+/// the [HDBSCAN algorithm](struct.Hdbscan.html).
+pub struct HdbscanHyperParams {
+    /// Minimum number of points a group needs to have to be considered a cluster
+    /// and not noise.
+    min_cluster_size: usize,
+    /// Number of neighbors (including the point itself) used to estimate the density
+    /// around a point.
+    min_samples: usize,
+}
+
+/// Helper struct used to construct a set of hyperparameters for
+/// the [HDBSCAN algorithm](struct.Hdbscan.html).
+pub struct HdbscanHyperParamsBuilder {
+    min_cluster_size: usize,
+    min_samples: Option<usize>,
+}
+
+impl HdbscanHyperParamsBuilder {
+    /// Number of neighbors (including the point itself) used to estimate the density
+    /// around a point. Larger values make the clustering more conservative, more points
+    /// are declared as noise.
+    pub fn min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = Some(min_samples);
+        self
+    }
+
+    /// Return an instance of `HdbscanHyperParams` after having performed
+    /// validation checks on all hyperparameters.
+    ///
+    /// **Panics** if any of the validation checks fail.
+    pub fn build(self) -> HdbscanHyperParams {
+        let min_samples = self.min_samples.unwrap_or(self.min_cluster_size);
+        HdbscanHyperParams::build(self.min_cluster_size, min_samples)
+    }
+}
+
+impl HdbscanHyperParams {
+    /// Minimum number of points a group needs to have to be considered a cluster
+    /// and not noise.
+    ///
+    /// Defaults are provided if the optional parameters are not specified:
+    /// * `min_samples = min_cluster_size`
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(min_cluster_size: usize) -> HdbscanHyperParamsBuilder {
+        HdbscanHyperParamsBuilder {
+            min_cluster_size,
+            min_samples: None,
+        }
+    }
+
+    /// Minimum number of points a group needs to have to be considered a cluster
+    pub fn min_cluster_size(&self) -> usize {
+        self.min_cluster_size
+    }
+
+    /// Number of neighbors (including the point itself) used to estimate the density
+    /// around a point
+    pub fn min_samples(&self) -> usize {
+        self.min_samples
+    }
+
+    fn build(min_cluster_size: usize, min_samples: usize) -> Self {
+        // A single point can not be split any further, clusters need at least two points
+        if min_cluster_size <= 1 {
+            panic!("`min_cluster_size` must be greater than 1!");
+        }
+        if min_samples == 0 {
+            panic!("`min_samples` must be greater than 0!");
+        }
+        Self {
+            min_cluster_size,
+            min_samples,
+        }
+    }
+}
@@ -1,7 +1,7 @@
+use crate::k_means::empty_cluster::empty_clusters;
 use crate::k_means::helpers::IncrementalMean;
 use crate::k_means::hyperparameters::KMeansHyperParams;
 use ndarray::{s, Array1, Array2, ArrayBase, Axis, Data, DataMut, Ix1, Ix2, Zip};
-use ndarray_rand::rand;
 use ndarray_rand::rand::Rng;
 use ndarray_stats::DeviationExt;
 use serde::{Deserialize, Serialize};
@@ -32,7 +32,8 @@ use std::collections::HashMap;
 /// There are three steps in the standard algorithm:
 /// - initialisation step: how do we choose our initial set of centroids?
 /// - assignment step: assign each observation to the nearest cluster
-///                    (minimum distance between the observation and the cluster's centroid);
+///   (minimum distance between the observation and the cluster's centroid,
+///   ties go to the cluster with the lowest index);
 /// - update step: recompute the centroid of each cluster.
 ///
 /// The initialisation step is a one-off, done at the very beginning.
@@ -112,6 +113,7 @@ use std::collections::HashMap;
 pub struct KMeans {
     hyperparameters: KMeansHyperParams,
     centroids: Array2<f64>,
+    inertia: f64,
 }
 
 impl KMeans {
@@ -120,22 +122,58 @@ impl KMeans {
     ///
     /// An instance of `KMeans` is returned.
     ///
+    /// **Panics** if the `sample_weight` hyperparameter does not have one entry per observation.
+    ///
     pub fn fit(
         hyperparameters: KMeansHyperParams,
         observations: &ArrayBase<impl Data<Elem = f64> + Sync, Ix2>,
         rng: &mut impl Rng,
     ) -> Self {
-        let mut centroids = get_random_centroids(hyperparameters.n_clusters(), observations, rng);
+        let mut centroids =
+            hyperparameters
+                .init()
+                .centroids(hyperparameters.n_clusters(), observations, rng);
 
         let mut has_converged;
         let mut n_iterations = 0;
 
-        let mut memberships = Array1::zeros(observations.dim().0);
+        let n_observations = observations.dim().0;
+        let sample_weight = match hyperparameters.sample_weight() {
+            Some(sample_weight) if sample_weight.len() != n_observations => {
+                panic!("`sample_weight` must have one entry per observation!")
+            }
+            Some(sample_weight) => sample_weight.clone(),
+            None => Array1::ones(n_observations),
+        };
+        let mut memberships = Array1::zeros(n_observations);
 
         loop {
             update_cluster_memberships(&centroids, observations, &mut memberships);
-            let new_centroids =
-                compute_centroids(hyperparameters.n_clusters(), observations, &memberships);
+            let mut new_centroids = compute_centroids(
+                hyperparameters.n_clusters(),
+                observations,
+                &memberships,
+                &sample_weight,
+            );
+
+            // the mean of an empty cluster is undefined, it keeps its centroid unless the
+            // centroid can be moved to a poorly represented observation
+            let empty = empty_clusters(hyperparameters.n_clusters(), &memberships, &sample_weight);
+            if !empty.is_empty() {
+                for cluster in &empty {
+                    new_centroids
+                        .row_mut(*cluster)
+                        .assign(&centroids.row(*cluster));
+                }
+                hyperparameters.empty_cluster().relocate(
+                    &empty,
+                    &mut new_centroids,
+                    observations,
+                    &mut memberships,
+                    &sample_weight,
+                    rng,
+                );
+            }
 
             let distance = centroids
                 .sq_l2_dist(&new_centroids)
@@ -151,14 +189,19 @@ impl KMeans {
             }
         }
 
+        update_cluster_memberships(&centroids, observations, &mut memberships);
+        let inertia = compute_inertia(&centroids, observations, &memberships, &sample_weight);
+
         Self {
             hyperparameters,
             centroids,
+            inertia,
         }
     }
 
     /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
     /// `predict` returns, for each observation, the index of the closest cluster/centroid.
+    /// An observation equidistant from several centroids is assigned to the lowest index.
     ///
     /// You can retrieve the centroid associated to an index using the
     /// [`centroids` method](#method.centroids).
@@ -172,6 +215,12 @@ impl KMeans {
         &self.centroids
     }
 
+    /// Return the inertia of the training observations, the sum of the squared
+    /// distances to their closest centroid weighted by `sample_weight`.
+    pub fn inertia(&self) -> f64 {
+        self.inertia
+    }
+
     /// Return the hyperparameters used to train this K-means model instance.
     pub fn hyperparameters(&self) -> &KMeansHyperParams {
         &self.hyperparameters
@@ -198,8 +247,11 @@ fn compute_centroids(
     observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
     // (n_observations,)
     cluster_memberships: &ArrayBase<impl Data<Elem = usize>, Ix1>,
+    // (n_observations,)
+    sample_weight: &ArrayBase<impl Data<Elem = f64>, Ix1>,
 ) -> Array2<f64> {
-    let centroids_hashmap = compute_centroids_hashmap(&observations, &cluster_memberships);
+    let centroids_hashmap =
+        compute_centroids_hashmap(observations, cluster_memberships, sample_weight);
     let (_, n_features) = observations.dim();
 
     let mut centroids: Array2<f64> = Array2::zeros((n_clusters, n_features));
@@ -213,28 +265,58 @@ fn compute_centroids(
 
 /// Iterate over our observations and capture in a HashMap the new centroids.
 /// The HashMap is a (cluster_index => new centroid) mapping.
+/// Each centroid is the mean of its observations weighted by `sample_weight`.
 fn compute_centroids_hashmap(
     // (n_observations, n_features)
     observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
     // (n_observations,)
     cluster_memberships: &ArrayBase<impl Data<Elem = usize>, Ix1>,
+    // (n_observations,)
+    sample_weight: &ArrayBase<impl Data<Elem = f64>, Ix1>,
 ) -> HashMap<usize, IncrementalMean> {
     let mut new_centroids: HashMap<usize, IncrementalMean> = HashMap::new();
     Zip::from(observations.genrows())
         .and(cluster_memberships)
-        .apply(|observation, cluster_membership| {
+        .and(sample_weight)
+        .apply(|observation, cluster_membership, weight| {
             if let Some(incremental_mean) = new_centroids.get_mut(cluster_membership) {
-                incremental_mean.update(&observation);
+                incremental_mean.update(&observation, *weight);
             } else {
                 new_centroids.insert(
                     *cluster_membership,
-                    IncrementalMean::new(observation.to_owned()),
+                    IncrementalMean::new(observation.to_owned(), *weight),
                 );
             }
         });
     new_centroids
 }
 
+/// Sum of the squared distances between each observation and the centroid of
+/// its cluster, weighted by `sample_weight`.
+fn compute_inertia(
+    // (n_centroids, n_features)
+    centroids: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    // (n_observations, n_features)
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    // (n_observations,)
+    cluster_memberships: &ArrayBase<impl Data<Elem = usize>, Ix1>,
+    // (n_observations,)
+    sample_weight: &ArrayBase<impl Data<Elem = f64>, Ix1>,
+) -> f64 {
+    let mut inertia = 0.;
+    Zip::from(observations.genrows())
+        .and(cluster_memberships)
+        .and(sample_weight)
+        .apply(|observation, cluster_membership, weight| {
+            let distance = centroids
+                .row(*cluster_membership)
+                .sq_l2_dist(&observation)
+                .expect("Failed to compute distance");
+            inertia += weight * distance;
+        });
+    inertia
+}
+
 /// Given a matrix of centroids with shape (n_centroids, n_features)
 /// and a matrix of observations with shape (n_observations, n_features),
 /// update the 1-dimensional `cluster_memberships` array such that:
@@ -249,7 +331,7 @@ fn update_cluster_memberships(
     Zip::from(observations.axis_iter(Axis(0)))
         .and(cluster_memberships)
         .par_apply(|observation, cluster_membership| {
-            *cluster_membership = closest_centroid(&centroids, &observation)
+            *cluster_membership = closest_centroid(centroids, &observation)
         });
 }
 
@@ -266,34 +348,35 @@ fn compute_cluster_memberships(
     observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
 ) -> Array1<usize> {
     observations.map_axis(Axis(1), |observation| {
-        closest_centroid(&centroids, &observation)
+        closest_centroid(centroids, &observation)
     })
 }
 
 /// Given a matrix of centroids with shape (n_centroids, n_features) and an observation,
 /// return the index of the closest centroid (the index of the corresponding row in `centroids`).
-fn closest_centroid(
+///
+/// If the observation is equidistant from several centroids the lowest index among them is
+/// returned, so the assignment only depends on the order of the centroids.
+pub(crate) fn closest_centroid(
     // (n_centroids, n_features)
     centroids: &ArrayBase<impl Data<Elem = f64>, Ix2>,
     // (n_features)
     observation: &ArrayBase<impl Data<Elem = f64>, Ix1>,
 ) -> usize {
-    let mut iterator = centroids.genrows().into_iter().peekable();
+    let mut iterator = centroids.genrows().into_iter().enumerate();
 
-    let first_centroid = iterator
-        .peek()
+    let (mut closest_index, first_centroid) = iterator
+        .next()
         .expect("There has to be at least one centroid");
-    let (mut closest_index, mut minimum_distance) = (
-        0,
-        first_centroid
-            .sq_l2_dist(&observation)
-            .expect("Failed to compute distance"),
-    );
-
-    for (centroid_index, centroid) in iterator.enumerate() {
+    let mut minimum_distance = first_centroid
+        .sq_l2_dist(observation)
+        .expect("Failed to compute distance");
+
+    for (centroid_index, centroid) in iterator {
         let distance = centroid
-            .sq_l2_dist(&observation)
+            .sq_l2_dist(observation)
             .expect("Failed to compute distance");
+        // a strict comparison keeps the first centroid on ties
         if distance < minimum_distance {
             closest_index = centroid_index;
             minimum_distance = distance;
@@ -302,22 +385,10 @@ fn closest_centroid(
     closest_index
 }
 
-fn get_random_centroids<S>(
-    n_clusters: usize,
-    observations: &ArrayBase<S, Ix2>,
-    rng: &mut impl Rng,
-) -> Array2<f64>
-where
-    S: Data<Elem = f64>,
-{
-    let (n_samples, _) = observations.dim();
-    let indices = rand::seq::index::sample(rng, n_samples, n_clusters).into_vec();
-    observations.select(Axis(0), &indices)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::k_means::EmptyClusterStrategy;
     use approx::assert_abs_diff_eq;
     use ndarray::{array, stack, Array, Array1, Array2, Axis};
     use ndarray_rand::rand::SeedableRng;
@@ -346,7 +417,12 @@ mod tests {
         let memberships = stack(Axis(0), &[memberships_1.view(), memberships_2.view()]).unwrap();
 
         // Does it work?
-        let centroids = compute_centroids(2, &observations, &memberships);
+        let centroids = compute_centroids(
+            2,
+            &observations,
+            &memberships,
+            &Array1::ones(2 * cluster_size),
+        );
         assert_abs_diff_eq!(
             centroids.index_axis(Axis(0), 0),
             expected_centroid_1,
@@ -361,6 +437,83 @@ mod tests {
         assert_eq!(centroids.len_of(Axis(0)), 2);
     }
 
+    #[test]
+    fn weighted_centroids_and_inertia() {
+        let observations = array![[0., 0.], [2., 0.], [10., 10.], [10., 14.]];
+        let memberships = array![0, 0, 1, 1];
+        let sample_weight = array![1., 3., 2., 2.];
+
+        let centroids = compute_centroids(2, &observations, &memberships, &sample_weight);
+        assert_abs_diff_eq!(centroids, array![[1.5, 0.], [10., 12.]], epsilon = 1e-10);
+
+        // 1 * 1.5^2 + 3 * 0.5^2 + 2 * 2^2 + 2 * 2^2
+        let inertia = compute_inertia(&centroids, &observations, &memberships, &sample_weight);
+        assert_abs_diff_eq!(inertia, 19., epsilon = 1e-10);
+    }
+
+    #[test]
+    fn zero_weights_are_ignored() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let observations = array![[0., 0.], [1., 0.], [0., 1.], [100., 100.]];
+        let hyperparams = KMeansHyperParams::new(1)
+            .sample_weight(array![1., 1., 1., 0.])
+            .build();
+
+        let model = KMeans::fit(hyperparams, &observations, &mut rng);
+        assert_abs_diff_eq!(
+            model.centroids(),
+            &array![[1. / 3., 1. / 3.]],
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(model.inertia(), 4. / 3., epsilon = 1e-10);
+    }
+
+    #[test]
+    fn empty_clusters_are_relocated() {
+        // picking two of the duplicated observations as initial centroids empties a cluster
+        let observations = array![[0.], [0.], [0.], [0.], [5.], [10.]];
+
+        for strategy in &[
+            EmptyClusterStrategy::FarthestPoint,
+            EmptyClusterStrategy::RandomPoint,
+        ] {
+            for seed in 0..20 {
+                let mut rng = Isaac64Rng::seed_from_u64(seed);
+                let hyperparams = KMeansHyperParams::new(3).empty_cluster(*strategy).build();
+
+                let model = KMeans::fit(hyperparams, &observations, &mut rng);
+                assert!(model.centroids().iter().all(|x| x.is_finite()));
+
+                let mut labels = model.predict(&observations).to_vec();
+                labels.sort();
+                labels.dedup();
+                assert_eq!(labels, vec![0, 1, 2]);
+            }
+        }
+
+        // the farthest observation of the overloaded cluster becomes a centroid of its own
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let model = KMeans::fit(KMeansHyperParams::new(3).build(), &observations, &mut rng);
+        let mut centroids = model.centroids().column(0).to_vec();
+        centroids.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_abs_diff_eq!(
+            Array1::from(centroids),
+            array![0., 5., 10.],
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(model.inertia(), 0., epsilon = 1e-10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sample_weight_must_match_observations() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let hyperparams = KMeansHyperParams::new(1)
+            .sample_weight(array![1., 1.])
+            .build();
+        KMeans::fit(hyperparams, &array![[0.], [1.], [2.]], &mut rng);
+    }
+
     #[test]
     // An observation is closest to itself.
     fn nothing_is_closer_than_self() {
@@ -391,4 +544,22 @@ mod tests {
             memberships
         );
     }
+
+    #[test]
+    fn ties_are_assigned_to_the_lowest_index() {
+        // both observations are equidistant from the first two centroids
+        let centroids = array![[5., 5.], [-1., 0.], [1., 0.], [-1., 0.]];
+        let observations = array![[0., 0.], [0., 3.]];
+
+        for _ in 0..10 {
+            assert_eq!(
+                compute_cluster_memberships(&centroids, &observations),
+                array![1, 1]
+            );
+        }
+
+        let mut memberships = Array1::zeros(2);
+        update_cluster_memberships(&centroids, &observations, &mut memberships);
+        assert_eq!(memberships, array![1, 1]);
+    }
 }
@@ -0,0 +1,191 @@
+use ndarray::{Array1, Array2, ArrayBase, Data, Ix2};
+use ndarray_rand::rand::Rng;
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Strategies to handle empty clusters in the update step of the
+/// [K-means algorithm](struct.KMeans.html).
+///
+/// A cluster becomes empty when no observation with a positive weight is closer to its centroid
+/// than to any other centroid, typically on sparse or duplicated data. Its centroid is then
+/// undefined and is moved to one of the observations instead, so that `n_clusters` clusters are
+/// maintained.
+pub enum EmptyClusterStrategy {
+    /// Move the centroid to the observation farthest from the centroid of its cluster.
+    ///
+    /// The observation is the worst represented one, it is split off its cluster and lowers the
+    /// inertia the most.
+    FarthestPoint,
+    /// Move the centroid to an observation chosen uniformly at random.
+    RandomPoint,
+}
+
+impl EmptyClusterStrategy {
+    /// Move the centroid of each cluster in `empty_clusters` to a distinct observation and
+    /// assign the observation to it.
+    ///
+    /// Only observations with a positive weight which do not coincide with the centroid of their
+    /// cluster are candidates, moving a centroid onto another one would empty the cluster again.
+    /// Centroids are left untouched if there are not enough candidates.
+    pub(crate) fn relocate(
+        &self,
+        empty_clusters: &[usize],
+        // (n_centroids, n_features)
+        centroids: &mut Array2<f64>,
+        // (n_observations, n_features)
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        // (n_observations,)
+        cluster_memberships: &mut Array1<usize>,
+        // (n_observations,)
+        sample_weight: &Array1<f64>,
+        rng: &mut impl Rng,
+    ) {
+        let distances = observations
+            .genrows()
+            .into_iter()
+            .zip(cluster_memberships.iter())
+            .map(|(observation, cluster)| {
+                centroids
+                    .row(*cluster)
+                    .sq_l2_dist(&observation)
+                    .expect("Failed to compute distance")
+            })
+            .collect::<Vec<_>>();
+        let mut candidates = (0..observations.nrows())
+            .filter(|i| sample_weight[*i] > 0. && distances[*i] > 0.)
+            .collect::<Vec<_>>();
+
+        for cluster in empty_clusters {
+            if candidates.is_empty() {
+                return;
+            }
+
+            let position = match self {
+                EmptyClusterStrategy::FarthestPoint => {
+                    let mut farthest = 0;
+                    for (position, i) in candidates.iter().enumerate() {
+                        // a strict comparison keeps the first observation on ties
+                        if distances[*i] > distances[candidates[farthest]] {
+                            farthest = position;
+                        }
+                    }
+                    farthest
+                }
+                EmptyClusterStrategy::RandomPoint => rng.gen_range(0, candidates.len()),
+            };
+
+            // every observation fills at most one empty cluster
+            let observation = candidates.remove(position);
+            centroids
+                .row_mut(*cluster)
+                .assign(&observations.row(observation));
+            cluster_memberships[observation] = *cluster;
+        }
+    }
+}
+
+/// Return the clusters without any weight, either because no observation is assigned to them or
+/// because all of their observations have zero weight.
+pub(crate) fn empty_clusters(
+    n_clusters: usize,
+    // (n_observations,)
+    cluster_memberships: &Array1<usize>,
+    // (n_observations,)
+    sample_weight: &Array1<f64>,
+) -> Vec<usize> {
+    let mut cluster_weight = vec![0.; n_clusters];
+    for (cluster, weight) in cluster_memberships.iter().zip(sample_weight.iter()) {
+        cluster_weight[*cluster] += weight;
+    }
+
+    (0..n_clusters)
+        .filter(|cluster| cluster_weight[*cluster] <= 0.)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn empty_clusters_have_no_weight() {
+        let memberships = array![0, 0, 2, 2];
+        assert_eq!(
+            empty_clusters(4, &memberships, &array![1., 1., 1., 1.]),
+            vec![1, 3]
+        );
+        assert_eq!(
+            empty_clusters(4, &memberships, &array![1., 1., 0., 0.]),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn farthest_points_fill_empty_clusters() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let observations = array![[0.], [1.], [10.], [-4.], [2.]];
+        let mut centroids = array![[1.], [0.], [0.]];
+        let mut memberships = Array1::zeros(5);
+
+        EmptyClusterStrategy::FarthestPoint.relocate(
+            &[1, 2],
+            &mut centroids,
+            &observations,
+            &mut memberships,
+            &Array1::ones(5),
+            &mut rng,
+        );
+        assert_eq!(centroids, array![[1.], [10.], [-4.]]);
+        assert_eq!(memberships, array![0, 0, 1, 2, 0]);
+
+        // observations on their centroid are never chosen
+        let mut centroids = array![[0.], [5.]];
+        let mut memberships = Array1::zeros(3);
+        EmptyClusterStrategy::FarthestPoint.relocate(
+            &[1],
+            &mut centroids,
+            &array![[0.], [0.], [0.]],
+            &mut memberships,
+            &Array1::ones(3),
+            &mut rng,
+        );
+        assert_eq!(centroids, array![[0.], [5.]]);
+        assert_eq!(memberships, array![0, 0, 0]);
+    }
+
+    #[test]
+    fn random_points_fill_empty_clusters() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let observations = array![[0.], [1.], [10.], [-4.]];
+        let mut centroids = array![[2.], [0.], [0.], [0.]];
+        let mut memberships = Array1::zeros(4);
+        // the zero-weight observation is never chosen
+        let sample_weight = array![1., 1., 0., 1.];
+
+        EmptyClusterStrategy::RandomPoint.relocate(
+            &[1, 2, 3],
+            &mut centroids,
+            &observations,
+            &mut memberships,
+            &sample_weight,
+            &mut rng,
+        );
+
+        let mut relocated = centroids
+            .column(0)
+            .iter()
+            .skip(1)
+            .cloned()
+            .collect::<Vec<_>>();
+        relocated.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(relocated, vec![-4., 0., 1.]);
+        assert_eq!(memberships[2], 0);
+        for cluster in 1..4 {
+            assert_eq!(memberships.iter().filter(|m| **m == cluster).count(), 1);
+        }
+    }
+}
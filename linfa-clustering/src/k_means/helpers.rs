@@ -21,25 +21,36 @@ use ndarray::{Array1, ArrayBase, Data, Ix1};
 /// - the current mean (`current_mean`);
 /// - the number of observations we have seen so far (`n`).
 ///
+/// If the observations carry a weight `w`, the same reasoning gives a weighted mean
+/// when `n + 1` is replaced by the total weight `W` seen so far (including `w`):
+///
+/// new_mean = current_mean + (new_observation - current_mean) * w / W
+///
 /// We can store this information in a struct:
 pub(crate) struct IncrementalMean {
     pub current_mean: Array1<f64>,
     pub n_observations: usize,
+    pub total_weight: f64,
 }
 
 impl IncrementalMean {
-    pub fn new(first_observation: Array1<f64>) -> Self {
+    pub fn new(first_observation: Array1<f64>, weight: f64) -> Self {
         Self {
             current_mean: first_observation,
             n_observations: 1,
+            total_weight: weight,
         }
     }
 
-    pub fn update(&mut self, new_observation: &ArrayBase<impl Data<Elem = f64>, Ix1>) {
+    pub fn update(&mut self, new_observation: &ArrayBase<impl Data<Elem = f64>, Ix1>, weight: f64) {
         self.n_observations += 1;
-        let shift =
-            (new_observation - &self.current_mean).mapv_into(|x| x / self.n_observations as f64);
-        self.current_mean += &shift;
+        self.total_weight += weight;
+        // as long as all observations have zero weight the mean is left untouched
+        if self.total_weight > 0. {
+            let shift = (new_observation - &self.current_mean)
+                .mapv_into(|x| x * weight / self.total_weight);
+            self.current_mean += &shift;
+        }
     }
 }
 
@@ -47,7 +58,7 @@ impl IncrementalMean {
 mod tests {
     use super::*;
     use approx::assert_abs_diff_eq;
-    use ndarray::{Array, Array2, Axis};
+    use ndarray::{array, Array, Array2, Axis};
     use ndarray_rand::rand_distr::Uniform;
     use ndarray_rand::RandomExt;
 
@@ -64,14 +75,14 @@ mod tests {
         for observation in observations.genrows().into_iter() {
             // If it has already been initialised, update it
             if let Some(mean) = incremental_mean.as_mut() {
-                mean.update(&observation);
+                mean.update(&observation, 1.);
             // Otherwise, initialise it
             // Given that this branch is used only once, this is quite wasteful,
             // but it's easier to read... hence ¯\_(ツ)_/¯
             } else {
                 // `.to_owned` takes `observation`, which has type `ArrayView1`,
                 // and returns an `Array1`, performing an allocation.
-                incremental_mean = Some(IncrementalMean::new(observation.to_owned()));
+                incremental_mean = Some(IncrementalMean::new(observation.to_owned(), 1.));
             }
         }
 
@@ -85,4 +96,19 @@ mod tests {
             epsilon = 1e-5
         );
     }
+
+    #[test]
+    fn weighted_incremental_mean() {
+        let observations = array![[0., 1.], [2., 3.], [4., -1.], [-2., 5.]];
+        let weights = array![0., 3., 1., 0.5];
+
+        let mut incremental_mean = IncrementalMean::new(observations.row(0).to_owned(), 0.);
+        for (observation, weight) in observations.genrows().into_iter().zip(&weights).skip(1) {
+            incremental_mean.update(&observation, *weight);
+        }
+
+        let expected = observations.t().dot(&weights) / weights.sum();
+        assert_abs_diff_eq!(incremental_mean.total_weight, 4.5);
+        assert_abs_diff_eq!(incremental_mean.current_mean, expected, epsilon = 1e-10);
+    }
 }
@@ -1,3 +1,6 @@
+use crate::k_means::empty_cluster::EmptyClusterStrategy;
+use crate::k_means::init::KMeansInit;
+use ndarray::Array1;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -14,6 +17,13 @@ pub struct KMeansHyperParams {
     max_n_iterations: u64,
     /// The number of clusters we will be looking for in the training dataset.
     n_clusters: usize,
+    /// The strategy used to choose the initial centroids.
+    init: KMeansInit,
+    /// The strategy used to move the centroids of empty clusters.
+    empty_cluster: EmptyClusterStrategy,
+    /// The weight of each observation in the centroid update step and the
+    /// inertia, all observations are weighted equally if `None`.
+    sample_weight: Option<Array1<f64>>,
 }
 
 /// An helper struct used to construct a set of [valid hyperparameters](struct.KMeansHyperParams.html) for
@@ -22,6 +32,9 @@ pub struct KMeansHyperParamsBuilder {
     tolerance: f64,
     max_n_iterations: u64,
     n_clusters: usize,
+    init: KMeansInit,
+    empty_cluster: EmptyClusterStrategy,
+    sample_weight: Option<Array1<f64>>,
 }
 
 impl KMeansHyperParamsBuilder {
@@ -45,12 +58,48 @@ impl KMeansHyperParamsBuilder {
         self
     }
 
+    /// Set the value of `init`.
+    ///
+    /// The strategy used to choose the initial centroids, see [`KMeansInit`](enum.KMeansInit.html).
+    pub fn init(mut self, init: KMeansInit) -> Self {
+        self.init = init;
+        self
+    }
+
+    /// Set the value of `empty_cluster`.
+    ///
+    /// The strategy used to move the centroid of a cluster which lost all of its
+    /// observations in the assignment step, see
+    /// [`EmptyClusterStrategy`](enum.EmptyClusterStrategy.html).
+    pub fn empty_cluster(mut self, empty_cluster: EmptyClusterStrategy) -> Self {
+        self.empty_cluster = empty_cluster;
+        self
+    }
+
+    /// Set the value of `sample_weight`.
+    ///
+    /// Each centroid becomes the weighted mean of the observations in its
+    /// cluster, `c_k = sum(w_i * x_i) / sum(w_i)`, and the inertia is weighted
+    /// accordingly. The weights must be non-negative and have one entry per
+    /// observation passed to `KMeans::fit`.
+    pub fn sample_weight(mut self, sample_weight: Array1<f64>) -> Self {
+        self.sample_weight = Some(sample_weight);
+        self
+    }
+
     /// Return an instance of `KMeansHyperParams` after
     /// having performed validation checks on all the specified hyperparamters.
     ///
     /// **Panics** if any of the validation checks fails.
     pub fn build(self) -> KMeansHyperParams {
-        KMeansHyperParams::build(self.n_clusters, self.tolerance, self.max_n_iterations)
+        KMeansHyperParams::build(
+            self.n_clusters,
+            self.tolerance,
+            self.max_n_iterations,
+            self.init,
+            self.empty_cluster,
+            self.sample_weight,
+        )
     }
 }
 
@@ -62,18 +111,27 @@ impl KMeansHyperParams {
     ///   after a training iteration is lower or equal than `tolerance`;
     /// * we exit the training loop when the number of training iterations
     ///   exceeds `max_n_iterations` even if the `tolerance` convergence
-    ///   condition has not been met.
+    ///   condition has not been met;
+    /// * the initial centroids are chosen with the `init` strategy;
+    /// * the centroids of empty clusters are moved with the `empty_cluster` strategy;
+    /// * the observations are weighted by `sample_weight` in the update step.
     ///
     /// `n_clusters` is mandatory.
     ///
     /// Defaults are provided if optional parameters are not specified:
     /// * `tolerance = 1e-4`;
-    /// * `max_n_iterations = 300`.
+    /// * `max_n_iterations = 300`;
+    /// * `init = KMeansInit::Random`;
+    /// * `empty_cluster = EmptyClusterStrategy::FarthestPoint`;
+    /// * `sample_weight = None`, all observations are weighted equally.
     pub fn new(n_clusters: usize) -> KMeansHyperParamsBuilder {
         KMeansHyperParamsBuilder {
             tolerance: 1e-4,
             max_n_iterations: 300,
             n_clusters,
+            init: KMeansInit::Random,
+            empty_cluster: EmptyClusterStrategy::FarthestPoint,
+            sample_weight: None,
         }
     }
 
@@ -96,7 +154,29 @@ impl KMeansHyperParams {
         self.n_clusters
     }
 
-    fn build(n_clusters: usize, tolerance: f64, max_n_iterations: u64) -> Self {
+    /// The strategy used to choose the initial centroids.
+    pub fn init(&self) -> KMeansInit {
+        self.init
+    }
+
+    /// The strategy used to move the centroids of empty clusters.
+    pub fn empty_cluster(&self) -> EmptyClusterStrategy {
+        self.empty_cluster
+    }
+
+    /// The weight of each observation, `None` if all observations are weighted equally.
+    pub fn sample_weight(&self) -> Option<&Array1<f64>> {
+        self.sample_weight.as_ref()
+    }
+
+    fn build(
+        n_clusters: usize,
+        tolerance: f64,
+        max_n_iterations: u64,
+        init: KMeansInit,
+        empty_cluster: EmptyClusterStrategy,
+        sample_weight: Option<Array1<f64>>,
+    ) -> Self {
         if max_n_iterations == 0 {
             panic!("`max_n_iterations` cannot be 0!");
         }
@@ -106,10 +186,18 @@ impl KMeansHyperParams {
         if n_clusters == 0 {
             panic!("`n_clusters` cannot be 0!");
         }
+        if let Some(sample_weight) = &sample_weight {
+            if sample_weight.iter().any(|w| !(*w >= 0. && w.is_finite())) {
+                panic!("`sample_weight` must be non-negative and finite!");
+            }
+        }
         KMeansHyperParams {
             tolerance,
             max_n_iterations,
             n_clusters,
+            init,
+            empty_cluster,
+            sample_weight,
         }
     }
 }
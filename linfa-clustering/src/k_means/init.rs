@@ -0,0 +1,248 @@
+use crate::k_means::algorithm::closest_centroid;
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2, Zip};
+use ndarray_rand::rand;
+use ndarray_rand::rand::distributions::{Distribution, WeightedIndex};
+use ndarray_rand::rand::Rng;
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// Strategies to choose the initial centroids of the [K-means algorithm](struct.KMeans.html).
+///
+/// A good initialisation reduces the number of iterations needed to converge and the chance
+/// of getting stuck in a poor local minimum.
+pub enum KMeansInit {
+    /// Pick `n_clusters` distinct observations uniformly at random.
+    Random,
+    /// K-means++: pick the first centroid uniformly at random, every further centroid is an
+    /// observation chosen with probability proportional to its squared distance to the
+    /// closest centroid picked so far.
+    ///
+    /// The expected inertia of the initial centroids is within `O(log n_clusters)` of the
+    /// optimum, at the price of `n_clusters` sequential passes over the observations.
+    KMeansPlusPlus,
+    /// K-means||, the scalable variant of K-means++.
+    ///
+    /// Instead of one centroid per pass, `O(log n_observations)` rounds oversample
+    /// `2 * n_clusters` candidates each, independently for every observation.
+    /// The candidates are then weighted by the number of observations closest to them and
+    /// reduced to `n_clusters` centroids with a weighted K-means++.
+    KMeansParallel,
+}
+
+impl KMeansInit {
+    /// Choose `n_clusters` initial centroids among the rows of `observations`.
+    pub(crate) fn centroids(
+        &self,
+        n_clusters: usize,
+        observations: &ArrayBase<impl Data<Elem = f64> + Sync, Ix2>,
+        rng: &mut impl Rng,
+    ) -> Array2<f64> {
+        let indices = match self {
+            KMeansInit::Random => {
+                rand::seq::index::sample(rng, observations.nrows(), n_clusters).into_vec()
+            }
+            KMeansInit::KMeansPlusPlus => {
+                let weights = Array1::ones(observations.nrows());
+                k_means_plus_plus(n_clusters, observations, &weights, rng)
+            }
+            KMeansInit::KMeansParallel => {
+                let candidates = k_means_parallel_candidates(n_clusters, observations, rng);
+                let candidate_observations = observations.select(Axis(0), &candidates);
+
+                // weight each candidate by the number of observations closest to it
+                let mut weights = Array1::zeros(candidates.len());
+                for observation in observations.genrows() {
+                    weights[closest_centroid(&candidate_observations, &observation)] += 1.;
+                }
+
+                k_means_plus_plus(n_clusters, &candidate_observations, &weights, rng)
+                    .into_iter()
+                    .map(|i| candidates[i])
+                    .collect()
+            }
+        };
+        observations.select(Axis(0), &indices)
+    }
+}
+
+/// Pick `n_clusters` distinct rows of `observations` with (weighted) K-means++ and return
+/// their indices.
+fn k_means_plus_plus(
+    n_clusters: usize,
+    observations: &ArrayBase<impl Data<Elem = f64> + Sync, Ix2>,
+    weights: &Array1<f64>,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let n_observations = observations.nrows();
+    assert!(
+        n_clusters <= n_observations,
+        "`n_clusters` cannot be larger than the number of observations!"
+    );
+
+    let first = match WeightedIndex::new(weights.iter()) {
+        Ok(distribution) => distribution.sample(rng),
+        Err(_) => rng.gen_range(0, n_observations),
+    };
+    let mut chosen = vec![first];
+    let mut min_distances = Array1::from_elem(n_observations, f64::INFINITY);
+    update_min_distances(&mut min_distances, observations, &observations.row(first));
+
+    while chosen.len() < n_clusters {
+        let probabilities = &min_distances * weights;
+        let next = match WeightedIndex::new(probabilities.iter()) {
+            Ok(distribution) => distribution.sample(rng),
+            // every observation with a positive weight coincides with a chosen centroid
+            Err(_) => {
+                let remaining = (0..n_observations)
+                    .filter(|i| !chosen.contains(i))
+                    .collect::<Vec<_>>();
+                remaining[rng.gen_range(0, remaining.len())]
+            }
+        };
+        chosen.push(next);
+        update_min_distances(&mut min_distances, observations, &observations.row(next));
+    }
+    chosen
+}
+
+/// Oversample at least `n_clusters` distinct candidate centroids with the rounds of K-means||
+/// and return their indices.
+fn k_means_parallel_candidates(
+    n_clusters: usize,
+    observations: &ArrayBase<impl Data<Elem = f64> + Sync, Ix2>,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let n_observations = observations.nrows();
+    assert!(
+        n_clusters <= n_observations,
+        "`n_clusters` cannot be larger than the number of observations!"
+    );
+    let oversampling_factor = 2. * n_clusters as f64;
+    let n_rounds = (n_observations as f64).ln().ceil() as usize;
+
+    let first = rng.gen_range(0, n_observations);
+    let mut candidates = vec![first];
+    let mut min_distances = Array1::from_elem(n_observations, f64::INFINITY);
+    update_min_distances(&mut min_distances, observations, &observations.row(first));
+
+    for _ in 0..n_rounds {
+        let cost = min_distances.sum();
+        if cost <= 0. {
+            break;
+        }
+        // candidates have a distance of zero, hence they are never sampled twice
+        let sampled = min_distances
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| rng.gen::<f64>() < oversampling_factor * *distance / cost)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        for i in sampled {
+            update_min_distances(&mut min_distances, observations, &observations.row(i));
+            candidates.push(i);
+        }
+    }
+
+    // too few candidates, fill up with random observations
+    if candidates.len() < n_clusters {
+        let remaining = (0..n_observations)
+            .filter(|i| !candidates.contains(i))
+            .collect::<Vec<_>>();
+        let missing = n_clusters - candidates.len();
+        for i in rand::seq::index::sample(rng, remaining.len(), missing).into_iter() {
+            candidates.push(remaining[i]);
+        }
+    }
+    candidates
+}
+
+/// Lower the squared distance of each observation to its closest centroid given a new
+/// `centroid`, in parallel over the observations.
+fn update_min_distances(
+    min_distances: &mut Array1<f64>,
+    observations: &ArrayBase<impl Data<Elem = f64> + Sync, Ix2>,
+    centroid: &ArrayBase<impl Data<Elem = f64> + Sync, Ix1>,
+) {
+    Zip::from(min_distances)
+        .and(observations.genrows())
+        .par_apply(|min_distance, observation| {
+            let distance = observation
+                .sq_l2_dist(centroid)
+                .expect("Failed to compute distance");
+            if distance < *min_distance {
+                *min_distance = distance;
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_blobs;
+    use ndarray::{array, s};
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    const STRATEGIES: [KMeansInit; 3] = [
+        KMeansInit::Random,
+        KMeansInit::KMeansPlusPlus,
+        KMeansInit::KMeansParallel,
+    ];
+
+    #[test]
+    fn centroids_are_distinct_observations() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [10., 10.], [-10., 10.]];
+        let observations = generate_blobs(50, &centroids, &mut rng);
+
+        for init in &STRATEGIES {
+            let initial = init.centroids(5, &observations, &mut rng);
+            assert_eq!(initial.dim(), (5, 2));
+            for (i, centroid) in initial.genrows().into_iter().enumerate() {
+                assert!(observations
+                    .genrows()
+                    .into_iter()
+                    .any(|row| row == centroid));
+                for other in initial.genrows().into_iter().skip(i + 1) {
+                    assert_ne!(centroid, other);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn one_centroid_per_separated_blob() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [1000., 0.], [0., 1000.], [1000., 1000.]];
+        let observations = generate_blobs(100, &centroids, &mut rng);
+
+        for init in &[KMeansInit::KMeansPlusPlus, KMeansInit::KMeansParallel] {
+            for _ in 0..10 {
+                let initial = init.centroids(4, &observations, &mut rng);
+                let mut blobs = initial
+                    .genrows()
+                    .into_iter()
+                    .map(|c| closest_centroid(&centroids, &c))
+                    .collect::<Vec<_>>();
+                blobs.sort();
+                assert_eq!(blobs, vec![0, 1, 2, 3]);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicated_observations() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let mut observations: Array2<f64> = Array2::zeros((20, 2));
+        observations.slice_mut(s![10.., ..]).fill(1.);
+
+        // more clusters than distinct observations, both are still picked
+        for init in &[KMeansInit::KMeansPlusPlus, KMeansInit::KMeansParallel] {
+            let initial = init.centroids(3, &observations, &mut rng);
+            assert_eq!(initial.dim(), (3, 2));
+            assert!(initial.iter().any(|x| *x == 0.));
+            assert!(initial.iter().any(|x| *x == 1.));
+        }
+    }
+}
@@ -1,6 +1,10 @@
 mod algorithm;
+mod empty_cluster;
 mod helpers;
 mod hyperparameters;
+mod init;
 
 pub use algorithm::*;
+pub use empty_cluster::EmptyClusterStrategy;
 pub use hyperparameters::*;
+pub use init::*;
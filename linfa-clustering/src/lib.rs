@@ -22,11 +22,23 @@
 extern crate ndarray;
 extern crate ndarray_linalg;
 
+mod affinity_propagation;
+mod bisecting_k_means;
 mod dbscan;
+mod fuzzy_c_means;
+mod hdbscan;
 #[allow(clippy::new_ret_no_self)]
 mod k_means;
+mod mean_shift;
+mod optics;
 mod utils;
 
+pub use affinity_propagation::*;
+pub use bisecting_k_means::*;
 pub use dbscan::*;
+pub use fuzzy_c_means::*;
+pub use hdbscan::*;
 pub use k_means::*;
+pub use mean_shift::*;
+pub use optics::*;
 pub use utils::*;
@@ -0,0 +1,383 @@
+use crate::mean_shift::hyperparameters::{MeanShiftHyperParams, MeanShiftKernel};
+use ndarray::{s, Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2};
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Mean Shift is a mode-seeking clustering algorithm: every observation is
+/// repeatedly moved to the mean of its neighbors within the `bandwidth` until
+/// it converges to a local maximum of the density, a mode. Observations which
+/// converge to the same mode form a cluster.
+///
+/// In contrast to K-means the number of clusters is not specified, it is
+/// determined by the data and the bandwidth.
+///
+/// ## The algorithm
+///
+/// - Each observation is used as a seed and shifted to the (weighted) mean of
+///   the observations in its neighborhood until the shift is smaller than
+///   `tolerance * bandwidth` or `max_n_iterations` is reached
+/// - The converged seeds are sorted by the number of observations within their
+///   bandwidth, a seed becomes a cluster center unless a center with more
+///   neighbors lies within the bandwidth
+/// - Each observation is assigned to its closest cluster center
+///
+/// Each shift computes the distance to all observations, training is
+/// therefore `O(N^2)` per iteration.
+///
+/// ## Tutorial
+///
+/// ```
+/// use linfa_clustering::{MeanShiftHyperParams, MeanShift, generate_blobs};
+/// use ndarray::array;
+/// use ndarray_rand::rand::SeedableRng;
+/// use rand_isaac::Isaac64Rng;
+///
+/// let mut rng = Isaac64Rng::seed_from_u64(42);
+/// let expected_centroids = array![[10., 10.], [1., 12.], [20., 30.], [-20., 30.]];
+/// let observations = generate_blobs(100, &expected_centroids, &mut rng);
+///
+/// // The bandwidth is estimated from the observations if it is not specified
+/// let hyperparams = MeanShiftHyperParams::new().build();
+/// let model = MeanShift::fit(hyperparams, &observations);
+///
+/// // The number of clusters was determined by the algorithm
+/// let n_clusters = model.cluster_centers().nrows();
+/// let memberships = model.predict(&observations);
+/// assert_eq!(&memberships, model.labels());
+/// ```
+pub struct MeanShift {
+    hyperparameters: MeanShiftHyperParams,
+    bandwidth: f64,
+    cluster_centers: Array2<f64>,
+    labels: Array1<usize>,
+}
+
+impl MeanShift {
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `fit` finds the modes of the training data distribution.
+    ///
+    /// An instance of `MeanShift` is returned.
+    ///
+    /// **Panics** if `observations` is empty.
+    pub fn fit(
+        hyperparameters: MeanShiftHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> Self {
+        assert!(
+            observations.nrows() > 0,
+            "at least one observation is required"
+        );
+
+        let bandwidth = hyperparameters
+            .bandwidth()
+            .unwrap_or_else(|| median_bandwidth(observations));
+        let tolerance = hyperparameters.tolerance() * bandwidth;
+
+        let mut modes = observations
+            .genrows()
+            .into_iter()
+            .map(|seed| {
+                let mut mode = seed.to_owned();
+                for _ in 0..hyperparameters.max_n_iterations() {
+                    let shifted = shift(observations, &mode, bandwidth, hyperparameters.kernel());
+                    let distance = shifted.l2_dist(&mode).unwrap();
+                    mode = shifted;
+                    if distance < tolerance {
+                        break;
+                    }
+                }
+                let n_neighbors = count_neighbors(observations, &mode, bandwidth);
+                (mode, n_neighbors)
+            })
+            .collect::<Vec<_>>();
+
+        // modes with more neighbors absorb the modes within their bandwidth, the sort is
+        // stable to keep the result deterministic
+        modes.sort_by_key(|mode| Reverse(mode.1));
+        let mut centers: Vec<Array1<f64>> = Vec::new();
+        for (mode, _) in modes {
+            if centers
+                .iter()
+                .all(|center| center.l2_dist(&mode).unwrap() >= bandwidth)
+            {
+                centers.push(mode);
+            }
+        }
+
+        let mut cluster_centers = Array2::zeros((centers.len(), observations.ncols()));
+        for (mut row, center) in cluster_centers.genrows_mut().into_iter().zip(&centers) {
+            row.assign(center);
+        }
+
+        let labels = closest_centers(&cluster_centers, observations);
+
+        Self {
+            hyperparameters,
+            bandwidth,
+            cluster_centers,
+            labels,
+        }
+    }
+
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// `predict` returns, for each observation, the index of the closest cluster center.
+    ///
+    /// You can retrieve the cluster center associated to an index using the
+    /// [`cluster_centers` method](#method.cluster_centers).
+    pub fn predict(&self, observations: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> Array1<usize> {
+        closest_centers(&self.cluster_centers, observations)
+    }
+
+    /// Return the cluster centers, the modes found in the training data, as a 2-dimensional
+    /// matrix with shape `(n_clusters, n_features)`.
+    pub fn cluster_centers(&self) -> &Array2<f64> {
+        &self.cluster_centers
+    }
+
+    /// Return the index of the cluster center of each training observation, identical to
+    /// calling [`predict`](#method.predict) on the training data.
+    pub fn labels(&self) -> &Array1<usize> {
+        &self.labels
+    }
+
+    /// Return the bandwidth used for training, either the specified or the estimated one.
+    pub fn bandwidth(&self) -> f64 {
+        self.bandwidth
+    }
+
+    /// Return the hyperparameters used to train this Mean Shift model instance.
+    pub fn hyperparameters(&self) -> &MeanShiftHyperParams {
+        &self.hyperparameters
+    }
+}
+
+/// Estimate the bandwidth as the median of the pairwise distances between the observations.
+///
+/// This is the estimate used if no bandwidth is specified. If more than half of the pairs
+/// coincide the median is zero, the median of the positive distances is used instead. If all
+/// observations coincide a bandwidth of one is returned, they form a single cluster for any
+/// bandwidth.
+///
+/// All `n_observations * (n_observations - 1) / 2` distances are kept in memory.
+///
+/// **Panics** if `observations` is empty.
+pub fn median_bandwidth(observations: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> f64 {
+    let n_observations = observations.nrows();
+    assert!(n_observations > 0, "at least one observation is required");
+
+    let mut distances = Vec::with_capacity(n_observations * (n_observations - 1) / 2);
+    for (i, observation) in observations.genrows().into_iter().enumerate() {
+        for other in observations.slice(s![i + 1.., ..]).genrows() {
+            distances.push(other.l2_dist(&observation).unwrap());
+        }
+    }
+    distances.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    match median(&distances) {
+        Some(median) if median > 0. => median,
+        _ => {
+            let n_zeros = distances.iter().take_while(|x| **x <= 0.).count();
+            median(&distances[n_zeros..]).unwrap_or(1.)
+        }
+    }
+}
+
+/// Median of sorted values, `None` if there are none
+fn median(sorted: &[f64]) -> Option<f64> {
+    let n = sorted.len();
+    if n == 0 {
+        None
+    } else if n % 2 == 1 {
+        Some(sorted[n / 2])
+    } else {
+        Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.)
+    }
+}
+
+/// Estimate the bandwidth as the mean distance of each observation to its
+/// `quantile * n_observations`-th nearest neighbor (counting the observation itself).
+///
+/// Smaller quantiles result in smaller bandwidths and therefore more clusters. In contrast to
+/// [`median_bandwidth`](fn.median_bandwidth.html) the estimate adapts to the size of the
+/// clusters instead of the distances between them, it can be passed to
+/// [`MeanShiftHyperParamsBuilder::bandwidth`](struct.MeanShiftHyperParamsBuilder.html#method.bandwidth).
+/// The estimate is zero if the nearest neighbors coincide.
+///
+/// **Panics** if `quantile` is not in `(0, 1]` or `observations` is empty.
+pub fn estimate_bandwidth(
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    quantile: f64,
+) -> f64 {
+    assert!(
+        quantile > 0. && quantile <= 1.,
+        "`quantile` must be in the interval (0, 1]"
+    );
+    let n_observations = observations.nrows();
+    assert!(n_observations > 0, "at least one observation is required");
+
+    let n_neighbors = ((n_observations as f64 * quantile) as usize).max(1);
+    let total = observations
+        .genrows()
+        .into_iter()
+        .map(|observation| {
+            let mut distances = observations
+                .genrows()
+                .into_iter()
+                .map(|other| other.l2_dist(&observation).unwrap())
+                .collect::<Vec<_>>();
+            distances.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            distances[n_neighbors - 1]
+        })
+        .sum::<f64>();
+
+    total / n_observations as f64
+}
+
+fn closest_centers(
+    cluster_centers: &Array2<f64>,
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+) -> Array1<usize> {
+    observations.map_axis(Axis(1), |observation| {
+        cluster_centers
+            .genrows()
+            .into_iter()
+            .map(|center| center.sq_l2_dist(&observation).unwrap())
+            .enumerate()
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .map(|(index, _)| index)
+            .unwrap()
+    })
+}
+
+/// Move `point` to the mean of the observations weighted by the kernel
+fn shift(
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    point: &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    bandwidth: f64,
+    kernel: MeanShiftKernel,
+) -> Array1<f64> {
+    let mut sum = Array1::zeros(point.len());
+    let mut total_weight = 0.;
+    for observation in observations.genrows() {
+        let distance = observation.l2_dist(point).unwrap();
+        let weight = match kernel {
+            MeanShiftKernel::Flat if distance <= bandwidth => 1.,
+            MeanShiftKernel::Flat => continue,
+            MeanShiftKernel::Gaussian => (-0.5 * (distance / bandwidth).powi(2)).exp(),
+        };
+        sum.scaled_add(weight, &observation);
+        total_weight += weight;
+    }
+
+    // a point without any neighbors does not move
+    if total_weight > 0. {
+        sum / total_weight
+    } else {
+        point.to_owned()
+    }
+}
+
+fn count_neighbors(
+    observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    point: &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    bandwidth: f64,
+) -> usize {
+    observations
+        .genrows()
+        .into_iter()
+        .filter(|observation| observation.l2_dist(point).unwrap() <= bandwidth)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate_blobs;
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, s};
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    fn check_blobs(model: &MeanShift, observations: &Array2<f64>, centroids: &Array2<f64>) {
+        assert_eq!(model.cluster_centers().nrows(), centroids.nrows());
+
+        // every blob is a single cluster, whose center is close to the blob centroid
+        let memberships = model.predict(observations);
+        assert_eq!(&memberships, model.labels());
+        for (i, centroid) in centroids.genrows().into_iter().enumerate() {
+            let blob = memberships.slice(s![i * 100..(i + 1) * 100]);
+            assert!(blob.iter().all(|x| *x == blob[0]));
+
+            let center = model.cluster_centers().row(blob[0]);
+            assert!(center.l2_dist(&centroid).unwrap() < 0.5);
+        }
+    }
+
+    #[test]
+    fn finds_number_of_blobs() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [10., 10.], [-10., 10.]];
+        let observations = generate_blobs(100, &centroids, &mut rng);
+
+        let bandwidth = estimate_bandwidth(&observations, 0.3);
+        let params = MeanShiftHyperParams::new().bandwidth(bandwidth).build();
+        let model = MeanShift::fit(params, &observations);
+        check_blobs(&model, &observations, &centroids);
+    }
+
+    #[test]
+    fn median_of_pairwise_distances() {
+        // the pairwise distances are 1, 2, 3, 3, 5 and 6
+        let observations = array![[0.], [1.], [3.], [6.]];
+        assert_abs_diff_eq!(median_bandwidth(&observations), 3.);
+
+        let model = MeanShift::fit(MeanShiftHyperParams::new().build(), &observations);
+        assert_abs_diff_eq!(model.bandwidth(), 3.);
+
+        // most pairs coincide, the median of the positive distances is used
+        let observations = array![[1., 1.], [1., 1.], [1., 1.], [1., 1.], [1., 1.], [4., 5.]];
+        assert_abs_diff_eq!(median_bandwidth(&observations), 5.);
+    }
+
+    #[test]
+    fn coinciding_observations() {
+        let observations = Array2::ones((10, 2));
+        assert_abs_diff_eq!(median_bandwidth(&observations), 1.);
+        assert_abs_diff_eq!(median_bandwidth(&observations.slice(s![..1, ..])), 1.);
+
+        let params = MeanShiftHyperParams::new()
+            .kernel(MeanShiftKernel::Gaussian)
+            .build();
+        let model = MeanShift::fit(params, &observations);
+        assert_eq!(model.cluster_centers(), &Array2::ones((1, 2)));
+        assert!(model.labels().iter().all(|x| *x == 0));
+    }
+
+    #[test]
+    fn gaussian_kernel() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [10., 10.], [-10., 10.], [0., 20.]];
+        let observations = generate_blobs(100, &centroids, &mut rng);
+
+        let params = MeanShiftHyperParams::new()
+            .bandwidth(2.)
+            .kernel(MeanShiftKernel::Gaussian)
+            .build();
+        let model = MeanShift::fit(params, &observations);
+        check_blobs(&model, &observations, &centroids);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_bandwidth() {
+        MeanShiftHyperParams::new().bandwidth(0.).build();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_tolerance() {
+        MeanShiftHyperParams::new().tolerance(0.).build();
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+/// How the neighbors of a point are weighted when computing their mean
+pub enum MeanShiftKernel {
+    /// All observations within the bandwidth have the same weight
+    Flat,
+    /// Observations are weighted by a normal density with the bandwidth as
+    /// standard deviation
+    Gaussian,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The set of hyperparameters that can be specified for the execution of
+/// the [Mean Shift algorithm](struct.MeanShift.html).
+pub struct MeanShiftHyperParams {
+    /// Radius of the neighborhood used to compute the mean, estimated from the
+    /// data if not specified.
+    bandwidth: Option<f64>,
+    /// How the neighbors are weighted.
+    kernel: MeanShiftKernel,
+    /// We stop shifting a point when the number of iterations exceeds
+    /// `max_n_iterations` even if it has not converged.
+    max_n_iterations: u64,
+    /// A point has converged once it moves less than `tolerance * bandwidth`
+    /// in an iteration.
+    tolerance: f64,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters](struct.MeanShiftHyperParams.html) for
+/// the [Mean Shift algorithm](struct.MeanShift.html) (using the builder pattern).
+pub struct MeanShiftHyperParamsBuilder {
+    bandwidth: Option<f64>,
+    kernel: MeanShiftKernel,
+    max_n_iterations: u64,
+    tolerance: f64,
+}
+
+impl MeanShiftHyperParamsBuilder {
+    /// Set the value of `bandwidth`.
+    ///
+    /// The radius of the neighborhood used to compute the mean, it determines
+    /// how close modes can be while still being separate clusters. If it is not set
+    /// the bandwidth is estimated with [`median_bandwidth`](fn.median_bandwidth.html).
+    pub fn bandwidth(mut self, bandwidth: f64) -> Self {
+        self.bandwidth = Some(bandwidth);
+        self
+    }
+
+    /// Set the kernel used to weight the neighbors of a point.
+    pub fn kernel(mut self, kernel: MeanShiftKernel) -> Self {
+        self.kernel = kernel;
+        self
+    }
+
+    /// Set the value of `max_n_iterations`.
+    ///
+    /// We stop shifting a point when the number of iterations exceeds
+    /// `max_n_iterations` even if it has not converged.
+    pub fn max_n_iterations(mut self, max_n_iterations: u64) -> Self {
+        self.max_n_iterations = max_n_iterations;
+        self
+    }
+
+    /// Set the value of `tolerance`.
+    ///
+    /// A point has converged once it moves less than `tolerance * bandwidth`
+    /// in an iteration, the tolerance is therefore relative to the bandwidth.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Return an instance of `MeanShiftHyperParams` after
+    /// having performed validation checks on all the specified hyperparamters.
+    ///
+    /// **Panics** if any of the validation checks fails.
+    pub fn build(self) -> MeanShiftHyperParams {
+        MeanShiftHyperParams::build(
+            self.bandwidth,
+            self.kernel,
+            self.max_n_iterations,
+            self.tolerance,
+        )
+    }
+}
+
+impl MeanShiftHyperParams {
+    /// `new` lets us configure our training algorithm parameters, all of them are optional.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `bandwidth` is estimated from the observations;
+    /// * `kernel = MeanShiftKernel::Flat`;
+    /// * `max_n_iterations = 300`;
+    /// * `tolerance = 1e-3`.
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> MeanShiftHyperParamsBuilder {
+        MeanShiftHyperParamsBuilder {
+            bandwidth: None,
+            kernel: MeanShiftKernel::Flat,
+            max_n_iterations: 300,
+            tolerance: 1e-3,
+        }
+    }
+
+    /// The radius of the neighborhood used to compute the mean, `None` if it is estimated
+    /// from the observations.
+    pub fn bandwidth(&self) -> Option<f64> {
+        self.bandwidth
+    }
+
+    /// How the neighbors of a point are weighted.
+    pub fn kernel(&self) -> MeanShiftKernel {
+        self.kernel
+    }
+
+    /// We stop shifting a point when the number of iterations exceeds
+    /// `max_n_iterations` even if it has not converged.
+    pub fn max_n_iterations(&self) -> u64 {
+        self.max_n_iterations
+    }
+
+    /// A point has converged once it moves less than `tolerance * bandwidth`
+    /// in an iteration.
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    fn build(
+        bandwidth: Option<f64>,
+        kernel: MeanShiftKernel,
+        max_n_iterations: u64,
+        tolerance: f64,
+    ) -> Self {
+        if max_n_iterations == 0 {
+            panic!("`max_n_iterations` cannot be 0!");
+        }
+        if tolerance <= 0. || tolerance.is_nan() {
+            panic!("`tolerance` must be greater than 0!");
+        }
+        if let Some(bandwidth) = bandwidth {
+            if bandwidth <= 0. || bandwidth.is_nan() {
+                panic!("`bandwidth` must be greater than 0!");
+            }
+        }
+        MeanShiftHyperParams {
+            bandwidth,
+            kernel,
+            max_n_iterations,
+            tolerance,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use crate::optics::hyperparameters::OpticsHyperParams;
+use ndarray::{Array1, ArrayBase, Data, Ix2};
+use ndarray_stats::DeviationExt;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// OPTICS (Ordering Points To Identify the Clustering Structure) generalises
+/// DBSCAN to all distance tolerances up to `max_eps` at once. Instead of a
+/// clustering it produces an ordering of the observations together with their
+/// reachability distances, the reachability plot. Valleys in the plot are
+/// clusters, the depth of a valley corresponds to the density of a cluster.
+///
+/// Cutting the reachability plot at a tolerance `eps` yields the same clusters
+/// of core points as running DBSCAN with this tolerance, without recomputing
+/// any distances.
+///
+/// We provide an implementation of the O(N^2) algorithm, more details can be
+/// found in the next section or in
+/// [Ankerst et al. (1999)](https://doi.org/10.1145/304181.304187).
+///
+/// ## The algorithm
+///
+/// - The core distance of each point is the distance to its `min_points`-th
+///   nearest neighbor (counting the point itself), if it is below `max_eps`
+/// - Starting with the first observation, the algorithm repeatedly visits the
+///   unvisited observation with the smallest reachability distance
+/// - Whenever a core point is visited the reachability distance of its
+///   unvisited neighbors is lowered to the maximum of the core distance and their
+///   distance to the core point
+///
+/// ## Tutorial
+///
+/// ```
+/// use linfa_clustering::{OpticsHyperParams, Optics, generate_blobs};
+/// use ndarray::array;
+/// use ndarray_rand::rand::SeedableRng;
+/// use rand_isaac::Isaac64Rng;
+///
+/// let mut rng = Isaac64Rng::seed_from_u64(42);
+/// let expected_centroids = array![[0., 1.], [-10., 20.], [-1., 10.]];
+/// let observations = generate_blobs(100, &expected_centroids, &mut rng);
+///
+/// let hyperparams = OpticsHyperParams::new(5).max_eps(5.).build();
+/// let analysis = Optics::predict(&hyperparams, &observations);
+///
+/// // The reachability plot, the reachability distances in the order of the analysis
+/// let plot = analysis
+///     .ordering()
+///     .iter()
+///     .map(|i| analysis.reachability_distances()[*i])
+///     .collect::<Vec<_>>();
+///
+/// // Points are `None` if noise `Some(id)` if belonging to a cluster.
+/// let clusters = analysis.extract_dbscan(1.);
+/// ```
+pub struct Optics;
+
+impl Optics {
+    /// Given an input matrix `observations`, with shape `(n_observations, n_features)`,
+    /// compute the ordering and reachability distances of the observations.
+    pub fn predict(
+        hyperparameters: &OpticsHyperParams,
+        observations: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+    ) -> OpticsAnalysis {
+        let n_observations = observations.nrows();
+        let max_eps = hyperparameters.max_eps();
+        let distance =
+            |i: usize, j: usize| observations.row(i).l2_dist(&observations.row(j)).unwrap();
+
+        let core_distances = (0..n_observations)
+            .map(|i| {
+                let mut distances = (0..n_observations)
+                    .map(|j| distance(i, j))
+                    .collect::<Vec<_>>();
+                distances.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                match distances.get(hyperparameters.minimum_points() - 1) {
+                    Some(&dist) if dist < max_eps => dist,
+                    _ => f64::INFINITY,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut reachability = vec![f64::INFINITY; n_observations];
+        let mut processed = vec![false; n_observations];
+        let mut ordering = Vec::with_capacity(n_observations);
+        for _ in 0..n_observations {
+            // the unprocessed observation with the smallest reachability, the first one on ties
+            let point = (0..n_observations)
+                .filter(|i| !processed[*i])
+                .fold(None, |best: Option<usize>, i| match best {
+                    Some(best) if reachability[best] <= reachability[i] => Some(best),
+                    _ => Some(i),
+                })
+                .unwrap();
+            processed[point] = true;
+            ordering.push(point);
+
+            if core_distances[point].is_infinite() {
+                continue;
+            }
+            for neighbor in (0..n_observations).filter(|i| !processed[*i]) {
+                let dist = distance(point, neighbor);
+                if dist < max_eps {
+                    let reach = dist.max(core_distances[point]);
+                    reachability[neighbor] = reachability[neighbor].min(reach);
+                }
+            }
+        }
+
+        OpticsAnalysis {
+            ordering,
+            reachability,
+            core_distances,
+            max_eps,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The ordering and reachability distances computed by [OPTICS](struct.Optics.html)
+pub struct OpticsAnalysis {
+    ordering: Vec<usize>,
+    reachability: Vec<f64>,
+    core_distances: Vec<f64>,
+    max_eps: f64,
+}
+
+impl OpticsAnalysis {
+    /// Return the indices of the observations in the order in which they were visited
+    pub fn ordering(&self) -> &[usize] {
+        &self.ordering
+    }
+
+    /// Return the reachability distance of each observation, indexed by observation
+    ///
+    /// The reachability distance is infinite for observations which could not be reached from
+    /// a core point within `max_eps`, in particular for the first observation of each group.
+    /// Index with `ordering()` to obtain the reachability plot.
+    pub fn reachability_distances(&self) -> &[f64] {
+        &self.reachability
+    }
+
+    /// Return the core distance of each observation, infinite if it is not a core point for
+    /// any tolerance below `max_eps`
+    pub fn core_distances(&self) -> &[f64] {
+        &self.core_distances
+    }
+
+    /// Cut the reachability plot at the tolerance `eps` and return the cluster of each
+    /// observation, `None` for noise
+    ///
+    /// The clusters of core points are identical to the ones of DBSCAN with the same
+    /// tolerance. In contrast to DBSCAN, border points are assigned to the cluster of the core
+    /// point they were reached from.
+    ///
+    /// **Panics** if `eps` is larger than `max_eps`.
+    pub fn extract_dbscan(&self, eps: f64) -> Array1<Option<usize>> {
+        assert!(
+            eps <= self.max_eps,
+            "`eps` must not be larger than `max_eps` ({})",
+            self.max_eps
+        );
+
+        let mut labels = Array1::from(vec![None; self.ordering.len()]);
+        let mut n_clusters: usize = 0;
+        for &point in &self.ordering {
+            if self.reachability[point] < eps {
+                labels[point] = n_clusters.checked_sub(1);
+            } else if self.core_distances[point] < eps {
+                // not reachable from a previous cluster, but dense enough to start a new one
+                labels[point] = Some(n_clusters);
+                n_clusters += 1;
+            }
+        }
+
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_blobs, Dbscan, DbscanHyperParams};
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn extraction_matches_dbscan() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [4., 4.], [0., 10.]];
+        let observations = generate_blobs(50, &centroids, &mut rng);
+
+        let params = OpticsHyperParams::new(5).max_eps(3.).build();
+        let analysis = Optics::predict(&params, &observations);
+
+        for &eps in &[0.3, 0.7, 1.5, 3.] {
+            let params = DbscanHyperParams::new(5).tolerance(eps).build();
+            let expected = Dbscan::predict(&params, &observations.t());
+            let labels = analysis.extract_dbscan(eps);
+
+            // DBSCAN only labels core points, the partition of those has to be identical
+            let core = (0..expected.len())
+                .filter(|i| expected[*i].is_some())
+                .collect::<Vec<_>>();
+            for &i in &core {
+                assert!(labels[i].is_some());
+                for &j in &core {
+                    assert_eq!(expected[i] == expected[j], labels[i] == labels[j]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn separated_blobs_are_visited_consecutively() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [100., 100.]];
+        let observations = generate_blobs(30, &centroids, &mut rng);
+
+        let params = OpticsHyperParams::new(3).build();
+        let analysis = Optics::predict(&params, &observations);
+
+        let mut ordering = analysis.ordering().to_vec();
+        assert_eq!(ordering[0], 0);
+        assert!(analysis.reachability_distances()[0].is_infinite());
+
+        // the second blob is only entered once, with a large jump in the reachability plot
+        let jumps = ordering
+            .windows(2)
+            .filter(|w| (w[0] < 30) != (w[1] < 30))
+            .count();
+        assert_eq!(jumps, 1);
+        let jump = ordering.iter().position(|i| *i >= 30).unwrap();
+        assert!(analysis.reachability_distances()[ordering[jump]] > 100.);
+
+        ordering.sort_unstable();
+        assert_eq!(ordering, (0..60).collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic]
+    fn eps_larger_than_max_eps() {
+        let params = OpticsHyperParams::new(3).max_eps(1.).build();
+        let analysis = Optics::predict(&params, &array![[0., 0.], [1., 1.]]);
+        analysis.extract_dbscan(2.);
+    }
+}
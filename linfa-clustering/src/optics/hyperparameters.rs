@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// The set of hyperparameters that can be specified for the execution of
+/// the [OPTICS algorithm](struct.Optics.html).
+pub struct OpticsHyperParams {
+    /// Minimum number of neighboring points a point needs to have to be a core
+    /// point.
+    min_points: usize,
+    /// Largest distance between points for them to be considered neighbours.
+    max_eps: f64,
+}
+
+/// Helper struct used to construct a set of hyperparameters for
+/// the [OPTICS algorithm](struct.Optics.html).
+pub struct OpticsHyperParamsBuilder {
+    min_points: usize,
+    max_eps: f64,
+}
+
+impl OpticsHyperParamsBuilder {
+    /// Largest distance between points for them to be considered neighbours. Clusters can
+    /// only be extracted for smaller tolerances, but the algorithm runs faster on sparse
+    /// neighborhoods.
+    pub fn max_eps(mut self, max_eps: f64) -> Self {
+        self.max_eps = max_eps;
+        self
+    }
+
+    /// Return an instance of `OpticsHyperParams` after having performed
+    /// validation checks on all hyperparameters.
+    ///
+    /// **Panics** if any of the validation checks fail.
+    pub fn build(self) -> OpticsHyperParams {
+        OpticsHyperParams::build(self.min_points, self.max_eps)
+    }
+}
+
+impl OpticsHyperParams {
+    /// Minimum number of neighboring points a point needs to have to be a core
+    /// point.
+    ///
+    /// Defaults are provided if the optional parameters are not specified:
+    /// * `max_eps = f64::INFINITY`
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(min_points: usize) -> OpticsHyperParamsBuilder {
+        OpticsHyperParamsBuilder {
+            min_points,
+            max_eps: f64::INFINITY,
+        }
+    }
+
+    /// Minimum number of a points in a neighborhood around a point for it to
+    /// be a core point
+    pub fn minimum_points(&self) -> usize {
+        self.min_points
+    }
+
+    /// Two points are considered neighbors if the euclidean distance between
+    /// them is below this distance
+    pub fn max_eps(&self) -> f64 {
+        self.max_eps
+    }
+
+    fn build(min_points: usize, max_eps: f64) -> Self {
+        if max_eps <= 0. || max_eps.is_nan() {
+            panic!("`max_eps` must be greater than 0!");
+        }
+        // There is always at least one neighbor to a point (itself)
+        if min_points <= 1 {
+            panic!("`min_points` must be greater than 1!");
+        }
+        Self {
+            min_points,
+            max_eps,
+        }
+    }
+}
@@ -1,5 +1,8 @@
+mod mmd;
 mod sparse;
 
+pub use mmd::{mmd, mmd_permutation_test};
+
 use ndarray::prelude::*;
 use ndarray::Data;
 use ndarray::{linalg::Dot, NdFloat};
@@ -10,12 +13,68 @@ type SimFnc<A> = Box<dyn Fn(ArrayView1<A>, ArrayView1<A>) -> A>;
 pub enum KernelType {
     Dense,
     Sparse(usize),
+    /// Evaluate the entries on demand instead of storing the kernel matrix
+    Lazy,
 }
 
 #[derive(Debug)]
 pub enum KernelInner<A: NdFloat> {
     Dense(Array2<A>),
     Sparse(CsMat<A>),
+    /// No matrix is stored, entries are computed from the dataset whenever they are requested
+    ///
+    /// This needs memory linear in the number of samples, but every access to a column
+    /// evaluates the kernel function for all samples again.
+    Lazy,
+}
+
+/// Location of a non-finite (NaN or infinite) value in a kernel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NonFinite {
+    /// A value of the dataset with the index of the sample and feature
+    Dataset { sample: usize, feature: usize },
+    /// An entry of the kernel matrix with the index of the row and column
+    Matrix { row: usize, column: usize },
+}
+
+/// Find the first non-finite value in a dataset and return its sample and feature index
+///
+/// This can be used to validate a dataset before constructing a kernel from it.
+pub fn find_non_finite<A: NdFloat, D: Data<Elem = A>>(
+    dataset: &ArrayBase<D, Ix2>,
+) -> Option<(usize, usize)> {
+    dataset
+        .indexed_iter()
+        .find(|(_, x)| !x.is_finite())
+        .map(|(idx, _)| idx)
+}
+
+/// Estimate the width `eps` of a gaussian kernel from the data
+///
+/// This uses the "scale" heuristic `eps = n_features * var(X)`, where `var(X)` is the variance
+/// of the features averaged over all features. It corresponds to `gamma = 1 / (n_features * var(X))` for the
+/// kernel formulated as `exp(-gamma * ||a - b||^2)`. A dataset without any variance results in
+/// `eps = 1`.
+pub fn gaussian_eps_heuristic<A: NdFloat, D: Data<Elem = A>>(dataset: &ArrayBase<D, Ix2>) -> A {
+    let n_features = A::from(dataset.ncols()).unwrap();
+    let n_entries = A::from(dataset.len()).unwrap();
+
+    // variance of each feature, averaged over all features
+    let var = dataset
+        .gencolumns()
+        .into_iter()
+        .map(|column| {
+            let mean = column.fold(A::zero(), |sum, x| sum + *x) / A::from(column.len()).unwrap();
+            column.fold(A::zero(), |sum, x| sum + (*x - mean) * (*x - mean))
+        })
+        .fold(A::zero(), |sum, x| sum + x)
+        / n_entries;
+
+    if var > A::zero() && var.is_finite() {
+        n_features * var
+    } else {
+        A::one()
+    }
 }
 
 pub struct Kernel<'a, A: NdFloat, D: Data<Elem = A>> {
@@ -23,6 +82,8 @@ pub struct Kernel<'a, A: NdFloat, D: Data<Elem = A>> {
     pub fnc: SimFnc<A>,
     pub dataset: &'a ArrayBase<D, Ix2>,
     pub linear: bool,
+    /// The width of the kernel, if it was constructed as gaussian kernel
+    pub gaussian_eps: Option<A>,
 }
 
 impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A, D> {
@@ -35,6 +96,7 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
         let inner = match kind {
             KernelType::Dense => KernelInner::Dense(dense_from_fn(dataset, &fnc)),
             KernelType::Sparse(k) => KernelInner::Sparse(sparse_from_fn(dataset, k, &fnc)),
+            KernelType::Lazy => KernelInner::Lazy,
         };
 
         Kernel {
@@ -42,6 +104,7 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
             fnc: Box::new(fnc),
             dataset,
             linear,
+            gaussian_eps: None,
         }
     }
 
@@ -49,6 +112,14 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
         match &self.inner {
             KernelInner::Dense(mat) => mat.dot(rhs),
             KernelInner::Sparse(mat) => mat.dot(rhs),
+            KernelInner::Lazy => {
+                let mut product = Array2::zeros((self.size(), rhs.ncols()));
+                // the kernel is symmetric, the i-th column is also the i-th row
+                for (i, mut row) in product.outer_iter_mut().enumerate() {
+                    row.assign(&Array1::from(self.column(i)).dot(rhs));
+                }
+                product
+            }
         }
     }
 
@@ -64,6 +135,9 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
 
                 sum
             }
+            KernelInner::Lazy => (0..self.size())
+                .map(|i| self.column(i).into_iter().sum())
+                .collect(),
         }
     }
 
@@ -71,9 +145,14 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
         match &self.inner {
             KernelInner::Dense(mat) => mat.ncols(),
             KernelInner::Sparse(mat) => mat.cols(),
+            KernelInner::Lazy => self.dataset.nrows(),
         }
     }
 
+    /// Return the entries above the diagonal, row by row
+    ///
+    /// A lazy kernel evaluates and materializes all of these entries, which needs memory
+    /// quadratic in the number of samples.
     pub fn to_upper_triangle(&self) -> Vec<A> {
         match &self.inner {
             KernelInner::Dense(mat) => mat
@@ -88,13 +167,19 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
                     .map(|(_, val)| *val)
                     .collect()
             }
+            KernelInner::Lazy => (0..self.size())
+                .flat_map(|row| {
+                    (row + 1..self.size())
+                        .map(move |col| (self.fnc)(self.dataset.row(row), self.dataset.row(col)))
+                })
+                .collect(),
         }
     }
 
     pub fn diagonal(&self) -> Array1<A> {
         match &self.inner {
             KernelInner::Dense(mat) => mat.diag().to_owned(),
-            KernelInner::Sparse(_) => self
+            KernelInner::Sparse(_) | KernelInner::Lazy => self
                 .dataset
                 .outer_iter()
                 .map(|x| (self.fnc)(x.view(), x.view()))
@@ -108,6 +193,13 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
             KernelInner::Sparse(mat) => (0..self.size())
                 .map(|j| *mat.get(j, i).unwrap_or(&A::neg_zero()))
                 .collect::<Vec<_>>(),
+            KernelInner::Lazy => {
+                let sample = self.dataset.row(i);
+                self.dataset
+                    .outer_iter()
+                    .map(|x| (self.fnc)(x, sample))
+                    .collect()
+            }
         }
     }
 
@@ -123,33 +215,93 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
         self.linear
     }
 
-    pub fn linear(dataset: &'a ArrayBase<D, Ix2>) -> Kernel<A, D> {
-        let fnc = |a: ArrayView1<A>, b: ArrayView1<A>| a.dot(&b);
+    /// The width `eps` of a gaussian kernel, this is also the value chosen by
+    /// [gaussian_auto](#method.gaussian_auto)
+    pub fn gaussian_eps(&self) -> Option<A> {
+        self.gaussian_eps
+    }
+
+    /// Scan the dataset and the kernel matrix for NaN or infinite values
+    ///
+    /// The scan is linear in the size of the dataset and kernel matrix, it is therefore not
+    /// performed when the kernel is constructed. A lazy kernel is not materialized, only the
+    /// dataset and the diagonal are checked.
+    pub fn find_non_finite(&self) -> Option<NonFinite> {
+        if let Some((sample, feature)) = find_non_finite(self.dataset) {
+            return Some(NonFinite::Dataset { sample, feature });
+        }
 
-        Kernel::new(dataset, fnc, KernelType::Dense, true)
+        let entry = match &self.inner {
+            KernelInner::Dense(mat) => mat
+                .indexed_iter()
+                .find(|(_, val)| !val.is_finite())
+                .map(|(idx, _)| idx),
+            KernelInner::Sparse(mat) => mat
+                .iter()
+                .find(|(val, _)| !val.is_finite())
+                .map(|(_, idx)| idx),
+            KernelInner::Lazy => self
+                .diagonal()
+                .iter()
+                .position(|val| !val.is_finite())
+                .map(|idx| (idx, idx)),
+        };
+
+        entry.map(|(row, column)| NonFinite::Matrix { row, column })
+    }
+
+    pub fn linear(dataset: &'a ArrayBase<D, Ix2>) -> Kernel<A, D> {
+        Kernel::linear_with_type(dataset, KernelType::Dense)
     }
 
     pub fn linear_sparse(dataset: &'a ArrayBase<D, Ix2>, nneigh: usize) -> Kernel<A, D> {
+        Kernel::linear_with_type(dataset, KernelType::Sparse(nneigh))
+    }
+
+    /// Construct a linear kernel which evaluates its entries on demand
+    pub fn linear_lazy(dataset: &'a ArrayBase<D, Ix2>) -> Kernel<A, D> {
+        Kernel::linear_with_type(dataset, KernelType::Lazy)
+    }
+
+    /// Construct a linear kernel with the given representation of the kernel matrix
+    pub fn linear_with_type(dataset: &'a ArrayBase<D, Ix2>, kind: KernelType) -> Kernel<A, D> {
         let fnc = |a: ArrayView1<A>, b: ArrayView1<A>| a.dot(&b);
 
-        Kernel::new(dataset, fnc, KernelType::Sparse(nneigh), true)
+        Kernel::new(dataset, fnc, kind, true)
     }
 
     pub fn gaussian(dataset: &'a ArrayBase<D, Ix2>, eps: A) -> Kernel<A, D> {
-        let fnc = move |a: ArrayView1<A>, b: ArrayView1<A>| {
-            let distance = a
-                .iter()
-                .zip(b.iter())
-                .map(|(x, y)| (*x - *y) * (*x - *y))
-                .sum::<A>();
-
-            (-distance / eps).exp()
-        };
+        Kernel::gaussian_with_type(dataset, eps, KernelType::Dense)
+    }
 
-        Kernel::new(dataset, fnc, KernelType::Dense, false)
+    /// Construct a gaussian kernel with a width estimated from the data
+    ///
+    /// The width is chosen with [gaussian_eps_heuristic](fn.gaussian_eps_heuristic.html) and
+    /// can be retrieved afterwards with [gaussian_eps](#method.gaussian_eps). This is a
+    /// reasonable starting point if the data is roughly isotropic, otherwise the features should
+    /// be scaled first.
+    pub fn gaussian_auto(dataset: &'a ArrayBase<D, Ix2>) -> Kernel<A, D> {
+        Kernel::gaussian(dataset, gaussian_eps_heuristic(dataset))
     }
 
     pub fn gaussian_sparse(dataset: &'a ArrayBase<D, Ix2>, eps: A, nneigh: usize) -> Kernel<A, D> {
+        Kernel::gaussian_with_type(dataset, eps, KernelType::Sparse(nneigh))
+    }
+
+    /// Construct a gaussian kernel which evaluates its entries on demand
+    ///
+    /// Only the dataset and the width are kept, the kernel matrix is never materialized. This
+    /// allows to fit models on datasets whose dense kernel matrix would not fit in memory.
+    pub fn gaussian_lazy(dataset: &'a ArrayBase<D, Ix2>, eps: A) -> Kernel<A, D> {
+        Kernel::gaussian_with_type(dataset, eps, KernelType::Lazy)
+    }
+
+    /// Construct a gaussian kernel with the given representation of the kernel matrix
+    pub fn gaussian_with_type(
+        dataset: &'a ArrayBase<D, Ix2>,
+        eps: A,
+        kind: KernelType,
+    ) -> Kernel<A, D> {
         let fnc = move |a: ArrayView1<A>, b: ArrayView1<A>| {
             let distance = a
                 .iter()
@@ -160,13 +312,13 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
             (-distance / eps).exp()
         };
 
-        Kernel::new(dataset, fnc, KernelType::Sparse(nneigh), false)
+        let mut kernel = Kernel::new(dataset, fnc, kind, false);
+        kernel.gaussian_eps = Some(eps);
+        kernel
     }
 
     pub fn polynomial(dataset: &'a ArrayBase<D, Ix2>, c: A, d: A) -> Kernel<A, D> {
-        let fnc = move |a: ArrayView1<A>, b: ArrayView1<A>| (a.dot(&b) + c).powf(d);
-
-        Kernel::new(dataset, fnc, KernelType::Dense, false)
+        Kernel::polynomial_with_type(dataset, c, d, KernelType::Dense)
     }
 
     pub fn polynomial_sparse(
@@ -174,10 +326,25 @@ impl<'a, A: NdFloat + Default + std::iter::Sum, D: Data<Elem = A>> Kernel<'a, A,
         c: A,
         d: A,
         nneigh: usize,
+    ) -> Kernel<A, D> {
+        Kernel::polynomial_with_type(dataset, c, d, KernelType::Sparse(nneigh))
+    }
+
+    /// Construct a polynomial kernel which evaluates its entries on demand
+    pub fn polynomial_lazy(dataset: &'a ArrayBase<D, Ix2>, c: A, d: A) -> Kernel<A, D> {
+        Kernel::polynomial_with_type(dataset, c, d, KernelType::Lazy)
+    }
+
+    /// Construct a polynomial kernel with the given representation of the kernel matrix
+    pub fn polynomial_with_type(
+        dataset: &'a ArrayBase<D, Ix2>,
+        c: A,
+        d: A,
+        kind: KernelType,
     ) -> Kernel<A, D> {
         let fnc = move |a: ArrayView1<A>, b: ArrayView1<A>| (a.dot(&b) + c).powf(d);
 
-        Kernel::new(dataset, fnc, KernelType::Sparse(nneigh), false)
+        Kernel::new(dataset, fnc, kind, false)
     }
 }
 
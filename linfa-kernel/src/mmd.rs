@@ -0,0 +1,176 @@
+//! Maximum mean discrepancy between two samples
+//!
+//! The maximum mean discrepancy (MMD) is the distance between the mean embeddings of two
+//! distributions in the feature space of a kernel. For a characteristic kernel, like the gaussian
+//! kernel, it is zero if and only if both distributions are equal, which makes it a test
+//! statistic for the two-sample problem.
+use ndarray::{stack, Array2, ArrayBase, ArrayView2, Axis, Data, Ix2, NdFloat};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::Kernel;
+
+/// Estimate the squared maximum mean discrepancy between the samples `x` and `y`
+///
+/// This is the biased estimate
+/// ```ignore
+/// mean(k(x_i, x_j)) + mean(k(y_i, y_j)) - 2 mean(k(x_i, y_j))
+/// ```
+/// which is never negative. Only the kernel function is used, the dataset of `kernel` does not
+/// have to be related to `x` or `y`.
+///
+/// **Panics** if one of the samples is empty or if they have a different number of features.
+pub fn mmd<'a, A: NdFloat, D: Data<Elem = A>>(
+    x: ArrayView2<'a, A>,
+    y: ArrayView2<'a, A>,
+    kernel: &Kernel<A, D>,
+) -> A {
+    let gram = pooled_gram(x, y, kernel);
+    let indices = (0..gram.nrows()).collect::<Vec<_>>();
+
+    mmd_from_gram(&gram, &indices, x.nrows())
+}
+
+/// Test whether `x` and `y` are drawn from the same distribution with `n_permutations` random
+/// permutations of the pooled samples
+///
+/// Returns the squared maximum mean discrepancy of [mmd](fn.mmd.html) and the p-value, which is
+/// the fraction of permutations with a discrepancy at least as large as the observed one. The
+/// observed split counts as one of the permutations, so the p-value is never zero. The kernel
+/// matrix of the pooled samples is computed once, each permutation costs a pass over it.
+///
+/// **Panics** if one of the samples is empty or if they have a different number of features.
+pub fn mmd_permutation_test<'a, A: NdFloat, D: Data<Elem = A>, R: Rng>(
+    x: ArrayView2<'a, A>,
+    y: ArrayView2<'a, A>,
+    kernel: &Kernel<A, D>,
+    n_permutations: usize,
+    rng: &mut R,
+) -> (A, f64) {
+    let gram = pooled_gram(x, y, kernel);
+    let mut indices = (0..gram.nrows()).collect::<Vec<_>>();
+    let statistic = mmd_from_gram(&gram, &indices, x.nrows());
+
+    let mut n_extreme = 0;
+    for _ in 0..n_permutations {
+        indices.shuffle(rng);
+        if mmd_from_gram(&gram, &indices, x.nrows()) >= statistic {
+            n_extreme += 1;
+        }
+    }
+
+    let p_value = (n_extreme + 1) as f64 / (n_permutations + 1) as f64;
+
+    (statistic, p_value)
+}
+
+/// Evaluate the kernel function between all samples of `x` stacked on top of `y`
+fn pooled_gram<'a, A: NdFloat, D: Data<Elem = A>>(
+    x: ArrayView2<'a, A>,
+    y: ArrayView2<'a, A>,
+    kernel: &Kernel<A, D>,
+) -> Array2<A> {
+    assert!(
+        x.nrows() > 0 && y.nrows() > 0,
+        "both samples must be non-empty"
+    );
+    assert_eq!(
+        x.ncols(),
+        y.ncols(),
+        "both samples must have the same number of features"
+    );
+
+    let pooled = stack(Axis(0), &[x, y]).unwrap();
+    let n = pooled.nrows();
+
+    let mut gram = Array2::zeros((n, n));
+    for i in 0..n {
+        for j in i..n {
+            let val = (kernel.fnc)(pooled.row(i), pooled.row(j));
+            gram[(i, j)] = val;
+            gram[(j, i)] = val;
+        }
+    }
+
+    gram
+}
+
+/// The discrepancy between the first `n_x` samples in `indices` and the remaining ones
+fn mmd_from_gram<A: NdFloat, S: Data<Elem = A>>(
+    gram: &ArrayBase<S, Ix2>,
+    indices: &[usize],
+    n_x: usize,
+) -> A {
+    let (idx_x, idx_y) = indices.split_at(n_x);
+    let mean = |a: &[usize], b: &[usize]| {
+        let sum = a.iter().fold(A::zero(), |sum, i| {
+            b.iter().fold(sum, |sum, j| sum + gram[(*i, *j)])
+        });
+        sum / A::from(a.len() * b.len()).unwrap()
+    };
+
+    let two = A::one() + A::one();
+    // rounding may leave a tiny negative value for equal samples
+    (mean(idx_x, idx_x) + mean(idx_y, idx_y) - two * mean(idx_x, idx_y)).max(A::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{mmd, mmd_permutation_test};
+    use crate::Kernel;
+    use ndarray::{Array, Array2};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Normal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn equal_samples_have_no_discrepancy() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array::random_using((30, 2), Normal::new(0., 1.).unwrap(), &mut rng);
+        let kernel = Kernel::gaussian_lazy(&x, 2.);
+
+        assert_eq!(mmd(x.view(), x.view(), &kernel), 0.);
+    }
+
+    #[test]
+    fn shifted_distributions_are_detected() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array::random_using((50, 2), Normal::new(0., 1.).unwrap(), &mut rng);
+        let y = Array::random_using((40, 2), Normal::new(0., 1.).unwrap(), &mut rng) + 2.;
+        let kernel = Kernel::gaussian_lazy(&x, 2.);
+
+        let (statistic, p_value) = mmd_permutation_test(x.view(), y.view(), &kernel, 200, &mut rng);
+        assert!(statistic > 0.1);
+        assert_eq!(statistic, mmd(x.view(), y.view(), &kernel));
+        // no permutation is as extreme as the observed split, which still counts as one
+        assert_eq!(p_value, 1. / 201.);
+    }
+
+    #[test]
+    fn equal_distributions_are_not_rejected() {
+        // interleaved grids cover the unit interval as evenly as possible, a random split of
+        // the pooled samples differs more than the observed one
+        let x = Array2::from_shape_fn((50, 1), |(i, _)| i as f64 / 50.);
+        let y = Array2::from_shape_fn((50, 1), |(i, _)| (i as f64 + 0.5) / 50.);
+        let kernel = Kernel::gaussian_lazy(&x, 0.1);
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+
+        let (statistic, p_value) = mmd_permutation_test(x.view(), y.view(), &kernel, 200, &mut rng);
+        assert!(statistic < 1e-3);
+        assert!(p_value > 0.9);
+    }
+
+    #[test]
+    fn p_value_is_never_zero() {
+        let x = Array2::from_shape_fn((10, 1), |(i, _)| i as f64);
+        let y = x.clone() + 100.;
+        let kernel = Kernel::gaussian_lazy(&x, 1.);
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+
+        let (_, p_value) = mmd_permutation_test(x.view(), y.view(), &kernel, 99, &mut rng);
+        assert_eq!(p_value, 0.01);
+        let (_, p_value) = mmd_permutation_test(x.view(), y.view(), &kernel, 0, &mut rng);
+        assert_eq!(p_value, 1.);
+    }
+}
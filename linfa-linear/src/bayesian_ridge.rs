@@ -0,0 +1,283 @@
+//! Bayesian Ridge Regression
+//!
+//! Linear regression with a Gaussian prior on the coefficients. The targets are modelled as
+//! ```ignore
+//! y ~ N(Xw, 1 / alpha),    w ~ N(0, 1 / lambda * I)
+//! ```
+//! where the noise precision `alpha` and the weight precision `lambda` are estimated from the data
+//! by maximizing the marginal likelihood (evidence), following
+//! [MacKay (1992)](https://doi.org/10.1162/neco.1992.4.3.415). Both precisions have a weakly
+//! informative Gamma prior. The fitted model contains the posterior distribution of the
+//! coefficients, which gives an uncertainty for every prediction.
+//!
+//! The posterior mean is a ridge regression with penalty `lambda / alpha`. Like
+//! [RidgeRegression](../ridge/struct.RidgeRegression.html) the normal equations are decomposed
+//! once, every update of the precisions is then solved in `O(n_features^2)`.
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+use ndarray::{Array1, Array2, Axis};
+use ndarray_linalg::{Eigh, UPLO};
+
+/// Shape and rate of the Gamma priors over `alpha` and `lambda`
+const GAMMA_PRIOR: f64 = 1e-6;
+
+/// Bayesian ridge regression, a linear model with an estimated Gaussian prior
+pub struct BayesianRidge {
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for BayesianRidge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BayesianRidge {
+    /// Create a default Bayesian ridge regression model with an intercept
+    pub fn new() -> Self {
+        Self {
+            fit_intercept: true,
+            max_iter: 300,
+            tol: 1e-3,
+        }
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Maximum number of updates of the precisions
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion for the relative change of both precisions
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit a Bayesian ridge regression model given a feature matrix `x` with shape
+    /// `(n_samples, n_features)` and a target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedBayesianRidge<A>> {
+        self.validate(x, y)?;
+
+        let (x_offset, y_offset) = if self.fit_intercept {
+            (x.mean_axis(Axis(0)).unwrap(), y.mean().unwrap())
+        } else {
+            (Array1::zeros(x.ncols()), A::zero())
+        };
+        let x = x - &x_offset;
+        let y = y - y_offset;
+
+        // decompose the normal equations once, small negative eigenvalues are rounding errors
+        let (eigvals, eigvecs) = x.t().dot(&x).eigh(UPLO::Lower)?;
+        let eigvals = eigvals.mapv(|x| A::from(x).unwrap().max(A::zero()));
+        let rhs = eigvecs.t().dot(&x.t().dot(&y));
+
+        // posterior mean of the coefficients, a ridge regression with penalty `lambda / alpha`
+        let posterior_mean = |alpha: A, lambda: A| {
+            let rotated = &rhs / &eigvals.mapv(|val| val + lambda / alpha);
+            eigvecs.dot(&rotated)
+        };
+
+        let n_samples = A::from(x.nrows()).unwrap();
+        let prior = A::from(GAMMA_PRIOR).unwrap();
+        let two = A::from(2.).unwrap();
+        let tol = A::from(self.tol).unwrap();
+
+        let variance = y.dot(&y) / n_samples;
+        let mut alpha = A::one() / (variance + A::epsilon());
+        let mut lambda = A::one();
+        let mut n_iter = self.max_iter;
+
+        for iter in 0..self.max_iter {
+            let params = posterior_mean(alpha, lambda);
+            let residual = &y - &x.dot(&params);
+
+            // effective number of parameters determined by the data
+            let gamma = eigvals
+                .iter()
+                .map(|val| alpha * *val / (lambda + alpha * *val))
+                .sum::<A>();
+            let lambda_new = (gamma + two * prior) / (params.dot(&params) + two * prior);
+            let alpha_new =
+                (n_samples - gamma + two * prior) / (residual.dot(&residual) + two * prior);
+
+            let converged = ((alpha_new - alpha) / alpha).abs() < tol
+                && ((lambda_new - lambda) / lambda).abs() < tol;
+            alpha = alpha_new;
+            lambda = lambda_new;
+            if converged {
+                n_iter = iter + 1;
+                break;
+            }
+        }
+
+        let params = posterior_mean(alpha, lambda);
+        let intercept = y_offset - x_offset.dot(&params);
+        let scaled = &eigvecs / &eigvals.mapv(|val| alpha * val + lambda);
+        let sigma = scaled.dot(&eigvecs.t());
+
+        Ok(FittedBayesianRidge {
+            intercept,
+            params,
+            sigma,
+            x_offset,
+            alpha,
+            lambda,
+            n_iter,
+        })
+    }
+
+    fn validate<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<()> {
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if x.nrows() == 0 || x.nrows() != y.len() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                x.nrows(),
+                y.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A fitted Bayesian ridge regression model with the posterior distribution of the coefficients
+pub struct FittedBayesianRidge<A> {
+    intercept: A,
+    params: Array1<A>,
+    sigma: Array2<A>,
+    x_offset: Array1<A>,
+    alpha: A,
+    lambda: A,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedBayesianRidge<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the posterior mean of the target variable
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        x.dot(&self.params) + self.intercept
+    }
+
+    /// Predict the posterior mean and standard deviation of the target variable
+    ///
+    /// The variance of a prediction is the variance of the noise `1 / alpha` plus the
+    /// uncertainty of the coefficients, which grows with the distance to the mean of the
+    /// training data.
+    pub fn predict_with_std(&self, x: &Array2<A>) -> (Array1<A>, Array1<A>) {
+        let x_centered = x - &self.x_offset;
+        let noise = A::one() / self.alpha;
+        let std = (x_centered.dot(&self.sigma) * &x_centered)
+            .sum_axis(Axis(1))
+            .mapv(|var| (var + noise).sqrt());
+
+        (self.predict(x), std)
+    }
+
+    /// Get the posterior mean of the coefficients
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the posterior covariance of the coefficients with shape `(n_features, n_features)`
+    pub fn sigma(&self) -> &Array2<A> {
+        &self.sigma
+    }
+
+    /// Get the estimated precision of the noise
+    pub fn alpha(&self) -> A {
+        self.alpha
+    }
+
+    /// Get the estimated precision of the coefficients
+    pub fn lambda(&self) -> A {
+        self.lambda
+    }
+
+    /// Get the number of updates of the precisions
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, Array};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Normal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn predictive_std_grows_outside_of_training_range() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array::linspace(0., 1., 50).insert_axis(Axis(1));
+        let noise = Array1::random_using(50, Normal::new(0., 0.1).unwrap(), &mut rng);
+        let y = x.column(0).mapv(|x| 2. * x + 1.) + noise;
+
+        let model = BayesianRidge::new().fit(&x, &y).unwrap();
+        assert_abs_diff_eq!(model.params(), &array![2.], epsilon = 0.2);
+        assert_abs_diff_eq!(model.intercept(), 1., epsilon = 0.1);
+
+        let (mean, std) = model.predict_with_std(&array![[0.5], [2.], [10.], [100.]]);
+        assert_abs_diff_eq!(mean, model.predict(&array![[0.5], [2.], [10.], [100.]]));
+        assert!(std.as_slice().unwrap().windows(2).all(|w| w[0] < w[1]));
+        // close to the data the uncertainty is dominated by the noise
+        assert_abs_diff_eq!(std[0], 0.1, epsilon = 0.03);
+        assert!(std[3] > 10. * std[0]);
+    }
+
+    #[test]
+    fn estimates_noise_precision() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((200, 5), Normal::new(0., 1.).unwrap(), &mut rng);
+        let noise = Array1::random_using(200, Normal::new(0., 0.5).unwrap(), &mut rng);
+        let y = x.dot(&array![1., -2., 0., 0.5, 3.]) + noise;
+
+        let model = BayesianRidge::new().fit(&x, &y).unwrap();
+
+        assert!(model.n_iter() < 300);
+        assert_abs_diff_eq!(
+            model.params(),
+            &array![1., -2., 0., 0.5, 3.],
+            epsilon = 0.15
+        );
+        // the noise has a variance of 0.25
+        assert_abs_diff_eq!(model.alpha(), 4., epsilon = 1.);
+        assert_eq!(model.sigma().dim(), (5, 5));
+        assert!(model.lambda() > 0.);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0.], [1.]];
+
+        assert!(BayesianRidge::new()
+            .tol(0.)
+            .fit(&x, &array![0., 1.])
+            .is_err());
+        assert!(BayesianRidge::new().fit(&x, &array![0.]).is_err());
+    }
+}
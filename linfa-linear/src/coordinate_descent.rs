@@ -0,0 +1,269 @@
+//! Cyclical coordinate descent for least squares with L1 and L2 penalty
+//!
+//! Minimizes the objective
+//! ```ignore
+//! 1 / (2 * n_samples) * ||y - Xw||^2_2 + l1 * ||w||_1 + 0.5 * l2 * ||w||^2_2
+//! ```
+//! by updating a single coefficient at a time with the soft-thresholding operator. After a full
+//! sweep over all coefficients only the active set (non-zero coefficients) is updated, until the
+//! coefficients stabilize and another full sweep is performed. Convergence is checked with the
+//! duality gap of the problem.
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2};
+
+use crate::float::Float;
+
+/// Soft-thresholding operator `sign(x) * max(|x| - threshold, 0)`
+pub(crate) fn soft_threshold<A: Float>(x: A, threshold: A) -> A {
+    if x > threshold {
+        x - threshold
+    } else if x < -threshold {
+        x + threshold
+    } else {
+        A::zero()
+    }
+}
+
+/// Duality gap of the (elastic net) problem, scaled by the number of samples
+fn duality_gap<A: Float>(
+    x: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    y: &ArrayBase<impl Data<Elem = A>, Ix1>,
+    w: &Array1<A>,
+    residual: &Array1<A>,
+    l1: A,
+    l2: A,
+) -> A {
+    let n_samples = A::from(x.len_of(Axis(0))).unwrap();
+
+    duality_gap_with_correlation(x.t().dot(residual), y, w, residual, n_samples, l1, l2)
+}
+
+/// Duality gap of the (elastic net) problem, given the correlation `X^T r` of the features with
+/// the residual
+pub(crate) fn duality_gap_with_correlation<A: Float>(
+    xtr: Array1<A>,
+    y: &ArrayBase<impl Data<Elem = A>, Ix1>,
+    w: &Array1<A>,
+    residual: &Array1<A>,
+    n_samples: A,
+    l1: A,
+    l2: A,
+) -> A {
+    let (l1, l2) = (l1 * n_samples, l2 * n_samples);
+
+    let xta = xtr - w * l2;
+    let dual_norm_xta = xta.fold(A::zero(), |max, x| max.max(num_traits::Float::abs(*x)));
+    let r_norm2 = residual.dot(residual);
+    let w_norm2 = w.dot(w);
+
+    let (constant, mut gap) = if dual_norm_xta > l1 {
+        let constant = l1 / dual_norm_xta;
+        (
+            constant,
+            (r_norm2 + r_norm2 * constant * constant) / A::from(2.).unwrap(),
+        )
+    } else {
+        (A::one(), r_norm2)
+    };
+
+    let l1_norm = w.fold(A::zero(), |sum, x| sum + num_traits::Float::abs(*x));
+    gap += l1 * l1_norm - constant * residual.dot(y)
+        + A::from(0.5).unwrap() * l2 * (A::one() + constant * constant) * w_norm2;
+
+    gap
+}
+
+/// Run coordinate descent, starting from the coefficients `w`
+///
+/// Returns the coefficients, the duality gap and the number of iterations. The data is expected
+/// to be centered if an intercept is fitted.
+pub(crate) fn coordinate_descent<A: Float>(
+    x: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    y: &ArrayBase<impl Data<Elem = A>, Ix1>,
+    l1: A,
+    l2: A,
+    mut w: Array1<A>,
+    max_iter: usize,
+    tol: A,
+) -> (Array1<A>, A, usize) {
+    let n_samples = A::from(x.len_of(Axis(0))).unwrap();
+    let n_features = x.len_of(Axis(1));
+
+    let norms = x.map_axis(Axis(0), |column| column.dot(&column));
+    let mut residual = y - &x.dot(&w);
+    // the tolerance of the duality gap is relative to the norm of the target
+    let gap_tol = tol * y.dot(y);
+
+    let all_features = (0..n_features).collect::<Vec<_>>();
+    let mut active = all_features.clone();
+    let mut full_sweep = true;
+    let mut gap = A::infinity();
+
+    for n_iter in 0..max_iter {
+        let (mut w_max, mut d_w_max) = (A::zero(), A::zero());
+
+        for &j in &active {
+            if norms[j] == A::zero() {
+                continue;
+            }
+
+            let w_old = w[j];
+            let column = x.column(j);
+
+            // correlation of feature `j` with the residual, excluding its own contribution
+            let rho = column.dot(&residual) + norms[j] * w_old;
+            w[j] = soft_threshold(rho, l1 * n_samples) / (norms[j] + l2 * n_samples);
+
+            if w[j] != w_old {
+                residual.scaled_add(w_old - w[j], &column);
+            }
+
+            d_w_max = d_w_max.max((w[j] - w_old).abs());
+            w_max = w_max.max(w[j].abs());
+        }
+
+        if full_sweep {
+            gap = duality_gap(x, y, &w, &residual, l1, l2);
+            if gap <= gap_tol {
+                return (w, gap, n_iter + 1);
+            }
+
+            // continue with the non-zero coefficients only
+            active = (0..n_features).filter(|j| w[*j] != A::zero()).collect();
+            full_sweep = active.is_empty();
+        } else if w_max == A::zero() || d_w_max / w_max < tol {
+            // the active set has converged, check all coefficients in the next sweep
+            active = all_features.clone();
+            full_sweep = true;
+        }
+    }
+
+    (w, gap, max_iter)
+}
+
+/// Duality gap of the multi-task problem with L2,1 penalty, scaled by the number of samples
+fn duality_gap_multi_task<A: Float>(
+    x: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    y: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    w: &Array2<A>,
+    residual: &Array2<A>,
+    l1: A,
+    l2: A,
+) -> A {
+    let n_samples = A::from(x.len_of(Axis(0))).unwrap();
+    let (l1, l2) = (l1 * n_samples, l2 * n_samples);
+
+    let xta = x.t().dot(residual) - w * l2;
+    let dual_norm_xta = xta
+        .genrows()
+        .into_iter()
+        .fold(A::zero(), |max, row| max.max(row.dot(&row).sqrt()));
+    let r_norm2 = residual.iter().map(|x| *x * *x).sum::<A>();
+    let w_norm2 = w.iter().map(|x| *x * *x).sum::<A>();
+
+    let (constant, mut gap) = if dual_norm_xta > l1 {
+        let constant = l1 / dual_norm_xta;
+        (
+            constant,
+            (r_norm2 + r_norm2 * constant * constant) / A::from(2.).unwrap(),
+        )
+    } else {
+        (A::one(), r_norm2)
+    };
+
+    let l21_norm = w
+        .genrows()
+        .into_iter()
+        .map(|row| row.dot(&row).sqrt())
+        .sum::<A>();
+    let ry_sum = residual
+        .iter()
+        .zip(y.iter())
+        .map(|(r, y)| *r * *y)
+        .sum::<A>();
+    gap += l1 * l21_norm - constant * ry_sum
+        + A::from(0.5).unwrap() * l2 * (A::one() + constant * constant) * w_norm2;
+
+    gap
+}
+
+/// Run block coordinate descent for multiple targets, starting from the coefficients `w`
+///
+/// Each block contains the coefficients of a single feature for all targets, which are shrinked
+/// jointly towards zero. This enforces the same support for all targets. Returns the
+/// coefficients with shape `(n_features, n_targets)`, the duality gap and the number of
+/// iterations.
+pub(crate) fn block_coordinate_descent<A: Float>(
+    x: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    y: &ArrayBase<impl Data<Elem = A>, Ix2>,
+    l1: A,
+    l2: A,
+    mut w: Array2<A>,
+    max_iter: usize,
+    tol: A,
+) -> (Array2<A>, A, usize) {
+    let n_samples = A::from(x.len_of(Axis(0))).unwrap();
+    let n_features = x.len_of(Axis(1));
+
+    let norms = x.map_axis(Axis(0), |column| column.dot(&column));
+    let mut residual = y - &x.dot(&w);
+    let gap_tol = tol * y.iter().map(|x| *x * *x).sum::<A>();
+    let mut gap = A::infinity();
+
+    for n_iter in 0..max_iter {
+        let (mut w_max, mut d_w_max) = (A::zero(), A::zero());
+
+        for j in 0..n_features {
+            if norms[j] == A::zero() {
+                continue;
+            }
+
+            let w_old = w.row(j).to_owned();
+            let column = x.column(j);
+
+            // correlation of feature `j` with the residual, excluding its own contribution
+            let tmp = residual.t().dot(&column) + &w_old * norms[j];
+            let tmp_norm = tmp.dot(&tmp).sqrt();
+
+            let scale = if tmp_norm > l1 * n_samples {
+                (A::one() - l1 * n_samples / tmp_norm) / (norms[j] + l2 * n_samples)
+            } else {
+                A::zero()
+            };
+            let w_new = tmp * scale;
+
+            let diff = &w_old - &w_new;
+            if diff.iter().any(|x| *x != A::zero()) {
+                for (mut row, x_ij) in residual.genrows_mut().into_iter().zip(column.iter()) {
+                    row.scaled_add(*x_ij, &diff);
+                }
+            }
+
+            d_w_max =
+                d_w_max.max(diff.fold(A::zero(), |max, x| max.max(num_traits::Float::abs(*x))));
+            w_max = w_max.max(w_new.fold(A::zero(), |max, x| max.max(num_traits::Float::abs(*x))));
+            w.row_mut(j).assign(&w_new);
+        }
+
+        if w_max == A::zero() || d_w_max / w_max < tol || n_iter + 1 == max_iter {
+            gap = duality_gap_multi_task(x, y, &w, &residual, l1, l2);
+            if gap <= gap_tol {
+                return (w, gap, n_iter + 1);
+            }
+        }
+    }
+
+    (w, gap, max_iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::soft_threshold;
+
+    #[test]
+    fn soft_thresholding() {
+        assert_eq!(soft_threshold(3.0, 1.0), 2.0);
+        assert_eq!(soft_threshold(-3.0, 1.0), -2.0);
+        assert_eq!(soft_threshold(0.5, 1.0), 0.0);
+        assert_eq!(soft_threshold(-0.5, 1.0), 0.0);
+    }
+}
@@ -0,0 +1,750 @@
+//! Elastic Net
+//!
+//! Linear regression with a combined L1 and L2 penalty on the coefficients. The objective
+//! ```ignore
+//! 1 / (2 * n_samples) * ||y - Xw||^2_2
+//!     + alpha * l1_ratio * ||w||_1
+//!     + 0.5 * alpha * (1 - l1_ratio) * ||w||^2_2
+//! ```
+//! is minimized with the same coordinate descent solver as the [Lasso](../lasso/index.html).
+//! With `l1_ratio = 1` the model is identical to the lasso, while `l1_ratio = 0` results in
+//! ridge regression with a penalty of `alpha * n_samples`. Values in between keep the sparsity
+//! of the lasso, but select groups of correlated features together.
+//!
+//! The coefficients along a whole sequence of penalties, optionally scored with cross-validation,
+//! are computed with [ElasticNetPath](struct.ElasticNetPath.html).
+
+use crate::coordinate_descent::coordinate_descent;
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+#[cfg(feature = "sprs")]
+use crate::sparse::{sparse_coordinate_descent, sparse_dot, SparseColumns};
+
+use ndarray::{Array1, Array2, Axis};
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+
+/// Elastic net, a linear model with combined L1 and L2 penalty
+pub struct ElasticNet {
+    alpha: f64,
+    l1_ratio: f64,
+    fit_intercept: bool,
+    standardize: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for ElasticNet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElasticNet {
+    /// Create a default elastic net model with `alpha = 1.0`, `l1_ratio = 0.5` and an intercept
+    pub fn new() -> Self {
+        Self {
+            alpha: 1.0,
+            l1_ratio: 0.5,
+            fit_intercept: true,
+            standardize: false,
+            max_iter: 1000,
+            tol: 1e-4,
+        }
+    }
+
+    /// Constant that multiplies with the penalty terms and thus determines the
+    /// regularization strength
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// The mixing parameter between the L1 and L2 penalty, in the interval `[0, 1]`
+    ///
+    /// A value of 1 only uses the L1 penalty (lasso), a value of 0 only the L2 penalty (ridge).
+    pub fn l1_ratio(mut self, l1_ratio: f64) -> Self {
+        self.l1_ratio = l1_ratio;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Specifies whether the features are scaled to unit variance before fitting
+    ///
+    /// The penalty treats all coefficients equally and is therefore sensitive to the scale of
+    /// the features. The fitted coefficients are always reported on the original scale.
+    pub fn standardize(mut self, standardize: bool) -> Self {
+        self.standardize = standardize;
+        self
+    }
+
+    /// Maximum number of sweeps of the coordinate descent solver
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion for the duality gap, relative to the squared norm of the targets
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit an elastic net model given a feature matrix `x` with shape `(n_samples, n_features)`
+    /// and a target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedElasticNet<A>> {
+        self.validate(x.dim(), y.len())?;
+
+        let (x_offset, y_offset) = if self.fit_intercept {
+            (x.mean_axis(Axis(0)).unwrap(), y.mean().unwrap())
+        } else {
+            (Array1::zeros(x.ncols()), A::zero())
+        };
+        let mut x_centered = x - &x_offset;
+        let y_centered = y - y_offset;
+
+        // scale each feature by its standard deviation, constant features are left untouched
+        let x_scale = if self.standardize {
+            let n_samples = A::from(x.nrows()).unwrap();
+            let scale = x_centered.map_axis(Axis(0), |column| {
+                let std = (column.dot(&column) / n_samples).sqrt();
+                if std > A::zero() {
+                    std
+                } else {
+                    A::one()
+                }
+            });
+            x_centered /= &scale;
+            scale
+        } else {
+            Array1::ones(x.ncols())
+        };
+
+        let alpha = A::from(self.alpha).unwrap();
+        let l1_ratio = A::from(self.l1_ratio).unwrap();
+        let (params, duality_gap, n_iter) = coordinate_descent(
+            &x_centered,
+            &y_centered,
+            alpha * l1_ratio,
+            alpha * (A::one() - l1_ratio),
+            Array1::zeros(x.ncols()),
+            self.max_iter,
+            A::from(self.tol).unwrap(),
+        );
+        let params = params / &x_scale;
+        let intercept = y_offset - x_offset.dot(&params);
+
+        Ok(FittedElasticNet {
+            intercept,
+            params,
+            duality_gap,
+            n_iter,
+        })
+    }
+
+    /// Fit an elastic net model given a sparse feature matrix `x` with shape
+    /// `(n_samples, n_features)` and a target variable `y` with shape `(n_samples)`
+    ///
+    /// The features are centered and scaled implicitly, so their sparsity is preserved and each
+    /// coordinate update only touches the non-zero entries of its column.
+    #[cfg(feature = "sprs")]
+    pub fn fit_sparse<A: Float>(&self, x: &CsMat<A>, y: &Array1<A>) -> Result<FittedElasticNet<A>> {
+        self.validate(x.shape(), y.len())?;
+
+        let mut x_sparse = SparseColumns::new(x, self.fit_intercept);
+        let y_offset = if self.fit_intercept {
+            y.mean().unwrap()
+        } else {
+            A::zero()
+        };
+        let y_centered = y - y_offset;
+
+        // scale each feature by its standard deviation, constant features are left untouched
+        let x_scale = if self.standardize {
+            let n_samples = A::from(x_sparse.n_samples()).unwrap();
+            x_sparse.column_norms().mapv(|norm| {
+                let std = (norm / n_samples).sqrt();
+                if std > A::zero() {
+                    std
+                } else {
+                    A::one()
+                }
+            })
+        } else {
+            Array1::ones(x_sparse.n_features())
+        };
+        x_sparse.set_scale(x_scale.clone());
+
+        let alpha = A::from(self.alpha).unwrap();
+        let l1_ratio = A::from(self.l1_ratio).unwrap();
+        let (params, duality_gap, n_iter) = sparse_coordinate_descent(
+            &x_sparse,
+            &y_centered,
+            alpha * l1_ratio,
+            alpha * (A::one() - l1_ratio),
+            Array1::zeros(x_sparse.n_features()),
+            self.max_iter,
+            A::from(self.tol).unwrap(),
+        );
+        let params = params / &x_scale;
+        let intercept = y_offset - x_sparse.offset().dot(&params);
+
+        Ok(FittedElasticNet {
+            intercept,
+            params,
+            duality_gap,
+            n_iter,
+        })
+    }
+
+    fn validate(&self, (n_samples, _): (usize, usize), y_len: usize) -> Result<()> {
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+        if self.l1_ratio < 0. || self.l1_ratio > 1. || self.l1_ratio.is_nan() {
+            return Err(LinearError::InvalidValue(format!(
+                "L1 ratio must be in the interval [0, 1], got: {}",
+                self.l1_ratio
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if n_samples == 0 || n_samples != y_len {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                n_samples, y_len
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A fitted elastic net model which can be used for making predictions
+pub struct FittedElasticNet<A> {
+    intercept: A,
+    params: Array1<A>,
+    duality_gap: A,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedElasticNet<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the target variable according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        x.dot(&self.params) + self.intercept
+    }
+
+    /// Predict the target variable of a sparse input matrix `x`
+    #[cfg(feature = "sprs")]
+    pub fn predict_sparse(&self, x: &CsMat<A>) -> Array1<A> {
+        sparse_dot(x, &self.params) + self.intercept
+    }
+
+    /// Get the fitted parameters on the scale of the original features
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the number of non-zero coefficients
+    pub fn n_nonzero(&self) -> usize {
+        self.params.iter().filter(|x| **x != A::zero()).count()
+    }
+
+    /// Get the duality gap at the end of the optimization
+    pub fn duality_gap(&self) -> A {
+        self.duality_gap
+    }
+
+    /// Get the number of sweeps performed by the solver
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+/// Compute the coefficients of an elastic net for a decreasing sequence of penalties
+///
+/// The penalties form a geometric grid from `alpha_max`, the smallest penalty for which all
+/// coefficients are zero, down to `eps * alpha_max`. The solver is warm started with the solution
+/// of the previous penalty, which makes computing the whole path barely more expensive than a
+/// single fit at the smallest penalty. Optionally every penalty is scored with k-fold
+/// cross-validation, which selects the penalty like a lasso-CV model.
+///
+/// With the default `l1_ratio = 1` this is the lasso path.
+pub struct ElasticNetPath {
+    l1_ratio: f64,
+    n_alphas: usize,
+    eps: f64,
+    fit_intercept: bool,
+    n_folds: Option<usize>,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for ElasticNetPath {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElasticNetPath {
+    /// Create a lasso path with 100 penalties down to `alpha_max / 1000`, without
+    /// cross-validation
+    pub fn new() -> Self {
+        Self {
+            l1_ratio: 1.0,
+            n_alphas: 100,
+            eps: 1e-3,
+            fit_intercept: true,
+            n_folds: None,
+            max_iter: 1000,
+            tol: 1e-4,
+        }
+    }
+
+    /// The mixing parameter between the L1 and L2 penalty, in the interval `(0, 1]`
+    pub fn l1_ratio(mut self, l1_ratio: f64) -> Self {
+        self.l1_ratio = l1_ratio;
+        self
+    }
+
+    /// Number of penalties on the path
+    pub fn n_alphas(mut self, n_alphas: usize) -> Self {
+        self.n_alphas = n_alphas;
+        self
+    }
+
+    /// Ratio of the smallest to the largest penalty, in the interval `(0, 1)`
+    pub fn eps(mut self, eps: f64) -> Self {
+        self.eps = eps;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Score every penalty with k-fold cross-validation on the given number of consecutive folds
+    pub fn cross_validation(mut self, n_folds: usize) -> Self {
+        self.n_folds = Some(n_folds);
+        self
+    }
+
+    /// Maximum number of sweeps of the coordinate descent solver for each penalty
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion for the duality gap, relative to the squared norm of the targets
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Compute the path given a feature matrix `x` with shape `(n_samples, n_features)` and a
+    /// target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedElasticNetPath<A>> {
+        self.validate(x, y)?;
+
+        let alpha_max = self.alpha_max(x, y).to_f64().unwrap();
+        let alphas = if self.n_alphas == 1 {
+            Array1::from(vec![alpha_max])
+        } else {
+            let step = self.eps.ln() / (self.n_alphas - 1) as f64;
+            Array1::from_shape_fn(self.n_alphas, |i| alpha_max * (step * i as f64).exp())
+        };
+        // the grid starts exactly at `alpha_max`, where all coefficients vanish
+        let (params, intercepts) = self.solve_path(x, y, alphas.as_slice().unwrap());
+
+        let cv_mse = match self.n_folds {
+            Some(n_folds) => Some(self.cv_mse(x, y, alphas.as_slice().unwrap(), n_folds)?),
+            None => None,
+        };
+
+        Ok(FittedElasticNetPath {
+            alphas,
+            params,
+            intercepts,
+            cv_mse,
+        })
+    }
+
+    /// Center the data if an intercept is fitted, returns the centered data and the offsets
+    fn center<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+    ) -> (Array2<A>, Array1<A>, Array1<A>, A) {
+        let (x_offset, y_offset) = if self.fit_intercept {
+            (x.mean_axis(Axis(0)).unwrap(), y.mean().unwrap())
+        } else {
+            (Array1::zeros(x.ncols()), A::zero())
+        };
+
+        (x - &x_offset, y - y_offset, x_offset, y_offset)
+    }
+
+    /// The smallest penalty for which all coefficients are zero, `max_j |x_j^T y|` scaled by
+    /// `n_samples * l1_ratio`
+    fn alpha_max<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> A {
+        let (x_centered, y_centered, _, _) = self.center(x, y);
+        let correlation = x_centered
+            .t()
+            .dot(&y_centered)
+            .fold(A::zero(), |max, x| max.max(num_traits::Float::abs(*x)));
+
+        correlation / A::from(x.nrows() as f64 * self.l1_ratio).unwrap()
+    }
+
+    /// Solve for every penalty in `alphas`, warm starting from the previous solution
+    ///
+    /// Returns the coefficients with shape `(n_features, n_alphas)` and the intercepts.
+    fn solve_path<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        alphas: &[f64],
+    ) -> (Array2<A>, Array1<A>) {
+        let (x_centered, y_centered, x_offset, y_offset) = self.center(x, y);
+        let alpha_max = self.alpha_max(x, y);
+        let l1_ratio = A::from(self.l1_ratio).unwrap();
+
+        let mut params = Array2::zeros((x.ncols(), alphas.len()));
+        let mut intercepts = Array1::zeros(alphas.len());
+        let mut w = Array1::zeros(x.ncols());
+        for (i, alpha) in alphas.iter().enumerate() {
+            let alpha = A::from(*alpha).unwrap();
+            // above `alpha_max` the solution is known, which also avoids round-off noise at the
+            // start of the path
+            w = if alpha >= alpha_max {
+                Array1::zeros(x.ncols())
+            } else {
+                coordinate_descent(
+                    &x_centered,
+                    &y_centered,
+                    alpha * l1_ratio,
+                    alpha * (A::one() - l1_ratio),
+                    w,
+                    self.max_iter,
+                    A::from(self.tol).unwrap(),
+                )
+                .0
+            };
+
+            intercepts[i] = y_offset - x_offset.dot(&w);
+            params.column_mut(i).assign(&w);
+        }
+
+        (params, intercepts)
+    }
+
+    /// Mean squared error of every penalty on the held-out folds
+    fn cv_mse<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        alphas: &[f64],
+        n_folds: usize,
+    ) -> Result<Array1<A>> {
+        let n_samples = x.nrows();
+        if n_folds < 2 || n_folds > n_samples {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of folds must be between 2 and the number of samples, got: {}",
+                n_folds
+            )));
+        }
+
+        let mut mse = Array1::zeros(alphas.len());
+        for fold in 0..n_folds {
+            let (start, end) = (fold * n_samples / n_folds, (fold + 1) * n_samples / n_folds);
+            let (train, valid): (Vec<usize>, Vec<usize>) =
+                (0..n_samples).partition(|i| *i < start || *i >= end);
+
+            let (x_train, y_train) = (x.select(Axis(0), &train), y.select(Axis(0), &train));
+            let (x_valid, y_valid) = (x.select(Axis(0), &valid), y.select(Axis(0), &valid));
+
+            let (params, intercepts) = self.solve_path(&x_train, &y_train, alphas);
+            let residuals = x_valid.dot(&params) + &intercepts - y_valid.insert_axis(Axis(1));
+            mse += &residuals.mapv(|r| r * r).sum_axis(Axis(0));
+        }
+
+        Ok(mse / A::from(n_samples).unwrap())
+    }
+
+    fn validate<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<()> {
+        if self.l1_ratio <= 0. || self.l1_ratio > 1. || self.l1_ratio.is_nan() {
+            return Err(LinearError::InvalidValue(format!(
+                "L1 ratio must be in the interval (0, 1], got: {}",
+                self.l1_ratio
+            )));
+        }
+        if self.n_alphas == 0 {
+            return Err(LinearError::InvalidValue(
+                "At least one penalty term is required".into(),
+            ));
+        }
+        if self.eps <= 0. || self.eps >= 1. || self.eps.is_nan() {
+            return Err(LinearError::InvalidValue(format!(
+                "Ratio of the smallest to the largest penalty must be in the interval (0, 1), \
+                 got: {}",
+                self.eps
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if x.nrows() == 0 || x.nrows() != y.len() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                x.nrows(),
+                y.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// The coefficients of an elastic net along a sequence of penalties
+pub struct FittedElasticNetPath<A> {
+    alphas: Array1<f64>,
+    params: Array2<A>,
+    intercepts: Array1<A>,
+    cv_mse: Option<Array1<A>>,
+}
+
+impl<A: Float> FittedElasticNetPath<A> {
+    /// Get the penalties in decreasing order, starting with `alpha_max`
+    pub fn alphas(&self) -> &Array1<f64> {
+        &self.alphas
+    }
+
+    /// Get the fitted parameters with shape `(n_features, n_alphas)`, one column per penalty
+    pub fn params(&self) -> &Array2<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept of every penalty, 0. if no intercept was fitted
+    pub fn intercepts(&self) -> &Array1<A> {
+        &self.intercepts
+    }
+
+    /// Get the cross-validated mean squared error of every penalty, if cross-validation was
+    /// enabled
+    pub fn cv_mse(&self) -> Option<&Array1<A>> {
+        self.cv_mse.as_ref()
+    }
+
+    /// Get the index of the penalty with the smallest cross-validated mean squared error
+    pub fn best_index(&self) -> Option<usize> {
+        self.cv_mse.as_ref().map(|mse| {
+            mse.iter()
+                .enumerate()
+                .fold(0, |best, (i, x)| if *x < mse[best] { i } else { best })
+        })
+    }
+
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`, predict the target
+    /// variable with the coefficients of the penalty at `index`
+    pub fn predict(&self, x: &Array2<A>, index: usize) -> Array1<A> {
+        x.dot(&self.params.column(index)) + self.intercepts[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Lasso, RidgeRegression};
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    fn dataset() -> (Array2<f64>, Array1<f64>) {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((100, 5), StandardNormal, &mut rng);
+        let noise = Array1::<f64>::random_using(100, StandardNormal, &mut rng);
+        let y = x.dot(&array![2., 0., -1., 0., 0.5]) + noise * 0.1 + 3.;
+
+        (x, y)
+    }
+
+    #[test]
+    fn l1_ratio_one_matches_lasso() {
+        let (x, y) = dataset();
+
+        let enet = ElasticNet::new()
+            .alpha(0.1)
+            .l1_ratio(1.0)
+            .tol(1e-10)
+            .fit(&x, &y)
+            .unwrap();
+        let lasso = Lasso::new().alpha(0.1).tol(1e-10).fit(&x, &y).unwrap();
+
+        assert_abs_diff_eq!(enet.params(), lasso.params(), epsilon = 1e-8);
+        assert_abs_diff_eq!(enet.intercept(), lasso.intercept(), epsilon = 1e-8);
+    }
+
+    #[test]
+    fn l1_ratio_zero_matches_ridge() {
+        let (x, y) = dataset();
+
+        let enet = ElasticNet::new()
+            .alpha(0.1)
+            .l1_ratio(0.0)
+            .tol(1e-12)
+            .fit(&x, &y)
+            .unwrap();
+        // the ridge penalty is not scaled by the number of samples
+        let ridge = RidgeRegression::new()
+            .alpha(0.1 * 100.)
+            .fit(&x, &y)
+            .unwrap();
+
+        assert_abs_diff_eq!(enet.params(), ridge.params(), epsilon = 1e-6);
+        assert_abs_diff_eq!(enet.intercept(), ridge.intercept(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn standardized_params_on_original_scale() {
+        let (x, y) = dataset();
+        // a large feature scale gives the coefficient a tiny weight in the penalty
+        let mut x_scaled = x.clone();
+        x_scaled.column_mut(0).mapv_inplace(|x| x * 1000.);
+
+        let enet = ElasticNet::new().alpha(0.1).standardize(true);
+        let model = enet.fit(&x, &y).unwrap();
+        let model_scaled = enet.fit(&x_scaled, &y).unwrap();
+
+        // with standardization the fit does not depend on the scale of the features
+        assert_abs_diff_eq!(
+            model_scaled.params()[0] * 1000.,
+            model.params()[0],
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            model_scaled.predict(&x_scaled),
+            model.predict(&x),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn path_starts_at_zero_and_is_continuous() {
+        let (x, y) = dataset();
+        let path = ElasticNetPath::new().tol(1e-10).fit(&x, &y).unwrap();
+
+        let alphas = path.alphas();
+        assert_eq!(alphas.len(), 100);
+        assert_eq!(path.params().dim(), (5, 100));
+        assert_abs_diff_eq!(alphas[99] / alphas[0], 1e-3, epsilon = 1e-12);
+        assert!(alphas.as_slice().unwrap().windows(2).all(|w| w[1] < w[0]));
+
+        assert!(path.params().column(0).iter().all(|x| *x == 0.));
+        assert_abs_diff_eq!(path.intercepts()[0], y.mean().unwrap(), epsilon = 1e-12);
+        // the first penalty below `alpha_max` selects a feature
+        assert!(path.params().column(1).iter().any(|x| *x != 0.));
+        assert!(path.cv_mse().is_none());
+
+        for i in 1..100 {
+            let step = &path.params().column(i) - &path.params().column(i - 1);
+            assert!(step.iter().all(|x| x.abs() < 0.25));
+        }
+
+        // every point of the path is the solution of a single fit
+        let lasso = Lasso::new()
+            .alpha(alphas[50])
+            .tol(1e-10)
+            .fit(&x, &y)
+            .unwrap();
+        assert_abs_diff_eq!(
+            path.params().column(50),
+            lasso.params().view(),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(path.predict(&x, 50), lasso.predict(&x), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn cross_validation_beats_extreme_penalties() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let mut truth = Array1::<f64>::zeros(30);
+        truth[0] = 3.;
+        truth[1] = -2.;
+        truth[2] = 1.5;
+        let mut sample = |n_samples: usize| {
+            let x = Array2::<f64>::random_using((n_samples, 30), StandardNormal, &mut rng);
+            let noise = Array1::<f64>::random_using(n_samples, StandardNormal, &mut rng);
+            let y = x.dot(&truth) + noise;
+            (x, y)
+        };
+        // few samples for many features, the least regularized fit overfits
+        let (x, y) = sample(40);
+        let (x_test, y_test) = sample(200);
+
+        let path = ElasticNetPath::new()
+            .cross_validation(5)
+            .fit(&x, &y)
+            .unwrap();
+        assert_eq!(path.cv_mse().unwrap().len(), 100);
+        let best = path.best_index().unwrap();
+        assert!(best > 0 && best < 99);
+
+        let test_mse = |i: usize| {
+            let residuals = path.predict(&x_test, i) - &y_test;
+            residuals.dot(&residuals) / 200.
+        };
+        assert!(test_mse(best) < test_mse(0));
+        assert!(test_mse(best) < test_mse(99));
+    }
+
+    #[test]
+    fn invalid_path_values_are_rejected() {
+        let x = array![[0.], [1.]];
+        let y = array![0., 1.];
+
+        assert!(ElasticNetPath::new().l1_ratio(0.).fit(&x, &y).is_err());
+        assert!(ElasticNetPath::new().n_alphas(0).fit(&x, &y).is_err());
+        assert!(ElasticNetPath::new().eps(1.).fit(&x, &y).is_err());
+        assert!(ElasticNetPath::new()
+            .cross_validation(3)
+            .fit(&x, &y)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0.], [1.]];
+        let y = array![0., 1.];
+
+        assert!(ElasticNet::new().alpha(-1.).fit(&x, &y).is_err());
+        assert!(ElasticNet::new().l1_ratio(1.5).fit(&x, &y).is_err());
+        assert!(ElasticNet::new().l1_ratio(-0.5).fit(&x, &y).is_err());
+        assert!(ElasticNet::new().fit(&x, &array![0.]).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use ndarray_linalg::error::LinalgError;
 use std::error::Error;
 use std::fmt::{self, Display};
 
@@ -8,15 +9,21 @@ pub type Result<T> = std::result::Result<T, LinearError>;
 pub enum LinearError {
     /// When any of the hyperparameters are set the wrong value
     InvalidValue(String),
+    /// When the targets are outside of the support of the assumed distribution
+    TargetOutOfRange(String),
     /// Errors encountered when using argmin's solver
     Argmin(argmin::core::Error),
+    /// Errors encountered during linear algebra operations
+    Linalg(LinalgError),
 }
 
 impl Display for LinearError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::InvalidValue(message) => write!(f, "Invalid value encountered: {}", message),
+            Self::TargetOutOfRange(message) => write!(f, "Target out of range: {}", message),
             Self::Argmin(error) => write!(f, "Argmin Error: {}", error),
+            Self::Linalg(error) => write!(f, "Linalg Error: {}", error),
         }
     }
 }
@@ -28,3 +35,9 @@ impl From<argmin::core::Error> for LinearError {
         LinearError::Argmin(error)
     }
 }
+
+impl From<LinalgError> for LinearError {
+    fn from(error: LinalgError) -> LinearError {
+        LinearError::Linalg(error)
+    }
+}
@@ -1,11 +1,15 @@
 //! Generalized Linear Models (GLM)
 
 mod distribution;
+mod family;
+mod irls;
 pub mod link;
 
 use crate::error::{LinearError, Result};
 use crate::float::{ArgminParam, Float};
 use distribution::TweedieDistribution;
+pub use family::Family;
+pub(crate) use irls::Irls;
 pub use link::Link;
 
 use argmin::core::{ArgminOp, Executor};
@@ -125,7 +129,7 @@ impl TweedieRegressor {
         if !dist.in_range(&y) {
             // An error is sent when y has values in the range not applicable
             // for the distribution
-            return Err(LinearError::InvalidValue(format!(
+            return Err(LinearError::TargetOutOfRange(format!(
                 "Some value(s) of y are out of the valid range for power value {}",
                 self.power
             )));
@@ -279,11 +283,163 @@ impl<A: Float> FittedTweedieRegressor<A> {
     }
 }
 
+/// Generalized Linear Model (GLM) fitted with iteratively reweighted least squares
+///
+/// The distribution of the targets is chosen with the [`family`](Family), the link with
+/// [`link`](GeneralizedLinearRegressor::link). In contrast to the
+/// [`TweedieRegressor`](TweedieRegressor), which minimizes the deviance with L-BFGS, the
+/// solver converges in a few iterations to the precision of statistical packages like R's `glm`.
+///
+/// The penalized mean deviance
+/// ```ignore
+/// 1 / (2 * n_samples) * deviance(y, h(X w + c)) + alpha / 2 * ||w||^2_2
+/// ```
+/// with the inverse link `h` is minimized.
+pub struct GeneralizedLinearRegressor {
+    family: Family,
+    link: Option<Link>,
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for GeneralizedLinearRegressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeneralizedLinearRegressor {
+    /// Create a default unpenalized GLM of the Gaussian family with an intercept
+    pub fn new() -> Self {
+        Self {
+            family: Family::Gaussian,
+            link: None,
+            alpha: 0.,
+            fit_intercept: true,
+            max_iter: 100,
+            tol: 1e-8,
+        }
+    }
+
+    /// The distribution family of the targets
+    pub fn family(mut self, family: Family) -> Self {
+        self.family = family;
+        self
+    }
+
+    /// The link function of the GLM, the [`default link`](Family::default_link) of the family
+    /// if not set
+    pub fn link(mut self, link: Link) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    /// Constant that multiplies with the L2 penalty term and thus determines the
+    /// regularization strength. The intercept is not penalized.
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Maximum number of IRLS iterations
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion, the change of the objective between two iterations relative to the
+    /// objective
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit a GLM given a feature matrix `x` with shape `(n_samples, n_features)` and a target
+    /// variable `y` with shape `(n_samples)`
+    ///
+    /// Returns [`TargetOutOfRange`](LinearError::TargetOutOfRange) if a target is outside of
+    /// the support of the family, for example a negative target of the Gamma family.
+    pub fn fit<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+    ) -> Result<FittedGeneralizedLinearRegressor<A>> {
+        let link = self.link.unwrap_or_else(|| self.family.default_link());
+        let irls = Irls {
+            family: self.family,
+            link,
+            alpha: self.alpha,
+            fit_intercept: self.fit_intercept,
+            max_iter: self.max_iter,
+            tol: self.tol,
+        };
+        let fit = irls.fit(x, y, &Array1::zeros(y.len()))?;
+
+        Ok(FittedGeneralizedLinearRegressor {
+            intercept: fit.intercept,
+            params: fit.params,
+            deviance: fit.deviance,
+            n_iter: fit.n_iter,
+            link,
+        })
+    }
+}
+
+/// A fitted GLM which can be used for making predictions
+pub struct FittedGeneralizedLinearRegressor<A> {
+    intercept: A,
+    params: Array1<A>,
+    deviance: A,
+    n_iter: usize,
+    link: Link,
+}
+
+impl<A: Float> FittedGeneralizedLinearRegressor<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the expected target `h(x w + c)`
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        self.link.inverse(&(x.dot(&self.params) + self.intercept))
+    }
+
+    /// Get the fitted parameters
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the deviance of the fitted model on the training data
+    pub fn deviance(&self) -> A {
+        self.deviance
+    }
+
+    /// Get the number of IRLS iterations performed
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{LinearRegression, PoissonRegressor};
     use approx::assert_abs_diff_eq;
     use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
 
     macro_rules! test_tweedie {
         ($($name:ident: {power: $power:expr, intercept: $intercept:expr,},)*) => {
@@ -359,4 +515,76 @@ mod tests {
             intercept: false,
         },
     }
+
+    #[test]
+    fn gaussian_family_reproduces_ols() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((50, 3), StandardNormal, &mut rng);
+        let noise = Array1::<f64>::random_using(50, StandardNormal, &mut rng);
+        let y = x.dot(&array![1., -2., 0.5]) + noise + 3.;
+
+        let ols = LinearRegression::new().fit(&x, &y).unwrap();
+        let glm = GeneralizedLinearRegressor::new().fit(&x, &y).unwrap();
+
+        assert_abs_diff_eq!(glm.params(), ols.params(), epsilon = 1e-8);
+        assert_abs_diff_eq!(glm.intercept(), ols.intercept(), epsilon = 1e-8);
+        let residuals = &y - &ols.predict(&x);
+        assert_abs_diff_eq!(glm.deviance(), residuals.dot(&residuals), epsilon = 1e-8);
+    }
+
+    #[test]
+    fn poisson_family_reproduces_poisson_regressor() {
+        let x = array![[0., 1.], [1., 0.], [2., 1.], [3., 0.], [4., 1.], [5., 0.]];
+        let y = array![1., 0., 3., 2., 8., 6.];
+
+        let poisson = PoissonRegressor::new().alpha(0.1).fit(&x, &y).unwrap();
+        let glm = GeneralizedLinearRegressor::new()
+            .family(Family::Poisson)
+            .alpha(0.1)
+            .fit(&x, &y)
+            .unwrap();
+
+        assert_abs_diff_eq!(glm.params(), poisson.params(), epsilon = 1e-10);
+        assert_abs_diff_eq!(glm.intercept(), poisson.intercept(), epsilon = 1e-10);
+        assert_abs_diff_eq!(glm.deviance(), poisson.deviance(), epsilon = 1e-10);
+        assert_abs_diff_eq!(glm.predict(&x), poisson.predict(&x), epsilon = 1e-10);
+    }
+
+    #[test]
+    fn gamma_and_tweedie_families() {
+        let x = array![[0.], [1.], [2.], [3.], [4.]];
+        let y = x.column(0).mapv(|x: f64| (0.2 - 0.1 * x).exp());
+
+        for family in &[Family::Gamma, Family::Tweedie(1.5), Family::Tweedie(3.)] {
+            let glm = GeneralizedLinearRegressor::new()
+                .family(*family)
+                .fit(&x, &y)
+                .unwrap();
+
+            assert_abs_diff_eq!(glm.intercept(), 0.2, epsilon = 1e-6);
+            assert_abs_diff_eq!(glm.params(), &array![-0.1], epsilon = 1e-6);
+            assert_abs_diff_eq!(glm.deviance(), 0., epsilon = 1e-10);
+        }
+    }
+
+    #[test]
+    fn invalid_targets_are_rejected() {
+        let x = array![[0.], [1.], [2.]];
+
+        let gamma = GeneralizedLinearRegressor::new().family(Family::Gamma);
+        match gamma.fit(&x, &array![1., -1., 2.]) {
+            Err(LinearError::TargetOutOfRange(_)) => {}
+            _ => panic!("negative targets must be rejected by the Gamma family"),
+        }
+        match gamma.fit(&x, &array![1., 0., 2.]) {
+            Err(LinearError::TargetOutOfRange(_)) => {}
+            _ => panic!("zero targets must be rejected by the Gamma family"),
+        }
+
+        let tweedie = GeneralizedLinearRegressor::new().family(Family::Tweedie(0.5));
+        match tweedie.fit(&x, &array![1., 0., 2.]) {
+            Err(LinearError::InvalidValue(_)) => {}
+            _ => panic!("powers between 0 and 1 must be rejected"),
+        }
+    }
 }
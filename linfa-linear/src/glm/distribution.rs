@@ -93,10 +93,10 @@ impl TweedieDistribution {
                     if x == A::from(0.).unwrap() {
                         *y = A::from(0.).unwrap();
                     } else {
-                        *y = A::from(2.).unwrap() * (x * y.ln());
+                        *y = x * y.ln();
                     }
                 });
-                Ok(div - y + ypred)
+                Ok((div - y + ypred).mapv(|x| A::from(2.).unwrap() * x))
             }
             // Gamma distribution
             // 2 * (log(ypred / y) + (y / ypred) - 1)
@@ -196,6 +196,14 @@ mod tests {
         test_deviance_tweedie5: (TweedieDistribution::new(-4.).unwrap(), array![0.1, 1.5]),
     }
 
+    #[test]
+    fn test_deviance_poisson_value() {
+        // 2 * (y * ln(y / ypred) - y + ypred)
+        let dist = TweedieDistribution::new(1.).unwrap();
+        let output = dist.deviance(&array![1., 0.], &array![2., 0.5]).unwrap();
+        assert_abs_diff_eq!(output, 2. * (0.5f64.ln() + 1.) + 1., epsilon = 1e-9);
+    }
+
     macro_rules! test_deviance_derivative {
         ($($name:ident: {dist: $dist:expr, y: $y:expr, ypred: $ypred:expr, expected: $expected:expr,},)*) => {
             $(
@@ -0,0 +1,99 @@
+//! Distribution families of GLMs
+
+use ndarray::Array1;
+
+use super::distribution::TweedieDistribution;
+use super::link::Link;
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+/// The distribution of the targets of a GLM, given by its variance function
+///
+/// All families are members of the Tweedie family, whose variance is a power of the mean
+/// `Var[y] ∝ E[y]^power`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Family {
+    /// Normal distribution with constant variance, for any real targets
+    Gaussian,
+    /// Poisson distribution with `Var[y] ∝ E[y]`, for non-negative counts
+    Poisson,
+    /// Gamma distribution with `Var[y] ∝ E[y]^2`, for positive targets
+    Gamma,
+    /// Tweedie distribution with `Var[y] ∝ E[y]^power`, powers in `(0, 1)` are invalid
+    ///
+    /// Powers between 1 and 2 are compound Poisson Gamma distributions, which allow for exact
+    /// zeros among otherwise positive targets.
+    Tweedie(f64),
+}
+
+impl Family {
+    /// The power of the mean in the variance function
+    pub fn power(&self) -> f64 {
+        match self {
+            Self::Gaussian => 0.,
+            Self::Poisson => 1.,
+            Self::Gamma => 2.,
+            Self::Tweedie(power) => *power,
+        }
+    }
+
+    /// The link used if none is specified, the canonical [`identity`](Link::Identity) link
+    /// for the Normal distribution and the [`log`](Link::Log) link otherwise, which is
+    /// canonical for the Poisson distribution
+    pub fn default_link(&self) -> Link {
+        if self.power() <= 0. {
+            Link::Identity
+        } else {
+            Link::Log
+        }
+    }
+
+    /// The variance function `V(ypred)` up to the dispersion
+    pub fn variance<A: Float>(&self, ypred: &Array1<A>) -> Array1<A> {
+        let power = A::from(self.power()).unwrap();
+        ypred.mapv(|x| x.powf(power))
+    }
+
+    /// The deviance of the predicted means `ypred` for the targets `y`
+    pub fn deviance<A: Float>(&self, y: &Array1<A>, ypred: &Array1<A>) -> Result<A> {
+        TweedieDistribution::new(self.power())?.deviance(y, ypred)
+    }
+
+    /// Check that the power is valid and that all targets are in the support of the
+    /// distribution
+    pub(crate) fn check_targets<A: Float>(&self, y: &Array1<A>) -> Result<()> {
+        if !TweedieDistribution::new(self.power())?.in_range(y) {
+            return Err(LinearError::TargetOutOfRange(format!(
+                "Some value(s) of y are out of the valid range for the {:?} family",
+                self
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_family_targets() {
+        let y = array![-1., 0., 1.];
+        assert!(Family::Gaussian.check_targets(&y).is_ok());
+        assert!(Family::Poisson.check_targets(&y.mapv(f64::abs)).is_ok());
+        assert!(Family::Gamma.check_targets(&array![0.5, 1.]).is_ok());
+
+        for family in &[Family::Poisson, Family::Gamma, Family::Tweedie(1.5)] {
+            match family.check_targets(&y) {
+                Err(LinearError::TargetOutOfRange(_)) => {}
+                _ => panic!("negative targets must be rejected by {:?}", family),
+            }
+        }
+        match Family::Tweedie(0.5).check_targets(&y) {
+            Err(LinearError::InvalidValue(_)) => {}
+            _ => panic!("powers between 0 and 1 must be rejected"),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Iteratively reweighted least squares (IRLS) for GLMs
+//!
+//! The penalized mean deviance
+//! ```ignore
+//! 1 / (2 * n_samples) * deviance(y, h(X w + c + offset)) + alpha / 2 * ||w||^2_2
+//! ```
+//! with the inverse link `h` is minimized with Fisher scoring. In each iteration a weighted
+//! least squares problem with weights `h'(eta)^2 / V(ypred)` is solved, which coincides with
+//! Newton's method for canonical links. Steps are halved until the objective decreases and the
+//! iteration stops once the relative change of the objective is below the tolerance, as in R's
+//! `glm`.
+
+use ndarray::{array, s, stack, Array1, Array2, Axis};
+use ndarray_linalg::Solve;
+
+use super::family::Family;
+use super::link::Link;
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+pub(crate) struct Irls {
+    pub family: Family,
+    pub link: Link,
+    pub alpha: f64,
+    pub fit_intercept: bool,
+    pub max_iter: usize,
+    pub tol: f64,
+}
+
+pub(crate) struct IrlsFit<A> {
+    pub intercept: A,
+    pub params: Array1<A>,
+    pub deviance: A,
+    pub n_iter: usize,
+}
+
+impl Irls {
+    /// Fit the coefficients given the feature matrix `x`, the targets `y` and a fixed offset
+    /// of the linear predictor
+    pub fn fit<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        offset: &Array1<A>,
+    ) -> Result<IrlsFit<A>> {
+        self.validate(x, y)?;
+
+        let n_samples = x.nrows();
+        let design = if self.fit_intercept {
+            stack(Axis(1), &[Array2::ones((n_samples, 1)).view(), x.view()]).unwrap()
+        } else {
+            x.clone()
+        };
+        let first = if self.fit_intercept { 1 } else { 0 };
+
+        let n = A::from(n_samples).unwrap();
+        let half = A::from(0.5).unwrap();
+        let alpha = A::from(self.alpha).unwrap();
+        let tol = A::from(self.tol).unwrap();
+        let objective = |coef: &Array1<A>, ypred: &Array1<A>| -> Result<A> {
+            let params = coef.slice(s![first..]);
+            Ok(self.family.deviance(y, ypred)? / (n + n) + half * alpha * params.dot(&params))
+        };
+
+        // the intercept starts at the link of the mean target
+        let mut coef: Array1<A> = Array1::zeros(design.ncols());
+        if self.fit_intercept {
+            coef[0] = self.link.link(&array![y.mean().unwrap()])[0] - offset.mean().unwrap();
+        }
+        let mut lin_pred = design.dot(&coef) + offset;
+        let mut ypred = self.link.inverse(&lin_pred);
+        let mut current = objective(&coef, &ypred)?;
+
+        let mut n_iter = 0;
+        while n_iter < self.max_iter {
+            n_iter += 1;
+
+            // Fisher scoring step on the objective scaled by the number of samples
+            let derivative = self.link.inverse_derviative(&lin_pred);
+            let variance = self.family.variance(&ypred);
+            let weights = &derivative * &derivative / &variance;
+            let mut hessian = design
+                .t()
+                .dot(&(&design * &weights.view().insert_axis(Axis(1))));
+            let mut gradient = design.t().dot(&((y - &ypred) * &derivative / &variance));
+            for i in first..design.ncols() {
+                hessian[(i, i)] += n * alpha;
+                gradient[i] -= n * alpha * coef[i];
+            }
+            let step = hessian.solve_into(gradient)?;
+
+            let mut step_size = A::one();
+            let (new_coef, new_lin_pred, new_ypred, new) = loop {
+                let new_coef = &coef + &step.mapv(|s| s * step_size);
+                let new_lin_pred = design.dot(&new_coef) + offset;
+                let new_ypred = self.link.inverse(&new_lin_pred);
+                let new = objective(&new_coef, &new_ypred)?;
+                if new <= current || step_size < A::from(1e-10).unwrap() {
+                    break (new_coef, new_lin_pred, new_ypred, new);
+                }
+                step_size *= half;
+            };
+
+            let converged = (current - new).abs() < tol * (new.abs() + A::from(0.1).unwrap());
+            coef = new_coef;
+            lin_pred = new_lin_pred;
+            ypred = new_ypred;
+            current = new;
+            if converged {
+                break;
+            }
+        }
+
+        let intercept = if self.fit_intercept {
+            coef[0]
+        } else {
+            A::zero()
+        };
+
+        Ok(IrlsFit {
+            intercept,
+            params: coef.slice(s![first..]).to_owned(),
+            deviance: self.family.deviance(y, &ypred)?,
+            n_iter,
+        })
+    }
+
+    fn validate<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<()> {
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if x.nrows() == 0 || x.nrows() != y.len() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                x.nrows(),
+                y.len()
+            )));
+        }
+        self.family.check_targets(y)?;
+        if let Link::Log = self.link {
+            if self.fit_intercept && y.sum() <= A::zero() {
+                return Err(LinearError::TargetOutOfRange(
+                    "At least one target must be positive to fit an intercept with the log link"
+                        .into(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
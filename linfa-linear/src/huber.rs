@@ -0,0 +1,337 @@
+//! Huber Regression
+//!
+//! Linear regression which is robust to outliers. Residuals smaller than `epsilon` times the
+//! scale are penalized quadratically, larger residuals only linearly. The scale is estimated
+//! jointly with the coefficients by minimizing
+//! ```ignore
+//! sum_i (sigma + H_epsilon((y_i - x_i w - c) / sigma) * sigma) + alpha * ||w||^2_2
+//! ```
+//! with `H_epsilon(z) = z^2` for `|z| <= epsilon` and `2 * epsilon * |z| - epsilon^2` otherwise,
+//! following [Owen (2007)](https://statweb.stanford.edu/~owen/reports/hhu.pdf). The objective is
+//! convex in the coefficients and the scale and is minimized by alternating between iteratively
+//! reweighted least squares for the coefficients and an exact minimization for the scale.
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+use ndarray::{s, stack, Array1, Array2, Axis};
+use ndarray_linalg::Solve;
+use std::cmp::Ordering;
+
+/// Huber regressor, a linear model robust to outliers
+pub struct HuberRegressor {
+    epsilon: f64,
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for HuberRegressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HuberRegressor {
+    /// Create a default Huber regressor with `epsilon = 1.35`, `alpha = 1e-4` and an intercept
+    pub fn new() -> Self {
+        Self {
+            epsilon: 1.35,
+            alpha: 1e-4,
+            fit_intercept: true,
+            max_iter: 100,
+            tol: 1e-5,
+        }
+    }
+
+    /// Threshold relative to the scale above which residuals are treated as outliers, must be
+    /// at least 1
+    ///
+    /// Smaller values make the model more robust, the default of 1.35 retains 95% of the
+    /// statistical efficiency of least squares for normally distributed noise.
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Constant that multiplies with the L2 penalty term and thus determines the
+    /// regularization strength
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Maximum number of reweighting iterations
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion, the largest change of the coefficients and the scale between two
+    /// iterations
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit a Huber regressor given a feature matrix `x` with shape `(n_samples, n_features)`
+    /// and a target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedHuberRegressor<A>> {
+        self.validate(x, y)?;
+
+        let n_samples = x.nrows();
+        let design = if self.fit_intercept {
+            stack(Axis(1), &[Array2::ones((n_samples, 1)).view(), x.view()]).unwrap()
+        } else {
+            x.clone()
+        };
+        let offset = if self.fit_intercept { 1 } else { 0 };
+
+        let epsilon = A::from(self.epsilon).unwrap();
+        let alpha = A::from(self.alpha).unwrap();
+        let tol = A::from(self.tol).unwrap();
+
+        // the first iteration is an ordinary least squares fit
+        let mut weights: Array1<A> = Array1::ones(n_samples);
+        let mut coef: Array1<A> = Array1::zeros(design.ncols());
+        let mut scale = A::one();
+        let mut n_iter = 0;
+        while n_iter < self.max_iter {
+            n_iter += 1;
+
+            let weights_sqrt = weights.mapv(|w| w.sqrt());
+            let design_weighted = &design * &weights_sqrt.view().insert_axis(Axis(1));
+            let mut gram = design_weighted.t().dot(&design_weighted);
+            for i in offset..design.ncols() {
+                gram[(i, i)] += alpha * scale;
+            }
+            let new_coef = gram.solve_into(design_weighted.t().dot(&(y * &weights_sqrt)))?;
+
+            let residuals = y - &design.dot(&new_coef);
+            let new_scale = optimal_scale(&residuals, epsilon).max(A::epsilon());
+            weights = residuals.mapv(|r| {
+                if num_traits::Float::abs(r) <= epsilon * new_scale {
+                    A::one()
+                } else {
+                    epsilon * new_scale / num_traits::Float::abs(r)
+                }
+            });
+
+            let change = (&new_coef - &coef)
+                .fold(num_traits::Float::abs(new_scale - scale), |max, x| {
+                    max.max(num_traits::Float::abs(*x))
+                });
+            coef = new_coef;
+            scale = new_scale;
+            if change < tol {
+                break;
+            }
+        }
+
+        let outliers =
+            (y - &design.dot(&coef)).mapv(|r| num_traits::Float::abs(r) > epsilon * scale);
+        let intercept = if self.fit_intercept {
+            coef[0]
+        } else {
+            A::zero()
+        };
+
+        Ok(FittedHuberRegressor {
+            intercept,
+            params: coef.slice(s![offset..]).to_owned(),
+            scale,
+            outliers,
+            n_iter,
+        })
+    }
+
+    fn validate<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<()> {
+        if self.epsilon < 1. || self.epsilon.is_nan() {
+            return Err(LinearError::InvalidValue(format!(
+                "Epsilon must be at least 1, got: {}",
+                self.epsilon
+            )));
+        }
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if x.nrows() == 0 || x.nrows() != y.len() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                x.nrows(),
+                y.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Minimize the objective over the scale for fixed residuals
+///
+/// For a fixed set of outliers the minimum is at `sqrt(S / (n - m * epsilon^2))` with the sum of
+/// squared inlier residuals `S` and the number of outliers `m`. The objective is convex in the
+/// scale, so the intervals of constant outlier sets are searched from large to small scales until
+/// the minimum is found inside an interval or at its border.
+fn optimal_scale<A: Float>(residuals: &Array1<A>, epsilon: A) -> A {
+    let mut abs = residuals
+        .iter()
+        .map(|r| num_traits::Float::abs(*r))
+        .collect::<Vec<_>>();
+    abs.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let n = abs.len();
+    let mut squared_sums = Vec::with_capacity(n + 1);
+    squared_sums.push(A::zero());
+    for r in &abs {
+        squared_sums.push(*squared_sums.last().unwrap() + *r * *r);
+    }
+
+    for n_outliers in 0..=n {
+        let n_inliers = n - n_outliers;
+        let lower = if n_inliers > 0 {
+            abs[n_inliers - 1] / epsilon
+        } else {
+            A::zero()
+        };
+        let upper = if n_outliers > 0 {
+            abs[n_inliers] / epsilon
+        } else {
+            A::infinity()
+        };
+
+        let denominator = A::from(n).unwrap() - A::from(n_outliers).unwrap() * epsilon * epsilon;
+        if denominator <= A::zero() {
+            return upper;
+        }
+        let scale = (squared_sums[n_inliers] / denominator).sqrt();
+        if scale > upper {
+            return upper;
+        }
+        if scale >= lower {
+            return scale;
+        }
+    }
+
+    A::zero()
+}
+
+/// A fitted Huber regressor which can be used for making predictions
+pub struct FittedHuberRegressor<A> {
+    intercept: A,
+    params: Array1<A>,
+    scale: A,
+    outliers: Array1<bool>,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedHuberRegressor<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the target variable according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        x.dot(&self.params) + self.intercept
+    }
+
+    /// Get the fitted parameters
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the estimated scale of the residuals
+    pub fn scale(&self) -> A {
+        self.scale
+    }
+
+    /// Get a mask of the training samples treated as outliers, those with an absolute residual
+    /// larger than `epsilon * scale`
+    pub fn outliers(&self) -> &Array1<bool> {
+        &self.outliers
+    }
+
+    /// Get the number of reweighting iterations performed
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinearRegression;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn robust_to_gross_outliers() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((100, 2), StandardNormal, &mut rng);
+        let noise = Array1::<f64>::random_using(100, StandardNormal, &mut rng);
+        let mut y = x.dot(&array![2., -1.]) + noise * 0.1 + 1.;
+        // 10% of the targets are replaced by gross outliers
+        y.slice_mut(s![..10]).fill(50.);
+
+        let clean = LinearRegression::new()
+            .fit(
+                &x.slice(s![10.., ..]).to_owned(),
+                &y.slice(s![10..]).to_owned(),
+            )
+            .unwrap();
+        let ols = LinearRegression::new().fit(&x, &y).unwrap();
+        let huber = HuberRegressor::new().fit(&x, &y).unwrap();
+
+        assert_abs_diff_eq!(huber.params(), clean.params(), epsilon = 0.1);
+        assert_abs_diff_eq!(huber.intercept(), clean.intercept(), epsilon = 0.1);
+        assert!((ols.intercept() - clean.intercept()).abs() > 3.);
+
+        assert!(huber.outliers().slice(s![..10]).iter().all(|x| *x));
+        assert!(huber.n_iter() < 100);
+    }
+
+    #[test]
+    fn scale_of_fixed_residuals() {
+        // without outliers the scale is the root mean square of the residuals
+        let residuals = array![1., -1., 1., -1.];
+        assert_abs_diff_eq!(optimal_scale(&residuals, 1.35), 1.);
+
+        // the large residual is an outlier, the minimum satisfies n - m * eps^2 = S / scale^2
+        let residuals = array![1., -1., 1., 100.];
+        let expected = (3f64 / (4. - 1.35 * 1.35)).sqrt();
+        assert_abs_diff_eq!(optimal_scale(&residuals, 1.35), expected, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0.], [1.]];
+        let y = array![0., 1.];
+
+        assert!(HuberRegressor::new().epsilon(0.5).fit(&x, &y).is_err());
+        assert!(HuberRegressor::new().alpha(-1.).fit(&x, &y).is_err());
+        assert!(HuberRegressor::new().tol(0.).fit(&x, &y).is_err());
+        assert!(HuberRegressor::new().fit(&x, &array![0.]).is_err());
+    }
+}
@@ -0,0 +1,337 @@
+//! Isotonic Regression
+//!
+//! Fit a monotone, piecewise constant function to one-dimensional data by minimizing
+//! ```ignore
+//! sum_i w_i * (y_i - f(x_i))^2
+//! ```
+//! subject to `f` being non-decreasing (or non-increasing). The solution is found with the
+//! pool-adjacent-violators algorithm (PAV): samples are visited in the order of `x` and adjacent
+//! blocks violating the constraint are pooled to their weighted mean. Samples with equal `x` are
+//! pooled before, so that they always receive the same prediction.
+//!
+//! Predictions linearly interpolate between the fitted breakpoints and are clamped to the first
+//! and last fitted value outside of the training range. This makes the model suitable to
+//! calibrate the scores of a classifier.
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+use ndarray::Array1;
+use std::cmp::Ordering;
+
+/// Isotonic regression, a monotone one-dimensional regressor
+pub struct IsotonicRegression {
+    increasing: bool,
+}
+
+impl Default for IsotonicRegression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IsotonicRegression {
+    /// Create a default isotonic regression with a non-decreasing constraint
+    pub fn new() -> Self {
+        Self { increasing: true }
+    }
+
+    /// Specifies whether the fitted function is non-decreasing (`true`) or non-increasing
+    pub fn increasing(mut self, increasing: bool) -> Self {
+        self.increasing = increasing;
+        self
+    }
+
+    /// Fit an isotonic regression given the one-dimensional input `x` and the target variable
+    /// `y`, both with shape `(n_samples)`
+    pub fn fit<A: Float>(
+        &self,
+        x: &Array1<A>,
+        y: &Array1<A>,
+    ) -> Result<FittedIsotonicRegression<A>> {
+        self.fit_weighted(x, y, &Array1::ones(y.len()))
+    }
+
+    /// Fit an isotonic regression with a non-negative weight for each sample
+    ///
+    /// A sample with weight `k` has the same effect as `k` copies of the sample.
+    pub fn fit_weighted<A: Float>(
+        &self,
+        x: &Array1<A>,
+        y: &Array1<A>,
+        weights: &Array1<A>,
+    ) -> Result<FittedIsotonicRegression<A>> {
+        validate(x, y, weights)?;
+
+        let mut order = (0..x.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| x[*a].partial_cmp(&x[*b]).unwrap_or(Ordering::Equal));
+
+        // pool ties in x to their weighted mean, samples without weight are ignored
+        let mut groups: Vec<Block<A>> = Vec::new();
+        for i in order.into_iter().filter(|i| weights[*i] > A::zero()) {
+            let sample = Block {
+                first: x[i],
+                last: x[i],
+                value: y[i],
+                weight: weights[i],
+            };
+            match groups.last_mut() {
+                Some(group) if group.last == x[i] => group.pool(sample),
+                _ => groups.push(sample),
+            }
+        }
+
+        // a non-increasing fit is a non-decreasing fit of the negated targets
+        let sign = if self.increasing { A::one() } else { -A::one() };
+        let mut blocks: Vec<Block<A>> = Vec::with_capacity(groups.len());
+        for mut group in groups {
+            group.value *= sign;
+            blocks.push(group);
+            while blocks.len() > 1
+                && blocks[blocks.len() - 2].value > blocks[blocks.len() - 1].value
+            {
+                let last = blocks.pop().unwrap();
+                blocks.last_mut().unwrap().pool(last);
+            }
+        }
+
+        // each block is constant between its first and last x
+        let mut x_thresholds = Vec::with_capacity(2 * blocks.len());
+        let mut y_thresholds = Vec::with_capacity(2 * blocks.len());
+        for block in &blocks {
+            x_thresholds.push(block.first);
+            y_thresholds.push(block.value * sign);
+            if block.last > block.first {
+                x_thresholds.push(block.last);
+                y_thresholds.push(block.value * sign);
+            }
+        }
+
+        Ok(FittedIsotonicRegression {
+            x_thresholds: Array1::from(x_thresholds),
+            y_thresholds: Array1::from(y_thresholds),
+            increasing: self.increasing,
+        })
+    }
+}
+
+fn validate<A: Float>(x: &Array1<A>, y: &Array1<A>, weights: &Array1<A>) -> Result<()> {
+    if x.len() != y.len() || x.len() != weights.len() {
+        return Err(LinearError::InvalidValue(format!(
+            "Number of samples in x ({}), y ({}) and weights ({}) must be equal",
+            x.len(),
+            y.len(),
+            weights.len()
+        )));
+    }
+    if x.iter().chain(y.iter()).any(|v| !v.is_finite()) {
+        return Err(LinearError::InvalidValue("Samples must be finite".into()));
+    }
+    if weights.iter().any(|w| *w < A::zero() || !w.is_finite()) {
+        return Err(LinearError::InvalidValue(
+            "Weights must be non-negative".into(),
+        ));
+    }
+    if weights.sum() <= A::zero() {
+        return Err(LinearError::InvalidValue(
+            "At least one sample with a positive weight is required".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Consecutive samples pooled to their weighted mean
+struct Block<A> {
+    first: A,
+    last: A,
+    value: A,
+    weight: A,
+}
+
+impl<A: Float> Block<A> {
+    /// Merge the following block into this one
+    fn pool(&mut self, next: Block<A>) {
+        let weight = self.weight + next.weight;
+        self.value = (self.value * self.weight + next.value * next.weight) / weight;
+        self.weight = weight;
+        self.last = next.last;
+    }
+}
+
+/// A fitted isotonic regression which can be used for making predictions
+pub struct FittedIsotonicRegression<A> {
+    x_thresholds: Array1<A>,
+    y_thresholds: Array1<A>,
+    increasing: bool,
+}
+
+impl<A: Float> FittedIsotonicRegression<A> {
+    /// Given the one-dimensional input `x`, `predict` linearly interpolates between the
+    /// fitted breakpoints, values outside of the training range are clamped
+    pub fn predict(&self, x: &Array1<A>) -> Array1<A> {
+        let (xs, ys) = (&self.x_thresholds, &self.y_thresholds);
+        let last = xs.len() - 1;
+
+        x.mapv(|x| {
+            if x <= xs[0] {
+                return ys[0];
+            }
+            if x >= xs[last] {
+                return ys[last];
+            }
+            // the first breakpoint larger than x, the one before is smaller or equal
+            let upper = xs
+                .as_slice()
+                .unwrap()
+                .iter()
+                .position(|threshold| *threshold > x)
+                .unwrap();
+            let (x0, x1, y0, y1) = (xs[upper - 1], xs[upper], ys[upper - 1], ys[upper]);
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        })
+    }
+
+    /// Get the inputs of the fitted breakpoints in increasing order
+    pub fn x_thresholds(&self) -> &Array1<A> {
+        &self.x_thresholds
+    }
+
+    /// Get the fitted values at the breakpoints
+    pub fn y_thresholds(&self) -> &Array1<A> {
+        &self.y_thresholds
+    }
+
+    /// Whether the fitted function is non-decreasing or non-increasing
+    pub fn increasing(&self) -> bool {
+        self.increasing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::{StandardNormal, Uniform};
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn monotone_data_is_reproduced() {
+        let x = array![3., 1., 2., 5., 4.];
+        let y = array![2.5, 1., 2., 7., 4.];
+
+        let model = IsotonicRegression::new().fit(&x, &y).unwrap();
+        assert_abs_diff_eq!(model.predict(&x), y, epsilon = 1e-12);
+
+        let model = IsotonicRegression::new()
+            .increasing(false)
+            .fit(&x, &-&y)
+            .unwrap();
+        assert!(!model.increasing());
+        assert_abs_diff_eq!(model.predict(&x), -&y, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn interpolation_and_clamping() {
+        let x = array![0., 1., 2., 3.];
+        let y = array![0., 2., 1., 3.];
+
+        // the violators at x = 1 and x = 2 are pooled to their mean
+        let model = IsotonicRegression::new().fit(&x, &y).unwrap();
+        assert_abs_diff_eq!(model.x_thresholds(), &array![0., 1., 2., 3.]);
+        assert_abs_diff_eq!(model.y_thresholds(), &array![0., 1.5, 1.5, 3.]);
+
+        let predictions = model.predict(&array![-1., 0.5, 1.7, 2.5, 10.]);
+        assert_abs_diff_eq!(
+            predictions,
+            array![0., 0.75, 1.5, 2.25, 3.],
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn ties_are_averaged() {
+        let x = array![1., 1., 2., 2., 3.];
+        let y = array![0., 2., 4., 3., 5.];
+        let weights = array![1., 1., 1., 3., 1.];
+
+        let model = IsotonicRegression::new()
+            .fit_weighted(&x, &y, &weights)
+            .unwrap();
+        assert_abs_diff_eq!(model.x_thresholds(), &array![1., 2., 3.]);
+        assert_abs_diff_eq!(model.y_thresholds(), &array![1., 3.25, 5.]);
+    }
+
+    #[test]
+    fn output_is_monotone() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array1::<f64>::random_using(200, Uniform::new(0., 10.), &mut rng);
+        let noise = Array1::<f64>::random_using(200, StandardNormal, &mut rng);
+        let y = x.mapv(|x| x.sqrt()) + noise;
+        let grid = Array1::linspace(-1., 11., 500);
+
+        for &increasing in &[true, false] {
+            let model = IsotonicRegression::new()
+                .increasing(increasing)
+                .fit(&x, &y)
+                .unwrap();
+            let predictions = model.predict(&grid);
+            for pair in predictions.as_slice().unwrap().windows(2) {
+                if increasing {
+                    assert!(pair[0] <= pair[1]);
+                } else {
+                    assert!(pair[0] >= pair[1]);
+                }
+            }
+
+            // pooling preserves the sum of the targets
+            assert_abs_diff_eq!(model.predict(&x).sum(), y.sum(), epsilon = 1e-8);
+        }
+    }
+
+    #[test]
+    fn weights_match_duplicates() {
+        let x = array![0.5, 1., 2., 3., 4.];
+        let y = array![1., 3., 0., 2., 1.];
+        let weights = array![1., 3., 1., 2., 1.];
+
+        let x_dup = array![0.5, 1., 1., 1., 2., 3., 3., 4.];
+        let y_dup = array![1., 3., 3., 3., 0., 2., 2., 1.];
+
+        let weighted = IsotonicRegression::new()
+            .fit_weighted(&x, &y, &weights)
+            .unwrap();
+        let duplicated = IsotonicRegression::new().fit(&x_dup, &y_dup).unwrap();
+
+        assert_abs_diff_eq!(
+            weighted.x_thresholds(),
+            duplicated.x_thresholds(),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            weighted.y_thresholds(),
+            duplicated.y_thresholds(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![0., 1.];
+        let y = array![0., 1.];
+
+        assert!(IsotonicRegression::new().fit(&x, &array![0.]).is_err());
+        assert!(IsotonicRegression::new()
+            .fit_weighted(&x, &y, &array![1., -1.])
+            .is_err());
+        assert!(IsotonicRegression::new()
+            .fit_weighted(&x, &y, &array![0., 0.])
+            .is_err());
+        assert!(IsotonicRegression::new()
+            .fit(&array![0., f64::NAN], &y)
+            .is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! Lasso Regression
+//!
+//! Linear regression with a L1 penalty on the coefficients, which drives many of the coefficients
+//! to exactly zero and therefore performs feature selection. The objective
+//! ```ignore
+//! 1 / (2 * n_samples) * ||y - Xw||^2_2 + alpha * ||w||_1
+//! ```
+//! is minimized with cyclical coordinate descent.
+
+use crate::coordinate_descent::coordinate_descent;
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+#[cfg(feature = "sprs")]
+use crate::sparse::{sparse_coordinate_descent, sparse_dot, SparseColumns};
+
+use ndarray::{Array1, Array2, Axis};
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+
+/// Lasso regression, a linear model with L1 penalty
+pub struct Lasso {
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for Lasso {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Lasso {
+    pub fn new() -> Self {
+        Self {
+            alpha: 1.0,
+            fit_intercept: true,
+            max_iter: 1000,
+            tol: 1e-4,
+        }
+    }
+
+    /// Constant that multiplies with the penalty term and thus determines the
+    /// regularization strength. Larger values result in fewer non-zero coefficients.
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Maximum number of sweeps of the coordinate descent solver
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion for the duality gap, relative to the squared norm of the targets
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit a lasso model given a feature matrix `x` with shape `(n_samples, n_features)` and a
+    /// target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedLasso<A>> {
+        self.fit_with_initial_params(x, y, Array1::zeros(x.ncols()))
+    }
+
+    /// Fit a lasso model, starting the solver from `initial_params`
+    ///
+    /// Warm starting from the solution of a similar penalty can greatly reduce the number of
+    /// iterations, for example when a regularization path is computed.
+    pub fn fit_with_initial_params<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        initial_params: Array1<A>,
+    ) -> Result<FittedLasso<A>> {
+        self.validate(x.dim(), y.len(), initial_params.len())?;
+
+        let (x_offset, y_offset) = if self.fit_intercept {
+            (x.mean_axis(Axis(0)).unwrap(), y.mean().unwrap())
+        } else {
+            (Array1::zeros(x.ncols()), A::zero())
+        };
+        let x_centered = x - &x_offset;
+        let y_centered = y - y_offset;
+
+        let (params, duality_gap, n_iter) = coordinate_descent(
+            &x_centered,
+            &y_centered,
+            A::from(self.alpha).unwrap(),
+            A::zero(),
+            initial_params,
+            self.max_iter,
+            A::from(self.tol).unwrap(),
+        );
+        let intercept = y_offset - x_offset.dot(&params);
+
+        Ok(FittedLasso {
+            intercept,
+            params,
+            duality_gap,
+            n_iter,
+        })
+    }
+
+    /// Fit a lasso model given a sparse feature matrix `x` with shape `(n_samples, n_features)`
+    /// and a target variable `y` with shape `(n_samples)`
+    ///
+    /// The features are centered implicitly, so their sparsity is preserved and each coordinate
+    /// update only touches the non-zero entries of its column.
+    #[cfg(feature = "sprs")]
+    pub fn fit_sparse<A: Float>(&self, x: &CsMat<A>, y: &Array1<A>) -> Result<FittedLasso<A>> {
+        self.validate(x.shape(), y.len(), x.shape().1)?;
+
+        let x_sparse = SparseColumns::new(x, self.fit_intercept);
+        let y_offset = if self.fit_intercept {
+            y.mean().unwrap()
+        } else {
+            A::zero()
+        };
+        let y_centered = y - y_offset;
+
+        let (params, duality_gap, n_iter) = sparse_coordinate_descent(
+            &x_sparse,
+            &y_centered,
+            A::from(self.alpha).unwrap(),
+            A::zero(),
+            Array1::zeros(x_sparse.n_features()),
+            self.max_iter,
+            A::from(self.tol).unwrap(),
+        );
+        let intercept = y_offset - x_sparse.offset().dot(&params);
+
+        Ok(FittedLasso {
+            intercept,
+            params,
+            duality_gap,
+            n_iter,
+        })
+    }
+
+    fn validate(
+        &self,
+        (n_samples, n_features): (usize, usize),
+        y_len: usize,
+        n_params: usize,
+    ) -> Result<()> {
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if n_samples == 0 || n_samples != y_len {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                n_samples, y_len
+            )));
+        }
+        if n_params != n_features {
+            return Err(LinearError::InvalidValue(format!(
+                "Expected {} initial parameters, got: {}",
+                n_features, n_params
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A fitted lasso model which can be used for making predictions
+pub struct FittedLasso<A> {
+    intercept: A,
+    params: Array1<A>,
+    duality_gap: A,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedLasso<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the target variable according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        x.dot(&self.params) + self.intercept
+    }
+
+    /// Predict the target variable of a sparse input matrix `x`
+    #[cfg(feature = "sprs")]
+    pub fn predict_sparse(&self, x: &CsMat<A>) -> Array1<A> {
+        sparse_dot(x, &self.params) + self.intercept
+    }
+
+    /// Get the fitted parameters
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the number of non-zero coefficients
+    pub fn n_nonzero(&self) -> usize {
+        self.params.iter().filter(|x| **x != A::zero()).count()
+    }
+
+    /// Get the duality gap at the end of the optimization
+    pub fn duality_gap(&self) -> A {
+        self.duality_gap
+    }
+
+    /// Get the number of sweeps performed by the solver
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn large_alpha_zeroes_all_params() {
+        let x = array![[0., 1.], [1., 0.], [2., 3.], [3., 1.], [4., 5.]];
+        let y = array![1., 2., 0., 4., 3.];
+
+        // any alpha larger than max |X^T y| / n_samples results in an empty model
+        let model = Lasso::new().alpha(10.).fit(&x, &y).unwrap();
+
+        assert_eq!(model.n_nonzero(), 0);
+        assert_eq!(model.params(), &array![0., 0.]);
+        assert_abs_diff_eq!(model.intercept(), 2.);
+    }
+
+    #[test]
+    fn recovers_sparse_support() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((100, 10), StandardNormal, &mut rng);
+        let truth = array![3., 0., 0., 0., -2., 0., 0., 0., 0., 0.];
+        let y = x.dot(&truth) + 1.;
+
+        let model = Lasso::new().alpha(0.1).fit(&x, &y).unwrap();
+
+        assert_eq!(model.n_nonzero(), 2);
+        assert!(model.params()[0] != 0. && model.params()[4] != 0.);
+        assert_abs_diff_eq!(model.params(), &truth, epsilon = 0.2);
+        assert_abs_diff_eq!(model.intercept(), 1., epsilon = 0.2);
+    }
+
+    #[test]
+    fn warm_start_converges_faster() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((100, 10), StandardNormal, &mut rng);
+        let y = x.dot(&array![3., 0., 0., 0., -2., 0., 0., 0., 0., 0.]);
+
+        let lasso = Lasso::new().alpha(0.1).tol(1e-8);
+        let cold = lasso.fit(&x, &y).unwrap();
+        let warm = lasso
+            .fit_with_initial_params(&x, &y, cold.params().clone())
+            .unwrap();
+
+        assert!(warm.n_iter() <= cold.n_iter());
+        assert_abs_diff_eq!(warm.params(), cold.params(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0.], [1.]];
+        let y = array![0., 1.];
+
+        assert!(Lasso::new().alpha(-1.).fit(&x, &y).is_err());
+        assert!(Lasso::new().tol(0.).fit(&x, &y).is_err());
+        assert!(Lasso::new()
+            .fit_with_initial_params(&x, &y, array![0., 0.])
+            .is_err());
+    }
+}
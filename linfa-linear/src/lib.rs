@@ -1,7 +1,32 @@
+pub mod bayesian_ridge;
+mod coordinate_descent;
+pub mod elastic_net;
 pub mod error;
 mod float;
 pub mod glm;
+pub mod huber;
+pub mod isotonic;
+pub mod lasso;
+mod lsqr;
+pub mod multi_task_lasso;
 pub mod ols;
+pub mod pls;
+pub mod poisson;
+pub mod quantile;
+pub mod ridge;
+#[cfg(feature = "sprs")]
+mod sparse;
+mod special;
 
-pub use glm::TweedieRegressor;
-pub use ols::LinearRegression;
+pub use bayesian_ridge::BayesianRidge;
+pub use elastic_net::{ElasticNet, ElasticNetPath};
+pub use glm::{GeneralizedLinearRegressor, TweedieRegressor};
+pub use huber::HuberRegressor;
+pub use isotonic::IsotonicRegression;
+pub use lasso::Lasso;
+pub use multi_task_lasso::MultiTaskLasso;
+pub use ols::{LinearRegression, Solver};
+pub use pls::{Cca, PlsCanonical, PlsRegression};
+pub use poisson::PoissonRegressor;
+pub use quantile::QuantileRegression;
+pub use ridge::{AlphaSelection, RidgeCV, RidgeRegression};
@@ -0,0 +1,80 @@
+//! LSQR algorithm of Paige and Saunders for least squares problems
+//!
+//! Solves `min ||y - Xw||_2` with a Krylov method based on the Golub-Kahan bidiagonalization of
+//! `X`. It is mathematically equivalent to conjugate gradients on the normal equations, but more
+//! stable for ill-conditioned `X`. The matrix is only accessed through the products `Xv` and
+//! `X^T u`, which allows implicit operators, for example centered sparse matrices.
+use ndarray::{Array1, ArrayBase, Data, Ix1, LinalgScalar, ScalarOperand};
+use num_traits::Float;
+
+/// Run LSQR for the operator given by `dot` (`v -> Xv`) and `t_dot` (`u -> X^T u`)
+///
+/// `x_norm` is an estimate of the norm of `X`, for example its Frobenius norm. Iterations stop
+/// once either the relative residual or the norm of `X^T r` relative to `x_norm * ||r||` drops
+/// below `tol`.
+pub(crate) fn lsqr<A, F, G>(
+    dot: F,
+    t_dot: G,
+    x_norm: A,
+    y: &ArrayBase<impl Data<Elem = A>, Ix1>,
+    tol: f64,
+    max_iter: usize,
+) -> Array1<A>
+where
+    A: Float + LinalgScalar + ScalarOperand,
+    F: Fn(&Array1<A>) -> Array1<A>,
+    G: Fn(&Array1<A>) -> Array1<A>,
+{
+    let tol = A::from(tol).unwrap();
+    let norm = |x: &Array1<A>| x.dot(x).sqrt();
+
+    let mut u = y.to_owned();
+    let mut beta = norm(&u);
+    let y_norm = beta;
+    if beta > A::zero() {
+        u.mapv_inplace(|x| x / beta);
+    }
+    let mut v = t_dot(&u);
+    let mut params = Array1::zeros(v.len());
+    // zero targets or targets orthogonal to all features are solved by zero
+    let mut alpha = norm(&v);
+    if alpha == A::zero() {
+        return params;
+    }
+    v.mapv_inplace(|x| x / alpha);
+
+    let mut w = v.clone();
+    let mut phi_bar = beta;
+    let mut rho_bar = alpha;
+    for _ in 0..max_iter {
+        // continue the bidiagonalization
+        u = dot(&v) - u * alpha;
+        beta = norm(&u);
+        if beta > A::zero() {
+            u.mapv_inplace(|x| x / beta);
+        }
+        v = t_dot(&u) - v * beta;
+        alpha = norm(&v);
+        if alpha > A::zero() {
+            v.mapv_inplace(|x| x / alpha);
+        }
+
+        // eliminate the subdiagonal with a plane rotation
+        let rho = rho_bar.hypot(beta);
+        let (c, s) = (rho_bar / rho, beta / rho);
+        let theta = s * alpha;
+        rho_bar = -c * alpha;
+        let phi = c * phi_bar;
+        phi_bar = s * phi_bar;
+
+        params.scaled_add(phi / rho, &w);
+        w = &v - &(w * (theta / rho));
+
+        // phi_bar is the norm of the residual r and phi_bar * alpha * |c| the norm of X^T r
+        if phi_bar <= tol * y_norm || phi_bar * alpha * c.abs() <= tol * x_norm * phi_bar {
+            break;
+        }
+    }
+
+    params
+}
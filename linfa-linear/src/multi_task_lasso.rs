@@ -0,0 +1,189 @@
+//! Multi-task Lasso
+//!
+//! Linear regression for multiple targets with a mixed L2,1 penalty. The coefficients of a feature
+//! are penalized jointly over all targets, which selects the same features for every target. The
+//! objective
+//! ```ignore
+//! 1 / (2 * n_samples) * ||Y - XW||^2_Fro + alpha * ||W||_21
+//! ```
+//! with `||W||_21 = sum_j ||W_j||_2` the sum of the norms of the rows, is minimized with block
+//! coordinate descent.
+
+use crate::coordinate_descent::block_coordinate_descent;
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+use ndarray::{Array1, Array2, Axis};
+
+/// Multi-task Lasso, a linear model for multiple targets sharing the same sparse support
+pub struct MultiTaskLasso {
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl MultiTaskLasso {
+    /// Create a multi-task lasso model with penalty `alpha`, which is solved for at most
+    /// `max_iter` sweeps or until the relative duality gap is smaller than `tol`
+    pub fn new(alpha: f64, max_iter: usize, tol: f64) -> Self {
+        Self {
+            alpha,
+            fit_intercept: true,
+            max_iter,
+            tol,
+        }
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Fit a multi-task lasso model given a feature matrix `x` with shape
+    /// `(n_samples, n_features)` and the targets `y` with shape `(n_samples, n_targets)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array2<A>) -> Result<FittedMultiTaskLasso<A>> {
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if x.nrows() == 0 || x.nrows() != y.nrows() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                x.nrows(),
+                y.nrows()
+            )));
+        }
+
+        let (x_offset, y_offset) = if self.fit_intercept {
+            (x.mean_axis(Axis(0)).unwrap(), y.mean_axis(Axis(0)).unwrap())
+        } else {
+            (Array1::zeros(x.ncols()), Array1::zeros(y.ncols()))
+        };
+        let x_centered = x - &x_offset;
+        let y_centered = y - &y_offset;
+
+        let (params, duality_gap, n_iter) = block_coordinate_descent(
+            &x_centered,
+            &y_centered,
+            A::from(self.alpha).unwrap(),
+            A::zero(),
+            Array2::zeros((x.ncols(), y.ncols())),
+            self.max_iter,
+            A::from(self.tol).unwrap(),
+        );
+        let intercept = y_offset - x_offset.dot(&params);
+
+        Ok(FittedMultiTaskLasso {
+            intercept,
+            params,
+            duality_gap,
+            n_iter,
+        })
+    }
+}
+
+/// A fitted multi-task lasso model which can be used for making predictions
+pub struct FittedMultiTaskLasso<A> {
+    intercept: Array1<A>,
+    params: Array2<A>,
+    duality_gap: A,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedMultiTaskLasso<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`, `predict` returns the
+    /// targets with shape `(n_samples, n_targets)` according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array2<A> {
+        x.dot(&self.params) + &self.intercept
+    }
+
+    /// Get the fitted parameters with shape `(n_features, n_targets)`
+    pub fn params(&self) -> &Array2<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept for each target, 0. if no intercept was fitted
+    pub fn intercept(&self) -> &Array1<A> {
+        &self.intercept
+    }
+
+    /// Get the number of features selected for all targets
+    pub fn n_nonzero(&self) -> usize {
+        self.params
+            .genrows()
+            .into_iter()
+            .filter(|row| row.iter().any(|x| *x != A::zero()))
+            .count()
+    }
+
+    /// Get the duality gap at the end of the optimization
+    pub fn duality_gap(&self) -> A {
+        self.duality_gap
+    }
+
+    /// Get the number of sweeps performed by the solver
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn targets_share_support() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((100, 8), StandardNormal, &mut rng);
+        let truth = array![
+            [2., -1., 0.5],
+            [0., 0., 0.],
+            [-1., 3., 1.],
+            [0., 0., 0.],
+            [0., 0., 0.],
+            [0., 0., 0.],
+            [0., 0., 0.],
+            [0., 0., 0.]
+        ];
+        let y = x.dot(&truth) + &array![1., 2., 3.];
+
+        let model = MultiTaskLasso::new(0.1, 1000, 1e-6).fit(&x, &y).unwrap();
+
+        assert_eq!(model.n_nonzero(), 2);
+        for j in &[0, 2] {
+            assert!(model.params().row(*j).iter().all(|x| *x != 0.));
+        }
+        assert_abs_diff_eq!(model.params(), &truth, epsilon = 0.2);
+        assert_abs_diff_eq!(model.intercept(), &array![1., 2., 3.], epsilon = 0.2);
+        assert!(model.n_iter() < 1000);
+    }
+
+    #[test]
+    fn large_alpha_zeroes_all_params() {
+        let x = array![[0., 1.], [1., 0.], [2., 3.], [3., 1.], [4., 5.]];
+        let y = array![[1., 0.], [2., 1.], [0., 1.], [4., 0.], [3., 1.]];
+
+        let model = MultiTaskLasso::new(10., 100, 1e-4).fit(&x, &y).unwrap();
+
+        assert_eq!(model.n_nonzero(), 0);
+        assert_abs_diff_eq!(model.intercept(), &array![2., 0.6]);
+        let prediction = model.predict(&x);
+        assert_abs_diff_eq!(prediction.row(0).to_owned(), array![2., 0.6]);
+    }
+}
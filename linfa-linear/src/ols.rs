@@ -24,10 +24,21 @@
 //! ```
 
 #![allow(non_snake_case)]
-use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2, ScalarOperand};
-use ndarray_linalg::{Lapack, Scalar, Solve};
+use ndarray::{s, Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2, ScalarOperand};
+use ndarray_linalg::{FactorizeInto, Inverse, Lapack, Scalar, Solve, QR, SVD};
+
+use crate::lsqr::lsqr;
+#[cfg(feature = "sprs")]
+use crate::sparse::{sparse_dot, SparseColumns, LSQR_MAX_ITER, LSQR_TOL};
+use crate::special::{student_t_quantile, student_t_two_sided};
 use ndarray_stats::SummaryStatisticsExt;
 use num_traits::float::Float;
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+
+/// The sums of the features and targets, `X^T X` and `X^T y` accumulated
+/// over the batches of `fit_batches`
+type BatchStatistics<A> = (Array1<A>, A, Array2<A>, Array1<A>);
 
 /// An ordinary least squares linear regression model.
 ///
@@ -41,10 +52,46 @@ use num_traits::float::Float;
 ///
 /// by finding x and b which minimize the L_2 norm ||y - Ax - b||_2.
 ///
-/// It currently uses the [Moore-Penrose pseudo-inverse]()
-/// to solve y - b = Ax.
+/// The least squares problem is solved with one of the algorithms of
+/// [`Solver`](enum.Solver.html), chosen by the shape of the feature
+/// matrix by default.
 pub struct LinearRegression {
     options: Options,
+    solver: Solver,
+}
+
+/// Algorithm solving the least squares problem of a linear regression
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Solver {
+    /// Choose by the shape of the feature matrix: `Svd` if there are at
+    /// least as many features as samples, `NormalEquations` otherwise
+    Auto,
+    /// Solve `X^T X b = X^T y` with an LU factorization. This is the
+    /// fastest direct solver, but squares the condition number of `X` and
+    /// fails for rank-deficient feature matrices.
+    NormalEquations,
+    /// Compute the pseudo-inverse with a QR decomposition of `X` followed
+    /// by the singular value decomposition of the triangular factor.
+    /// Singular values below `max(n_samples, n_features) * eps` times the
+    /// largest are treated as zero, which gives the minimum norm solution
+    /// for rank-deficient or underdetermined problems.
+    Svd,
+    /// Iterative LSQR algorithm of Paige and Saunders, which only needs
+    /// products with `X` and `X^T`. Iterations stop once either the
+    /// relative residual or the relative norm of `X^T r` drops below `tol`.
+    Lsqr { tol: f64, max_iter: usize },
+}
+
+impl Solver {
+    /// Replace `Auto` with the solver for a feature matrix of shape
+    /// `(n_samples, n_features)`
+    fn resolve(self, n_samples: usize, n_features: usize) -> Solver {
+        match self {
+            Solver::Auto if n_features >= n_samples => Solver::Svd,
+            Solver::Auto => Solver::NormalEquations,
+            solver => solver,
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -68,6 +115,7 @@ impl Options {
 pub struct FittedLinearRegression<A> {
     intercept: A,
     params: Array1<A>,
+    with_intercept: bool,
 }
 
 impl Default for LinearRegression {
@@ -76,6 +124,12 @@ impl Default for LinearRegression {
     }
 }
 
+/// A fitted linear regression model for multiple targets.
+pub struct FittedMultiTargetLinearRegression<A> {
+    intercept: Array1<A>,
+    params: Array2<A>,
+}
+
 /// Configure and fit a linear regression model
 impl LinearRegression {
     /// Create a default linear regression model.
@@ -89,9 +143,17 @@ impl LinearRegression {
     pub fn new() -> LinearRegression {
         LinearRegression {
             options: Options::WithIntercept,
+            solver: Solver::Auto,
         }
     }
 
+    /// Configure the algorithm solving the least squares problem.
+    /// Defaults to `Solver::Auto` if not set.
+    pub fn solver(mut self, solver: Solver) -> Self {
+        self.solver = solver;
+        self
+    }
+
     /// Configure the linear regression model to fit an intercept.
     /// Defaults to `true` if not set.
     pub fn with_intercept(mut self, with_intercept: bool) -> Self {
@@ -154,17 +216,292 @@ impl LinearRegression {
                 .mean()
                 .ok_or_else(|| String::from("cannot compute mean of y"))?;
             let y_centered: Array1<A> = y - y_offset;
-            let params: Array1<A> =
-                compute_params(&X_centered, &y_centered, self.options.should_normalize())?;
+            let params: Array1<A> = compute_params(
+                &X_centered,
+                &y_centered,
+                self.options.should_normalize(),
+                self.solver,
+            )?;
+            let intercept: A = y_offset - X_offset.dot(&params);
+            Ok(FittedLinearRegression {
+                intercept,
+                params,
+                with_intercept: true,
+            })
+        } else {
+            Ok(FittedLinearRegression {
+                intercept: A::from(0).unwrap(),
+                params: solve_least_squares(X, y, self.solver)?,
+                with_intercept: false,
+            })
+        }
+    }
+
+    /// Fit a weighted linear regression model given a feature matrix `X`,
+    /// a target variable `y` and a non-negative weight for each sample.
+    ///
+    /// This minimizes the weighted residual sum of squares
+    /// `sum_i w_i (y_i - x_i b)^2` by scaling each row of `X` and `y` with
+    /// the square root of its weight. Samples with zero weight do not
+    /// influence the fit, and the intercept is computed from the weighted
+    /// means of `X` and `y`.
+    ///
+    /// Integer weights are equivalent to repeating the samples accordingly.
+    pub fn fit_weighted<A, B, C, D>(
+        &self,
+        X: &ArrayBase<B, Ix2>,
+        y: &ArrayBase<C, Ix1>,
+        sample_weight: &ArrayBase<D, Ix1>,
+    ) -> Result<FittedLinearRegression<A>, String>
+    where
+        A: Lapack + Scalar + ScalarOperand + Float,
+        B: Data<Elem = A>,
+        C: Data<Elem = A>,
+        D: Data<Elem = A>,
+    {
+        let (n_samples, _) = X.dim();
+
+        // Check that our inputs have compatible shapes
+        assert_eq!(y.dim(), n_samples);
+        assert_eq!(sample_weight.dim(), n_samples);
+
+        if sample_weight
+            .iter()
+            .any(|w| !Float::is_finite(*w) || *w < A::zero())
+        {
+            return Err(String::from(
+                "sample weights must be finite and non-negative",
+            ));
+        }
+        let weight_sum = sample_weight.sum();
+        if weight_sum == A::zero() {
+            return Err(String::from("at least one sample weight must be positive"));
+        }
+        let weight_sqrt = sample_weight.mapv(Float::sqrt).insert_axis(Axis(1));
+
+        if self.options.should_use_intercept() {
+            // The data is centered with the weighted means, such that the
+            // intercept is the weighted residual of the fitted parameters
+            let X_offset: Array1<A> = X.t().dot(sample_weight) / weight_sum;
+            let y_offset: A = y.dot(sample_weight) / weight_sum;
+            let X_scaled: Array2<A> = (X - &X_offset) * &weight_sqrt;
+            let y_scaled: Array1<A> = (y - y_offset) * weight_sqrt.column(0);
+            let params: Array1<A> = compute_params(
+                &X_scaled,
+                &y_scaled,
+                self.options.should_normalize(),
+                self.solver,
+            )?;
             let intercept: A = y_offset - X_offset.dot(&params);
-            Ok(FittedLinearRegression { intercept, params })
+            Ok(FittedLinearRegression {
+                intercept,
+                params,
+                with_intercept: true,
+            })
         } else {
+            let X_scaled: Array2<A> = X * &weight_sqrt;
+            let y_scaled: Array1<A> = y * &weight_sqrt.column(0);
             Ok(FittedLinearRegression {
                 intercept: A::from(0).unwrap(),
-                params: solve_normal_equation(X, y)?,
+                params: solve_least_squares(&X_scaled, &y_scaled, self.solver)?,
+                with_intercept: false,
             })
         }
     }
+
+    /// Fit a linear regression model given a feature matrix `X` and
+    /// multiple target variables `Y`.
+    ///
+    /// The feature matrix `X` must have shape `(n_samples, n_features)`
+    ///
+    /// The target variables `Y` must have shape `(n_samples, n_targets)`
+    ///
+    /// The direct solvers decompose the feature matrix once and solve for
+    /// every target, which is much cheaper than fitting a separate model
+    /// for each target.
+    pub fn fit_multi_target<A, B, C>(
+        &self,
+        X: &ArrayBase<B, Ix2>,
+        Y: &ArrayBase<C, Ix2>,
+    ) -> Result<FittedMultiTargetLinearRegression<A>, String>
+    where
+        A: Lapack + Scalar + ScalarOperand + Float,
+        B: Data<Elem = A>,
+        C: Data<Elem = A>,
+    {
+        let (n_samples, _) = X.dim();
+
+        // Check that our inputs have compatible shapes
+        assert_eq!(Y.nrows(), n_samples);
+
+        if self.options.should_use_intercept() {
+            let X_offset: Array1<A> = X
+                .mean_axis(Axis(0))
+                .ok_or_else(|| String::from("cannot compute mean of X"))?;
+            let X_centered: Array2<A> = X - &X_offset;
+            let Y_offset: Array1<A> = Y
+                .mean_axis(Axis(0))
+                .ok_or_else(|| String::from("cannot compute mean of Y"))?;
+            let Y_centered: Array2<A> = Y - &Y_offset;
+            let params: Array2<A> = compute_params_multi_target(
+                &X_centered,
+                &Y_centered,
+                self.options.should_normalize(),
+                self.solver,
+            )?;
+            let intercept: Array1<A> = Y_offset - X_offset.dot(&params);
+            Ok(FittedMultiTargetLinearRegression { intercept, params })
+        } else {
+            Ok(FittedMultiTargetLinearRegression {
+                intercept: Array1::zeros(Y.ncols()),
+                params: solve_least_squares_multi_target(X, Y, self.solver)?,
+            })
+        }
+    }
+
+    /// Fit a linear regression model to a sequence of batches `(X, y)`,
+    /// for datasets which do not fit into memory.
+    ///
+    /// Only the normal equations are accumulated over the batches, which
+    /// requires memory in `O(n_features^2)` independent of the number of
+    /// samples. The result is the same as fitting the concatenated batches
+    /// with `Solver::NormalEquations`, the configured solver is ignored.
+    pub fn fit_batches<A, I>(&self, batches: I) -> Result<FittedLinearRegression<A>, String>
+    where
+        A: Lapack + Scalar + ScalarOperand + Float,
+        I: IntoIterator<Item = (Array2<A>, Array1<A>)>,
+    {
+        let mut n_samples = 0;
+        let mut statistics: Option<BatchStatistics<A>> = None;
+        for (X, y) in batches {
+            // Check that our inputs have compatible shapes
+            assert_eq!(y.dim(), X.nrows());
+
+            let n_features = X.ncols();
+            let (sum_X, sum_y, gram, rhs) = statistics.get_or_insert_with(|| {
+                (
+                    Array1::zeros(n_features),
+                    A::zero(),
+                    Array2::zeros((n_features, n_features)),
+                    Array1::zeros(n_features),
+                )
+            });
+            if sum_X.len() != n_features {
+                return Err(format!(
+                    "batch has {} features, but the first batch has {}",
+                    n_features,
+                    sum_X.len()
+                ));
+            }
+
+            *sum_X += &X.sum_axis(Axis(0));
+            *sum_y += y.sum();
+            *gram += &X.t().dot(&X);
+            *rhs += &X.t().dot(&y);
+            n_samples += X.nrows();
+        }
+
+        let (sum_X, sum_y, mut gram, mut rhs) =
+            statistics.ok_or_else(|| String::from("at least one batch is required"))?;
+        if n_samples == 0 {
+            return Err(String::from("cannot fit a model without any samples"));
+        }
+
+        let with_intercept = self.options.should_use_intercept();
+        let n_samples = A::from(n_samples).unwrap();
+        let (X_offset, y_offset) = if with_intercept {
+            (&sum_X / n_samples, sum_y / n_samples)
+        } else {
+            (Array1::zeros(sum_X.len()), A::zero())
+        };
+
+        // center the normal equations, X_c^T X_c = X^T X - n * m m^T
+        gram -= &X_offset
+            .view()
+            .insert_axis(Axis(1))
+            .dot(&sum_X.view().insert_axis(Axis(0)));
+        rhs -= &(&X_offset * sum_y);
+
+        // same scaling as the dense model, the variance of each feature
+        let scale: Array1<A> = if self.options.should_normalize() {
+            gram.diag().mapv(|x| x / n_samples)
+        } else {
+            Array1::ones(sum_X.len())
+        };
+        let gram = &gram / &scale / scale.view().insert_axis(Axis(1));
+        let rhs = &rhs / &scale;
+
+        let params: Array1<A> = gram.solve_into(rhs).map_err(|err| format! {"{}", err})? / &scale;
+        let intercept: A = y_offset - X_offset.dot(&params);
+
+        Ok(FittedLinearRegression {
+            intercept,
+            params,
+            with_intercept,
+        })
+    }
+
+    /// Fit a linear regression model given a sparse feature matrix `X` with
+    /// shape `(n_samples, n_features)` and a target variable `y` with shape
+    /// `(n_samples)`.
+    ///
+    /// The feature matrix is centered (and normalized) implicitly, so its
+    /// sparsity is preserved. The least squares problem is always solved
+    /// with LSQR, which only needs sparse matrix-vector products, using the
+    /// tolerance and iteration limit of `Solver::Lsqr` if configured.
+    #[cfg(feature = "sprs")]
+    pub fn fit_sparse<A, C>(
+        &self,
+        X: &CsMat<A>,
+        y: &ArrayBase<C, Ix1>,
+    ) -> Result<FittedLinearRegression<A>, String>
+    where
+        A: crate::float::Float,
+        C: Data<Elem = A>,
+    {
+        let (n_samples, _) = X.shape();
+
+        // Check that our inputs have compatible shapes
+        assert_eq!(y.dim(), n_samples);
+
+        let with_intercept = self.options.should_use_intercept();
+        let mut X_sparse = SparseColumns::new(X, with_intercept);
+        let y_offset: A = if with_intercept {
+            y.mean()
+                .ok_or_else(|| String::from("cannot compute mean of y"))?
+        } else {
+            A::zero()
+        };
+        let y_centered: Array1<A> = y - y_offset;
+
+        // same scaling as the dense model, the variance of each feature
+        let scale: Array1<A> = if self.options.should_normalize() {
+            X_sparse.column_norms() / A::from(n_samples).unwrap()
+        } else {
+            Array1::ones(X_sparse.n_features())
+        };
+        X_sparse.set_scale(scale.clone());
+
+        let (tol, max_iter) = match self.solver {
+            Solver::Lsqr { tol, max_iter } => (tol, max_iter),
+            _ => (LSQR_TOL, LSQR_MAX_ITER),
+        };
+        let params: Array1<A> = lsqr(
+            |v| X_sparse.dot(v),
+            |u| X_sparse.t_dot(u),
+            X_sparse.frobenius_norm(),
+            &y_centered,
+            tol,
+            max_iter,
+        ) / &scale;
+        let intercept: A = y_offset - X_sparse.offset().dot(&params);
+
+        Ok(FittedLinearRegression {
+            intercept,
+            params,
+            with_intercept,
+        })
+    }
 }
 
 /// Compute the parameters for the linear regression model with
@@ -173,20 +510,43 @@ fn compute_params<A, B, C>(
     X: &ArrayBase<B, Ix2>,
     y: &ArrayBase<C, Ix1>,
     normalize: bool,
+    solver: Solver,
 ) -> Result<Array1<A>, String>
 where
-    A: Scalar + Lapack + Float,
+    A: Scalar + Lapack + ScalarOperand + Float,
     B: Data<Elem = A>,
     C: Data<Elem = A>,
 {
     if normalize {
         let scale: Array1<A> = X.map_axis(Axis(0), |column| column.central_moment(2).unwrap());
         let X: Array2<A> = X / &scale;
-        let mut params: Array1<A> = solve_normal_equation(&X, y)?;
+        let mut params: Array1<A> = solve_least_squares(&X, y, solver)?;
         params /= &scale;
         Ok(params)
     } else {
-        solve_normal_equation(X, y)
+        solve_least_squares(X, y, solver)
+    }
+}
+
+/// Solve the least squares problem Xb = y with the given solver.
+fn solve_least_squares<A, B, C>(
+    X: &ArrayBase<B, Ix2>,
+    y: &ArrayBase<C, Ix1>,
+    solver: Solver,
+) -> Result<Array1<A>, String>
+where
+    A: Scalar + Lapack + ScalarOperand + Float,
+    B: Data<Elem = A>,
+    C: Data<Elem = A>,
+{
+    let (n_samples, n_features) = X.dim();
+    match solver.resolve(n_samples, n_features) {
+        Solver::Svd => {
+            let params = solve_svd(X, &y.view().insert_axis(Axis(1)))?;
+            Ok(params.column(0).to_owned())
+        }
+        Solver::Lsqr { tol, max_iter } => Ok(solve_lsqr(X, y, tol, max_iter)),
+        _ => solve_normal_equation(X, y),
     }
 }
 
@@ -209,6 +569,128 @@ where
         .map_err(|err| format! {"{}", err})
 }
 
+/// Compute the parameters of all targets with or without normalization.
+fn compute_params_multi_target<A, B, C>(
+    X: &ArrayBase<B, Ix2>,
+    Y: &ArrayBase<C, Ix2>,
+    normalize: bool,
+    solver: Solver,
+) -> Result<Array2<A>, String>
+where
+    A: Scalar + Lapack + ScalarOperand + Float,
+    B: Data<Elem = A>,
+    C: Data<Elem = A>,
+{
+    if normalize {
+        let scale: Array1<A> = X.map_axis(Axis(0), |column| column.central_moment(2).unwrap());
+        let X: Array2<A> = X / &scale;
+        let mut params: Array2<A> = solve_least_squares_multi_target(&X, Y, solver)?;
+        params /= &scale.insert_axis(Axis(1));
+        Ok(params)
+    } else {
+        solve_least_squares_multi_target(X, Y, solver)
+    }
+}
+
+/// Solve the least squares problem XB = Y for every column of Y with the
+/// given solver.
+fn solve_least_squares_multi_target<A, B, C>(
+    X: &ArrayBase<B, Ix2>,
+    Y: &ArrayBase<C, Ix2>,
+    solver: Solver,
+) -> Result<Array2<A>, String>
+where
+    A: Scalar + Lapack + ScalarOperand + Float,
+    B: Data<Elem = A>,
+    C: Data<Elem = A>,
+{
+    let (n_samples, n_features) = X.dim();
+    match solver.resolve(n_samples, n_features) {
+        Solver::Svd => solve_svd(X, Y),
+        Solver::Lsqr { tol, max_iter } => {
+            let mut params = Array2::zeros((n_features, Y.ncols()));
+            for (mut column, y) in params.gencolumns_mut().into_iter().zip(Y.gencolumns()) {
+                column.assign(&solve_lsqr(X, &y, tol, max_iter));
+            }
+            Ok(params)
+        }
+        _ => solve_normal_equation_multi_target(X, Y),
+    }
+}
+
+/// Solve the normal equations X^T X B = X^T Y for every column of Y,
+/// re-using the LU factorization of X^T X.
+fn solve_normal_equation_multi_target<A, B, C>(
+    X: &ArrayBase<B, Ix2>,
+    Y: &ArrayBase<C, Ix2>,
+) -> Result<Array2<A>, String>
+where
+    A: Lapack + Scalar,
+    B: Data<Elem = A>,
+    C: Data<Elem = A>,
+{
+    let rhs = X.t().dot(Y);
+    let gram: Array2<A> = X.t().dot(X);
+    let factorized = gram.factorize_into().map_err(|err| format! {"{}", err})?;
+
+    let mut params = Array2::zeros(rhs.dim());
+    for (mut column, rhs) in params.gencolumns_mut().into_iter().zip(rhs.gencolumns()) {
+        let solution = factorized.solve(&rhs).map_err(|err| format! {"{}", err})?;
+        column.assign(&solution);
+    }
+
+    Ok(params)
+}
+
+/// Compute the minimum norm least squares solution of XB = Y with the
+/// pseudo-inverse of X.
+///
+/// X = QR is decomposed first, such that only the small triangular factor
+/// R with at most `n_features` rows is passed to the SVD. Small singular
+/// values are truncated, which keeps rank-deficient problems stable.
+fn solve_svd<A, B, C>(X: &ArrayBase<B, Ix2>, Y: &ArrayBase<C, Ix2>) -> Result<Array2<A>, String>
+where
+    A: Scalar + Lapack + ScalarOperand + Float,
+    B: Data<Elem = A>,
+    C: Data<Elem = A>,
+{
+    let (n_samples, n_features) = X.dim();
+    let (q, r) = X.qr().map_err(|err| format! {"{}", err})?;
+    let (u, sigma, vt) = r.svd(true, true).map_err(|err| format! {"{}", err})?;
+    let (u, vt) = (u.unwrap(), vt.unwrap());
+
+    // the singular values are sorted in decreasing order
+    let sigma = sigma.mapv(|x| A::from(x).unwrap());
+    let cutoff = sigma.get(0).cloned().unwrap_or_else(A::zero)
+        * <A as Float>::epsilon()
+        * A::from(n_samples.max(n_features)).unwrap();
+    let rank = sigma.iter().take_while(|x| **x > cutoff).count();
+
+    // coordinates of Y in the left singular vectors of X, scaled by the
+    // inverse singular values
+    let mut coefficients = u.slice(s![.., ..rank]).t().dot(&q.t().dot(Y));
+    coefficients /= &sigma.slice(s![..rank]).insert_axis(Axis(1));
+
+    Ok(vt.slice(s![..rank, ..]).t().dot(&coefficients))
+}
+
+/// Solve the least squares problem Xb = y with LSQR
+fn solve_lsqr<A, B, C>(
+    X: &ArrayBase<B, Ix2>,
+    y: &ArrayBase<C, Ix1>,
+    tol: f64,
+    max_iter: usize,
+) -> Array1<A>
+where
+    A: Scalar + ScalarOperand + Float,
+    B: Data<Elem = A>,
+    C: Data<Elem = A>,
+{
+    let x_norm = Float::sqrt(X.iter().fold(A::zero(), |sum, x| sum + *x * *x));
+
+    lsqr(|v| X.dot(v), |u| X.t().dot(u), x_norm, y, tol, max_iter)
+}
+
 /// View the fitted parameters and make predictions with a fitted
 /// linear regresssion model.
 impl<A: Scalar + ScalarOperand> FittedLinearRegression<A> {
@@ -230,10 +712,220 @@ impl<A: Scalar + ScalarOperand> FittedLinearRegression<A> {
     }
 }
 
+#[cfg(feature = "sprs")]
+impl<A: crate::float::Float> FittedLinearRegression<A> {
+    /// Given a sparse input matrix `X`, with shape `(n_samples, n_features)`,
+    /// `predict_sparse` returns the target variable according to the linear
+    /// model learned from the training data distribution.
+    pub fn predict_sparse(&self, X: &CsMat<A>) -> Array1<A> {
+        sparse_dot(X, &self.params) + self.intercept
+    }
+}
+
+/// Inference statistics of a single coefficient of a linear regression model
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoefficientSummary<A> {
+    /// The fitted value of the coefficient
+    pub estimate: A,
+    /// Estimated standard error of the coefficient
+    pub std_error: A,
+    /// t statistic for the null hypothesis that the coefficient is zero
+    pub t_value: A,
+    /// Two-sided p-value of the t statistic
+    pub p_value: A,
+    /// Lower and upper bound of the confidence interval
+    pub conf_int: (A, A),
+}
+
+/// Inference summary of a fitted linear regression model, see
+/// [FittedLinearRegression::summary](struct.FittedLinearRegression.html#method.summary)
+pub struct OlsSummary<A> {
+    intercept: Option<CoefficientSummary<A>>,
+    params: Vec<CoefficientSummary<A>>,
+    residual_std_error: A,
+    df_residual: usize,
+    r2: A,
+    adjusted_r2: A,
+}
+
+impl<A: Copy> OlsSummary<A> {
+    /// Get the statistics of the intercept, `None` if no intercept was fitted
+    pub fn intercept(&self) -> Option<&CoefficientSummary<A>> {
+        self.intercept.as_ref()
+    }
+
+    /// Get the statistics of the parameters, in the order of the features
+    pub fn params(&self) -> &[CoefficientSummary<A>] {
+        &self.params
+    }
+
+    /// Get the residual standard error, the estimated standard deviation
+    /// of the noise
+    pub fn residual_std_error(&self) -> A {
+        self.residual_std_error
+    }
+
+    /// Get the degrees of freedom of the residuals
+    pub fn df_residual(&self) -> usize {
+        self.df_residual
+    }
+
+    /// Get the coefficient of determination R^2
+    pub fn r2(&self) -> A {
+        self.r2
+    }
+
+    /// Get the R^2 adjusted for the number of parameters
+    pub fn adjusted_r2(&self) -> A {
+        self.adjusted_r2
+    }
+}
+
+impl<A> FittedLinearRegression<A>
+where
+    A: Lapack + Scalar + ScalarOperand + Float,
+{
+    /// Compute an inference summary of the fitted model on its training
+    /// data `X` and `y`.
+    ///
+    /// The covariance of the coefficients is estimated as
+    /// `sigma^2 (X^T X)^-1`, with `sigma^2` the residual variance. The
+    /// t statistics, two-sided p-values and the confidence intervals with
+    /// coverage `level` (e.g. `0.95`) follow from Student's t-distribution
+    /// with `n_samples - n_coefficients` degrees of freedom. Without an
+    /// intercept, R^2 is computed relative to zero instead of the mean of
+    /// `y`.
+    ///
+    /// The summary is only valid for models fitted without sample weights.
+    pub fn summary<B, C>(
+        &self,
+        X: &ArrayBase<B, Ix2>,
+        y: &ArrayBase<C, Ix1>,
+        level: f64,
+    ) -> Result<OlsSummary<A>, String>
+    where
+        B: Data<Elem = A>,
+        C: Data<Elem = A>,
+    {
+        let (n_samples, n_features) = X.dim();
+        assert_eq!(y.dim(), n_samples);
+        assert_eq!(self.params.len(), n_features);
+
+        if level <= 0. || level >= 1. || level.is_nan() {
+            return Err(format!(
+                "confidence level must be in the interval (0, 1), got: {}",
+                level
+            ));
+        }
+        let n_coefficients = n_features + self.with_intercept as usize;
+        if n_samples <= n_coefficients {
+            return Err(String::from(
+                "the number of samples must exceed the number of coefficients",
+            ));
+        }
+        let df_residual = n_samples - n_coefficients;
+        let df = df_residual as f64;
+
+        // the design matrix includes a column of ones for the intercept
+        let design: Array2<A> = if self.with_intercept {
+            ndarray::stack(Axis(1), &[Array2::ones((n_samples, 1)).view(), X.view()])
+                .map_err(|err| format! {"{}", err})?
+        } else {
+            X.to_owned()
+        };
+        let cov_unscaled = design
+            .t()
+            .dot(&design)
+            .inv()
+            .map_err(|err| format! {"{}", err})?;
+
+        let residuals: Array1<A> = y - &(X.dot(&self.params) + self.intercept);
+        let rss = residuals.dot(&residuals);
+        let sigma2 = rss / A::from(df).unwrap();
+
+        let tss = if self.with_intercept {
+            let y_centered: Array1<A> = y - y.mean().unwrap();
+            y_centered.dot(&y_centered)
+        } else {
+            y.dot(y)
+        };
+        let r2 = A::one() - rss / tss;
+        let adjusted_r2 = A::one()
+            - (A::one() - r2) * A::from(n_samples - self.with_intercept as usize).unwrap()
+                / A::from(df).unwrap();
+
+        let quantile = A::from(student_t_quantile(0.5 + level / 2., df)).unwrap();
+        let intercept = if self.with_intercept {
+            Some(self.intercept)
+        } else {
+            None
+        };
+        let mut coefficients = cov_unscaled
+            .diag()
+            .iter()
+            .zip(intercept.into_iter().chain(self.params.iter().cloned()))
+            .map(|(var, estimate)| {
+                let std_error = Float::sqrt(sigma2 * *var);
+                let t_value = estimate / std_error;
+                let p_value = student_t_two_sided(t_value.to_f64().unwrap(), df);
+
+                CoefficientSummary {
+                    estimate,
+                    std_error,
+                    t_value,
+                    p_value: A::from(p_value).unwrap(),
+                    conf_int: (
+                        estimate - quantile * std_error,
+                        estimate + quantile * std_error,
+                    ),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let intercept = if self.with_intercept {
+            Some(coefficients.remove(0))
+        } else {
+            None
+        };
+
+        Ok(OlsSummary {
+            intercept,
+            params: coefficients,
+            residual_std_error: Float::sqrt(sigma2),
+            df_residual,
+            r2,
+            adjusted_r2,
+        })
+    }
+}
+
+/// View the fitted parameters and make predictions with a fitted
+/// linear regresssion model for multiple targets.
+impl<A: Scalar + ScalarOperand> FittedMultiTargetLinearRegression<A> {
+    /// Given an input matrix `X`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the target variables with shape
+    /// `(n_samples, n_targets)` according to the linear model learned
+    /// from the training data distribution.
+    pub fn predict(&self, X: &Array2<A>) -> Array2<A> {
+        X.dot(&self.params) + &self.intercept
+    }
+
+    /// Get the fitted parameters with shape `(n_features, n_targets)`
+    pub fn params(&self) -> &Array2<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept of each target, 0. if no intercept was
+    /// fitted
+    pub fn intercept(&self) -> &Array1<A> {
+        &self.intercept
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use approx::abs_diff_eq;
+    use approx::{abs_diff_eq, assert_abs_diff_eq};
     use ndarray::{array, s, Array1, Array2};
 
     #[test]
@@ -247,6 +939,51 @@ mod tests {
         abs_diff_eq!(result, &array![1., 2.], epsilon = 1e-12);
     }
 
+    #[test]
+    fn batches_agree_with_full_fit() {
+        let X: Array2<f64> = array![
+            [1., 2.],
+            [3., -1.],
+            [0., 4.],
+            [2., 2.],
+            [5., 1.],
+            [-1., 3.],
+            [4., 0.]
+        ];
+        let y: Array1<f64> = array![3., 1., 5., 4., 2., 6., 0.5];
+        let batches = || {
+            vec![
+                (X.slice(s![..3, ..]).to_owned(), y.slice(s![..3]).to_owned()),
+                (
+                    X.slice(s![3..4, ..]).to_owned(),
+                    y.slice(s![3..4]).to_owned(),
+                ),
+                (X.slice(s![4.., ..]).to_owned(), y.slice(s![4..]).to_owned()),
+            ]
+        };
+
+        for lin_reg in &[
+            LinearRegression::new(),
+            LinearRegression::new().with_intercept(false),
+            LinearRegression::new().with_intercept_and_normalize(),
+        ] {
+            let full = lin_reg.fit(&X, &y).unwrap();
+            let batched = lin_reg.fit_batches(batches()).unwrap();
+
+            assert_abs_diff_eq!(batched.params(), full.params(), epsilon = 1e-10);
+            assert_abs_diff_eq!(batched.intercept(), full.intercept(), epsilon = 1e-10);
+        }
+
+        assert!(LinearRegression::new()
+            .fit_batches(Vec::<(Array2<f64>, Array1<f64>)>::new())
+            .is_err());
+        let mismatched = vec![
+            (X.clone(), y.clone()),
+            (X.slice(s![.., ..1]).to_owned(), y.clone()),
+        ];
+        assert!(LinearRegression::new().fit_batches(mismatched).is_err());
+    }
+
     /// When `with_intercept` is set to false, the
     /// fitted line runs through the origin. For a perfect
     /// fit we only need to provide one point.
@@ -379,4 +1116,292 @@ mod tests {
         abs_diff_eq!(model2.intercept(), model3.intercept());
         abs_diff_eq!(model3.intercept(), model4.intercept());
     }
+
+    /// Duplicating a sample is equivalent to doubling its weight
+    #[test]
+    fn duplicated_sample_equals_doubled_weight() {
+        let lin_reg = LinearRegression::new();
+        let A: Array2<f64> = array![[0., 1.], [1., 0.], [2., 3.], [3., 1.], [4., 5.]];
+        let b: Array1<f64> = array![1., 2., 0., 4., 3.];
+        let weights: Array1<f64> = array![1., 2., 1., 1., 1.];
+
+        let A_dup: Array2<f64> = array![[0., 1.], [1., 0.], [1., 0.], [2., 3.], [3., 1.], [4., 5.]];
+        let b_dup: Array1<f64> = array![1., 2., 2., 0., 4., 3.];
+
+        let weighted = lin_reg.fit_weighted(&A, &b, &weights).unwrap();
+        let duplicated = lin_reg.fit(&A_dup, &b_dup).unwrap();
+
+        assert_abs_diff_eq!(weighted.params(), duplicated.params(), epsilon = 1e-10);
+        assert_abs_diff_eq!(
+            weighted.intercept(),
+            duplicated.intercept(),
+            epsilon = 1e-10
+        );
+
+        let lin_reg = LinearRegression::new().with_intercept(false);
+        let weighted = lin_reg.fit_weighted(&A, &b, &weights).unwrap();
+        let duplicated = lin_reg.fit(&A_dup, &b_dup).unwrap();
+
+        assert_abs_diff_eq!(weighted.params(), duplicated.params(), epsilon = 1e-10);
+    }
+
+    /// Samples with zero weight are excluded from the fit
+    #[test]
+    fn zero_weight_excludes_sample() {
+        let lin_reg = LinearRegression::new();
+        let A: Array2<f64> = array![[0.], [1.], [2.], [100.]];
+        let b: Array1<f64> = array![1., 3., 5., -1000.];
+        let weights: Array1<f64> = array![1., 1., 1., 0.];
+
+        let model = lin_reg.fit_weighted(&A, &b, &weights).unwrap();
+
+        assert_abs_diff_eq!(model.params(), &array![2.], epsilon = 1e-10);
+        assert_abs_diff_eq!(model.intercept(), 1., epsilon = 1e-10);
+
+        assert!(lin_reg
+            .fit_weighted(&A, &b, &array![1., -1., 1., 1.])
+            .is_err());
+        assert!(lin_reg
+            .fit_weighted(&A, &b, &array![0., 0., 0., 0.])
+            .is_err());
+    }
+
+    /// Fitting multiple targets at once gives the same results as
+    /// independent fits of each target
+    #[test]
+    fn multi_target_matches_single_fits() {
+        let A: Array2<f64> = array![
+            [0., 0., 0.],
+            [1., 1., 1.],
+            [2., 4., 8.],
+            [3., 9., 27.],
+            [4., 1., 0.]
+        ];
+        let B: Array2<f64> = array![[1., 0.], [8., 1.], [27., 0.], [64., 1.], [3., 2.]];
+
+        for lin_reg in &[
+            LinearRegression::new(),
+            LinearRegression::new().with_intercept(false),
+            LinearRegression::new().with_intercept_and_normalize(),
+        ] {
+            let multi = lin_reg.fit_multi_target(&A, &B).unwrap();
+            assert_eq!(multi.params().dim(), (3, 2));
+            assert_eq!(multi.predict(&A).dim(), (5, 2));
+
+            for (j, b) in B.gencolumns().into_iter().enumerate() {
+                let single = lin_reg.fit(&A, &b).unwrap();
+
+                assert_abs_diff_eq!(
+                    multi.params().column(j),
+                    single.params().view(),
+                    epsilon = 1e-10
+                );
+                assert_abs_diff_eq!(multi.intercept()[j], single.intercept(), epsilon = 1e-10);
+            }
+        }
+    }
+
+    /// All solvers agree on a well-conditioned problem, for single and
+    /// multiple targets
+    #[test]
+    fn solvers_agree() {
+        let A: Array2<f64> = Array2::from_shape_fn((30, 4), |(i, j)| {
+            (i as f64 * (1.3 + 0.7 * j as f64) + j as f64).sin() + 0.1 * j as f64
+        });
+        let b: Array1<f64> = A.dot(&array![1., -2., 0.5, 3.])
+            + Array1::from_shape_fn(30, |i| 0.01 * (7. * i as f64).cos());
+        let B: Array2<f64> = ndarray::stack(
+            Axis(1),
+            &[
+                b.view().insert_axis(Axis(1)),
+                (&b * 2.).view().insert_axis(Axis(1)),
+            ],
+        )
+        .unwrap();
+
+        for lin_reg in &[
+            LinearRegression::new(),
+            LinearRegression::new().with_intercept(false),
+            LinearRegression::new().with_intercept_and_normalize(),
+        ] {
+            let reference = lin_reg.fit(&A, &b).unwrap();
+
+            for solver in &[
+                Solver::NormalEquations,
+                Solver::Svd,
+                Solver::Lsqr {
+                    tol: 1e-12,
+                    max_iter: 100,
+                },
+            ] {
+                let lin_reg = LinearRegression {
+                    solver: *solver,
+                    ..*lin_reg
+                };
+                let model = lin_reg.fit(&A, &b).unwrap();
+                assert_abs_diff_eq!(model.params(), reference.params(), epsilon = 1e-8);
+                assert_abs_diff_eq!(model.intercept(), reference.intercept(), epsilon = 1e-8);
+
+                let multi = lin_reg.fit_multi_target(&A, &B).unwrap();
+                let doubled = reference.params() * 2.;
+                assert_abs_diff_eq!(multi.params().column(1), doubled.view(), epsilon = 1e-8);
+            }
+        }
+    }
+
+    /// The Läuchli matrix has nearly collinear columns, `X^T X` rounds to
+    /// a singular matrix in double precision while the SVD recovers the
+    /// coefficients
+    #[test]
+    fn svd_solves_ill_conditioned_problem() {
+        let eps = 1e-8;
+        let A: Array2<f64> = array![[1., 1.], [eps, 0.], [0., eps]];
+        let b: Array1<f64> = A.dot(&array![1., 2.]);
+
+        let model = LinearRegression::new()
+            .with_intercept(false)
+            .solver(Solver::Svd)
+            .fit(&A, &b)
+            .unwrap();
+        assert_abs_diff_eq!(model.params(), &array![1., 2.], epsilon = 1e-6);
+
+        let normal = LinearRegression::new()
+            .with_intercept(false)
+            .solver(Solver::NormalEquations)
+            .fit(&A, &b);
+        // 1 + eps^2 rounds to 1, the Gram matrix [[1, 1], [1, 1]] is exactly singular
+        assert!(normal.is_err());
+    }
+
+    /// With more features than samples the automatic choice gives the
+    /// minimum norm solution, the normal equations are singular
+    #[test]
+    fn auto_solver_handles_underdetermined_problem() {
+        let A: Array2<f64> = array![[1., 0., 0.], [0., 1., 1.]];
+        let b: Array1<f64> = array![1., 2.];
+
+        let model = LinearRegression::new()
+            .with_intercept(false)
+            .fit(&A, &b)
+            .unwrap();
+        assert_abs_diff_eq!(model.params(), &array![1., 1., 1.], epsilon = 1e-10);
+        assert_abs_diff_eq!(model.predict(&A), b, epsilon = 1e-10);
+
+        let lsqr = LinearRegression::new()
+            .with_intercept(false)
+            .solver(Solver::Lsqr {
+                tol: 1e-12,
+                max_iter: 10,
+            })
+            .fit(&A, &b)
+            .unwrap();
+        assert_abs_diff_eq!(lsqr.params(), &array![1., 1., 1.], epsilon = 1e-10);
+
+        assert!(LinearRegression::new()
+            .with_intercept(false)
+            .solver(Solver::NormalEquations)
+            .fit(&A, &b)
+            .is_err());
+    }
+
+    /// Compare the inference summary with the output of
+    /// `summary(lm(y ~ x1 + x2))` and `confint` in R, the reference
+    /// values were computed in high precision
+    #[test]
+    fn inference_summary_matches_reference() {
+        let A: Array2<f64> = array![
+            [1., 2.],
+            [2., 1.],
+            [3., 4.],
+            [4., 3.],
+            [5., 6.],
+            [6., 5.],
+            [7., 8.],
+            [8., 9.]
+        ];
+        let b: Array1<f64> = array![3.1, 3.9, 7.2, 7.8, 11.1, 11.8, 15.2, 16.9];
+
+        let model = LinearRegression::new().fit(&A, &b).unwrap();
+        let summary = model.summary(&A, &b, 0.95).unwrap();
+
+        assert_eq!(summary.df_residual(), 5);
+        assert_abs_diff_eq!(
+            summary.residual_std_error(),
+            0.100975632859480,
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(summary.r2(), 0.999709472049256, epsilon = 1e-10);
+        assert_abs_diff_eq!(summary.adjusted_r2(), 0.999593260868958, epsilon = 1e-10);
+
+        // estimate, standard error, t value, p-value, confidence interval
+        let expected = [
+            [
+                0.552941176470588,
+                0.0787249407838617,
+                7.02371028755272,
+                0.000902588444054453,
+                0.350572273680049,
+                0.755310079261128,
+            ],
+            [
+                1.33921568627451,
+                0.0430033572538457,
+                31.1421194017299,
+                6.4088051677315e-7,
+                1.22867203724639,
+                1.44975933530262,
+            ],
+            [
+                0.641176470588235,
+                0.0374093804477234,
+                17.1394570804034,
+                1.23767808781662e-5,
+                0.545012596726909,
+                0.737340344449561,
+            ],
+        ];
+        let coefficients = summary.intercept().into_iter().chain(summary.params());
+        for (coef, expected) in coefficients.zip(expected.iter()) {
+            assert_abs_diff_eq!(coef.estimate, expected[0], epsilon = 1e-10);
+            assert_abs_diff_eq!(coef.std_error, expected[1], epsilon = 1e-10);
+            assert_abs_diff_eq!(coef.t_value, expected[2], epsilon = 1e-8);
+            assert_abs_diff_eq!(coef.p_value, expected[3], epsilon = 1e-10);
+            assert_abs_diff_eq!(coef.conf_int.0, expected[4], epsilon = 1e-9);
+            assert_abs_diff_eq!(coef.conf_int.1, expected[5], epsilon = 1e-9);
+        }
+
+        // without intercept, R^2 is relative to zero like `lm(y ~ x1 + x2 - 1)`
+        let model = LinearRegression::new()
+            .with_intercept(false)
+            .fit(&A, &b)
+            .unwrap();
+        let summary = model.summary(&A, &b, 0.95).unwrap();
+
+        assert!(summary.intercept().is_none());
+        assert_eq!(summary.df_residual(), 6);
+        assert_abs_diff_eq!(
+            summary.residual_std_error(),
+            0.303858106859012,
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(summary.r2(), 0.999395615868837, epsilon = 1e-10);
+        assert_abs_diff_eq!(summary.adjusted_r2(), 0.999194154491783, epsilon = 1e-10);
+        assert_abs_diff_eq!(
+            summary.params()[1].p_value,
+            0.00135614293654359,
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(
+            summary.params()[0].conf_int.0,
+            1.15013295884529,
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(
+            summary.params()[0].conf_int.1,
+            1.74234015943428,
+            epsilon = 1e-9
+        );
+
+        assert!(model.summary(&A, &b, 1.5).is_err());
+    }
 }
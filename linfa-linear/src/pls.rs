@@ -0,0 +1,551 @@
+//! Partial Least Squares
+//!
+//! PLS finds latent variables, the scores, which explain the covariance between the features `X`
+//! and the targets `Y`. The components are extracted one at a time with the NIPALS algorithm:
+//! the pair of weight vectors with maximal covariance (or correlation for CCA) is found with a
+//! power iteration, then the scores are projected out of the data and the next component is
+//! computed from the residuals.
+//!
+//! The three models in this module share this core and only differ in how the weights are
+//! estimated and how the targets are deflated:
+//! * [PlsRegression](struct.PlsRegression.html) deflates `Y` with the scores of `X`, which
+//!   results in a regression model predicting `Y` from `X`
+//! * [PlsCanonical](struct.PlsCanonical.html) treats both blocks symmetrically
+//! * [Cca](struct.Cca.html) estimates the weights by least squares, which maximizes the
+//!   correlation instead of the covariance of the scores
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+use ndarray::{s, Array1, Array2, ArrayView1, Axis};
+use ndarray_linalg::{Eigh, Inverse, UPLO};
+
+/// How the weights of a block are estimated in the inner NIPALS loop
+#[derive(Clone, Copy, PartialEq)]
+enum WeightMode {
+    /// Covariance between block and scores of the other block (PLS)
+    Covariance,
+    /// Least squares regression of the scores on the block (CCA)
+    LeastSquares,
+}
+
+/// How the targets are deflated after a component was extracted
+#[derive(Clone, Copy, PartialEq)]
+enum Deflation {
+    Regression,
+    Canonical,
+}
+
+/// The parameters of the NIPALS algorithm shared by all variants
+struct Nipals {
+    n_components: usize,
+    max_iter: usize,
+    tol: f64,
+    scale: bool,
+    mode: WeightMode,
+    deflation: Deflation,
+}
+
+macro_rules! impl_pls {
+    ($(#[$meta:meta])* $name:ident, $mode:expr, $deflation:expr) => {
+        $(#[$meta])*
+        pub struct $name {
+            n_components: usize,
+            max_iter: usize,
+            tol: f64,
+            scale: bool,
+        }
+
+        impl $name {
+            /// Create a model with `n_components` latent variables. The inner NIPALS loop of
+            /// each component runs for at most `max_iter` iterations or until the squared
+            /// change of the weights is smaller than `tol`.
+            pub fn new(n_components: usize, max_iter: usize, tol: f64) -> Self {
+                Self {
+                    n_components,
+                    max_iter,
+                    tol,
+                    scale: true,
+                }
+            }
+
+            /// Specifies whether features and targets are scaled to unit variance, defaults to
+            /// `true`
+            pub fn scale(mut self, scale: bool) -> Self {
+                self.scale = scale;
+                self
+            }
+
+            /// Fit the model given a feature matrix `x` with shape `(n_samples, n_features)`
+            /// and the targets `y` with shape `(n_samples, n_targets)`
+            ///
+            /// If the targets are explained completely by fewer than `n_components`
+            /// components, the fitted model only contains these components.
+            pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array2<A>) -> Result<FittedPls<A>> {
+                Nipals {
+                    n_components: self.n_components,
+                    max_iter: self.max_iter,
+                    tol: self.tol,
+                    scale: self.scale,
+                    mode: $mode,
+                    deflation: $deflation,
+                }
+                .fit(x, y)
+            }
+        }
+    };
+}
+
+impl_pls!(
+    /// Partial Least Squares regression
+    ///
+    /// Predicts the targets from the features through a small number of latent variables. This
+    /// is useful if the features are highly collinear or outnumber the samples.
+    PlsRegression,
+    WeightMode::Covariance,
+    Deflation::Regression
+);
+
+impl_pls!(
+    /// Partial Least Squares in canonical mode
+    ///
+    /// Features and targets are treated symmetrically, the scores of both blocks have maximal
+    /// covariance.
+    PlsCanonical,
+    WeightMode::Covariance,
+    Deflation::Canonical
+);
+
+impl_pls!(
+    /// Canonical Correlation Analysis with the NIPALS algorithm
+    ///
+    /// Like [PlsCanonical](struct.PlsCanonical.html), but the scores of both blocks have
+    /// maximal correlation instead of covariance.
+    Cca,
+    WeightMode::LeastSquares,
+    Deflation::Canonical
+);
+
+impl Nipals {
+    fn validate<A: Float>(&self, x: &Array2<A>, y: &Array2<A>) -> Result<()> {
+        if x.nrows() < 2 || x.nrows() != y.nrows() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and at least two",
+                x.nrows(),
+                y.nrows()
+            )));
+        }
+
+        let max_components = match self.deflation {
+            Deflation::Regression => x.ncols(),
+            Deflation::Canonical => x.ncols().min(y.ncols()),
+        }
+        .min(x.nrows());
+        if self.n_components == 0 || self.n_components > max_components {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of components must be in the interval [1, {}], got: {}",
+                max_components, self.n_components
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn fit<A: Float>(&self, x: &Array2<A>, y: &Array2<A>) -> Result<FittedPls<A>> {
+        self.validate(x, y)?;
+
+        let (mut xk, x_mean, x_std) = center_scale(x, self.scale);
+        let (mut yk, y_mean, y_std) = center_scale(y, self.scale);
+
+        let n = self.n_components;
+        let mut x_weights = Array2::zeros((x.ncols(), n));
+        let mut y_weights = Array2::zeros((y.ncols(), n));
+        let mut x_scores = Array2::zeros((x.nrows(), n));
+        let mut y_scores = Array2::zeros((y.nrows(), n));
+        let mut x_loadings = Array2::zeros((x.ncols(), n));
+        let mut y_loadings = Array2::zeros((y.ncols(), n));
+        let mut n_iter = Vec::with_capacity(n);
+
+        for k in 0..n {
+            // stop early if the targets are explained completely
+            if yk.iter().all(|x| num_traits::Float::abs(*x) < A::epsilon()) {
+                break;
+            }
+
+            let (x_w, y_w, iter) = self.inner_loop(&xk, &yk)?;
+            n_iter.push(iter);
+
+            let x_s = xk.dot(&x_w);
+            let y_s = yk.dot(&y_w) / y_w.dot(&y_w);
+
+            // project the scores out of the blocks
+            let x_l = xk.t().dot(&x_s) / x_s.dot(&x_s);
+            subtract_outer(&mut xk, x_s.view(), x_l.view());
+            let y_l = match self.deflation {
+                Deflation::Regression => {
+                    let y_l = yk.t().dot(&x_s) / x_s.dot(&x_s);
+                    subtract_outer(&mut yk, x_s.view(), y_l.view());
+                    y_l
+                }
+                Deflation::Canonical => {
+                    let y_l = yk.t().dot(&y_s) / y_s.dot(&y_s);
+                    subtract_outer(&mut yk, y_s.view(), y_l.view());
+                    y_l
+                }
+            };
+
+            x_weights.column_mut(k).assign(&x_w);
+            y_weights.column_mut(k).assign(&y_w);
+            x_scores.column_mut(k).assign(&x_s);
+            y_scores.column_mut(k).assign(&y_s);
+            x_loadings.column_mut(k).assign(&x_l);
+            y_loadings.column_mut(k).assign(&y_l);
+        }
+
+        // keep only the components which were extracted
+        let n = n_iter.len();
+        let truncate = |block: Array2<A>| block.slice(s![.., ..n]).to_owned();
+        let (x_weights, y_weights) = (truncate(x_weights), truncate(y_weights));
+        let (x_scores, y_scores) = (truncate(x_scores), truncate(y_scores));
+        let (x_loadings, y_loadings) = (truncate(x_loadings), truncate(y_loadings));
+
+        // rotations map the (scaled) blocks directly to their scores, without components the
+        // targets are constant and predicted by their mean
+        let (x_rotations, y_rotations) = if n > 0 {
+            (
+                x_weights.dot(&x_loadings.t().dot(&x_weights).inv()?),
+                y_weights.dot(&y_loadings.t().dot(&y_weights).inv()?),
+            )
+        } else {
+            (Array2::zeros((x.ncols(), 0)), Array2::zeros((y.ncols(), 0)))
+        };
+
+        // coefficients on the original scale of features and targets
+        let mut coef = x_rotations.dot(&y_loadings.t());
+        for (mut row, x_std) in coef.genrows_mut().into_iter().zip(x_std.iter()) {
+            row /= *x_std;
+            row *= &y_std;
+        }
+
+        Ok(FittedPls {
+            x_mean,
+            x_std,
+            y_mean,
+            y_std,
+            x_weights,
+            y_weights,
+            x_scores,
+            y_scores,
+            x_loadings,
+            y_loadings,
+            x_rotations,
+            y_rotations,
+            coef,
+            n_iter,
+        })
+    }
+
+    /// Find the first pair of weight vectors of the (deflated) blocks with a power iteration
+    fn inner_loop<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array2<A>,
+    ) -> Result<(Array1<A>, Array1<A>, usize)> {
+        let tol = A::from(self.tol).unwrap();
+
+        // start with the first target which is not yet explained
+        let first = y
+            .gencolumns()
+            .into_iter()
+            .find(|col| {
+                col.iter()
+                    .any(|x| num_traits::Float::abs(*x) > A::epsilon())
+            })
+            .unwrap();
+        let mut y_score = first.to_owned();
+
+        let (x_pinv, y_pinv) = if self.mode == WeightMode::LeastSquares {
+            (Some(pinv(x)?), Some(pinv(y)?))
+        } else {
+            (None, None)
+        };
+
+        let mut x_weights: Array1<A> = Array1::zeros(x.ncols());
+        let mut y_weights: Array1<A> = Array1::zeros(y.ncols());
+        let mut n_iter = self.max_iter;
+
+        for iter in 0..self.max_iter {
+            let mut x_w = match &x_pinv {
+                Some(pinv) => pinv.dot(&y_score),
+                None => x.t().dot(&y_score) / y_score.dot(&y_score),
+            };
+            x_w /= x_w.dot(&x_w).sqrt() + A::epsilon();
+            let x_score = x.dot(&x_w);
+
+            y_weights = match &y_pinv {
+                Some(pinv) => pinv.dot(&x_score),
+                None => y.t().dot(&x_score) / x_score.dot(&x_score),
+            };
+            if self.deflation == Deflation::Canonical {
+                y_weights /= y_weights.dot(&y_weights).sqrt() + A::epsilon();
+            }
+            y_score = y.dot(&y_weights) / (y_weights.dot(&y_weights) + A::epsilon());
+
+            let diff = &x_w - &x_weights;
+            x_weights = x_w;
+
+            // a single target converges in one iteration
+            if diff.dot(&diff) < tol || y.ncols() == 1 {
+                n_iter = iter + 1;
+                break;
+            }
+        }
+
+        Ok((x_weights, y_weights, n_iter))
+    }
+}
+
+/// Center each column and optionally scale it to unit variance
+fn center_scale<A: Float>(x: &Array2<A>, scale: bool) -> (Array2<A>, Array1<A>, Array1<A>) {
+    let mean = x.mean_axis(Axis(0)).unwrap();
+    let mut centered = x - &mean;
+
+    let std = if scale {
+        let n_samples = A::from(x.nrows() - 1).unwrap();
+        let std = centered.map_axis(Axis(0), |column| {
+            let std = (column.dot(&column) / n_samples).sqrt();
+            if std > A::zero() {
+                std
+            } else {
+                A::one()
+            }
+        });
+        centered /= &std;
+        std
+    } else {
+        Array1::ones(x.ncols())
+    };
+
+    (centered, mean, std)
+}
+
+/// Subtract the outer product of `a` and `b` from `x`
+fn subtract_outer<A: Float>(x: &mut Array2<A>, a: ArrayView1<A>, b: ArrayView1<A>) {
+    for (mut row, a) in x.genrows_mut().into_iter().zip(a.iter()) {
+        row.scaled_add(-*a, &b);
+    }
+}
+
+/// Moore-Penrose pseudo-inverse of `x` computed from the eigendecomposition of `x^T x`
+///
+/// The deflated blocks lose one rank with each component, directions without variance are
+/// therefore ignored.
+fn pinv<A: Float>(x: &Array2<A>) -> Result<Array2<A>> {
+    let (eigvals, eigvecs) = x.t().dot(x).eigh(UPLO::Lower)?;
+    let eigvals = eigvals.mapv(|x| A::from(x).unwrap());
+
+    let max_eigval = eigvals.fold(A::zero(), |a, b| a.max(*b));
+    let threshold = A::epsilon() * max_eigval * A::from(x.nrows().max(x.ncols())).unwrap();
+    let inv_eigvals = eigvals.mapv(|x| {
+        if x > threshold {
+            A::one() / x
+        } else {
+            A::zero()
+        }
+    });
+
+    Ok((&eigvecs * &inv_eigvals).dot(&eigvecs.t()).dot(&x.t()))
+}
+
+/// A fitted Partial Least Squares model
+pub struct FittedPls<A> {
+    x_mean: Array1<A>,
+    x_std: Array1<A>,
+    y_mean: Array1<A>,
+    y_std: Array1<A>,
+    x_weights: Array2<A>,
+    y_weights: Array2<A>,
+    x_scores: Array2<A>,
+    y_scores: Array2<A>,
+    x_loadings: Array2<A>,
+    y_loadings: Array2<A>,
+    x_rotations: Array2<A>,
+    y_rotations: Array2<A>,
+    coef: Array2<A>,
+    n_iter: Vec<usize>,
+}
+
+impl<A: Float> FittedPls<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`, `predict` returns the
+    /// targets with shape `(n_samples, n_targets)` according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array2<A> {
+        (x - &self.x_mean).dot(&self.coef) + &self.y_mean
+    }
+
+    /// Project the features onto the latent variables, returning the scores with shape
+    /// `(n_samples, n_components)`
+    pub fn transform(&self, x: &Array2<A>) -> Array2<A> {
+        ((x - &self.x_mean) / &self.x_std).dot(&self.x_rotations)
+    }
+
+    /// Project the targets onto the latent variables, returning the scores with shape
+    /// `(n_samples, n_components)`
+    pub fn transform_targets(&self, y: &Array2<A>) -> Array2<A> {
+        ((y - &self.y_mean) / &self.y_std).dot(&self.y_rotations)
+    }
+
+    /// Get the weights of the features with shape `(n_features, n_components)`
+    pub fn x_weights(&self) -> &Array2<A> {
+        &self.x_weights
+    }
+
+    /// Get the weights of the targets with shape `(n_targets, n_components)`
+    pub fn y_weights(&self) -> &Array2<A> {
+        &self.y_weights
+    }
+
+    /// Get the scores of the training features with shape `(n_samples, n_components)`
+    pub fn x_scores(&self) -> &Array2<A> {
+        &self.x_scores
+    }
+
+    /// Get the scores of the training targets with shape `(n_samples, n_components)`
+    pub fn y_scores(&self) -> &Array2<A> {
+        &self.y_scores
+    }
+
+    /// Get the loadings of the features with shape `(n_features, n_components)`
+    pub fn x_loadings(&self) -> &Array2<A> {
+        &self.x_loadings
+    }
+
+    /// Get the loadings of the targets with shape `(n_targets, n_components)`
+    pub fn y_loadings(&self) -> &Array2<A> {
+        &self.y_loadings
+    }
+
+    /// Get the regression coefficients with shape `(n_features, n_targets)`
+    pub fn coef(&self) -> &Array2<A> {
+        &self.coef
+    }
+
+    /// Get the number of iterations of the inner loop for each component
+    pub fn n_iter(&self) -> &[usize] {
+        &self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinearRegression;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn all_components_equal_least_squares() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((50, 3), StandardNormal, &mut rng);
+        let noise = Array2::<f64>::random_using((50, 2), StandardNormal, &mut rng);
+        let y = x.dot(&array![[1., -2.], [0.5, 0.], [-1., 3.]]) + noise * 0.1;
+
+        let pls = PlsRegression::new(3, 500, 1e-12).fit(&x, &y).unwrap();
+
+        // with as many components as features PLS is equivalent to ordinary least squares
+        for j in 0..2 {
+            let ols = LinearRegression::new()
+                .fit(&x, &y.column(j).to_owned())
+                .unwrap();
+            assert_abs_diff_eq!(pls.coef().column(j), ols.params().view(), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn single_latent_variable() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let latent = Array2::<f64>::random_using((100, 1), StandardNormal, &mut rng);
+        let x = latent.dot(&array![[1., 2., -1., 0.5]])
+            + Array2::<f64>::random_using((100, 4), StandardNormal, &mut rng) * 0.01;
+        let y = latent.dot(&array![[2., -1.]]) + 1.;
+
+        let pls = PlsRegression::new(1, 500, 1e-10).fit(&x, &y).unwrap();
+
+        assert_eq!(pls.x_scores().dim(), (100, 1));
+        assert_eq!(pls.coef().dim(), (4, 2));
+        assert_abs_diff_eq!(pls.predict(&x), y, epsilon = 0.1);
+        assert_abs_diff_eq!(pls.transform(&x), pls.x_scores(), epsilon = 1e-10);
+    }
+
+    #[test]
+    fn canonical_scores_are_related() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let latent = Array1::<f64>::random_using(100, StandardNormal, &mut rng);
+        let mut x = Array2::<f64>::random_using((100, 3), StandardNormal, &mut rng);
+        let mut y = Array2::<f64>::random_using((100, 2), StandardNormal, &mut rng);
+        x.column_mut(0).scaled_add(10., &latent);
+        y.column_mut(1).scaled_add(10., &latent);
+
+        for (x_scores, y_scores) in [
+            {
+                let model = PlsCanonical::new(2, 500, 1e-10).fit(&x, &y).unwrap();
+                (model.x_scores().clone(), model.y_scores().clone())
+            },
+            {
+                let model = Cca::new(2, 500, 1e-10).fit(&x, &y).unwrap();
+                (model.x_scores().clone(), model.y_scores().clone())
+            },
+        ] {
+            let (a, b) = (x_scores.column(0), y_scores.column(0));
+            let corr = a.dot(&b) / (a.dot(&a) * b.dot(&b)).sqrt();
+            assert!(corr.abs() > 0.95);
+
+            // the scores of different components are orthogonal
+            assert_abs_diff_eq!(
+                x_scores.column(0).dot(&x_scores.column(1)),
+                0.,
+                epsilon = 1e-8
+            );
+        }
+    }
+
+    #[test]
+    fn stops_when_targets_are_explained() {
+        // the target is the first feature, which is orthogonal to the second
+        let x = array![[1., 0.], [-1., 0.], [0., 1.], [0., -1.]];
+        let y = array![[1.], [-1.], [0.], [0.]];
+
+        let pls = PlsRegression::new(2, 100, 1e-10).fit(&x, &y).unwrap();
+        assert_eq!(pls.x_weights().dim(), (2, 1));
+        assert_eq!(pls.x_scores().dim(), (4, 1));
+        assert_eq!(pls.n_iter().len(), 1);
+        assert_abs_diff_eq!(pls.predict(&x), y, epsilon = 1e-10);
+        assert_abs_diff_eq!(pls.transform(&x), pls.x_scores(), epsilon = 1e-10);
+
+        // constant targets are explained without any component
+        let y = array![[3.], [3.], [3.], [3.]];
+        let pls = PlsRegression::new(2, 100, 1e-10).fit(&x, &y).unwrap();
+        assert_eq!(pls.x_scores().dim(), (4, 0));
+        assert_abs_diff_eq!(pls.predict(&x), y);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0., 1.], [1., 0.], [2., 2.]];
+        let y = array![[0.], [1.], [2.]];
+
+        assert!(PlsRegression::new(0, 100, 1e-6).fit(&x, &y).is_err());
+        assert!(PlsRegression::new(3, 100, 1e-6).fit(&x, &y).is_err());
+        assert!(PlsCanonical::new(2, 100, 1e-6).fit(&x, &y).is_err());
+        assert!(PlsRegression::new(1, 100, 0.).fit(&x, &y).is_err());
+    }
+}
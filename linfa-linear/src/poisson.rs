@@ -0,0 +1,271 @@
+//! Poisson Regression
+//!
+//! Generalized linear model for count data with a Poisson distribution and the log link. The
+//! expected count is `exp(x_i w + c + offset_i)`, where the optional offset is the logarithm of
+//! the exposure of each sample, for example the length of the observation period. This allows to
+//! model rates instead of counts. The penalized mean deviance
+//! ```ignore
+//! 1 / (2 * n_samples) * deviance(y, exp(X w + c + offset)) + alpha / 2 * ||w||^2_2
+//! ```
+//! with the Poisson deviance `2 * sum_i (y_i * ln(y_i / mu_i) - y_i + mu_i)` is minimized with
+//! iteratively reweighted least squares, i.e. Newton's method, shared with the
+//! [`GeneralizedLinearRegressor`](crate::glm::GeneralizedLinearRegressor) of the Poisson family.
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+use crate::glm::{Family, Irls, Link};
+
+use ndarray::{Array1, Array2};
+
+/// Poisson regression, a generalized linear model for count targets
+pub struct PoissonRegressor {
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for PoissonRegressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoissonRegressor {
+    /// Create a default Poisson regressor without penalty and with an intercept
+    pub fn new() -> Self {
+        Self {
+            alpha: 0.,
+            fit_intercept: true,
+            max_iter: 100,
+            tol: 1e-8,
+        }
+    }
+
+    /// Constant that multiplies with the L2 penalty term and thus determines the
+    /// regularization strength. The intercept is not penalized.
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Maximum number of Newton iterations
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion, the change of the objective between two iterations relative to the
+    /// objective
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit a Poisson regression given a feature matrix `x` with shape `(n_samples, n_features)`
+    /// and non-negative counts `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedPoissonRegressor<A>> {
+        self.fit_offset(x, y, Array1::zeros(y.len()))
+    }
+
+    /// Fit a Poisson regression of the rates `y / exposure`, given a feature matrix `x` with
+    /// shape `(n_samples, n_features)`, non-negative counts `y` and the positive exposure of each
+    /// sample, both with shape `(n_samples)`
+    ///
+    /// The logarithm of the exposure is added to the linear predictor with a fixed coefficient
+    /// of one, the fitted model therefore predicts the expected count for unit exposure.
+    pub fn fit_with_exposure<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        exposure: &Array1<A>,
+    ) -> Result<FittedPoissonRegressor<A>> {
+        validate_exposure(exposure, y.len())?;
+        self.fit_offset(x, y, exposure.mapv(|e| e.ln()))
+    }
+
+    fn fit_offset<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        offset: Array1<A>,
+    ) -> Result<FittedPoissonRegressor<A>> {
+        let irls = Irls {
+            family: Family::Poisson,
+            link: Link::Log,
+            alpha: self.alpha,
+            fit_intercept: self.fit_intercept,
+            max_iter: self.max_iter,
+            tol: self.tol,
+        };
+        let fit = irls.fit(x, y, &offset)?;
+
+        Ok(FittedPoissonRegressor {
+            intercept: fit.intercept,
+            params: fit.params,
+            deviance: fit.deviance,
+            n_iter: fit.n_iter,
+        })
+    }
+}
+
+fn validate_exposure<A: Float>(exposure: &Array1<A>, n_samples: usize) -> Result<()> {
+    if exposure.len() != n_samples {
+        return Err(LinearError::InvalidValue(format!(
+            "Number of samples ({}) and exposures ({}) must be equal",
+            n_samples,
+            exposure.len()
+        )));
+    }
+    if exposure.iter().any(|e| *e <= A::zero() || !e.is_finite()) {
+        return Err(LinearError::InvalidValue(
+            "Exposure must be positive".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// A fitted Poisson regressor which can be used for making predictions
+pub struct FittedPoissonRegressor<A> {
+    intercept: A,
+    params: Array1<A>,
+    deviance: A,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedPoissonRegressor<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the expected counts `exp(x w + c)` for unit exposure
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        (x.dot(&self.params) + self.intercept).mapv(|eta| eta.exp())
+    }
+
+    /// Return the expected counts for the given exposure of each sample
+    ///
+    /// **Panics** if the exposure is not positive or its length differs from the number of
+    /// samples.
+    pub fn predict_with_exposure(&self, x: &Array2<A>, exposure: &Array1<A>) -> Array1<A> {
+        validate_exposure(exposure, x.nrows()).unwrap();
+        self.predict(x) * exposure
+    }
+
+    /// Get the fitted parameters
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the Poisson deviance of the fitted model on the training data
+    pub fn deviance(&self) -> A {
+        self.deviance
+    }
+
+    /// Get the number of Newton iterations performed
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+
+    /// The counts of Dobson (1990), p. 93, used in the documentation of R's `glm`, with dummy
+    /// encoded outcome (levels 2 and 3) and treatment (levels 2 and 3)
+    fn dobson() -> (Array2<f64>, Array1<f64>) {
+        let x = array![
+            [0., 0., 0., 0.],
+            [1., 0., 0., 0.],
+            [0., 1., 0., 0.],
+            [0., 0., 1., 0.],
+            [1., 0., 1., 0.],
+            [0., 1., 1., 0.],
+            [0., 0., 0., 1.],
+            [1., 0., 0., 1.],
+            [0., 1., 0., 1.]
+        ];
+        let y = array![18., 17., 15., 20., 10., 20., 25., 13., 12.];
+
+        (x, y)
+    }
+
+    #[test]
+    fn matches_glm_of_r() {
+        let (x, y) = dobson();
+        let model = PoissonRegressor::new().fit(&x, &y).unwrap();
+
+        // glm(counts ~ outcome + treatment, family = poisson())
+        assert_abs_diff_eq!(model.intercept(), 3.044522, epsilon = 1e-4);
+        assert_abs_diff_eq!(
+            model.params(),
+            &array![-0.4542553, -0.2929871, 0., 0.],
+            epsilon = 1e-4
+        );
+        assert_abs_diff_eq!(model.deviance(), 5.129141, epsilon = 1e-4);
+        assert!(model.n_iter() < 10);
+
+        let counts = model.predict(&x);
+        assert_abs_diff_eq!(counts.sum(), y.sum(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn exposure_scales_rates() {
+        let (x, y) = dobson();
+        let model = PoissonRegressor::new().fit(&x, &y).unwrap();
+
+        // doubling the exposure halves the rate, only the intercept changes
+        let exposure = Array1::from_elem(9, 2.);
+        let rates = PoissonRegressor::new()
+            .fit_with_exposure(&x, &y, &exposure)
+            .unwrap();
+        assert_abs_diff_eq!(
+            rates.intercept(),
+            model.intercept() - 2f64.ln(),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(rates.params(), model.params(), epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            rates.predict_with_exposure(&x, &exposure),
+            model.predict(&x),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn penalty_shrinks_params() {
+        let (x, y) = dobson();
+        let model = PoissonRegressor::new().fit(&x, &y).unwrap();
+        let penalized = PoissonRegressor::new().alpha(1.).fit(&x, &y).unwrap();
+
+        assert!(penalized.params()[0].abs() < model.params()[0].abs());
+        assert!(penalized.params()[1].abs() < model.params()[1].abs());
+        assert!(penalized.deviance() > model.deviance());
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0.], [1.]];
+        let y = array![0., 1.];
+
+        assert!(PoissonRegressor::new().fit(&x, &array![-1., 1.]).is_err());
+        assert!(PoissonRegressor::new().fit(&x, &array![0., 0.]).is_err());
+        assert!(PoissonRegressor::new().alpha(-1.).fit(&x, &y).is_err());
+        assert!(PoissonRegressor::new().fit(&x, &array![0.]).is_err());
+        assert!(PoissonRegressor::new()
+            .fit_with_exposure(&x, &y, &array![1., 0.])
+            .is_err());
+    }
+}
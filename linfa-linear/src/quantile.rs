@@ -0,0 +1,300 @@
+//! Quantile Regression
+//!
+//! Linear regression of a conditional quantile instead of the conditional mean. The pinball loss
+//! ```ignore
+//! 1 / n_samples * sum_i rho_tau(y_i - x_i w - c) + alpha * ||w||^2_2
+//! ```
+//! with `rho_tau(r) = tau * r` for `r >= 0` and `(tau - 1) * r` otherwise is minimized, such that
+//! a fraction `tau` of the targets lies below the fitted hyperplane. Fitting the 10%, 50% and 90%
+//! quantiles for example yields the median and a prediction interval.
+//!
+//! The loss is minimized with the majorize-minimize algorithm of
+//! [Hunter and Lange (2000)](https://doi.org/10.1080/10618600.2000.10474866), which solves a
+//! reweighted least squares problem in each iteration. Residuals are bounded away from zero by a
+//! tiny value relative to the targets to keep the weights finite.
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+use ndarray::{s, stack, Array1, Array2, Axis};
+use ndarray_linalg::Solve;
+
+/// Quantile regression, a linear model of a conditional quantile
+pub struct QuantileRegression {
+    quantile: f64,
+    alpha: f64,
+    fit_intercept: bool,
+    max_iter: usize,
+    tol: f64,
+}
+
+impl Default for QuantileRegression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuantileRegression {
+    /// Create a default quantile regression of the median, without penalty and with an intercept
+    pub fn new() -> Self {
+        Self {
+            quantile: 0.5,
+            alpha: 0.,
+            fit_intercept: true,
+            max_iter: 1000,
+            tol: 1e-6,
+        }
+    }
+
+    /// The quantile to estimate, in the open interval `(0, 1)`
+    pub fn quantile(mut self, quantile: f64) -> Self {
+        self.quantile = quantile;
+        self
+    }
+
+    /// Constant that multiplies with the L2 penalty term and thus determines the
+    /// regularization strength
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Maximum number of reweighting iterations per quantile
+    pub fn max_iter(mut self, max_iter: usize) -> Self {
+        self.max_iter = max_iter;
+        self
+    }
+
+    /// Stopping criterion, the largest change of the coefficients between two iterations
+    /// relative to the largest coefficient
+    pub fn tol(mut self, tol: f64) -> Self {
+        self.tol = tol;
+        self
+    }
+
+    /// Fit a quantile regression given a feature matrix `x` with shape
+    /// `(n_samples, n_features)` and a target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+    ) -> Result<FittedQuantileRegression<A>> {
+        let mut models = self.fit_quantiles(x, y, &[self.quantile])?;
+        Ok(models.remove(0))
+    }
+
+    /// Fit one model for each of the given quantiles, ignoring the quantile of the builder
+    ///
+    /// The design matrix is shared and each fit starts from the solution of the previous
+    /// quantile, passing the quantiles in increasing order is therefore faster than fitting
+    /// them one by one.
+    pub fn fit_quantiles<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        quantiles: &[f64],
+    ) -> Result<Vec<FittedQuantileRegression<A>>> {
+        self.validate(x, y, quantiles)?;
+
+        let n_samples = x.nrows();
+        let design = if self.fit_intercept {
+            stack(Axis(1), &[Array2::ones((n_samples, 1)).view(), x.view()]).unwrap()
+        } else {
+            x.clone()
+        };
+        let offset = if self.fit_intercept { 1 } else { 0 };
+
+        let half = A::from(0.5).unwrap();
+        let penalty = A::from(2. * self.alpha * n_samples as f64).unwrap();
+        let tol = A::from(self.tol).unwrap();
+        let smoothing =
+            A::from(1e-6).unwrap() * y.fold(A::one(), |max, y| max.max(num_traits::Float::abs(*y)));
+
+        let mut coef: Array1<A> = Array1::zeros(design.ncols());
+        let mut models = Vec::with_capacity(quantiles.len());
+        for &quantile in quantiles {
+            let tau = A::from(quantile).unwrap();
+
+            let mut n_iter = 0;
+            while n_iter < self.max_iter {
+                n_iter += 1;
+
+                // majorize |r| by the parabola touching it at the current residual
+                let residuals = y - &design.dot(&coef);
+                let weights = residuals.mapv(|r| half / num_traits::Float::abs(r).max(smoothing));
+
+                let mut gram = design
+                    .t()
+                    .dot(&(&design * &weights.view().insert_axis(Axis(1))));
+                for i in offset..design.ncols() {
+                    gram[(i, i)] += penalty;
+                }
+                let rhs = design.t().dot(&(&weights * y + (tau - half)));
+                let new_coef = gram.solve_into(rhs)?;
+
+                let max_coef =
+                    new_coef.fold(A::one(), |max, x| max.max(num_traits::Float::abs(*x)));
+                let change = (&new_coef - &coef)
+                    .fold(A::zero(), |max, x| max.max(num_traits::Float::abs(*x)));
+                coef = new_coef;
+                if change < tol * max_coef {
+                    break;
+                }
+            }
+
+            let intercept = if self.fit_intercept {
+                coef[0]
+            } else {
+                A::zero()
+            };
+            models.push(FittedQuantileRegression {
+                quantile: tau,
+                intercept,
+                params: coef.slice(s![offset..]).to_owned(),
+                n_iter,
+            });
+        }
+
+        Ok(models)
+    }
+
+    fn validate<A: Float>(&self, x: &Array2<A>, y: &Array1<A>, quantiles: &[f64]) -> Result<()> {
+        if let Some(quantile) = quantiles
+            .iter()
+            .find(|q| **q <= 0. || **q >= 1. || q.is_nan())
+        {
+            return Err(LinearError::InvalidValue(format!(
+                "Quantile must be in the interval (0, 1), got: {}",
+                quantile
+            )));
+        }
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+        if self.tol <= 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Tolerance must be positive, got: {}",
+                self.tol
+            )));
+        }
+        if x.nrows() == 0 || x.nrows() != y.len() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) must be equal and non-zero",
+                x.nrows(),
+                y.len()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A fitted quantile regression which can be used for making predictions
+pub struct FittedQuantileRegression<A> {
+    quantile: A,
+    intercept: A,
+    params: Array1<A>,
+    n_iter: usize,
+}
+
+impl<A: Float> FittedQuantileRegression<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the estimated quantile of the target variable
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        x.dot(&self.params) + self.intercept
+    }
+
+    /// Get the fitted parameters
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the estimated quantile
+    pub fn quantile(&self) -> A {
+        self.quantile
+    }
+
+    /// Get the number of reweighting iterations performed
+    pub fn n_iter(&self) -> usize {
+        self.n_iter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::{StandardNormal, Uniform};
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn quantiles_of_heteroscedastic_data() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((500, 1), Uniform::new(0., 10.), &mut rng);
+        let noise = Array1::<f64>::random_using(500, StandardNormal, &mut rng);
+        // the spread of the targets grows with x
+        let y = x.column(0).mapv(|x| 1. + 2. * x) + &x.column(0).mapv(|x| 0.5 + 0.5 * x) * &noise;
+
+        let models = QuantileRegression::new()
+            .fit_quantiles(&x, &y, &[0.1, 0.5, 0.9])
+            .unwrap();
+
+        // the slope of the quantile `tau` is 2 + 0.5 * z_tau with the normal quantile `z_tau`
+        for (model, z) in models.iter().zip(&[-1.2816, 0., 1.2816]) {
+            let below = y
+                .iter()
+                .zip(model.predict(&x).iter())
+                .filter(|(y, pred)| y < pred)
+                .count();
+            assert_abs_diff_eq!(below as f64 / 500., model.quantile(), epsilon = 0.02);
+            assert_abs_diff_eq!(model.params()[0], 2. + 0.5 * z, epsilon = 0.2);
+            assert!(model.n_iter() < 1000);
+        }
+    }
+
+    #[test]
+    fn penalty_shrinks_params() {
+        let x = array![[0.], [1.], [2.], [3.], [4.], [5.]];
+        let y = array![0., 2., 4., 6., 8., 10.];
+
+        let model = QuantileRegression::new().fit(&x, &y).unwrap();
+        assert_abs_diff_eq!(model.params()[0], 2., epsilon = 1e-3);
+        assert_abs_diff_eq!(model.intercept(), 0., epsilon = 1e-3);
+
+        // the gradient of the loss is bounded by the mean of x
+        let model = QuantileRegression::new().alpha(100.).fit(&x, &y).unwrap();
+        let slope: f64 = model.params()[0];
+        assert!(slope.abs() < 2.5 / 200.);
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let x = array![[0.], [1.]];
+        let y = array![0., 1.];
+
+        assert!(QuantileRegression::new().quantile(0.).fit(&x, &y).is_err());
+        assert!(QuantileRegression::new().quantile(1.).fit(&x, &y).is_err());
+        assert!(QuantileRegression::new().alpha(-1.).fit(&x, &y).is_err());
+        assert!(QuantileRegression::new()
+            .fit_quantiles(&x, &y, &[0.5, 1.5])
+            .is_err());
+        assert!(QuantileRegression::new().fit(&x, &array![0.]).is_err());
+    }
+}
@@ -0,0 +1,651 @@
+//! Ridge Regression
+//!
+//! Linear regression with a L2 penalty on the coefficients. The penalty shrinks the coefficients
+//! towards zero and makes the problem well-posed, even if the features are highly correlated or
+//! there are more features than samples.
+//!
+//! The coefficients are found by solving
+//! ```ignore
+//! (X^T X + alpha * I) b = X^T y
+//! ```
+//! after centering `X` and `y`, so that the intercept is not penalized. The symmetric matrix
+//! `X^T X` is decomposed only once, every additional value of `alpha` is then solved in
+//! `O(n_features^2)`. This makes it cheap to select `alpha` with [RidgeCV](struct.RidgeCV.html).
+
+use crate::error::{LinearError, Result};
+use crate::float::Float;
+
+#[cfg(feature = "sprs")]
+use ndarray::{s, stack};
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+use ndarray_linalg::{Eigh, UPLO};
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+
+#[cfg(feature = "sprs")]
+use crate::lsqr::lsqr;
+#[cfg(feature = "sprs")]
+use crate::sparse::{sparse_dot, SparseColumns, LSQR_MAX_ITER, LSQR_TOL};
+
+/// Ridge regression, a linear model with L2 penalty
+///
+/// The parameter `alpha` determines the strength of the regularization, with `alpha = 0`
+/// equivalent to ordinary least squares.
+pub struct RidgeRegression {
+    alpha: f64,
+    fit_intercept: bool,
+}
+
+impl Default for RidgeRegression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RidgeRegression {
+    /// Create a default ridge regression model with `alpha = 1.0` and an intercept
+    pub fn new() -> Self {
+        Self {
+            alpha: 1.0,
+            fit_intercept: true,
+        }
+    }
+
+    /// Constant that multiplies with the penalty term and thus determines the
+    /// regularization strength. `alpha` set to 0 is equivalent to ordinary least squares.
+    pub fn alpha(mut self, alpha: f64) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Fit a ridge regression model given a feature matrix `x` with shape
+    /// `(n_samples, n_features)` and a target variable `y` with shape `(n_samples)`
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedRidgeRegression<A>> {
+        let problem = RidgeProblem::new(x, y.view().insert_axis(Axis(1)), self.fit_intercept)?;
+
+        problem.solve(self.alpha)
+    }
+
+    /// Fit a ridge regression model given a sparse feature matrix `x` with shape
+    /// `(n_samples, n_features)` and a target variable `y` with shape `(n_samples)`
+    ///
+    /// Decomposing the normal equations would destroy the sparsity, instead the augmented least
+    /// squares problem `min ||[X; sqrt(alpha) I] b - [y; 0]||` is solved with LSQR, which only
+    /// needs products with the (implicitly centered) sparse matrix.
+    #[cfg(feature = "sprs")]
+    pub fn fit_sparse<A: Float>(
+        &self,
+        x: &CsMat<A>,
+        y: &Array1<A>,
+    ) -> Result<FittedRidgeRegression<A>> {
+        let (n_samples, n_features) = x.shape();
+        if n_samples != y.len() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) differ",
+                n_samples,
+                y.len()
+            )));
+        }
+        if n_samples == 0 {
+            return Err(LinearError::InvalidValue(
+                "Cannot fit a model without any samples".into(),
+            ));
+        }
+        if self.alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                self.alpha
+            )));
+        }
+
+        let x_sparse = SparseColumns::new(x, self.fit_intercept);
+        let y_offset = if self.fit_intercept {
+            y.mean().unwrap()
+        } else {
+            A::zero()
+        };
+        let rhs = stack(
+            Axis(0),
+            &[(y - y_offset).view(), Array1::zeros(n_features).view()],
+        )
+        .unwrap();
+
+        let damp = A::from(self.alpha).unwrap().sqrt();
+        let x_norm =
+            (x_sparse.column_norms().sum() + damp * damp * A::from(n_features).unwrap()).sqrt();
+        let params = lsqr(
+            |v| stack(Axis(0), &[x_sparse.dot(v).view(), (v * damp).view()]).unwrap(),
+            |u| x_sparse.t_dot(&u.slice(s![..n_samples])) + &(&u.slice(s![n_samples..]) * damp),
+            x_norm,
+            &rhs,
+            LSQR_TOL,
+            LSQR_MAX_ITER,
+        );
+        let intercept = y_offset - x_sparse.offset().dot(&params);
+
+        Ok(FittedRidgeRegression {
+            alpha: self.alpha,
+            intercept,
+            params,
+        })
+    }
+
+    /// Fit a ridge regression model for multiple targets given a feature matrix `x` with shape
+    /// `(n_samples, n_features)` and the targets `y` with shape `(n_samples, n_targets)`
+    ///
+    /// The decomposition of the features is shared by all targets, which is much cheaper than
+    /// fitting a separate model for each target.
+    pub fn fit_multi_target<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array2<A>,
+    ) -> Result<FittedMultiTargetRidgeRegression<A>> {
+        let problem = RidgeProblem::new(x, y.view(), self.fit_intercept)?;
+
+        problem.solve_multi_target(self.alpha)
+    }
+
+    /// Fit a ridge regression model for each value in `alphas`
+    ///
+    /// The normal equations are decomposed once and re-used for every penalty, which is much
+    /// cheaper than fitting separate models.
+    pub fn fit_path<A: Float>(
+        &self,
+        x: &Array2<A>,
+        y: &Array1<A>,
+        alphas: &[f64],
+    ) -> Result<Vec<FittedRidgeRegression<A>>> {
+        let problem = RidgeProblem::new(x, y.view().insert_axis(Axis(1)), self.fit_intercept)?;
+
+        alphas.iter().map(|alpha| problem.solve(*alpha)).collect()
+    }
+}
+
+/// The eigendecomposition of the (centered) normal equations, shared between different penalties
+struct RidgeProblem<A> {
+    x_offset: Array1<A>,
+    y_offset: Array1<A>,
+    eigvals: Array1<A>,
+    eigvecs: Array2<A>,
+    /// The right hand side `X^T Y` rotated into the eigenbasis, one column per target
+    rhs: Array2<A>,
+    fit_intercept: bool,
+}
+
+impl<A: Float> RidgeProblem<A> {
+    fn new(x: &Array2<A>, y: ArrayView2<A>, fit_intercept: bool) -> Result<Self> {
+        if x.nrows() != y.nrows() {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of samples in x ({}) and y ({}) differ",
+                x.nrows(),
+                y.nrows()
+            )));
+        }
+        if x.nrows() == 0 {
+            return Err(LinearError::InvalidValue(
+                "Cannot fit a model without any samples".into(),
+            ));
+        }
+
+        // center the data, such that the intercept is not affected by the penalty
+        let (x_offset, y_offset) = if fit_intercept {
+            (x.mean_axis(Axis(0)).unwrap(), y.mean_axis(Axis(0)).unwrap())
+        } else {
+            (Array1::zeros(x.ncols()), Array1::zeros(y.ncols()))
+        };
+        let x = x - &x_offset;
+        let y = &y - &y_offset;
+
+        let (eigvals, eigvecs) = x.t().dot(&x).eigh(UPLO::Lower)?;
+        let eigvals = eigvals.mapv(|x| A::from(x).unwrap());
+        let rhs = eigvecs.t().dot(&x.t().dot(&y));
+
+        Ok(RidgeProblem {
+            x_offset,
+            y_offset,
+            eigvals,
+            eigvecs,
+            rhs,
+            fit_intercept,
+        })
+    }
+
+    /// Eigenvalues up to this cutoff belong to directions without any variance
+    fn cutoff(&self) -> A {
+        let max_eigval = self.eigvals.fold(A::zero(), |a, b| a.max(*b));
+
+        A::epsilon() * max_eigval * A::from(self.eigvals.len()).unwrap()
+    }
+
+    /// Solve the rotated normal equations for a single penalty
+    ///
+    /// Directions without any variance are ignored, which results in the minimum norm solution
+    /// for `alpha = 0`.
+    fn params(&self, alpha: A) -> Array2<A> {
+        let cutoff = self.cutoff();
+
+        let mut rotated = self.rhs.clone();
+        for (mut row, val) in rotated.genrows_mut().into_iter().zip(self.eigvals.iter()) {
+            if *val > cutoff {
+                let denom = *val + alpha;
+                row.mapv_inplace(|rhs| rhs / denom);
+            } else {
+                row.fill(A::zero());
+            }
+        }
+
+        self.eigvecs.dot(&rotated)
+    }
+
+    /// Effective degrees of freedom, the trace of the hat matrix
+    ///
+    /// Uses the same cutoff as [params](#method.params), ignored directions do not contribute.
+    fn degrees_of_freedom(&self, alpha: A) -> A {
+        let cutoff = self.cutoff();
+        let df = self
+            .eigvals
+            .iter()
+            .filter(|val| **val > cutoff)
+            .map(|val| *val / (*val + alpha))
+            .sum::<A>();
+
+        if self.fit_intercept {
+            df + A::one()
+        } else {
+            df
+        }
+    }
+
+    fn solve_multi_target(&self, alpha: f64) -> Result<FittedMultiTargetRidgeRegression<A>> {
+        if alpha < 0. {
+            return Err(LinearError::InvalidValue(format!(
+                "Penalty term must be a non-negative number, got: {}",
+                alpha
+            )));
+        }
+
+        let params = self.params(A::from(alpha).unwrap());
+        let intercept = &self.y_offset - &self.x_offset.dot(&params);
+
+        Ok(FittedMultiTargetRidgeRegression {
+            alpha,
+            intercept,
+            params,
+        })
+    }
+
+    /// Solve for the first (and only) target
+    fn solve(&self, alpha: f64) -> Result<FittedRidgeRegression<A>> {
+        let model = self.solve_multi_target(alpha)?;
+
+        Ok(FittedRidgeRegression {
+            alpha,
+            intercept: model.intercept[0],
+            params: model.params.column(0).to_owned(),
+        })
+    }
+}
+
+/// A fitted ridge regression model which can be used for making predictions
+pub struct FittedRidgeRegression<A> {
+    alpha: f64,
+    intercept: A,
+    params: Array1<A>,
+}
+
+impl<A: Float> FittedRidgeRegression<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`,
+    /// `predict` returns the target variable according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        x.dot(&self.params) + self.intercept
+    }
+
+    /// Predict the target variable of a sparse input matrix `x`
+    #[cfg(feature = "sprs")]
+    pub fn predict_sparse(&self, x: &CsMat<A>) -> Array1<A> {
+        sparse_dot(x, &self.params) + self.intercept
+    }
+
+    /// Get the fitted parameters
+    pub fn params(&self) -> &Array1<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept, 0. if no intercept was fitted
+    pub fn intercept(&self) -> A {
+        self.intercept
+    }
+
+    /// Get the penalty used to fit this model
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+}
+
+/// A fitted ridge regression model for multiple targets
+pub struct FittedMultiTargetRidgeRegression<A> {
+    alpha: f64,
+    intercept: Array1<A>,
+    params: Array2<A>,
+}
+
+impl<A: Float> FittedMultiTargetRidgeRegression<A> {
+    /// Given an input matrix `x`, with shape `(n_samples, n_features)`, `predict` returns the
+    /// targets with shape `(n_samples, n_targets)` according to the learned linear model
+    pub fn predict(&self, x: &Array2<A>) -> Array2<A> {
+        x.dot(&self.params) + &self.intercept
+    }
+
+    /// Get the fitted parameters with shape `(n_features, n_targets)`
+    pub fn params(&self) -> &Array2<A> {
+        &self.params
+    }
+
+    /// Get the fitted intercept for each target, 0. if no intercept was fitted
+    pub fn intercept(&self) -> &Array1<A> {
+        &self.intercept
+    }
+
+    /// Get the penalty used to fit this model
+    pub fn alpha(&self) -> f64 {
+        self.alpha
+    }
+}
+
+/// Strategy used by [RidgeCV](struct.RidgeCV.html) to score the penalties
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlphaSelection {
+    /// Generalized cross-validation, an efficient approximation of leave-one-out
+    Gcv,
+    /// K-fold cross-validation with the given number of consecutive folds
+    KFold(usize),
+}
+
+/// Ridge regression with built-in selection of the penalty
+///
+/// Every penalty in `alphas` is scored by its mean squared error, estimated either with
+/// generalized cross-validation or k-fold cross-validation. The model is then fitted on the whole
+/// dataset with the best penalty.
+pub struct RidgeCV {
+    alphas: Vec<f64>,
+    fit_intercept: bool,
+    selection: AlphaSelection,
+}
+
+impl RidgeCV {
+    /// Create a new model, selecting from `alphas` with generalized cross-validation
+    pub fn new(alphas: Vec<f64>) -> Self {
+        Self {
+            alphas,
+            fit_intercept: true,
+            selection: AlphaSelection::Gcv,
+        }
+    }
+
+    /// Specifies whether a bias or intercept should be added to the model
+    pub fn fit_intercept(mut self, fit_intercept: bool) -> Self {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Set the strategy used to score the penalties
+    pub fn selection(mut self, selection: AlphaSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Estimate the mean squared error for each penalty in `alphas`
+    ///
+    /// Generalized cross-validation is undefined for a penalty which lets the model interpolate
+    /// the data, for example a tiny penalty with more features than samples. These penalties get
+    /// an infinite score.
+    pub fn scores<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<Array1<A>> {
+        if self.alphas.is_empty() {
+            return Err(LinearError::InvalidValue(
+                "At least one penalty term is required".into(),
+            ));
+        }
+
+        match self.selection {
+            AlphaSelection::Gcv => self.gcv_scores(x, y),
+            AlphaSelection::KFold(k) => self.kfold_scores(x, y, k),
+        }
+    }
+
+    /// Select the best penalty and fit a ridge regression model on the whole dataset
+    ///
+    /// Penalties with a non-finite score are never selected. Returns an error if no penalty has
+    /// a finite score.
+    pub fn fit<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<FittedRidgeRegression<A>> {
+        let scores = self.scores(x, y)?;
+
+        let best = scores
+            .iter()
+            .enumerate()
+            .filter(|(_, score)| score.is_finite())
+            .fold(None, |best: Option<usize>, (i, score)| match best {
+                Some(best) if scores[best] <= *score => Some(best),
+                _ => Some(i),
+            })
+            .ok_or_else(|| {
+                LinearError::InvalidValue(
+                    "None of the penalty terms has a finite cross-validation score".into(),
+                )
+            })?;
+
+        RidgeRegression::new()
+            .alpha(self.alphas[best])
+            .fit_intercept(self.fit_intercept)
+            .fit(x, y)
+    }
+
+    fn gcv_scores<A: Float>(&self, x: &Array2<A>, y: &Array1<A>) -> Result<Array1<A>> {
+        let problem = RidgeProblem::new(x, y.view().insert_axis(Axis(1)), self.fit_intercept)?;
+        let n_samples = A::from(x.nrows()).unwrap();
+
+        self.alphas
+            .iter()
+            .map(|alpha| {
+                let model = problem.solve(*alpha)?;
+                let mse = (y - &model.predict(x)).mapv(|r| r * r).sum() / n_samples;
+                let df = problem.degrees_of_freedom(A::from(*alpha).unwrap());
+
+                // close to `df = n` the residuals and the denominator both vanish and the score
+                // is dominated by rounding errors
+                let denom = A::one() - df / n_samples;
+                if denom <= A::epsilon().sqrt() {
+                    return Ok(A::infinity());
+                }
+
+                let score = mse / (denom * denom);
+                Ok(if score.is_finite() {
+                    score
+                } else {
+                    A::infinity()
+                })
+            })
+            .collect()
+    }
+
+    fn kfold_scores<A: Float>(&self, x: &Array2<A>, y: &Array1<A>, k: usize) -> Result<Array1<A>> {
+        let n_samples = x.nrows();
+        if k < 2 || k > n_samples {
+            return Err(LinearError::InvalidValue(format!(
+                "Number of folds must be between 2 and the number of samples, got: {}",
+                k
+            )));
+        }
+
+        let mut scores = Array1::zeros(self.alphas.len());
+        for fold in 0..k {
+            let (start, end) = (fold * n_samples / k, (fold + 1) * n_samples / k);
+            let (train, valid): (Vec<usize>, Vec<usize>) =
+                (0..n_samples).partition(|i| *i < start || *i >= end);
+
+            let (x_train, y_train) = (x.select(Axis(0), &train), y.select(Axis(0), &train));
+            let (x_valid, y_valid) = (x.select(Axis(0), &valid), y.select(Axis(0), &valid));
+
+            let models = RidgeRegression::new()
+                .fit_intercept(self.fit_intercept)
+                .fit_path(&x_train, &y_train, &self.alphas)?;
+
+            for (score, model) in scores.iter_mut().zip(models.iter()) {
+                *score += (&y_valid - &model.predict(&x_valid)).mapv(|r| r * r).sum();
+            }
+        }
+
+        Ok(scores / A::from(n_samples).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LinearRegression;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+
+    fn dataset() -> (Array2<f64>, Array1<f64>) {
+        let x = array![
+            [0., 1.],
+            [1., 0.],
+            [2., 3.],
+            [3., 1.],
+            [4., 5.],
+            [5., 2.],
+            [6., 6.],
+            [7., 3.]
+        ];
+        let y = x.dot(&array![1.5, -2.]) + 3.;
+
+        (x, y)
+    }
+
+    #[test]
+    fn small_alpha_recovers_ols() {
+        let (x, y) = dataset();
+
+        let ols = LinearRegression::new().fit(&x, &y).unwrap();
+        let ridge = RidgeRegression::new().alpha(1e-10).fit(&x, &y).unwrap();
+
+        assert_abs_diff_eq!(ridge.params(), ols.params(), epsilon = 1e-6);
+        assert_abs_diff_eq!(ridge.intercept(), ols.intercept(), epsilon = 1e-6);
+        assert_abs_diff_eq!(ridge.params(), &array![1.5, -2.], epsilon = 1e-6);
+    }
+
+    #[test]
+    fn large_alpha_shrinks_params() {
+        let (x, y) = dataset();
+
+        let ridge = RidgeRegression::new().alpha(1e10).fit(&x, &y).unwrap();
+
+        assert_abs_diff_eq!(ridge.params(), &array![0., 0.], epsilon = 1e-6);
+        assert_abs_diff_eq!(ridge.intercept(), y.mean().unwrap(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn path_matches_single_fits() {
+        let (x, y) = dataset();
+        let alphas = [0.1, 1.0, 10.0];
+
+        let path = RidgeRegression::new().fit_path(&x, &y, &alphas).unwrap();
+        let mut last_norm = f64::INFINITY;
+        for (alpha, model) in alphas.iter().zip(path.iter()) {
+            // compare with the closed form solution of the centered problem
+            let x_c = &x - &x.mean_axis(Axis(0)).unwrap();
+            let y_c = &y - y.mean().unwrap();
+            let lhs = x_c.t().dot(&x_c) + Array2::<f64>::eye(2) * *alpha;
+            let params = ndarray_linalg::Solve::solve_into(&lhs, x_c.t().dot(&y_c)).unwrap();
+
+            assert_abs_diff_eq!(model.params(), &params, epsilon = 1e-8);
+            assert_eq!(model.alpha(), *alpha);
+
+            // increasing the penalty shrinks the coefficients
+            let norm = model.params().dot(model.params());
+            assert!(norm < last_norm);
+            last_norm = norm;
+        }
+    }
+
+    #[test]
+    fn multi_target_matches_single_fits() {
+        let (x, y) = dataset();
+        let y_multi = ndarray::stack(
+            Axis(1),
+            &[
+                y.view().insert_axis(Axis(1)),
+                x.column(0).insert_axis(Axis(1)),
+                (&y * 2. - 1.).view().insert_axis(Axis(1)),
+            ],
+        )
+        .unwrap();
+
+        let ridge = RidgeRegression::new().alpha(0.5);
+        let multi = ridge.fit_multi_target(&x, &y_multi).unwrap();
+        assert_eq!(multi.params().dim(), (2, 3));
+        assert_eq!(multi.predict(&x).dim(), (8, 3));
+
+        for (j, target) in y_multi.gencolumns().into_iter().enumerate() {
+            let single = ridge.fit(&x, &target.to_owned()).unwrap();
+
+            assert_abs_diff_eq!(
+                multi.params().column(j),
+                single.params().view(),
+                epsilon = 1e-12
+            );
+            assert_abs_diff_eq!(multi.intercept()[j], single.intercept(), epsilon = 1e-12);
+            let (prediction, expected) = (multi.predict(&x), single.predict(&x));
+            assert_abs_diff_eq!(prediction.column(j), expected.view(), epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn negative_alpha_is_rejected() {
+        let (x, y) = dataset();
+
+        assert!(RidgeRegression::new().alpha(-1.0).fit(&x, &y).is_err());
+    }
+
+    #[test]
+    fn cross_validation_selects_small_alpha() {
+        // the dataset is noise free, the smallest penalty is optimal
+        let (x, y) = dataset();
+        let alphas = vec![1e-6, 1.0, 100.0];
+
+        let model = RidgeCV::new(alphas.clone()).fit(&x, &y).unwrap();
+        assert_eq!(model.alpha(), 1e-6);
+
+        let model = RidgeCV::new(alphas.clone())
+            .selection(AlphaSelection::KFold(4))
+            .fit(&x, &y)
+            .unwrap();
+        assert_eq!(model.alpha(), 1e-6);
+
+        assert!(RidgeCV::new(alphas)
+            .selection(AlphaSelection::KFold(1))
+            .fit(&x, &y)
+            .is_err());
+    }
+
+    #[test]
+    fn gcv_skips_interpolating_penalty() {
+        // with more features than samples a tiny penalty interpolates the data
+        let x = Array2::from_shape_fn((5, 8), |(i, j)| ((i * 8 + j) as f64 * 0.7).sin());
+        let y = array![1., -1., 2., 0.5, -0.5];
+
+        let scores = RidgeCV::new(vec![1e-12, 1.0]).scores(&x, &y).unwrap();
+        assert_eq!(scores[0], f64::INFINITY);
+        assert!(scores[1].is_finite());
+
+        let model = RidgeCV::new(vec![1e-12, 1.0]).fit(&x, &y).unwrap();
+        assert_eq!(model.alpha(), 1.0);
+
+        assert!(RidgeCV::new(vec![1e-12]).fit(&x, &y).is_err());
+    }
+}
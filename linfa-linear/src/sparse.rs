@@ -0,0 +1,339 @@
+//! Sparse feature matrices
+//!
+//! Linear models can be fitted on sparse feature matrices of the `sprs` crate, which requires the
+//! `sprs` feature. Centering the features would destroy their sparsity, the matrices are
+//! therefore stored column by column and centered (and scaled) implicitly in every product.
+use ndarray::{Array1, ArrayBase, Data, Ix1};
+use sprs::CsMat;
+
+use crate::coordinate_descent::{duality_gap_with_correlation, soft_threshold};
+use crate::float::Float;
+
+/// A sparse feature matrix `X`, which represents the implicitly centered and scaled matrix
+/// `(X - offset) / scale`
+pub(crate) struct SparseColumns<A> {
+    n_samples: usize,
+    /// Row index and value of the non-zero entries of each column
+    columns: Vec<Vec<(usize, A)>>,
+    offset: Array1<A>,
+    scale: Array1<A>,
+}
+
+impl<A: Float> SparseColumns<A> {
+    /// Collect the columns of `x`, which can be stored in either CSR or CSC format
+    pub fn new(x: &CsMat<A>, center: bool) -> SparseColumns<A> {
+        let (n_samples, n_features) = x.shape();
+        let mut columns = vec![Vec::new(); n_features];
+        for (value, (row, col)) in x.iter() {
+            columns[col].push((row, *value));
+        }
+
+        let offset = if center {
+            let n = A::from(n_samples).unwrap();
+            columns
+                .iter()
+                .map(|column| column.iter().map(|(_, x)| *x).sum::<A>() / n)
+                .collect()
+        } else {
+            Array1::zeros(n_features)
+        };
+
+        SparseColumns {
+            n_samples,
+            columns,
+            offset,
+            scale: Array1::ones(n_features),
+        }
+    }
+
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    pub fn n_features(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The offset of each feature, on the original scale
+    pub fn offset(&self) -> &Array1<A> {
+        &self.offset
+    }
+
+    /// Divide each feature by `scale`
+    pub fn set_scale(&mut self, scale: Array1<A>) {
+        self.scale = scale;
+    }
+
+    /// Squared norm of each centered and scaled column
+    pub fn column_norms(&self) -> Array1<A> {
+        let n = A::from(self.n_samples).unwrap();
+        self.columns
+            .iter()
+            .zip(self.offset.iter().zip(self.scale.iter()))
+            .map(|(column, (offset, scale))| {
+                let sum_squares = column.iter().map(|(_, x)| *x * *x).sum::<A>();
+                // cancellation can result in slightly negative values for constant columns
+                let norm = (sum_squares - n * *offset * *offset) / (*scale * *scale);
+                norm.max(A::zero())
+            })
+            .collect()
+    }
+
+    /// Frobenius norm of the centered and scaled matrix
+    pub fn frobenius_norm(&self) -> A {
+        self.column_norms().sum().sqrt()
+    }
+
+    /// Compute the product with a vector of coefficients `w`
+    pub fn dot(&self, w: &ArrayBase<impl Data<Elem = A>, Ix1>) -> Array1<A> {
+        let w = w / &self.scale;
+        let mut result = Array1::from_elem(self.n_samples, -self.offset.dot(&w));
+        for (column, w) in self.columns.iter().zip(w.iter()) {
+            for (row, value) in column {
+                result[*row] += *value * *w;
+            }
+        }
+
+        result
+    }
+
+    /// Compute the product of the transposed matrix with a vector `r` of length `n_samples`
+    pub fn t_dot(&self, r: &ArrayBase<impl Data<Elem = A>, Ix1>) -> Array1<A> {
+        let sum = r.sum();
+        self.columns
+            .iter()
+            .zip(self.offset.iter().zip(self.scale.iter()))
+            .map(|(column, (offset, scale))| {
+                let dot = column.iter().map(|(row, x)| *x * r[*row]).sum::<A>();
+                (dot - *offset * sum) / *scale
+            })
+            .collect()
+    }
+}
+
+/// Run coordinate descent on a sparse feature matrix, starting from the coefficients `w`
+///
+/// This is the same algorithm as the dense coordinate descent, but a coordinate update only
+/// touches the non-zero entries of its column. The centered residual `y - Xw` is represented as
+/// the residual of the uncentered matrix plus a constant, which absorbs the offsets. Returns the
+/// coefficients, the duality gap and the number of iterations.
+pub(crate) fn sparse_coordinate_descent<A: Float>(
+    x: &SparseColumns<A>,
+    y: &Array1<A>,
+    l1: A,
+    l2: A,
+    mut w: Array1<A>,
+    max_iter: usize,
+    tol: A,
+) -> (Array1<A>, A, usize) {
+    let n_samples = A::from(x.n_samples()).unwrap();
+    let n_features = x.n_features();
+
+    // scaled offsets and column sums of the scaled, uncentered columns
+    let offset = &x.offset / &x.scale;
+    let column_sums = x
+        .columns
+        .iter()
+        .zip(x.scale.iter())
+        .map(|(column, scale)| column.iter().map(|(_, x)| *x).sum::<A>() / *scale)
+        .collect::<Array1<A>>();
+    let norms = x.column_norms();
+
+    // the centered residual is `residual + shift`, where `residual` ignores the offsets
+    let mut shift = offset.dot(&w);
+    let mut residual = y - &(x.dot(&w) + shift);
+    let mut residual_sum = residual.sum();
+    let gap_tol = tol * y.dot(y);
+
+    let all_features = (0..n_features).collect::<Vec<_>>();
+    let mut active = all_features.clone();
+    let mut full_sweep = true;
+    let mut gap = A::infinity();
+
+    for n_iter in 0..max_iter {
+        let (mut w_max, mut d_w_max) = (A::zero(), A::zero());
+
+        for &j in &active {
+            if norms[j] == A::zero() {
+                continue;
+            }
+
+            let w_old = w[j];
+            let (column, scale) = (&x.columns[j], x.scale[j]);
+
+            // correlation of the centered feature `j` with the centered residual
+            let dot = column.iter().map(|(row, x)| *x * residual[*row]).sum::<A>() / scale;
+            let correlation =
+                dot + shift * column_sums[j] - offset[j] * (residual_sum + n_samples * shift);
+
+            let rho = correlation + norms[j] * w_old;
+            w[j] = soft_threshold(rho, l1 * n_samples) / (norms[j] + l2 * n_samples);
+
+            if w[j] != w_old {
+                let diff = w[j] - w_old;
+                for (row, value) in column {
+                    residual[*row] -= diff * *value / scale;
+                }
+                residual_sum -= diff * column_sums[j];
+                shift += diff * offset[j];
+            }
+
+            d_w_max = d_w_max.max((w[j] - w_old).abs());
+            w_max = w_max.max(w[j].abs());
+        }
+
+        if full_sweep {
+            let centered_residual = &residual + shift;
+            let correlation = x.t_dot(&centered_residual);
+            gap = duality_gap_with_correlation(
+                correlation,
+                y,
+                &w,
+                &centered_residual,
+                n_samples,
+                l1,
+                l2,
+            );
+            if gap <= gap_tol {
+                return (w, gap, n_iter + 1);
+            }
+
+            // continue with the non-zero coefficients only
+            active = (0..n_features).filter(|j| w[*j] != A::zero()).collect();
+            full_sweep = active.is_empty();
+        } else if w_max == A::zero() || d_w_max / w_max < tol {
+            // the active set has converged, check all coefficients in the next sweep
+            active = all_features.clone();
+            full_sweep = true;
+        }
+    }
+
+    (w, gap, max_iter)
+}
+
+/// Compute the product of a sparse matrix with a vector of coefficients
+pub(crate) fn sparse_dot<A: Float>(x: &CsMat<A>, w: &Array1<A>) -> Array1<A> {
+    let mut result = Array1::zeros(x.shape().0);
+    for (value, (row, col)) in x.iter() {
+        result[row] += *value * w[col];
+    }
+
+    result
+}
+
+/// Tolerance of LSQR for sparse least squares problems
+pub(crate) const LSQR_TOL: f64 = 1e-10;
+
+/// Maximum number of LSQR iterations for sparse least squares problems
+pub(crate) const LSQR_MAX_ITER: usize = 1000;
+
+#[cfg(test)]
+mod tests {
+    use crate::{ElasticNet, Lasso, LinearRegression, RidgeRegression};
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, Array1, Array2};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+    use sprs::{CsMat, TriMat};
+
+    /// A design matrix with about 70% zeros, together with its sparse representation
+    fn sparse_problem() -> (Array2<f64>, CsMat<f64>, Array1<f64>) {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((60, 8), Uniform::new(0., 1.), &mut rng).mapv(|x| {
+            if x < 0.7 {
+                0.
+            } else {
+                10. * x - 5.
+            }
+        });
+        let noise = Array1::random_using(60, Uniform::new(-0.1, 0.1), &mut rng);
+        let y = x.dot(&array![1., 0., -2., 0., 0.5, 0., 0., 3.]) + 2. + noise;
+
+        let mut triplets = TriMat::new(x.dim());
+        for ((row, col), value) in x.indexed_iter() {
+            if *value != 0. {
+                triplets.add_triplet(row, col, *value);
+            }
+        }
+
+        (x, triplets.to_csr(), y)
+    }
+
+    #[test]
+    fn linear_regression_agrees_with_dense_fit() {
+        let (x, x_sparse, y) = sparse_problem();
+
+        for model in &[
+            LinearRegression::new(),
+            LinearRegression::new().with_intercept(false),
+            LinearRegression::new().with_intercept_and_normalize(),
+        ] {
+            let dense = model.fit(&x, &y).unwrap();
+            let sparse = model.fit_sparse(&x_sparse, &y).unwrap();
+
+            assert_abs_diff_eq!(sparse.params(), dense.params(), epsilon = 1e-6);
+            assert_abs_diff_eq!(sparse.intercept(), dense.intercept(), epsilon = 1e-6);
+            assert_abs_diff_eq!(
+                sparse.predict_sparse(&x_sparse),
+                dense.predict(&x),
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn ridge_agrees_with_dense_fit() {
+        let (x, x_sparse, y) = sparse_problem();
+
+        for model in &[
+            RidgeRegression::new().alpha(10.),
+            RidgeRegression::new().alpha(10.).fit_intercept(false),
+        ] {
+            let dense = model.fit(&x, &y).unwrap();
+            let sparse = model.fit_sparse(&x_sparse, &y).unwrap();
+
+            assert_abs_diff_eq!(sparse.params(), dense.params(), epsilon = 1e-6);
+            assert_abs_diff_eq!(sparse.intercept(), dense.intercept(), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn lasso_agrees_with_dense_fit() {
+        let (x, x_sparse, y) = sparse_problem();
+
+        for model in &[
+            Lasso::new().alpha(0.1).tol(1e-10),
+            Lasso::new().alpha(0.1).tol(1e-10).fit_intercept(false),
+        ] {
+            let dense = model.fit(&x, &y).unwrap();
+            let sparse = model.fit_sparse(&x_sparse, &y).unwrap();
+
+            assert_eq!(sparse.n_nonzero(), dense.n_nonzero());
+            assert_abs_diff_eq!(sparse.params(), dense.params(), epsilon = 1e-5);
+            assert_abs_diff_eq!(sparse.intercept(), dense.intercept(), epsilon = 1e-5);
+            assert_abs_diff_eq!(
+                sparse.predict_sparse(&x_sparse),
+                dense.predict(&x),
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn elastic_net_agrees_with_dense_fit() {
+        let (x, x_sparse, y) = sparse_problem();
+
+        for model in &[
+            ElasticNet::new().alpha(0.1).tol(1e-10),
+            ElasticNet::new().alpha(0.1).tol(1e-10).standardize(true),
+        ] {
+            let dense = model.fit(&x, &y).unwrap();
+            let sparse = model.fit_sparse(&x_sparse, &y).unwrap();
+
+            assert_abs_diff_eq!(sparse.params(), dense.params(), epsilon = 1e-5);
+            assert_abs_diff_eq!(sparse.intercept(), dense.intercept(), epsilon = 1e-5);
+        }
+    }
+}
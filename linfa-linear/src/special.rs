@@ -0,0 +1,197 @@
+//! Special functions needed for statistical inference
+//!
+//! The distribution functions of Student's t-distribution are expressed with the regularized
+//! incomplete beta function, which is evaluated with a continued fraction. All computations are
+//! performed in double precision.
+
+/// Natural logarithm of the gamma function with the Lanczos approximation (g = 7, n = 9)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1. - x);
+    }
+
+    let x = x - 1.;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.));
+
+    0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Continued fraction of the incomplete beta function, evaluated with the modified Lentz method
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITER: usize = 300;
+    const EPS: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+
+    let (qab, qap, qam) = (a + b, a + 1., a - 1.);
+    let mut c = 1.;
+    let mut d = 1. - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1. / d;
+    let mut h = d;
+
+    for m in 1..=MAX_ITER {
+        let m = m as f64;
+        let m2 = 2. * m;
+
+        // even step
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1. + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1. + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1. / d;
+        h *= d * c;
+
+        // odd step
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1. + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1. + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1. / d;
+        let delta = d * c;
+        h *= delta;
+
+        if (delta - 1.).abs() < EPS {
+            break;
+        }
+    }
+
+    h
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`
+pub(crate) fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0. {
+        return 0.;
+    }
+    if x >= 1. {
+        return 1.;
+    }
+
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1. - x).ln();
+
+    // the continued fraction converges quickly for x < (a + 1) / (a + b + 2)
+    if x < (a + 1.) / (a + b + 2.) {
+        ln_front.exp() * beta_continued_fraction(a, b, x) / a
+    } else {
+        1. - ln_front.exp() * beta_continued_fraction(b, a, 1. - x) / b
+    }
+}
+
+/// Two-sided tail probability `P(|T| >= |t|)` of Student's t-distribution with `df` degrees
+/// of freedom
+pub(crate) fn student_t_two_sided(t: f64, df: f64) -> f64 {
+    incomplete_beta(df / 2., 0.5, df / (df + t * t))
+}
+
+/// Cumulative distribution function of Student's t-distribution with `df` degrees of freedom
+pub(crate) fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = student_t_two_sided(t, df) / 2.;
+    if t > 0. {
+        1. - tail
+    } else {
+        tail
+    }
+}
+
+/// Quantile function of Student's t-distribution with `df` degrees of freedom, the inverse of
+/// the cumulative distribution function found by bisection
+pub(crate) fn student_t_quantile(p: f64, df: f64) -> f64 {
+    if p == 0.5 {
+        return 0.;
+    }
+    if p < 0.5 {
+        return -student_t_quantile(1. - p, df);
+    }
+
+    // bracket the quantile before bisecting
+    let (mut lo, mut hi) = (0., 1.);
+    while student_t_cdf(hi, df) < p {
+        lo = hi;
+        hi *= 2.;
+    }
+
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if student_t_cdf(mid, df) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+        if hi - lo < 1e-14 * hi {
+            break;
+        }
+    }
+
+    0.5 * (lo + hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn student_t_distribution() {
+        assert_abs_diff_eq!(
+            student_t_cdf(2., 10.),
+            0.963_305_982_614_63,
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(
+            student_t_cdf(-1.5, 3.),
+            0.115_291_932_622_412,
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(
+            student_t_cdf(0.3, 1.),
+            0.592_773_579_077_742,
+            epsilon = 1e-10
+        );
+
+        assert_abs_diff_eq!(
+            student_t_quantile(0.975, 10.),
+            2.228_138_851_986_27,
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(
+            student_t_quantile(0.95, 2.),
+            2.919_985_580_353_73,
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(
+            student_t_quantile(0.025, 10.),
+            -2.228_138_851_986_27,
+            epsilon = 1e-9
+        );
+    }
+}
@@ -41,7 +41,7 @@ impl<F: Float> ArgminDot<ArgminParam<F>, F> for ArgminParam<F> {
 
 impl<F: Float> ArgminNorm<F> for ArgminParam<F> {
     fn norm(&self) -> F {
-        self.0.dot(&self.0)
+        self.0.dot(&self.0).sqrt()
     }
 }
 
@@ -0,0 +1,86 @@
+//! Feature matrices of the logistic regression
+//!
+//! The logistic loss and its gradient only need the products of the feature matrix with a vector
+//! of coefficients and of its transpose with a vector of residuals. Any matrix which implements
+//! these products can be fitted with the same solvers. Besides dense arrays this includes sparse
+//! matrices of the `sprs` crate, which requires the `sprs` feature. Their products only visit
+//! the non-zero entries, so neither time nor memory scale with the zeros.
+use ndarray::{Array1, ArrayBase, Data, Ix2};
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
+
+use crate::float::Float;
+
+/// A feature matrix `X` with shape `(n_samples, n_features)`
+pub(crate) trait DesignMatrix<F: Float> {
+    /// The number of samples
+    fn n_samples(&self) -> usize;
+
+    /// The number of features
+    fn n_features(&self) -> usize;
+
+    /// The product `X w` with a vector of coefficients
+    fn dot_params(&self, w: &Array1<F>) -> Array1<F>;
+
+    /// The product `X^T r` with a vector of residuals
+    fn t_dot_residuals(&self, r: &Array1<F>) -> Array1<F>;
+
+    /// Whether all (stored) entries are finite
+    fn is_finite(&self) -> bool;
+}
+
+impl<F: Float, A: Data<Elem = F>> DesignMatrix<F> for ArrayBase<A, Ix2> {
+    fn n_samples(&self) -> usize {
+        self.nrows()
+    }
+
+    fn n_features(&self) -> usize {
+        self.ncols()
+    }
+
+    fn dot_params(&self, w: &Array1<F>) -> Array1<F> {
+        self.dot(w)
+    }
+
+    fn t_dot_residuals(&self, r: &Array1<F>) -> Array1<F> {
+        self.t().dot(r)
+    }
+
+    fn is_finite(&self) -> bool {
+        self.iter().all(|x| x.is_finite())
+    }
+}
+
+/// Sparse matrices can be stored in either CSR or CSC format
+#[cfg(feature = "sprs")]
+impl<F: Float> DesignMatrix<F> for CsMat<F> {
+    fn n_samples(&self) -> usize {
+        self.rows()
+    }
+
+    fn n_features(&self) -> usize {
+        self.cols()
+    }
+
+    fn dot_params(&self, w: &Array1<F>) -> Array1<F> {
+        let mut result = Array1::zeros(self.rows());
+        for (value, (row, col)) in self.iter() {
+            result[row] += *value * w[col];
+        }
+
+        result
+    }
+
+    fn t_dot_residuals(&self, r: &Array1<F>) -> Array1<F> {
+        let mut result = Array1::zeros(self.cols());
+        for (value, (row, col)) in self.iter() {
+            result[col] += *value * r[row];
+        }
+
+        result
+    }
+
+    fn is_finite(&self) -> bool {
+        self.data().iter().all(|x| x.is_finite())
+    }
+}
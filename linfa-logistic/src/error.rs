@@ -0,0 +1,34 @@
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+
+use crate::float::Float;
+use crate::FittedLogisticRegression;
+
+/// An error when fitting a logistic regression
+#[derive(Debug, PartialEq)]
+pub enum LogisticError<F: Float, C: PartialOrd + Clone> {
+    /// The data or the hyperparameters are invalid
+    InvalidInput(String),
+    /// The solver failed
+    Solver(String),
+    /// The solver reached the maximal number of iterations before the gradient tolerance, the
+    /// model of the last iteration is attached
+    NonConverged(Box<FittedLogisticRegression<F, C>>),
+}
+
+impl<F: Float, C: PartialOrd + Clone> Display for LogisticError<F, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+            Self::Solver(message) => write!(f, "Error running solver: {}", message),
+            Self::NonConverged(model) => write!(
+                f,
+                "Solver did not converge within {} iterations, the gradient norm is {}",
+                model.iterations(),
+                model.gradient_norm()
+            ),
+        }
+    }
+}
+
+impl<F: Float, C: PartialOrd + Clone + Debug> Error for LogisticError<F, C> {}
@@ -1,6 +1,10 @@
 //! # Logistic Regression
 //!
-//! `linfa-logistic` provides a two class logistic regression model.
+//! `linfa-logistic` provides a two class logistic regression model and a
+//! multinomial (softmax) model for two or more classes.
+//!
+//! With the `sprs` feature the two class model can also be fitted to sparse
+//! feature matrices, see `LogisticRegression::fit_sparse`.
 //!
 //! `linfa-logistic` is part of the `linfa` crate, which is an
 //! effort to bootstrap a toolkit for classical Machine Learning
@@ -9,21 +13,35 @@
 use argmin::prelude::*;
 use argmin::solver::linesearch::MoreThuenteLineSearch;
 use argmin::solver::quasinewton::lbfgs::LBFGS;
-use ndarray::{s, Array, Array1, ArrayBase, Data, Ix1, Ix2};
+use ndarray::{s, Array, Array1, Array2, ArrayBase, Data, Ix1, Ix2};
+#[cfg(feature = "sprs")]
+use sprs::CsMat;
 use std::default::Default;
 
 mod argmin_param;
+mod design_matrix;
+mod error;
 mod float;
+mod multinomial;
+mod proximal;
 
 use argmin_param::ArgminParam;
+use design_matrix::DesignMatrix;
 use float::Float;
 
+pub use error::LogisticError;
+pub use multinomial::{FittedMultiLogisticRegression, MultiLogisticRegression};
+
 /// A two-class logistic regression model.
 pub struct LogisticRegression<F: Float> {
     alpha: F,
+    l1_ratio: F,
+    l1_penalty: F,
+    class_weight: ClassWeight<F>,
     fit_intercept: bool,
     max_iterations: u64,
     gradient_tolerance: F,
+    lbfgs_memory: usize,
     initial_params: Option<(Array1<F>, F)>,
 }
 
@@ -35,26 +53,106 @@ impl<F: Float> Default for LogisticRegression<F> {
 
 type LBFGSType<F> = LBFGS<MoreThuenteLineSearch<ArgminParam<F>, F>, ArgminParam<F>, F>;
 
+/// The weights of the two classes in the loss of a logistic regression
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClassWeight<F> {
+    /// Every sample has weight one
+    Uniform,
+    /// Weights of the smaller and the larger class (by PartialOrd)
+    Manual(F, F),
+    /// Weight every class with `n_samples / (n_classes * n_class_samples)`
+    Balanced,
+}
+
+impl<F: Float> ClassWeight<F> {
+    /// Compute the weight of every sample from the targets `-1.0` and `1.0`
+    fn sample_weight(&self, target: &Array1<F>) -> Array1<F> {
+        let (negative, positive) = match *self {
+            ClassWeight::Uniform => (F::one(), F::one()),
+            ClassWeight::Manual(negative, positive) => (negative, positive),
+            ClassWeight::Balanced => {
+                let n_samples = F::from(target.len()).unwrap();
+                let n_positive =
+                    F::from(target.iter().filter(|t| **t > F::zero()).count()).unwrap();
+                let two = F::from(2.0).unwrap();
+                (
+                    n_samples / (two * (n_samples - n_positive)),
+                    n_samples / (two * n_positive),
+                )
+            }
+        };
+
+        target.mapv(|t| if t > F::zero() { positive } else { negative })
+    }
+}
+
 impl<F: Float> LogisticRegression<F> {
     /// Creates a new LogisticRegression with default configuration.
     pub fn new() -> LogisticRegression<F> {
         LogisticRegression {
             alpha: F::from(1.0).unwrap(),
+            l1_ratio: F::zero(),
+            l1_penalty: F::zero(),
+            class_weight: ClassWeight::Uniform,
             fit_intercept: true,
             max_iterations: 100,
             gradient_tolerance: F::from(1e-4).unwrap(),
+            lbfgs_memory: 10,
             initial_params: None,
         }
     }
 
     /// Set the normalization parameter `alpha` used for L2 normalization,
     /// defaults to `1.0`.
+    ///
+    /// Together with `l1_ratio` this is the elastic-net penalty
+    /// `alpha * (l1_ratio * |w|_1 + (1 - l1_ratio) / 2 * ||w||^2)`.
     pub fn alpha(mut self, alpha: F) -> LogisticRegression<F> {
         self.alpha = alpha;
         self
     }
 
+    /// Set the share of `alpha` which is used for the L1 penalty, defaults to
+    /// `0.0`.
+    ///
+    /// The ratio must lie in `[0, 1]`. With `0.0` the penalty is pure L2 and
+    /// with `1.0` it is the same as `alpha(0.0).l1_penalty(alpha)`. In between
+    /// the elastic net still produces sparse parameters, but spreads the
+    /// weight among correlated features instead of picking one of them.
+    pub fn l1_ratio(mut self, l1_ratio: F) -> LogisticRegression<F> {
+        self.l1_ratio = l1_ratio;
+        self
+    }
+
+    /// Set the strength of the L1 penalty on the parameters, defaults to `0.0`.
+    ///
+    /// A positive L1 penalty produces sparse parameters, where the parameters
+    /// of irrelevant features are exactly zero. The problem is then solved by
+    /// accelerated proximal gradient descent (FISTA) instead of LBFGS, the
+    /// intercept is never penalized.
+    pub fn l1_penalty(mut self, l1_penalty: F) -> LogisticRegression<F> {
+        self.l1_penalty = l1_penalty;
+        self
+    }
+
+    /// Set the weights of the two classes in the loss, defaults to
+    /// `ClassWeight::Uniform`.
+    ///
+    /// The loss of every sample is multiplied by the weight of its class. For
+    /// imbalanced data `ClassWeight::Balanced` gives both classes the same
+    /// total weight, so that the minority class is not ignored.
+    pub fn class_weight(mut self, class_weight: ClassWeight<F>) -> LogisticRegression<F> {
+        self.class_weight = class_weight;
+        self
+    }
+
     /// Configure if an intercept should be fitted, defaults to `true`.
+    ///
+    /// The intercept is an additional coordinate of the optimization, which
+    /// is never penalized by `alpha` or `l1_penalty`. Shrinking it would bias
+    /// the predicted probabilities towards one half. Without an intercept the
+    /// decision function passes through the origin, which is only suitable
+    /// for centered data.
     pub fn with_intercept(mut self, fit_intercept: bool) -> LogisticRegression<F> {
         self.fit_intercept = fit_intercept;
         self
@@ -62,6 +160,10 @@ impl<F: Float> LogisticRegression<F> {
 
     /// Configure the maximum number of iterations that the solver should perform,
     /// defaults to `100`.
+    ///
+    /// If the gradient tolerance is not reached within this number of
+    /// iterations, `fit` returns `LogisticError::NonConverged` with the model
+    /// of the last iteration.
     pub fn max_iterations(mut self, max_iterations: u64) -> LogisticRegression<F> {
         self.max_iterations = max_iterations;
         self
@@ -69,11 +171,24 @@ impl<F: Float> LogisticRegression<F> {
 
     /// Configure the minimum change to the gradient to continue the solver,
     /// defaults to `1e-4`.
+    ///
+    /// The solver stops once the norm of the gradient of the penalized loss,
+    /// or of the gradient mapping for an L1 penalty, falls below this value.
     pub fn gradient_tolerance(mut self, gradient_tolerance: F) -> LogisticRegression<F> {
         self.gradient_tolerance = gradient_tolerance;
         self
     }
 
+    /// Configure the number of past gradients which LBFGS keeps to
+    /// approximate the Hessian, defaults to `10`.
+    ///
+    /// A larger memory can speed up the convergence on ill-conditioned
+    /// problems. It is not used by the proximal solver of the L1 penalty.
+    pub fn lbfgs_memory(mut self, lbfgs_memory: usize) -> LogisticRegression<F> {
+        self.lbfgs_memory = lbfgs_memory;
+        self
+    }
+
     /// Configure the initial parameters from where the optimization starts.
     /// The `params` array must have the same size as the number of columns of
     /// the feature matrix `x` passed to the `fit` method
@@ -82,6 +197,21 @@ impl<F: Float> LogisticRegression<F> {
         self
     }
 
+    /// Start the optimization from the parameters of a previously fitted
+    /// model.
+    ///
+    /// This is a shortcut for `initial_params` with the coefficients and the
+    /// intercept of `model`, which must have been fitted to the same features
+    /// and the same two classes. Refitting with slightly different
+    /// hyperparameters or data, for example along a regularization path,
+    /// then needs far fewer iterations than starting from zero.
+    pub fn warm_start<C: PartialOrd + Clone>(
+        self,
+        model: &FittedLogisticRegression<F, C>,
+    ) -> LogisticRegression<F> {
+        self.initial_params(model.params().clone(), model.intercept())
+    }
+
     /// Given a 2-dimensional feature matrix array `x` with shape
     /// (n_samples, n_features) and an iterable of target classes to predict,
     /// create a `FittedLinearRegression` object which allows making
@@ -94,66 +224,180 @@ impl<F: Float> LogisticRegression<F> {
     /// The iterable `y` must also produces exactly `n_samples` items, i.e.
     /// exactly as many items as there are rows in the feature matrix `x`.
     ///
-    /// This method returns an error if any of the preconditions are violated,
-    /// i.e. any values are `Inf` or `NaN`, `y` doesn't have as many items as
-    /// `x` has rows, or if other parameters (gradient_tolerance, alpha) have
-    /// been set to inalid values.
+    /// This method returns `LogisticError::InvalidInput` if any of the
+    /// preconditions are violated, i.e. any values are `Inf` or `NaN`, `y`
+    /// doesn't have as many items as `x` has rows, or if other parameters
+    /// (gradient_tolerance, alpha) have been set to inalid values. If the
+    /// solver stops at `max_iterations` before reaching the gradient tolerance
+    /// it returns `LogisticError::NonConverged` with the partial solution.
     pub fn fit<'a, A, II, C>(
         &self,
         x: &ArrayBase<A, Ix2>,
         y: II,
-    ) -> Result<FittedLogisticRegression<F, C>, String>
+    ) -> Result<FittedLogisticRegression<F, C>, LogisticError<F, C>>
     where
         A: Data<Elem = F>,
         II: IntoIterator<Item = &'a C>,
         C: 'a + PartialOrd + Clone,
     {
-        let (labels, target) = label_classes(y)?;
-        self.validate_data(x, &target)?;
-        let problem = self.setup_problem(x, target);
-        let solver = self.setup_solver();
-        let init_params = self.setup_init_params(x);
-        let result = self.run_solver(problem, solver, init_params)?;
-        self.convert_result(labels, &result)
+        self.fit_matrix(x, y, None::<&Array1<F>>)
     }
 
-    /// Ensure that `x` and `y` have the right shape and that all data and
-    /// configuration parameters are finite.
-    fn validate_data<A, B>(
+    /// Fit the model like `fit`, weighting the loss of every sample by
+    /// `sample_weight`.
+    ///
+    /// The weights must be finite, non-negative and have one entry per row of
+    /// `x`. They are multiplied with the class weights, and integer weights
+    /// are equivalent to repeating the samples accordingly. This is useful
+    /// for boosting or survey weights.
+    pub fn fit_weighted<'a, A, B, II, C>(
         &self,
         x: &ArrayBase<A, Ix2>,
-        y: &ArrayBase<B, Ix1>,
-    ) -> Result<(), String>
+        y: II,
+        sample_weight: &ArrayBase<B, Ix1>,
+    ) -> Result<FittedLogisticRegression<F, C>, LogisticError<F, C>>
     where
         A: Data<Elem = F>,
         B: Data<Elem = F>,
+        II: IntoIterator<Item = &'a C>,
+        C: 'a + PartialOrd + Clone,
+    {
+        self.fit_matrix(x, y, Some(sample_weight))
+    }
+
+    /// Given a sparse feature matrix `x` with shape (n_samples, n_features)
+    /// in CSR or CSC format and an iterable of target classes to predict,
+    /// create a `FittedLogisticRegression` object like `fit`.
+    ///
+    /// The solvers only use products of `x` with vectors, which visit the
+    /// non-zero entries of `x` and never densify it. This is useful for
+    /// high-dimensional features like hashed text features.
+    #[cfg(feature = "sprs")]
+    pub fn fit_sparse<'a, II, C>(
+        &self,
+        x: &CsMat<F>,
+        y: II,
+    ) -> Result<FittedLogisticRegression<F, C>, LogisticError<F, C>>
+    where
+        II: IntoIterator<Item = &'a C>,
+        C: 'a + PartialOrd + Clone,
+    {
+        self.fit_matrix(x, y, None::<&Array1<F>>)
+    }
+
+    /// Fit the model to a dense or sparse feature matrix, with optional
+    /// sample weights.
+    fn fit_matrix<'a, X, B, II, C>(
+        &self,
+        x: &X,
+        y: II,
+        sample_weight: Option<&ArrayBase<B, Ix1>>,
+    ) -> Result<FittedLogisticRegression<F, C>, LogisticError<F, C>>
+    where
+        X: DesignMatrix<F>,
+        B: Data<Elem = F>,
+        II: IntoIterator<Item = &'a C>,
+        C: 'a + PartialOrd + Clone,
+    {
+        let (labels, target) = label_classes(y).map_err(LogisticError::InvalidInput)?;
+        self.validate_data(x, &target)
+            .map_err(LogisticError::InvalidInput)?;
+        // the weight of a sample is the product of its own and its class weight
+        let mut class_weight = self.class_weight.sample_weight(&target);
+        if let Some(sample_weight) = sample_weight {
+            validate_sample_weight(target.len(), sample_weight)
+                .map_err(LogisticError::InvalidInput)?;
+            class_weight *= sample_weight;
+        }
+        let sample_weight = class_weight;
+        let init_params = self.setup_init_params(x);
+        let (l1_penalty, l2_penalty) = self.penalties();
+        let (params, iterations, gradient_norm, converged) = if l1_penalty > F::zero() {
+            let result = self.run_proximal_solver(
+                x,
+                &target,
+                &sample_weight,
+                l1_penalty,
+                l2_penalty,
+                init_params,
+            );
+            let converged = result.gradient_norm < self.gradient_tolerance;
+            (
+                result.params,
+                result.iterations,
+                result.gradient_norm,
+                converged,
+            )
+        } else {
+            let problem = self.setup_problem(x, target.clone(), sample_weight.clone(), l2_penalty);
+            let solver = self.setup_solver();
+            let result = self
+                .run_solver(problem, solver, init_params)
+                .map_err(LogisticError::Solver)?;
+            let state = result.state();
+            let params = state.best_param.as_array().clone();
+            let gradient = logistic_grad(x, &target, &sample_weight, l2_penalty, &params);
+            let converged = !matches!(state.termination_reason, TerminationReason::MaxItersReached);
+            (
+                params,
+                state.iter,
+                gradient.dot(&gradient).sqrt(),
+                converged,
+            )
+        };
+
+        let model = self.convert_result(labels, params, iterations, gradient_norm);
+        if converged {
+            Ok(model)
+        } else {
+            Err(LogisticError::NonConverged(Box::new(model)))
+        }
+    }
+
+    /// Ensure that `x` and `y` have the right shape and that all data and
+    /// configuration parameters are finite.
+    fn validate_data<X, B>(&self, x: &X, y: &ArrayBase<B, Ix1>) -> Result<(), String>
+    where
+        X: DesignMatrix<F>,
+        B: Data<Elem = F>,
     {
-        if x.shape()[0] != y.len() {
+        if x.n_samples() != y.len() {
             return Err(
                 "Incompatible shapes of data, expected `x` and `y` to have same number of rows"
                     .to_string(),
             );
         }
-        if x.iter().any(|x| !x.is_finite())
-            || y.iter().any(|y| !y.is_finite())
-            || !self.alpha.is_finite()
-        {
+        if !x.is_finite() || y.iter().any(|y| !y.is_finite()) || !self.alpha.is_finite() {
             return Err("Values must be finite and not `Inf`, `-Inf` or `NaN`".to_string());
         }
         if !self.gradient_tolerance.is_finite() || self.gradient_tolerance <= F::zero() {
             return Err("gradient_tolerance must be a positive, finite number".to_string());
         }
+        if self.lbfgs_memory == 0 {
+            return Err("lbfgs_memory must be positive".to_string());
+        }
+        if !self.l1_penalty.is_finite() || self.l1_penalty < F::zero() {
+            return Err("l1_penalty must be a non-negative, finite number".to_string());
+        }
+        if !(self.l1_ratio >= F::zero() && self.l1_ratio <= F::one()) {
+            return Err("l1_ratio must be in the interval [0, 1]".to_string());
+        }
+        if let ClassWeight::Manual(negative, positive) = self.class_weight {
+            let valid = |weight: F| weight.is_finite() && weight > F::zero();
+            if !valid(negative) || !valid(positive) {
+                return Err("class weights must be positive, finite numbers".to_string());
+            }
+        }
         self.validate_init_params(x)?;
         Ok(())
     }
 
-    fn validate_init_params<A>(&self, x: &ArrayBase<A, Ix2>) -> Result<(), String>
+    fn validate_init_params<X>(&self, x: &X) -> Result<(), String>
     where
-        A: Data<Elem = F>,
+        X: DesignMatrix<F>,
     {
         if let Some((params, intercept)) = self.initial_params.as_ref() {
-            let (_, n_features) = x.dim();
-            if n_features != params.dim() {
+            if x.n_features() != params.dim() {
                 return Err("Size of initial parameter guess must be the same as the number of columns in the feature matrix `x`".to_string());
             }
             if params.iter().any(|p| !p.is_finite()) || !intercept.is_finite() {
@@ -164,25 +408,35 @@ impl<F: Float> LogisticRegression<F> {
     }
 
     /// Create a `LogisticRegressionProblem`.
-    fn setup_problem<'a, A: Data<Elem = F>>(
+    fn setup_problem<'a, X: DesignMatrix<F>>(
         &self,
-        x: &'a ArrayBase<A, Ix2>,
+        x: &'a X,
         target: Array1<F>,
-    ) -> LogisticRegressionProblem<'a, F, A> {
+        sample_weight: Array1<F>,
+        alpha: F,
+    ) -> LogisticRegressionProblem<'a, F, X> {
         LogisticRegressionProblem {
             x,
             target,
-            alpha: self.alpha,
+            sample_weight,
+            alpha,
         }
     }
 
+    /// Split the penalty into the total L1 and L2 strength.
+    fn penalties(&self) -> (F, F) {
+        let l1_penalty = self.alpha * self.l1_ratio + self.l1_penalty;
+        let l2_penalty = self.alpha * (F::one() - self.l1_ratio);
+        (l1_penalty, l2_penalty)
+    }
+
     /// Create the initial parameters, either from a user supplied guess
     ///  or a 1-d array of `0`s.
-    fn setup_init_params<A>(&self, x: &ArrayBase<A, Ix2>) -> Array1<F>
+    fn setup_init_params<X>(&self, x: &X) -> Array1<F>
     where
-        A: Data<Elem = F>,
+        X: DesignMatrix<F>,
     {
-        let n_features = x.shape()[1];
+        let n_features = x.n_features();
         let param_len = if self.fit_intercept {
             n_features + 1
         } else {
@@ -205,18 +459,18 @@ impl<F: Float> LogisticRegression<F> {
     /// tolerance.
     fn setup_solver(&self) -> LBFGSType<F> {
         let linesearch = MoreThuenteLineSearch::new();
-        LBFGS::new(linesearch, 10).with_tol_grad(self.gradient_tolerance)
+        LBFGS::new(linesearch, self.lbfgs_memory).with_tol_grad(self.gradient_tolerance)
     }
 
     /// Run the LBFGS solver until it converges or runs out of iterations.
-    fn run_solver<'a, A>(
+    fn run_solver<'a, X>(
         &self,
-        problem: LogisticRegressionProblem<'a, F, A>,
+        problem: LogisticRegressionProblem<'a, F, X>,
         solver: LBFGSType<F>,
         init_params: Array1<F>,
-    ) -> Result<ArgminResult<LogisticRegressionProblem<'a, F, A>>, String>
+    ) -> Result<ArgminResult<LogisticRegressionProblem<'a, F, X>>, String>
     where
-        A: Data<Elem = F>,
+        X: DesignMatrix<F>,
     {
         Executor::new(problem, solver, ArgminParam(init_params))
             .max_iters(self.max_iterations)
@@ -224,24 +478,77 @@ impl<F: Float> LogisticRegression<F> {
             .map_err(|err| format!("Error running solver: {}", err))
     }
 
-    /// Take an ArgminResult and return a FittedLogisticRegression.
-    fn convert_result<A, C>(
+    /// Minimize the logistic loss with the L1 or elastic-net penalty by
+    /// proximal gradient descent, until the same gradient tolerance is reached.
+    fn run_proximal_solver<X>(
+        &self,
+        x: &X,
+        target: &Array1<F>,
+        sample_weight: &Array1<F>,
+        l1_penalty: F,
+        l2_penalty: F,
+        init_params: Array1<F>,
+    ) -> proximal::ProximalResult<F>
+    where
+        X: DesignMatrix<F>,
+    {
+        // the whole penalty is handled by the proximal operator
+        proximal::fista(
+            |w| logistic_loss(x, target, sample_weight, F::zero(), w),
+            |w| logistic_grad(x, target, sample_weight, F::zero(), w),
+            proximal::Penalty {
+                n_penalized: x.n_features(),
+                l1: l1_penalty,
+                l2: l2_penalty,
+            },
+            init_params,
+            self.max_iterations,
+            self.gradient_tolerance,
+        )
+    }
+
+    /// Take the optimal parameters and return a FittedLogisticRegression.
+    fn convert_result<C>(
         &self,
         labels: ClassLabels<F, C>,
-        result: &ArgminResult<LogisticRegressionProblem<F, A>>,
-    ) -> Result<FittedLogisticRegression<F, C>, String>
+        mut params: Array1<F>,
+        iterations: u64,
+        gradient_norm: F,
+    ) -> FittedLogisticRegression<F, C>
     where
-        A: Data<Elem = F>,
         C: PartialOrd + Clone,
     {
         let mut intercept = F::from(0.0).unwrap();
-        let mut params = result.state().best_param.as_array().clone();
         if self.fit_intercept {
             intercept = params[params.len() - 1];
             params = params.slice(s![..params.len() - 1]).to_owned();
         }
-        Ok(FittedLogisticRegression::new(intercept, params, labels))
+        FittedLogisticRegression::new(intercept, params, labels, iterations, gradient_norm)
+    }
+}
+
+/// Ensure that there is one finite, non-negative sample weight per sample
+/// and that not all of them are zero.
+pub(crate) fn validate_sample_weight<F: Float, B: Data<Elem = F>>(
+    n_samples: usize,
+    sample_weight: &ArrayBase<B, Ix1>,
+) -> Result<(), String> {
+    if sample_weight.len() != n_samples {
+        return Err(
+            "Incompatible shapes of data, expected `sample_weight` to have one entry per row of `x`"
+                .to_string(),
+        );
+    }
+    if sample_weight
+        .iter()
+        .any(|w| !w.is_finite() || *w < F::zero())
+    {
+        return Err("sample weights must be finite and non-negative".to_string());
+    }
+    if sample_weight.sum() == F::zero() {
+        return Err("at least one sample weight must be positive".to_string());
     }
+    Ok(())
 }
 
 /// Identify the distinct values of the classes  `y` and associate
@@ -258,17 +565,11 @@ where
 {
     let mut classes: Vec<&C> = vec![];
     let mut target_vec = vec![];
-    let mut use_negative_label: bool = true;
     for item in y {
-        if let Some(last_item) = classes.last() {
-            if *last_item != item {
-                use_negative_label = !use_negative_label;
-            }
-        }
         if !classes.contains(&item) {
             classes.push(item);
         }
-        target_vec.push(if use_negative_label {
+        target_vec.push(if classes[0] == item {
             F::NEGATIVE_LABEL
         } else {
             F::POSITIVE_LABEL
@@ -347,41 +648,47 @@ fn log_logistic<F: Float>(x: F) -> F {
 /// $P(y|z) = \sigma(yz)$ for both $y=1$ and $y=-1$.
 ///
 /// Thus, the log loss can be written as
-/// $$-\sum_{i=1}^{N} \log(\sigma(y_i z_i)) + \frac{\alpha}{2}\text{params}^T\text{params}$$
-fn logistic_loss<F: Float, A: Data<Elem = F>>(
-    x: &ArrayBase<A, Ix2>,
+/// $$-\sum_{i=1}^{N} s_i \log(\sigma(y_i z_i)) + \frac{\alpha}{2}\text{params}^T\text{params}$$
+///
+/// where $s_i$ is the weight of sample $i$.
+fn logistic_loss<F: Float, X: DesignMatrix<F>>(
+    x: &X,
     y: &Array1<F>,
+    sample_weight: &Array1<F>,
     alpha: F,
     w: &Array1<F>,
 ) -> F {
-    let n_features = x.shape()[1];
-    let (params, intercept) = convert_params(n_features, &w);
-    let mut yz = (x.dot(&params) + intercept) * y;
+    let n_features = x.n_features();
+    let (params, intercept) = convert_params(n_features, w);
+    let mut yz = (x.dot_params(&params) + intercept) * y;
     yz.mapv_inplace(log_logistic);
+    yz *= sample_weight;
     -yz.sum() + F::from(0.5).unwrap() * alpha * params.dot(&params)
 }
 
 /// Computes the gradient of the logistic loss function
-fn logistic_grad<F: Float, A: Data<Elem = F>>(
-    x: &ArrayBase<A, Ix2>,
+fn logistic_grad<F: Float, X: DesignMatrix<F>>(
+    x: &X,
     y: &Array1<F>,
+    sample_weight: &Array1<F>,
     alpha: F,
     w: &Array1<F>,
 ) -> Array1<F> {
-    let n_features = x.shape()[1];
-    let (params, intercept) = convert_params(n_features, &w);
-    let mut yz = (x.dot(&params) + intercept) * y;
+    let n_features = x.n_features();
+    let (params, intercept) = convert_params(n_features, w);
+    let mut yz = (x.dot_params(&params) + intercept) * y;
     yz.mapv_inplace(logistic);
     yz -= F::one();
     yz *= y;
+    yz *= sample_weight;
     if w.len() == n_features + 1 {
         let mut grad = Array::zeros(w.len());
         grad.slice_mut(s![..n_features])
-            .assign(&(x.t().dot(&yz) + &(params * alpha)));
+            .assign(&(x.t_dot_residuals(&yz) + &(params * alpha)));
         grad[n_features] = yz.sum();
         grad
     } else {
-        x.t().dot(&yz) + &(params * alpha)
+        x.t_dot_residuals(&yz) + &(params * alpha)
     }
 }
 
@@ -392,6 +699,8 @@ pub struct FittedLogisticRegression<F: Float, C: PartialOrd + Clone> {
     intercept: F,
     params: Array1<F>,
     labels: ClassLabels<F, C>,
+    iterations: u64,
+    gradient_norm: F,
 }
 
 impl<F: Float, C: PartialOrd + Clone> FittedLogisticRegression<F, C> {
@@ -399,15 +708,30 @@ impl<F: Float, C: PartialOrd + Clone> FittedLogisticRegression<F, C> {
         intercept: F,
         params: Array1<F>,
         labels: ClassLabels<F, C>,
+        iterations: u64,
+        gradient_norm: F,
     ) -> FittedLogisticRegression<F, C> {
         FittedLogisticRegression {
             threshold: F::from(0.5).unwrap(),
             intercept,
             params,
             labels,
+            iterations,
+            gradient_norm,
         }
     }
 
+    /// The number of iterations the solver performed.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// The norm of the gradient of the penalized loss at the solution, for
+    /// an L1 penalty the norm of the gradient mapping.
+    pub fn gradient_norm(&self) -> F {
+        self.gradient_norm
+    }
+
     /// Set the probability threshold for which the 'positive' class will be
     /// predicted. Defaults to 0.5.
     pub fn set_threshold(mut self, threshold: F) -> FittedLogisticRegression<F, C> {
@@ -418,29 +742,79 @@ impl<F: Float, C: PartialOrd + Clone> FittedLogisticRegression<F, C> {
         self
     }
 
+    /// The intercept of the decision function, zero if no intercept was
+    /// fitted.
     pub fn intercept(&self) -> F {
         self.intercept
     }
 
+    /// The coefficient of each feature, without the intercept.
     pub fn params(&self) -> &Array1<F> {
         &self.params
     }
 
+    /// The number of parameters which are not exactly zero, which is the
+    /// number of selected features when fitted with an L1 penalty.
+    pub fn n_nonzero(&self) -> usize {
+        self.params.iter().filter(|p| **p != F::zero()).count()
+    }
+
     /// Given a feature matrix, predict the probabilities that a sample
     /// should be classified as the larger of the two classes learned when the
     /// model was fitted.
     pub fn predict_probabilities<A: Data<Elem = F>>(&self, x: &ArrayBase<A, Ix2>) -> Array1<F> {
-        let mut probs = x.dot(&self.params) + self.intercept;
-        probs.mapv_inplace(logistic);
+        self.probabilities(x)
+    }
+
+    /// Given a feature matrix, predict the probability of both classes with
+    /// shape (n_samples, 2). The first column belongs to the smaller and the
+    /// second column to the larger of the two classes, each row sums to one.
+    ///
+    /// The probabilities are computed from the numerically stable log of the
+    /// logistic function, samples far from the decision boundary get a
+    /// probability of zero or one instead of `NaN`.
+    pub fn predict_proba<A: Data<Elem = F>>(&self, x: &ArrayBase<A, Ix2>) -> Array2<F> {
+        let margins = x.dot(&self.params) + self.intercept;
+        let mut probs = Array2::zeros((margins.len(), 2));
+        probs
+            .column_mut(0)
+            .assign(&margins.mapv(|z| log_logistic(-z).exp()));
+        probs
+            .column_mut(1)
+            .assign(&margins.mapv(|z| log_logistic(z).exp()));
         probs
     }
 
     /// Given a feature matrix, predict the classes learned when the model was
     /// fitted.
     pub fn predict_classes<A: Data<Elem = F>>(&self, x: &ArrayBase<A, Ix2>) -> Vec<C> {
+        self.classes(x)
+    }
+
+    /// Given a sparse feature matrix, predict the probabilities of the
+    /// larger class like `predict_probabilities`.
+    #[cfg(feature = "sprs")]
+    pub fn predict_probabilities_sparse(&self, x: &CsMat<F>) -> Array1<F> {
+        self.probabilities(x)
+    }
+
+    /// Given a sparse feature matrix, predict the classes like
+    /// `predict_classes`.
+    #[cfg(feature = "sprs")]
+    pub fn predict_classes_sparse(&self, x: &CsMat<F>) -> Vec<C> {
+        self.classes(x)
+    }
+
+    fn probabilities<X: DesignMatrix<F>>(&self, x: &X) -> Array1<F> {
+        let mut probs = x.dot_params(&self.params) + self.intercept;
+        probs.mapv_inplace(logistic);
+        probs
+    }
+
+    fn classes<X: DesignMatrix<F>>(&self, x: &X) -> Vec<C> {
         let pos_class = class_from_label(&self.labels, F::POSITIVE_LABEL);
         let neg_class = class_from_label(&self.labels, F::NEGATIVE_LABEL);
-        self.predict_probabilities(x)
+        self.probabilities(x)
             .iter()
             .map(|probability| {
                 if *probability >= self.threshold {
@@ -472,13 +846,14 @@ fn class_from_label<F: Float, C: PartialOrd + Clone>(labels: &[ClassLabel<F, C>]
 
 /// Internal representation of a logistic regression problem.
 /// This data structure exists to be handed to Argmin.
-struct LogisticRegressionProblem<'a, F: Float, A: Data<Elem = F>> {
-    x: &'a ArrayBase<A, Ix2>,
+struct LogisticRegressionProblem<'a, F: Float, X: DesignMatrix<F>> {
+    x: &'a X,
     target: Array1<F>,
+    sample_weight: Array1<F>,
     alpha: F,
 }
 
-impl<'a, F: Float, A: Data<Elem = F>> ArgminOp for LogisticRegressionProblem<'a, F, A> {
+impl<'a, F: Float, X: DesignMatrix<F>> ArgminOp for LogisticRegressionProblem<'a, F, X> {
     /// Type of the parameter vector
     type Param = ArgminParam<F>;
     /// Type of the return value computed by the cost function
@@ -493,7 +868,13 @@ impl<'a, F: Float, A: Data<Elem = F>> ArgminOp for LogisticRegressionProblem<'a,
     /// Apply the cost function to a parameter `p`
     fn apply(&self, p: &Self::Param) -> Result<Self::Output, Error> {
         let w = p.as_array();
-        Ok(logistic_loss(self.x, &self.target, self.alpha, w))
+        Ok(logistic_loss(
+            self.x,
+            &self.target,
+            &self.sample_weight,
+            self.alpha,
+            w,
+        ))
     }
 
     /// Compute the gradient at parameter `p`.
@@ -502,6 +883,7 @@ impl<'a, F: Float, A: Data<Elem = F>> ArgminOp for LogisticRegressionProblem<'a,
         Ok(ArgminParam(logistic_grad(
             self.x,
             &self.target,
+            &self.sample_weight,
             self.alpha,
             w,
         )))
@@ -512,7 +894,11 @@ impl<'a, F: Float, A: Data<Elem = F>> ArgminOp for LogisticRegressionProblem<'a,
 mod test {
     use super::*;
     use approx::AbsDiffEq;
-    use ndarray::{array, Array2};
+    use ndarray::{array, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Normal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
 
     /// Test that the logistic loss function works as expected.
     /// The expected values were obtained from running sklearn's
@@ -571,7 +957,7 @@ mod test {
             .flat_map(|w| alphas.iter().map(move |&alpha| (w, alpha)))
             .zip(&expecteds)
         {
-            assert_eq!(logistic_loss(&x, &y, alpha, &w), *exp);
+            assert_eq!(logistic_loss(&x, &y, &Array1::ones(10), alpha, w), *exp);
         }
     }
 
@@ -632,7 +1018,7 @@ mod test {
             .flat_map(|w| alphas.iter().map(move |&alpha| (w, alpha)))
             .zip(&expecteds)
         {
-            let actual = logistic_grad(&x, &y, alpha, &w);
+            let actual = logistic_grad(&x, &y, &Array1::ones(10), alpha, w);
             assert!(actual.abs_diff_eq(exp, 1e-8));
         }
     }
@@ -662,6 +1048,19 @@ mod test {
         assert_eq!(res.predict_classes(&x), y);
     }
 
+    #[test]
+    fn labels_each_sample_by_its_class() {
+        // the label of a sample must not depend on the class of the previous sample
+        let (labels, target) = label_classes::<f64, _, _>(&[0, 1, 0, 0]).unwrap();
+        assert_eq!(target, array![-1.0, 1.0, -1.0, -1.0]);
+        assert_eq!(class_from_label(&labels, -1.0), 0);
+        assert_eq!(class_from_label(&labels, 1.0), 1);
+
+        // the larger class is found first
+        let (_, target) = label_classes::<f64, _, _>(&["dog", "cat", "cat", "dog", "dog"]).unwrap();
+        assert_eq!(target, array![1.0, -1.0, -1.0, 1.0, 1.0]);
+    }
+
     #[test]
     fn simple_example_2() {
         let log_reg = LogisticRegression::default().alpha(1.0);
@@ -679,11 +1078,368 @@ mod test {
         ];
         let y = array![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
         let res = log_reg.fit(&x, &y).unwrap();
-        assert!(res.intercept().abs_diff_eq(&-4.124, 1e-3));
+        assert!(res.intercept().abs_diff_eq(&-4.126, 1e-3));
         assert!(res.params().abs_diff_eq(&array![1.181], 1e-3));
         assert_eq!(res.predict_classes(&x), y.to_vec());
     }
 
+    #[test]
+    fn predict_proba_is_stable() {
+        let log_reg = LogisticRegression::default().alpha(1.0);
+        let x = Array::linspace(0.0, 9.0, 10).insert_axis(Axis(1));
+        let y = array![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
+        let res = log_reg.fit(&x, &y).unwrap();
+
+        let probs = res.predict_proba(&x);
+        assert_eq!(probs.dim(), (10, 2));
+        assert!(probs
+            .sum_axis(Axis(1))
+            .abs_diff_eq(&Array1::ones(10), 1e-12));
+        assert!(probs
+            .column(1)
+            .abs_diff_eq(&res.predict_probabilities(&x), 1e-12));
+
+        // the decision boundary, where the margin is zero
+        let boundary = array![[-res.intercept() / res.params()[0]]];
+        assert!(res
+            .predict_proba(&boundary)
+            .abs_diff_eq(&array![[0.5, 0.5]], 1e-8));
+
+        // far from the boundary the probability of the true class saturates to one
+        let far = array![[-1e6f64], [1e6]];
+        let probs = res.predict_proba(&far);
+        assert!(probs.iter().all(|p| !p.is_nan()));
+        let log_loss = -(probs[(0, 0)].ln() + probs[(1, 1)].ln());
+        assert!(log_loss.is_finite());
+    }
+
+    #[test]
+    fn l1_penalty_removes_noise_features() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((200, 10), Normal::new(0.0, 1.0).unwrap(), &mut rng);
+        let noise = Array1::random_using(200, Normal::new(0.0, 0.5).unwrap(), &mut rng);
+        // only the first two features are relevant
+        let y = (x.column(0).mapv(|x| 2.0 * x) - x.column(1).mapv(|x| 3.0 * x) + noise)
+            .mapv(|z| z > 0.0);
+
+        let dense = LogisticRegression::default().fit(&x, &y).unwrap();
+        assert_eq!(dense.n_nonzero(), 10);
+
+        let sparse = LogisticRegression::default()
+            .alpha(0.0)
+            .l1_penalty(10.0)
+            .max_iterations(1000)
+            .fit(&x, &y)
+            .unwrap();
+        assert!(sparse.params()[0] > 0.0);
+        assert!(sparse.params()[1] < 0.0);
+        assert!(sparse.n_nonzero() <= 4);
+        // the removed parameters are exactly zero, not merely small
+        assert!(sparse.params().iter().all(|p| *p == 0.0 || p.abs() > 1e-6));
+        let n_correct = sparse
+            .predict_classes(&x)
+            .iter()
+            .zip(&y)
+            .filter(|(a, b)| a == b)
+            .count();
+        assert!(n_correct > 180);
+    }
+
+    #[test]
+    fn rejects_negative_l1_penalty() {
+        let x = array![[-1.0], [1.0]];
+        let res = LogisticRegression::default()
+            .l1_penalty(-1.0)
+            .fit(&x, &[0, 1]);
+        assert_eq!(
+            res,
+            Err(LogisticError::InvalidInput(
+                "l1_penalty must be a non-negative, finite number".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn elastic_net_limits_match_l1_and_l2() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((50, 3), Normal::new(0.0, 1.0).unwrap(), &mut rng);
+        let y = x.column(0).mapv(|x| x > 0.0);
+
+        let l2 = LogisticRegression::default().fit(&x, &y).unwrap();
+        let ratio_zero = LogisticRegression::default()
+            .l1_ratio(0.0)
+            .fit(&x, &y)
+            .unwrap();
+        assert_eq!(l2, ratio_zero);
+
+        let l1 = LogisticRegression::default()
+            .alpha(0.0)
+            .l1_penalty(2.0)
+            .max_iterations(1000)
+            .fit(&x, &y)
+            .unwrap();
+        let ratio_one = LogisticRegression::default()
+            .alpha(2.0)
+            .l1_ratio(1.0)
+            .max_iterations(1000)
+            .fit(&x, &y)
+            .unwrap();
+        assert_eq!(l1, ratio_one);
+    }
+
+    #[test]
+    fn elastic_net_spreads_weight_over_correlated_features() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let signal = Array1::random_using(200, Normal::new(0.0, 1.0).unwrap(), &mut rng);
+        let noise = Array1::random_using(200, Normal::new(0.0, 0.25).unwrap(), &mut rng);
+        // the second feature is a scaled copy of the first
+        let x = Array2::from_shape_fn((200, 2), |(i, j)| signal[i] * (j + 1) as f64);
+        let y = (&signal + &noise).mapv(|z| z > 0.0);
+
+        let l1 = LogisticRegression::default()
+            .l1_ratio(1.0)
+            .max_iterations(1000)
+            .fit(&x, &y)
+            .unwrap();
+        // the L1 penalty puts all weight on a single feature
+        assert_eq!(l1.params()[0], 0.0);
+        assert!(l1.params()[1] > 0.0);
+
+        let elastic_net = LogisticRegression::default()
+            .l1_ratio(0.2)
+            .max_iterations(1000)
+            .fit(&x, &y)
+            .unwrap();
+        assert!(elastic_net.params()[0] > 0.1);
+        assert!(elastic_net.params()[1] > elastic_net.params()[0]);
+    }
+
+    #[test]
+    fn rejects_invalid_l1_ratio() {
+        let x = array![[-1.0], [1.0]];
+        for ratio in &[-0.1, 1.1, f64::NAN] {
+            let res = LogisticRegression::default()
+                .l1_ratio(*ratio)
+                .fit(&x, &[0, 1]);
+            assert_eq!(
+                res,
+                Err(LogisticError::InvalidInput(
+                    "l1_ratio must be in the interval [0, 1]".to_string()
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn balanced_class_weight_improves_minority_recall() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        // 2% of the samples belong to the positive class, which is shifted by 1.5
+        let negative = Array2::random_using((980, 1), Normal::new(0.0, 1.0).unwrap(), &mut rng);
+        let positive = Array2::random_using((20, 1), Normal::new(1.5, 1.0).unwrap(), &mut rng);
+        let x = ndarray::stack(Axis(0), &[negative.view(), positive.view()]).unwrap();
+        let y = (0..1000).map(|i| i >= 980).collect::<Vec<_>>();
+
+        let recall = |model: &FittedLogisticRegression<f64, bool>| {
+            let pred = model.predict_classes(&x);
+            pred[980..].iter().filter(|p| **p).count() as f64 / 20.0
+        };
+
+        let uniform = LogisticRegression::default().fit(&x, &y).unwrap();
+        let balanced = LogisticRegression::default()
+            .class_weight(ClassWeight::Balanced)
+            .fit(&x, &y)
+            .unwrap();
+        assert!(recall(&uniform) < 0.2);
+        assert!(recall(&balanced) > 0.5);
+
+        // balanced weights are the same as weighting with n / (2 * n_class)
+        let manual = LogisticRegression::default()
+            .class_weight(ClassWeight::Manual(1000.0 / 1960.0, 1000.0 / 40.0))
+            .fit(&x, &y)
+            .unwrap();
+        assert!(manual.params().abs_diff_eq(balanced.params(), 1e-10));
+    }
+
+    #[test]
+    fn sample_weight_equals_duplicated_samples() {
+        let x = array![
+            [-2.0, 1.0],
+            [-1.0, 0.5],
+            [-0.5, -1.0],
+            [0.5, 0.0],
+            [1.0, 2.0],
+            [3.0, 0.5]
+        ];
+        let y = array![0, 0, 1, 0, 1, 1];
+        // the second and the fifth sample are duplicated
+        let x_duplicated =
+            ndarray::stack(Axis(0), &[x.view(), x.select(Axis(0), &[1, 4]).view()]).unwrap();
+        let y_duplicated = array![0, 0, 1, 0, 1, 1, 0, 1];
+        let sample_weight = array![1.0, 2.0, 1.0, 1.0, 2.0, 1.0];
+
+        // the sample weights multiply the class weights
+        for (l1_penalty, class_weight) in &[
+            (0.0, ClassWeight::Uniform),
+            (0.2, ClassWeight::Uniform),
+            (0.0, ClassWeight::Manual(0.5, 3.0)),
+        ] {
+            let log_reg = LogisticRegression::default()
+                .l1_penalty(*l1_penalty)
+                .class_weight(*class_weight)
+                .gradient_tolerance(1e-8)
+                .max_iterations(1000);
+            let weighted = log_reg.fit_weighted(&x, &y, &sample_weight).unwrap();
+            let duplicated = log_reg.fit(&x_duplicated, &y_duplicated).unwrap();
+
+            assert!(weighted.params().abs_diff_eq(duplicated.params(), 1e-6));
+            assert!(weighted
+                .intercept()
+                .abs_diff_eq(&duplicated.intercept(), 1e-6));
+        }
+
+        // zero weights remove samples from the fit
+        let weighted = LogisticRegression::default()
+            .fit_weighted(&x, &y, &array![1.0, 1.0, 0.0, 1.0, 1.0, 1.0])
+            .unwrap();
+        let removed = LogisticRegression::default()
+            .fit(&x.select(Axis(0), &[0, 1, 3, 4, 5]), &[0, 0, 0, 1, 1])
+            .unwrap();
+        assert!(weighted.params().abs_diff_eq(removed.params(), 1e-4));
+    }
+
+    #[test]
+    fn rejects_invalid_sample_weight() {
+        let x = array![[-1.0], [1.0]];
+        let y = array![0, 1];
+        let log_reg = LogisticRegression::<f64>::default();
+
+        for (sample_weight, message) in &[
+            (array![1.0], "Incompatible shapes of data, expected `sample_weight` to have one entry per row of `x`"),
+            (array![1.0, -1.0], "sample weights must be finite and non-negative"),
+            (array![1.0, f64::NAN], "sample weights must be finite and non-negative"),
+            (array![0.0, 0.0], "at least one sample weight must be positive"),
+        ] {
+            assert_eq!(
+                log_reg.fit_weighted(&x, &y, sample_weight),
+                Err(LogisticError::InvalidInput(message.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_class_weight() {
+        let x = array![[-1.0], [1.0]];
+        let res = LogisticRegression::default()
+            .class_weight(ClassWeight::Manual(1.0, 0.0))
+            .fit(&x, &[0, 1]);
+        assert_eq!(
+            res,
+            Err(LogisticError::InvalidInput(
+                "class weights must be positive, finite numbers".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn intercept_is_not_penalized() {
+        // without informative features the intercept is the log-odds of the base rate
+        let x = Array2::<f64>::zeros((100, 2));
+        let y = (0..100).map(|i| i < 30).collect::<Vec<_>>();
+        let log_odds = (0.3f64 / 0.7).ln();
+
+        for alpha in &[0.0, 1.0, 100.0, 1e4] {
+            let res = LogisticRegression::default()
+                .alpha(*alpha)
+                .fit(&x, &y)
+                .unwrap();
+            // the larger class `true` has the positive label
+            assert!((res.intercept() - log_odds).abs() < 1e-3);
+            assert_eq!(res.params(), &Array1::<f64>::zeros(2));
+        }
+
+        let res = LogisticRegression::default()
+            .l1_penalty(100.0)
+            .max_iterations(1000)
+            .fit(&x, &y)
+            .unwrap();
+        assert!((res.intercept() - log_odds).abs() < 1e-3);
+
+        let res = LogisticRegression::default()
+            .with_intercept(false)
+            .fit(&x, &y)
+            .unwrap();
+        assert_eq!(res.intercept(), 0.0);
+    }
+
+    #[test]
+    fn reports_convergence() {
+        let x = array![[-2.0], [-1.0], [-0.5], [0.5], [1.0], [3.0]];
+        let y = array![0, 0, 1, 0, 1, 1];
+
+        let res = LogisticRegression::default()
+            .lbfgs_memory(5)
+            .fit(&x, &y)
+            .unwrap();
+        assert!(res.iterations() > 0 && res.iterations() < 100);
+        assert!(res.gradient_norm() < 1e-3);
+
+        let res = LogisticRegression::default()
+            .l1_penalty(0.5)
+            .fit(&x, &y)
+            .unwrap();
+        assert!(res.iterations() < 100);
+        assert!(res.gradient_norm() < 1e-4);
+    }
+
+    #[test]
+    fn gradient_tolerance_bounds_the_gradient_norm() {
+        // the solver compares the euclidean norm of the gradient with the
+        // tolerance, not its square
+        assert_eq!(ArgminParam(array![3.0, 4.0]).norm(), 5.0);
+
+        let x = array![[-2.0], [-1.0], [-0.5], [0.5], [1.0], [3.0]];
+        let y = array![0, 0, 1, 0, 1, 1];
+        for tolerance in &[1e-2, 1e-4, 1e-6] {
+            let res = LogisticRegression::default()
+                .gradient_tolerance(*tolerance)
+                .max_iterations(1000)
+                .fit(&x, &y)
+                .unwrap();
+            assert!(res.gradient_norm() < *tolerance);
+        }
+    }
+
+    #[test]
+    fn returns_partial_solution_without_convergence() {
+        let x = array![[-2.0], [-1.0], [-0.5], [0.5], [1.0], [3.0]];
+        let y = array![0, 0, 1, 0, 1, 1];
+
+        for log_reg in &[
+            LogisticRegression::default().max_iterations(1),
+            LogisticRegression::default()
+                .l1_penalty(0.5)
+                .max_iterations(1),
+        ] {
+            match log_reg.fit(&x, &y) {
+                Err(LogisticError::NonConverged(model)) => {
+                    assert_eq!(model.iterations(), 1);
+                    assert!(model.gradient_norm() > 1e-4);
+                    // the partial solution already moved towards the positive class
+                    assert!(model.params()[0] > 0.0);
+                }
+                _ => panic!("stopping after a single iteration must be reported"),
+            }
+        }
+
+        let res = LogisticRegression::default().lbfgs_memory(0).fit(&x, &y);
+        assert_eq!(
+            res,
+            Err(LogisticError::InvalidInput(
+                "lbfgs_memory must be positive".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn rejects_mismatching_x_y() {
         let log_reg = LogisticRegression::default();
@@ -692,10 +1448,10 @@ mod test {
         let res = log_reg.fit(&x, &y);
         assert_eq!(
             res,
-            Err(
+            Err(LogisticError::InvalidInput(
                 "Incompatible shapes of data, expected `x` and `y` to have same number of rows"
                     .to_string()
-            )
+            ))
         );
     }
 
@@ -706,7 +1462,9 @@ mod test {
         let log_reg = LogisticRegression::default();
         let normal_x = array![[-1.0], [1.0]];
         let y = array![0.0, 1.0];
-        let expected = Err("Values must be finite and not `Inf`, `-Inf` or `NaN`".to_string());
+        let expected = Err(LogisticError::InvalidInput(
+            "Values must be finite and not `Inf`, `-Inf` or `NaN`".to_string(),
+        ));
         for inf_x in &inf_xs {
             let res = log_reg.fit(inf_x, &y);
             assert_eq!(res, expected);
@@ -724,7 +1482,9 @@ mod test {
             let res = log_reg.fit(&normal_x, &y);
             assert_eq!(
                 res,
-                Err("gradient_tolerance must be a positive, finite number".to_string())
+                Err(LogisticError::InvalidInput(
+                    "gradient_tolerance must be a positive, finite number".to_string()
+                ))
             );
         }
     }
@@ -734,7 +1494,9 @@ mod test {
         let infs = vec![std::f64::INFINITY, std::f64::NEG_INFINITY, std::f64::NAN];
         let normal_x = array![[-1.0], [1.0]];
         let normal_y = array![0.0, 1.0];
-        let expected = Err("Initial parameter guess must be finite".to_string());
+        let expected = Err(LogisticError::InvalidInput(
+            "Initial parameter guess must be finite".to_string(),
+        ));
         for inf in &infs {
             let log_reg = LogisticRegression::default().initial_params(array![*inf], 0.0);
             let res = log_reg.fit(&normal_x, &normal_y);
@@ -748,7 +1510,7 @@ mod test {
         {
             let log_reg = LogisticRegression::default().initial_params(array![0.0, 0.0], 0.0);
             let res = log_reg.fit(&normal_x, &normal_y);
-            assert_eq!(res, Err("Size of initial parameter guess must be the same as the number of columns in the feature matrix `x`".to_string()));
+            assert_eq!(res, Err(LogisticError::InvalidInput("Size of initial parameter guess must be the same as the number of columns in the feature matrix `x`".to_string())));
         }
     }
 
@@ -771,12 +1533,113 @@ mod test {
             [9.0]
         ];
         let y = array![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0];
-        let res = log_reg.fit(&x, &y).unwrap();
+        let res = match log_reg.fit(&x, &y) {
+            Ok(res) => res,
+            Err(LogisticError::NonConverged(res)) => *res,
+            Err(err) => panic!("{}", err),
+        };
         assert!(res.intercept().abs_diff_eq(&-4.124, 1e-3));
         assert!(res.params().abs_diff_eq(&array![1.181], 1e-3));
         assert_eq!(res.predict_classes(&x), y.to_vec());
     }
 
+    #[test]
+    fn warm_start_along_regularization_path() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((100, 3), Normal::new(0., 1.).unwrap(), &mut rng);
+        let noise = Array1::random_using(100, Normal::new(0., 1.).unwrap(), &mut rng);
+        let y = (x.dot(&array![2., -1., 0.5]) + noise).mapv(|z| z > 0.);
+
+        let path = [10., 3., 1., 0.3, 0.1, 0.03, 0.01];
+        let params = |alpha| {
+            LogisticRegression::default()
+                .alpha(alpha)
+                .gradient_tolerance(1e-6)
+        };
+
+        let (mut cold_iterations, mut warm_iterations) = (0, 0);
+        let mut previous: Option<FittedLogisticRegression<f64, bool>> = None;
+        for alpha in &path {
+            let cold = params(*alpha).fit(&x, &y).unwrap();
+            let warm = match previous {
+                Some(ref model) => params(*alpha).warm_start(model),
+                None => params(*alpha),
+            }
+            .fit(&x, &y)
+            .unwrap();
+
+            assert!(cold.params().abs_diff_eq(warm.params(), 1e-4));
+            assert!(cold.intercept().abs_diff_eq(&warm.intercept(), 1e-4));
+
+            cold_iterations += cold.iterations();
+            warm_iterations += warm.iterations();
+            previous = Some(warm);
+        }
+
+        assert!(warm_iterations < cold_iterations);
+
+        // the model must have been fitted to the same features
+        let res = params(1.)
+            .warm_start(&previous.unwrap())
+            .fit(&x.slice(s![.., ..2]), &y);
+        assert_eq!(res, Err(LogisticError::InvalidInput("Size of initial parameter guess must be the same as the number of columns in the feature matrix `x`".to_string())));
+    }
+
+    #[cfg(feature = "sprs")]
+    #[test]
+    fn sparse_fit_matches_dense_fit() {
+        use ndarray_rand::rand_distr::Uniform;
+        use sprs::TriMat;
+
+        // about 80% of the features are zero, like hashed text features
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((200, 10), Uniform::new(0., 1.), &mut rng).mapv(|x| {
+            if x < 0.8 {
+                0.
+            } else {
+                10. * x - 8.
+            }
+        });
+        let noise = Array1::random_using(200, Normal::new(0., 0.5).unwrap(), &mut rng);
+        let y =
+            (x.dot(&array![1.5, -2., 0., 0., 1., 0., -1., 0., 0., 2.]) + noise).mapv(|z| z > 0.);
+
+        let mut triplets = TriMat::new(x.dim());
+        for ((row, col), value) in x.indexed_iter() {
+            if *value != 0. {
+                triplets.add_triplet(row, col, *value);
+            }
+        }
+        let (x_csr, x_csc) = (triplets.to_csr(), triplets.to_csc());
+        assert!(x_csr.nnz() < 500);
+
+        for log_reg in &[
+            LogisticRegression::default().gradient_tolerance(1e-6),
+            LogisticRegression::default()
+                .with_intercept(false)
+                .gradient_tolerance(1e-6),
+            LogisticRegression::default()
+                .l1_penalty(1.)
+                .gradient_tolerance(1e-6)
+                .max_iterations(1000),
+        ] {
+            let dense = log_reg.fit(&x, &y).unwrap();
+            for x_sparse in &[&x_csr, &x_csc] {
+                let sparse = log_reg.fit_sparse(x_sparse, &y).unwrap();
+
+                assert!(sparse.params().abs_diff_eq(dense.params(), 1e-5));
+                assert!(sparse.intercept().abs_diff_eq(&dense.intercept(), 1e-5));
+                assert!(sparse
+                    .predict_probabilities_sparse(x_sparse)
+                    .abs_diff_eq(&dense.predict_probabilities(&x), 1e-6));
+                assert_eq!(
+                    sparse.predict_classes_sparse(x_sparse),
+                    dense.predict_classes(&x)
+                );
+            }
+        }
+    }
+
     #[test]
     fn works_with_f32() {
         let log_reg = LogisticRegression::default();
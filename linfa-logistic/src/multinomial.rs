@@ -0,0 +1,545 @@
+//! Multinomial logistic regression
+//!
+//! Extends logistic regression to `k` classes with the softmax likelihood
+//! $$P(y = c | x) = \frac{\exp(w_c^T x + b_c)}{\sum_{j=1}^{k} \exp(w_j^T x + b_j)}$$
+//! and an L2 penalty on the coefficients. Unlike one-vs-rest classification,
+//! all classes are fitted jointly and the predicted probabilities sum to one.
+
+use argmin::prelude::*;
+use argmin::solver::linesearch::MoreThuenteLineSearch;
+use argmin::solver::quasinewton::lbfgs::LBFGS;
+use ndarray::{s, Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2};
+use std::cmp::Ordering;
+
+use crate::argmin_param::ArgminParam;
+use crate::float::Float;
+use crate::{validate_sample_weight, LBFGSType};
+
+/// A multinomial logistic regression model for two or more classes.
+pub struct MultiLogisticRegression<F: Float> {
+    alpha: F,
+    fit_intercept: bool,
+    max_iterations: u64,
+    gradient_tolerance: F,
+}
+
+impl<F: Float> Default for MultiLogisticRegression<F> {
+    fn default() -> MultiLogisticRegression<F> {
+        MultiLogisticRegression::new()
+    }
+}
+
+impl<F: Float> MultiLogisticRegression<F> {
+    /// Creates a new MultiLogisticRegression with default configuration.
+    pub fn new() -> MultiLogisticRegression<F> {
+        MultiLogisticRegression {
+            alpha: F::from(1.0).unwrap(),
+            fit_intercept: true,
+            max_iterations: 100,
+            gradient_tolerance: F::from(1e-4).unwrap(),
+        }
+    }
+
+    /// Set the normalization parameter `alpha` used for L2 normalization,
+    /// defaults to `1.0`.
+    pub fn alpha(mut self, alpha: F) -> MultiLogisticRegression<F> {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Configure if an intercept should be fitted, defaults to `true`.
+    ///
+    /// The intercepts are not penalized by `alpha`.
+    pub fn with_intercept(mut self, fit_intercept: bool) -> MultiLogisticRegression<F> {
+        self.fit_intercept = fit_intercept;
+        self
+    }
+
+    /// Configure the maximum number of iterations that the solver should perform,
+    /// defaults to `100`.
+    pub fn max_iterations(mut self, max_iterations: u64) -> MultiLogisticRegression<F> {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Configure the minimum change to the gradient to continue the solver,
+    /// defaults to `1e-4`.
+    pub fn gradient_tolerance(mut self, gradient_tolerance: F) -> MultiLogisticRegression<F> {
+        self.gradient_tolerance = gradient_tolerance;
+        self
+    }
+
+    /// Given a 2-dimensional feature matrix array `x` with shape
+    /// (n_samples, n_features) and an iterable of target classes to predict,
+    /// create a `FittedMultiLogisticRegression` object which allows making
+    /// predictions.
+    ///
+    /// The iterable of target classes `y` must have at least two distinct
+    /// values and produce exactly `n_samples` items. The classes are ordered
+    /// by `PartialOrd`, which determines the order of the rows of the
+    /// coefficients and the columns of the predicted probabilities.
+    pub fn fit<'a, A, II, C>(
+        &self,
+        x: &ArrayBase<A, Ix2>,
+        y: II,
+    ) -> Result<FittedMultiLogisticRegression<F, C>, String>
+    where
+        A: Data<Elem = F>,
+        II: IntoIterator<Item = &'a C>,
+        C: 'a + PartialOrd + Clone,
+    {
+        self.fit_impl(x, y, None::<&Array1<F>>)
+    }
+
+    /// Fit the model like `fit`, weighting the loss of every sample by
+    /// `sample_weight`.
+    ///
+    /// The weights must be finite, non-negative and have one entry per row of
+    /// `x`. Integer weights are equivalent to repeating the samples
+    /// accordingly.
+    pub fn fit_weighted<'a, A, B, II, C>(
+        &self,
+        x: &ArrayBase<A, Ix2>,
+        y: II,
+        sample_weight: &ArrayBase<B, Ix1>,
+    ) -> Result<FittedMultiLogisticRegression<F, C>, String>
+    where
+        A: Data<Elem = F>,
+        B: Data<Elem = F>,
+        II: IntoIterator<Item = &'a C>,
+        C: 'a + PartialOrd + Clone,
+    {
+        self.fit_impl(x, y, Some(sample_weight))
+    }
+
+    fn fit_impl<'a, A, B, II, C>(
+        &self,
+        x: &ArrayBase<A, Ix2>,
+        y: II,
+        sample_weight: Option<&ArrayBase<B, Ix1>>,
+    ) -> Result<FittedMultiLogisticRegression<F, C>, String>
+    where
+        A: Data<Elem = F>,
+        B: Data<Elem = F>,
+        II: IntoIterator<Item = &'a C>,
+        C: 'a + PartialOrd + Clone,
+    {
+        let (classes, target) = label_classes_multi(y)?;
+        self.validate_data(x, &target)?;
+        let sample_weight = match sample_weight {
+            Some(sample_weight) => {
+                validate_sample_weight(target.nrows(), sample_weight)?;
+                sample_weight.to_owned()
+            }
+            None => Array1::ones(target.nrows()),
+        };
+
+        let (n_features, n_classes) = (x.ncols(), classes.len());
+        let n_rows = if self.fit_intercept {
+            n_features + 1
+        } else {
+            n_features
+        };
+        let problem = MultiLogisticRegressionProblem {
+            x,
+            target,
+            sample_weight,
+            alpha: self.alpha,
+        };
+        let solver: LBFGSType<F> =
+            LBFGS::new(MoreThuenteLineSearch::new(), 10).with_tol_grad(self.gradient_tolerance);
+        let init_params = ArgminParam(Array1::zeros(n_rows * n_classes));
+        let result = Executor::new(problem, solver, init_params)
+            .max_iters(self.max_iterations)
+            .run()
+            .map_err(|err| format!("Error running solver: {}", err))?;
+
+        let (params, mut intercept) =
+            convert_params_multi(n_features, n_classes, result.state().best_param.as_array());
+        // the intercepts are not penalized and only determined up to a common shift
+        let shift = intercept.mean().unwrap();
+        intercept -= shift;
+
+        Ok(FittedMultiLogisticRegression {
+            intercept,
+            params: params.reversed_axes(),
+            classes,
+        })
+    }
+
+    /// Ensure that `x` and `y` have the right shape and that all data and
+    /// configuration parameters are finite.
+    fn validate_data<A>(&self, x: &ArrayBase<A, Ix2>, y: &Array2<F>) -> Result<(), String>
+    where
+        A: Data<Elem = F>,
+    {
+        if x.nrows() != y.nrows() {
+            return Err(
+                "Incompatible shapes of data, expected `x` and `y` to have same number of rows"
+                    .to_string(),
+            );
+        }
+        if x.iter().any(|x| !x.is_finite()) || !self.alpha.is_finite() {
+            return Err("Values must be finite and not `Inf`, `-Inf` or `NaN`".to_string());
+        }
+        if !self.gradient_tolerance.is_finite() || self.gradient_tolerance <= F::zero() {
+            return Err("gradient_tolerance must be a positive, finite number".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Identify the distinct classes of `y`, ordered by `PartialOrd`, and
+/// encode the targets as a one-hot matrix with shape (n_samples, n_classes).
+fn label_classes_multi<'a, F, II, C>(y: II) -> Result<(Vec<C>, Array2<F>), String>
+where
+    F: Float,
+    II: IntoIterator<Item = &'a C>,
+    C: 'a + PartialOrd + Clone,
+{
+    let y = y.into_iter().collect::<Vec<_>>();
+    let mut classes: Vec<&C> = vec![];
+    for item in &y {
+        if !classes.contains(item) {
+            classes.push(item);
+        }
+    }
+    if classes.len() < 2 {
+        return Err("Expected at least two classes for logistic regression".to_string());
+    }
+    classes.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let mut target = Array2::zeros((y.len(), classes.len()));
+    for (i, item) in y.iter().enumerate() {
+        let class = classes.iter().position(|c| c == item).unwrap();
+        target[(i, class)] = F::one();
+    }
+
+    Ok((classes.into_iter().cloned().collect(), target))
+}
+
+/// Split the flat parameter vector `w` into the coefficients with shape
+/// (n_features, n_classes) and the intercept of each class.
+fn convert_params_multi<F: Float>(
+    n_features: usize,
+    n_classes: usize,
+    w: &Array1<F>,
+) -> (Array2<F>, Array1<F>) {
+    let w = w
+        .view()
+        .into_shape((w.len() / n_classes, n_classes))
+        .unwrap();
+    let intercept = if w.nrows() > n_features {
+        w.row(n_features).to_owned()
+    } else {
+        Array1::zeros(n_classes)
+    };
+
+    (w.slice(s![..n_features, ..]).to_owned(), intercept)
+}
+
+/// Replace each row of the decision values `z` by its log-probabilities,
+/// subtracting the maximum before exponentiating to avoid overflows.
+fn log_softmax<F: Float>(z: &mut Array2<F>) {
+    for mut row in z.genrows_mut() {
+        let max = row.fold(F::neg_infinity(), |max, x| max.max(*x));
+        let log_sum = max + row.mapv(|x| (x - max).exp()).sum().ln();
+        row.mapv_inplace(|x| x - log_sum);
+    }
+}
+
+/// Computes the negative log-likelihood of the softmax model for the one-hot
+/// targets `y`
+/// $$-\sum_{i=1}^{N} s_i \log P(y_i | x_i) + \frac{\alpha}{2} ||W||_F^2$$
+///
+/// where $s_i$ is the weight of sample $i$.
+fn multi_logistic_loss<F: Float, A: Data<Elem = F>>(
+    x: &ArrayBase<A, Ix2>,
+    y: &Array2<F>,
+    sample_weight: &Array1<F>,
+    alpha: F,
+    w: &Array1<F>,
+) -> F {
+    let (params, intercept) = convert_params_multi(x.ncols(), y.ncols(), w);
+    let mut log_prob = x.dot(&params) + &intercept;
+    log_softmax(&mut log_prob);
+    let log_likelihood = (log_prob * y).sum_axis(Axis(1));
+    -log_likelihood.dot(sample_weight) + F::from(0.5).unwrap() * alpha * (&params * &params).sum()
+}
+
+/// Computes the gradient of the multinomial logistic loss, which is
+/// `X^T S (P - Y) + alpha * W` for the coefficients and the column sums of
+/// `S (P - Y)` for the intercepts, where `S` scales each sample by its weight.
+fn multi_logistic_grad<F: Float, A: Data<Elem = F>>(
+    x: &ArrayBase<A, Ix2>,
+    y: &Array2<F>,
+    sample_weight: &Array1<F>,
+    alpha: F,
+    w: &Array1<F>,
+) -> Array1<F> {
+    let (n_features, n_classes) = (x.ncols(), y.ncols());
+    let (params, intercept) = convert_params_multi(n_features, n_classes, w);
+    let mut prob = x.dot(&params) + &intercept;
+    log_softmax(&mut prob);
+    prob.mapv_inplace(|x| x.exp());
+    let diff = (prob - y) * sample_weight.view().insert_axis(Axis(1));
+
+    let mut grad = Array2::<F>::zeros((w.len() / n_classes, n_classes));
+    grad.slice_mut(s![..n_features, ..])
+        .assign(&(x.t().dot(&diff) + &(params * alpha)));
+    if grad.nrows() > n_features {
+        grad.row_mut(n_features).assign(&diff.sum_axis(Axis(0)));
+    }
+    grad.into_shape(w.len()).unwrap()
+}
+
+/// A fitted multinomial logistic regression which can make predictions
+#[derive(PartialEq, Debug)]
+pub struct FittedMultiLogisticRegression<F: Float, C: PartialOrd + Clone> {
+    intercept: Array1<F>,
+    params: Array2<F>,
+    classes: Vec<C>,
+}
+
+impl<F: Float, C: PartialOrd + Clone> FittedMultiLogisticRegression<F, C> {
+    /// The intercept of each class, shifted to sum to zero.
+    pub fn intercept(&self) -> &Array1<F> {
+        &self.intercept
+    }
+
+    /// The coefficients with shape (n_classes, n_features), one row per class.
+    ///
+    /// The softmax probabilities do not change if the same vector is added
+    /// to all rows. With a positive `alpha` the penalty selects the unique
+    /// solution whose rows sum to zero for every feature, without penalty the
+    /// coefficients are only determined up to this shift.
+    pub fn params(&self) -> &Array2<F> {
+        &self.params
+    }
+
+    /// The classes learned when the model was fitted, in ascending order.
+    pub fn classes(&self) -> &[C] {
+        &self.classes
+    }
+
+    /// Given a feature matrix, predict the probability of each class with
+    /// shape (n_samples, n_classes). The columns follow the order of
+    /// `classes()`.
+    pub fn predict_probabilities<A: Data<Elem = F>>(&self, x: &ArrayBase<A, Ix2>) -> Array2<F> {
+        let mut prob = x.dot(&self.params.t()) + &self.intercept;
+        log_softmax(&mut prob);
+        prob.mapv_inplace(|x| x.exp());
+        prob
+    }
+
+    /// Given a feature matrix, predict the most probable class of each
+    /// sample.
+    pub fn predict_classes<A: Data<Elem = F>>(&self, x: &ArrayBase<A, Ix2>) -> Vec<C> {
+        self.predict_probabilities(x)
+            .genrows()
+            .into_iter()
+            .map(|row| {
+                let best = row
+                    .iter()
+                    .enumerate()
+                    .fold(0, |best, (i, p)| if *p > row[best] { i } else { best });
+                self.classes[best].clone()
+            })
+            .collect()
+    }
+}
+
+/// Internal representation of a multinomial logistic regression problem.
+/// This data structure exists to be handed to Argmin.
+struct MultiLogisticRegressionProblem<'a, F: Float, A: Data<Elem = F>> {
+    x: &'a ArrayBase<A, Ix2>,
+    target: Array2<F>,
+    sample_weight: Array1<F>,
+    alpha: F,
+}
+
+impl<'a, F: Float, A: Data<Elem = F>> ArgminOp for MultiLogisticRegressionProblem<'a, F, A> {
+    /// Type of the parameter vector
+    type Param = ArgminParam<F>;
+    /// Type of the return value computed by the cost function
+    type Output = F;
+    /// Type of the Hessian. Can be `()` if not needed.
+    type Hessian = ();
+    /// Type of the Jacobian. Can be `()` if not needed.
+    type Jacobian = Array1<F>;
+    /// Floating point precision
+    type Float = F;
+
+    /// Apply the cost function to a parameter `p`
+    fn apply(&self, p: &Self::Param) -> Result<Self::Output, Error> {
+        let w = p.as_array();
+        Ok(multi_logistic_loss(
+            self.x,
+            &self.target,
+            &self.sample_weight,
+            self.alpha,
+            w,
+        ))
+    }
+
+    /// Compute the gradient at parameter `p`.
+    fn gradient(&self, p: &Self::Param) -> Result<Self::Param, Error> {
+        let w = p.as_array();
+        Ok(ArgminParam(multi_logistic_grad(
+            self.x,
+            &self.target,
+            &self.sample_weight,
+            self.alpha,
+            w,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::LogisticRegression;
+    use approx::AbsDiffEq;
+    use ndarray::{array, stack};
+
+    /// Three well separated blobs on a grid around the centers
+    fn blobs() -> (Array2<f64>, Vec<usize>) {
+        let centers = [(0.0, 0.0), (6.0, 0.0), (0.0, 6.0)];
+        let offsets = [-1.0, -0.5, 0.0, 0.5, 1.0];
+        let mut records = vec![];
+        let mut targets = vec![];
+        for (class, (cx, cy)) in centers.iter().enumerate() {
+            for dx in &offsets {
+                for dy in &offsets {
+                    records.push(array![[cx + dx, cy + dy]]);
+                    targets.push(class);
+                }
+            }
+        }
+        let views = records.iter().map(|x| x.view()).collect::<Vec<_>>();
+
+        (stack(Axis(0), &views).unwrap(), targets)
+    }
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let x = array![[0.5, -1.0], [1.5, 2.0], [-0.5, 0.0], [2.0, 1.0]];
+        let y = array![
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 1.0, 0.0]
+        ];
+        let w = array![0.1, -0.2, 0.3, 0.4, 0.0, -0.5, 0.2, 0.1, -0.1];
+        let sample_weight = array![1.0, 2.0, 0.5, 0.0];
+
+        let grad = multi_logistic_grad(&x, &y, &sample_weight, 0.5, &w);
+        let eps = 1e-6;
+        for i in 0..w.len() {
+            let (mut w_plus, mut w_minus) = (w.clone(), w.clone());
+            w_plus[i] += eps;
+            w_minus[i] -= eps;
+            let numeric = (multi_logistic_loss(&x, &y, &sample_weight, 0.5, &w_plus)
+                - multi_logistic_loss(&x, &y, &sample_weight, 0.5, &w_minus))
+                / (2.0 * eps);
+            assert!(grad[i].abs_diff_eq(&numeric, 1e-6));
+        }
+    }
+
+    #[test]
+    fn sample_weight_equals_duplicated_samples() {
+        let x = array![
+            [0.0, 1.0],
+            [1.0, 0.5],
+            [2.0, -1.0],
+            [0.5, 2.0],
+            [1.5, 1.5],
+            [2.5, 0.0]
+        ];
+        let y = [0, 1, 2, 0, 1, 2];
+        let x_duplicated = stack(Axis(0), &[x.view(), x.select(Axis(0), &[0, 5]).view()]).unwrap();
+        let y_duplicated = [0, 1, 2, 0, 1, 2, 0, 2];
+
+        let model = MultiLogisticRegression::default()
+            .gradient_tolerance(1e-8)
+            .max_iterations(1000);
+        let weighted = model
+            .fit_weighted(&x, &y, &array![2.0, 1.0, 1.0, 1.0, 1.0, 2.0])
+            .unwrap();
+        let duplicated = model.fit(&x_duplicated, &y_duplicated).unwrap();
+
+        assert!(weighted.params().abs_diff_eq(duplicated.params(), 1e-6));
+        assert!(weighted
+            .intercept()
+            .abs_diff_eq(duplicated.intercept(), 1e-6));
+
+        assert_eq!(
+            model.fit_weighted(&x, &y, &array![1.0, 1.0]),
+            Err(
+                "Incompatible shapes of data, expected `sample_weight` to have one entry per row of `x`"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn separates_three_blobs() {
+        let (x, y) = blobs();
+        let res = MultiLogisticRegression::default().fit(&x, &y).unwrap();
+
+        assert_eq!(res.classes(), &[0, 1, 2]);
+        assert_eq!(res.params().dim(), (3, 2));
+        assert_eq!(res.predict_classes(&x), y);
+
+        let prob = res.predict_probabilities(&x);
+        assert!(prob
+            .sum_axis(Axis(1))
+            .abs_diff_eq(&Array1::ones(x.nrows()), 1e-10));
+        // the penalty makes the coefficients of each feature sum to zero
+        assert!(res
+            .params()
+            .sum_axis(Axis(0))
+            .abs_diff_eq(&array![0.0, 0.0], 1e-3));
+        assert!(res.intercept().sum().abs() < 1e-10);
+    }
+
+    #[test]
+    fn agrees_with_one_vs_rest() {
+        let (x, y) = blobs();
+        let multinomial = MultiLogisticRegression::default().fit(&x, &y).unwrap();
+
+        // fit a binary model for each class against all others
+        let one_vs_rest = (0..3)
+            .map(|class| {
+                let is_class = y.iter().map(|c| *c == class).collect::<Vec<_>>();
+                LogisticRegression::default()
+                    .fit(&x, &is_class)
+                    .unwrap()
+                    .predict_probabilities(&x)
+            })
+            .collect::<Vec<_>>();
+        let one_vs_rest = (0..x.nrows())
+            .map(|i| {
+                (0..3).fold(0, |best, c| {
+                    if one_vs_rest[c][i] > one_vs_rest[best][i] {
+                        c
+                    } else {
+                        best
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(multinomial.predict_classes(&x), one_vs_rest);
+    }
+
+    #[test]
+    fn rejects_single_class() {
+        let x = array![[0.0], [1.0]];
+        let res = MultiLogisticRegression::<f64>::default().fit(&x, &[1, 1]);
+        assert_eq!(
+            res,
+            Err("Expected at least two classes for logistic regression".to_string())
+        );
+    }
+}
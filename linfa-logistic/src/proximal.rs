@@ -0,0 +1,157 @@
+//! Proximal gradient descent for L1 and elastic-net penalized objectives.
+//!
+//! The L1 norm is not differentiable at zero, which LBFGS cannot handle. The
+//! accelerated proximal gradient method FISTA (Beck & Teboulle, 2009) instead
+//! takes a gradient step on the smooth part of the objective and then applies
+//! the proximal operator of the penalty. For the elastic net this is soft
+//! thresholding followed by shrinkage, which sets small coefficients exactly
+//! to zero.
+
+use ndarray::{s, Array1};
+
+use crate::float::Float;
+
+/// The elastic-net penalty `l1 * ||v||_1 + l2 / 2 * ||v||^2` of the
+/// parameters `v = w[..n_penalized]`.
+#[derive(Clone, Copy)]
+pub(crate) struct Penalty<F> {
+    pub n_penalized: usize,
+    pub l1: F,
+    pub l2: F,
+}
+
+/// Minimize `loss(w) + penalty(w)` with FISTA, starting at `init_params`.
+///
+/// `loss` must be smooth with gradient `grad`. The step size is found by
+/// backtracking and the momentum is restarted whenever it points uphill
+/// (O'Donoghue & Candès, 2015), which avoids the oscillations of FISTA close
+/// to the minimum. The iteration stops once the norm of the gradient mapping,
+/// the proximal analogue of the gradient, falls below `tolerance` or after
+/// `max_iterations` steps.
+pub(crate) fn fista<F, L, G>(
+    loss: L,
+    grad: G,
+    penalty: Penalty<F>,
+    init_params: Array1<F>,
+    max_iterations: u64,
+    tolerance: F,
+) -> ProximalResult<F>
+where
+    F: Float,
+    L: Fn(&Array1<F>) -> F,
+    G: Fn(&Array1<F>) -> Array1<F>,
+{
+    let two = F::from(2.0).unwrap();
+    let four = F::from(4.0).unwrap();
+    let prox = |w: Array1<F>, step: F| -> Array1<F> {
+        let mut w = w;
+        w.slice_mut(s![..penalty.n_penalized]).mapv_inplace(|x| {
+            soft_threshold(x, penalty.l1 * step) / (F::one() + penalty.l2 * step)
+        });
+        w
+    };
+
+    let mut params = init_params;
+    let mut momentum = params.clone();
+    let mut t = F::one();
+    let mut lipschitz = F::one();
+    let mut iterations = 0;
+    let mut gradient_norm = F::infinity();
+
+    while iterations < max_iterations {
+        iterations += 1;
+        let loss_momentum = loss(&momentum);
+        let grad_momentum = grad(&momentum);
+
+        // backtrack until the quadratic model at the momentum point
+        // majorizes the loss, up to the rounding errors of the loss which
+        // would otherwise inflate the step size estimate close to the minimum
+        let rounding = F::epsilon() * loss_momentum.abs();
+        let next = loop {
+            let candidate = prox(&momentum - &(&grad_momentum / lipschitz), lipschitz.recip());
+            let diff = &candidate - &momentum;
+            let model =
+                loss_momentum + grad_momentum.dot(&diff) + lipschitz / two * diff.dot(&diff);
+            if loss(&candidate) <= model + rounding || !model.is_finite() {
+                break candidate;
+            }
+            lipschitz *= two;
+        };
+
+        let mapping = (&momentum - &next) * lipschitz;
+        let step = &next - &params;
+        // restart if the gradient mapping points against the last step
+        if mapping.dot(&step) > F::zero() {
+            t = F::one();
+        }
+        let t_next = (F::one() + (F::one() + four * t * t).sqrt()) / two;
+        momentum = &next + &(&step * ((t - F::one()) / t_next));
+        params = next;
+        t = t_next;
+
+        gradient_norm = mapping.dot(&mapping).sqrt();
+        if gradient_norm < tolerance {
+            break;
+        }
+    }
+
+    ProximalResult {
+        params,
+        iterations,
+        gradient_norm,
+    }
+}
+
+/// The solution of the proximal gradient method
+pub(crate) struct ProximalResult<F> {
+    pub params: Array1<F>,
+    /// The number of iterations performed
+    pub iterations: u64,
+    /// The norm of the gradient mapping in the last iteration
+    pub gradient_norm: F,
+}
+
+/// Shrink `x` towards zero by `threshold`, values within the threshold
+/// become exactly zero.
+fn soft_threshold<F: Float>(x: F, threshold: F) -> F {
+    if x > threshold {
+        x - threshold
+    } else if x < -threshold {
+        x + threshold
+    } else {
+        F::zero()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use approx::AbsDiffEq;
+    use ndarray::array;
+
+    #[test]
+    fn solves_lasso_in_closed_form() {
+        // for a separable quadratic the solution is the soft thresholded target
+        let target = array![3.0, -0.5, 0.2, -2.0];
+        let loss = |w: &Array1<f64>| 0.5 * (w - &target).mapv(|x| x * x).sum();
+        let grad = |w: &Array1<f64>| w - &target;
+
+        let lasso = Penalty {
+            n_penalized: 3,
+            l1: 1.0,
+            l2: 0.0,
+        };
+        let params = fista(loss, grad, lasso, Array1::zeros(4), 100, 1e-10).params;
+        assert!(params.abs_diff_eq(&array![2.0, 0.0, 0.0, -2.0], 1e-8));
+        assert_eq!(params[1], 0.0);
+        assert_eq!(params[2], 0.0);
+
+        // the additional L2 penalty shrinks the thresholded target
+        let elastic_net = Penalty { l2: 1.0, ..lasso };
+        let result = fista(loss, grad, elastic_net, Array1::zeros(4), 100, 1e-10);
+        assert!(result.gradient_norm < 1e-10);
+        assert!(result.iterations < 100);
+        let params = result.params;
+        assert!(params.abs_diff_eq(&array![1.0, 0.0, 0.0, -2.0], 1e-8));
+    }
+}
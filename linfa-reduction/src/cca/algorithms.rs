@@ -0,0 +1,194 @@
+//! Canonical Correlation Analysis
+//!
+//! Given two views `X` and `Y` of the same observations, CCA finds pairs of linear projections
+//! `X w_x` and `Y w_y` with maximal correlation. The covariance matrices of both views are
+//! whitened and the singular value decomposition of the whitened cross-covariance
+//! ```ignore
+//! Cxx^{-1/2} Cxy Cyy^{-1/2} = U S V^T
+//! ```
+//! then yields the canonical correlations `S` and the weights `w_x = Cxx^{-1/2} U` and
+//! `w_y = Cyy^{-1/2} V`.
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+use ndarray_linalg::{eigh::Eigh, lapack::UPLO, svd::SVD};
+
+use crate::Float;
+
+/// Canonical Correlation Analysis
+pub struct Cca {
+    n_components: usize,
+}
+
+impl Cca {
+    /// Create a model extracting `n_components` pairs of canonical variates
+    pub fn new(n_components: usize) -> Self {
+        Cca { n_components }
+    }
+
+    /// Fit the model to two views with shapes `(n_samples, n_features_x)` and
+    /// `(n_samples, n_features_y)`
+    ///
+    /// Panics if the number of samples differs, or if more components are requested than
+    /// features are available in either view.
+    pub fn fit<A: Float>(&self, x: ArrayView2<A>, y: ArrayView2<A>) -> CcaResult<A> {
+        assert_eq!(
+            x.nrows(),
+            y.nrows(),
+            "both views must contain the same number of samples"
+        );
+        assert!(x.nrows() > 1, "at least two samples are required");
+        assert!(
+            self.n_components > 0 && self.n_components <= x.ncols().min(y.ncols()),
+            "number of components must be in the interval [1, {}]",
+            x.ncols().min(y.ncols())
+        );
+
+        let n_samples = A::from_usize(x.nrows() - 1).unwrap();
+        let x_mean = x.mean_axis(Axis(0)).unwrap();
+        let y_mean = y.mean_axis(Axis(0)).unwrap();
+        let x_centered = &x - &x_mean;
+        let y_centered = &y - &y_mean;
+
+        let cov_xx = x_centered.t().dot(&x_centered) / n_samples;
+        let cov_yy = y_centered.t().dot(&y_centered) / n_samples;
+        let cov_xy = x_centered.t().dot(&y_centered) / n_samples;
+
+        let x_whiten = inverse_sqrt(&cov_xx);
+        let y_whiten = inverse_sqrt(&cov_yy);
+
+        let (u, sigma, v_t) = x_whiten
+            .dot(&cov_xy)
+            .dot(&y_whiten)
+            .svd(true, true)
+            .expect("singular value decomposition of the cross-covariance failed");
+        let (u, v_t) = (u.unwrap(), v_t.unwrap());
+
+        let k = self.n_components;
+        let x_weights = x_whiten.dot(&u.slice(s![.., ..k]));
+        let y_weights = y_whiten.dot(&v_t.slice(s![..k, ..]).t());
+        let correlations = sigma
+            .slice(s![..k])
+            .mapv(|x| A::from(x).unwrap().min(A::one()));
+
+        // the canonical variates have unit variance, the loadings are then the covariance between
+        // features and variates
+        let x_loadings = cov_xx.dot(&x_weights);
+        let y_loadings = cov_yy.dot(&y_weights);
+
+        CcaResult {
+            x_mean,
+            y_mean,
+            x_weights,
+            y_weights,
+            x_loadings,
+            y_loadings,
+            correlations,
+        }
+    }
+}
+
+/// Calculate the inverse square root of a symmetric positive definite matrix
+fn inverse_sqrt<A: Float>(mat: &Array2<A>) -> Array2<A> {
+    let (eigvals, eigvecs) = mat
+        .eigh(UPLO::Lower)
+        .expect("eigendecomposition of the covariance failed");
+
+    let scale = eigvals.mapv(|x| {
+        let x = A::from(x).unwrap();
+        assert!(
+            x > A::zero(),
+            "covariance matrix is singular, features are linearly dependent"
+        );
+        A::one() / num_traits::Float::sqrt(x)
+    });
+
+    (&eigvecs * &scale).dot(&eigvecs.t())
+}
+
+/// Fitted Canonical Correlation Analysis
+pub struct CcaResult<A> {
+    x_mean: Array1<A>,
+    y_mean: Array1<A>,
+    x_weights: Array2<A>,
+    y_weights: Array2<A>,
+    x_loadings: Array2<A>,
+    y_loadings: Array2<A>,
+    correlations: Array1<A>,
+}
+
+impl<A: Float> CcaResult<A> {
+    /// Project both views onto the canonical variates
+    pub fn transform(&self, x: ArrayView2<A>, y: ArrayView2<A>) -> (Array2<A>, Array2<A>) {
+        (
+            (&x - &self.x_mean).dot(&self.x_weights),
+            (&y - &self.y_mean).dot(&self.y_weights),
+        )
+    }
+
+    /// Return the weights of the first view with shape `(n_features_x, n_components)`
+    pub fn x_weights(&self) -> &Array2<A> {
+        &self.x_weights
+    }
+
+    /// Return the weights of the second view with shape `(n_features_y, n_components)`
+    pub fn y_weights(&self) -> &Array2<A> {
+        &self.y_weights
+    }
+
+    /// Return the covariance between the features of the first view and its canonical variates
+    pub fn x_loadings(&self) -> &Array2<A> {
+        &self.x_loadings
+    }
+
+    /// Return the covariance between the features of the second view and its canonical variates
+    pub fn y_loadings(&self) -> &Array2<A> {
+        &self.y_loadings
+    }
+
+    /// Return the canonical correlations in decreasing order
+    pub fn correlations(&self) -> &Array1<A> {
+        &self.correlations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Cca;
+    use approx::assert_abs_diff_eq;
+    use ndarray::{Array1, Array2, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_recover_linear_relationship() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((200, 3), StandardNormal, &mut rng);
+        let noise = Array1::<f64>::random_using(200, StandardNormal, &mut rng);
+        let unrelated = Array2::<f64>::random_using((200, 1), StandardNormal, &mut rng);
+
+        // the first feature of y depends linearly on x, the second one is independent
+        let related = (&x.column(0) * 2. - x.column(1) + &noise * 0.01).insert_axis(Axis(1));
+        let y = stack![Axis(1), related, unrelated];
+
+        let cca = Cca::new(2).fit(x.view(), y.view());
+
+        let correlations = cca.correlations();
+        assert!(correlations[0] > 0.99);
+        assert!(correlations[1] < 0.3);
+
+        // the first weight vector points in the direction of the relationship
+        let weights = cca.x_weights().column(0).to_owned();
+        let direction = ndarray::arr1(&[2., -1., 0.]) / 5f64.sqrt();
+        let cosine = weights.dot(&direction) / weights.dot(&weights).sqrt();
+        assert_abs_diff_eq!(cosine.abs(), 1.0, epsilon = 1e-3);
+
+        // the canonical variates are uncorrelated and have unit variance
+        let (x_scores, y_scores) = cca.transform(x.view(), y.view());
+        let cov = x_scores.t().dot(&x_scores) / 199.;
+        assert_abs_diff_eq!(cov, Array2::<f64>::eye(2), epsilon = 1e-8);
+
+        let cross = x_scores.t().dot(&y_scores) / 199.;
+        assert_abs_diff_eq!(cross.diag(), correlations.view(), epsilon = 1e-8);
+    }
+}
@@ -0,0 +1,3 @@
+mod algorithms;
+
+pub use algorithms::*;
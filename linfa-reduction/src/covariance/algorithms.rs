@@ -0,0 +1,220 @@
+//! Shrunk covariance estimators
+//!
+//! The sample covariance matrix is a poor estimator when the number of features is large compared
+//! to the number of observations: it becomes ill-conditioned or even singular. Shrinkage pulls the
+//! sample covariance towards a scaled identity matrix
+//! ```ignore
+//! cov = (1 - shrinkage) * sample_cov + shrinkage * mu * I
+//! ```
+//! with `mu` the average variance of the features. Both estimators in this module choose the
+//! shrinkage coefficient analytically from the data, no cross-validation is required.
+use ndarray::{Array1, Array2, ArrayView2, Axis};
+use ndarray_linalg::{error::Result as LinalgResult, solveh::InverseH};
+
+use crate::Float;
+
+/// Covariance estimate of a shrinkage estimator
+pub struct CovResult<A> {
+    location: Array1<A>,
+    covariance: Array2<A>,
+    shrinkage: A,
+}
+
+impl<A: Float> CovResult<A> {
+    fn new(location: Array1<A>, emp_cov: Array2<A>, mu: A, shrinkage: A) -> Self {
+        let mut covariance = emp_cov * (A::one() - shrinkage);
+        covariance.diag_mut().mapv_inplace(|x| x + shrinkage * mu);
+
+        CovResult {
+            location,
+            covariance,
+            shrinkage,
+        }
+    }
+
+    /// Return the estimated mean of each feature
+    pub fn location(&self) -> &Array1<A> {
+        &self.location
+    }
+
+    /// Return the shrunk covariance matrix
+    pub fn covariance(&self) -> &Array2<A> {
+        &self.covariance
+    }
+
+    /// Calculate the precision matrix, the inverse of the shrunk covariance matrix
+    ///
+    /// Returns an error if the shrunk covariance matrix is singular, for example if all
+    /// observations are equal.
+    pub fn precision(&self) -> LinalgResult<Array2<A>> {
+        self.covariance.invh()
+    }
+
+    /// Return the shrinkage coefficient in the interval [0, 1]
+    pub fn shrinkage(&self) -> A {
+        self.shrinkage
+    }
+}
+
+/// Center the observations and calculate the empirical covariance matrix (normalized by the number
+/// of observations)
+fn center_and_covariance<A: Float>(x: ArrayView2<A>) -> (Array2<A>, Array1<A>, Array2<A>) {
+    assert!(
+        x.nrows() > 0,
+        "at least one observation is required to estimate the covariance"
+    );
+
+    let n_samples = A::from_usize(x.nrows()).unwrap();
+    let location = x.mean_axis(Axis(0)).unwrap();
+    let centered = &x - &location;
+    let emp_cov = centered.t().dot(&centered) / n_samples;
+
+    (centered, location, emp_cov)
+}
+
+/// Ledoit-Wolf covariance estimator
+///
+/// Estimates the shrinkage coefficient with the analytical formula of Ledoit and Wolf, minimizing
+/// the expected squared Frobenius distance to the true covariance matrix.
+///
+/// See "A Well-Conditioned Estimator for Large-Dimensional Covariance Matrices", Ledoit and Wolf,
+/// Journal of Multivariate Analysis, 2004
+pub struct LedoitWolf;
+
+impl LedoitWolf {
+    /// Estimate the covariance of observations with shape `(n_samples, n_features)`
+    pub fn fit<A: Float>(x: ArrayView2<A>) -> CovResult<A> {
+        let (centered, location, emp_cov) = center_and_covariance(x);
+
+        let n_samples = A::from_usize(centered.nrows()).unwrap();
+        let n_features = A::from_usize(centered.ncols()).unwrap();
+
+        let x2 = centered.mapv(|x| x * x);
+        let emp_cov_trace = x2.sum_axis(Axis(0)) / n_samples;
+        let mu = emp_cov_trace.sum() / n_features;
+
+        // estimate the variance of the entries of the sample covariance
+        let beta = x2.t().dot(&x2).sum();
+        let delta = centered.t().dot(&centered).mapv(|x| x * x).sum() / (n_samples * n_samples);
+        let beta = (beta / n_samples - delta) / (n_features * n_samples);
+
+        // squared distance between sample covariance and the shrinkage target
+        let delta = (delta - A::from(2.).unwrap() * mu * emp_cov_trace.sum()
+            + n_features * mu * mu)
+            / n_features;
+
+        let beta = beta.min(delta);
+        let shrinkage = if beta == A::zero() {
+            A::zero()
+        } else {
+            beta / delta
+        };
+
+        CovResult::new(location, emp_cov, mu, shrinkage)
+    }
+}
+
+/// Oracle Approximating Shrinkage covariance estimator
+///
+/// Under the assumption of gaussian distributed observations the shrinkage coefficient converges
+/// faster to the oracle than the one of [Ledoit-Wolf](struct.LedoitWolf.html).
+///
+/// See "Shrinkage Algorithms for MMSE Covariance Estimation", Chen et al., IEEE Transactions on
+/// Signal Processing, 2010
+pub struct Oas;
+
+impl Oas {
+    /// Estimate the covariance of observations with shape `(n_samples, n_features)`
+    pub fn fit<A: Float>(x: ArrayView2<A>) -> CovResult<A> {
+        let (_, location, emp_cov) = center_and_covariance(x);
+
+        let n_samples = A::from_usize(x.nrows()).unwrap();
+        let n_features = A::from_usize(x.ncols()).unwrap();
+
+        let mu = emp_cov.diag().sum() / n_features;
+        let alpha = emp_cov.mapv(|x| x * x).mean().unwrap();
+
+        let num = alpha + mu * mu;
+        let den = (n_samples + A::one()) * (alpha - mu * mu / n_features);
+
+        let shrinkage = if den == A::zero() {
+            A::one()
+        } else {
+            (num / den).min(A::one())
+        };
+
+        CovResult::new(location, emp_cov, mu, shrinkage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LedoitWolf, Oas};
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, Array2};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::StandardNormal;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn test_shrinkage_preserves_trace() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((20, 5), StandardNormal, &mut rng);
+        let centered = &x - &x.mean_axis(ndarray::Axis(0)).unwrap();
+        let emp_cov = centered.t().dot(&centered) / 20.;
+
+        for res in &[LedoitWolf::fit(x.view()), Oas::fit(x.view())] {
+            assert!(res.shrinkage() >= 0. && res.shrinkage() <= 1.);
+            assert_abs_diff_eq!(
+                res.covariance().diag().sum(),
+                emp_cov.diag().sum(),
+                epsilon = 1e-10
+            );
+            assert_abs_diff_eq!(
+                res.covariance().dot(&res.precision().unwrap()),
+                Array2::<f64>::eye(5),
+                epsilon = 1e-8
+            );
+        }
+    }
+
+    #[test]
+    fn test_high_dimensional() {
+        // more features than samples leads to a singular sample covariance
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::<f64>::random_using((5, 20), StandardNormal, &mut rng);
+
+        let res = LedoitWolf::fit(x.view());
+        assert!(res.shrinkage() > 0.);
+        assert_abs_diff_eq!(
+            res.covariance().dot(&res.precision().unwrap()),
+            Array2::<f64>::eye(20),
+            epsilon = 1e-8
+        );
+    }
+
+    #[test]
+    fn test_isotropic_target() {
+        // if the sample covariance is already a scaled identity no shrinkage is needed
+        let x = array![[1., 1.], [-1., -1.], [1., -1.], [-1., 1.]];
+
+        let res = LedoitWolf::fit(x.view());
+        assert_abs_diff_eq!(res.shrinkage(), 0.);
+        assert_abs_diff_eq!(res.covariance(), &Array2::<f64>::eye(2), epsilon = 1e-12);
+
+        let res = Oas::fit(x.view());
+        assert_abs_diff_eq!(res.covariance(), &Array2::<f64>::eye(2), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_constant_observations() {
+        // without any variance the shrunk covariance is zero and has no inverse
+        let x = Array2::<f64>::ones((4, 3));
+
+        for res in &[LedoitWolf::fit(x.view()), Oas::fit(x.view())] {
+            assert_abs_diff_eq!(res.covariance(), &Array2::<f64>::zeros((3, 3)));
+            assert!(res.precision().is_err());
+        }
+    }
+}
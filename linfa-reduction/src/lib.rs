@@ -1,10 +1,14 @@
 #[macro_use]
 extern crate ndarray;
 
+pub mod cca;
+pub mod covariance;
 pub mod diffusion_map;
 pub mod pca;
 pub mod utils;
 
+pub use cca::{Cca, CcaResult};
+pub use covariance::{CovResult, LedoitWolf, Oas};
 pub use diffusion_map::{DiffusionMap, DiffusionMapHyperParams};
 pub use pca::PrincipalComponentAnalysis;
 pub use utils::to_gaussian_similarity;
@@ -2,7 +2,7 @@
 ///
 /// Reduce dimensionality with a linear projection using Singular Value Decomposition. The data is
 /// centered before applying the SVD. This uses TruncatedSvd from ndarray-linalg package.
-use ndarray::{Array1, Array2, ArrayBase, Axis, DataMut, Ix2};
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, DataMut, Ix2};
 use ndarray_linalg::{TruncatedOrder, TruncatedSvd};
 
 /// Pincipal Component Analysis
@@ -42,6 +42,17 @@ impl PrincipalComponentAnalysis {
         (dataset - &self.mean).dot(&self.embedding.t())
     }
 
+    /// Map projected data points back to the original space
+    ///
+    /// This reconstructs the data from the principal components, it is exact if the embedding
+    /// size covers the rank of the centered data.
+    pub fn inverse_transform<S: Data<Elem = f64>>(
+        &self,
+        embedding: &ArrayBase<S, Ix2>,
+    ) -> Array2<f64> {
+        embedding.dot(&self.embedding) + &self.mean
+    }
+
     /// Return the amount of explained variance per element
     pub fn explained_variance(&self) -> Array1<f64> {
         self.explained_variance.clone()
@@ -64,7 +64,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // Fit a support vector machine classifier with C values of `7` for negative samples and `0.6`
     // for positive, because our dataset is unbalanced
-    let model = SVClassify::fit_c(&params, &kernel, train_targets, 7.0, 0.6);
+    let model = SVClassify::fit_c(&params, &kernel, train_targets, 7.0, 0.6)?;
 
     // print model
     println!("{}", model);
@@ -1,9 +1,11 @@
+use ndarray::{Array1, ArrayBase, ArrayView2, Data, Ix1};
 use std::cmp::Ordering;
 
+use super::error::{Result, SvmError};
 use super::permutable_kernel::{Kernel, PermutableKernel, PermutableKernelOneClass};
 use super::solver_smo::SolverState;
 use super::SolverParams;
-use super::{Float, SvmResult};
+use super::{check_kernel, check_nu, check_positive, Float, SvmResult};
 
 /// Support Vector Classification with C-penalizing parameter
 ///
@@ -24,19 +26,92 @@ use super::{Float, SvmResult};
 pub fn fit_c<'a, A: Float>(
     params: &'a SolverParams<A>,
     kernel: &'a Kernel<A>,
-    targets: &'a [bool],
+    targets: &[bool],
+    cpos: A,
+    cneg: A,
+) -> Result<SvmResult<'a, A>> {
+    fit_c_warm_start(params, kernel, targets, cpos, cneg, &[])
+}
+
+/// Support Vector Classification with C-penalizing parameter, starting from a previous solution
+///
+/// This solves the same problem as [fit_c](fn.fit_c.html), but the solver starts from the
+/// coefficients `alpha` of a previous model instead of zero. This is useful when samples are
+/// added incrementally, for example in active learning: the previous samples keep their
+/// coefficients, the new samples appended to the end of the dataset start at zero, and only a
+/// few SMO steps are necessary to restore optimality.
+/// ```rust, ignore
+/// let model = fit_c(&params, &kernel, &targets, 1.0, 1.0)?;
+/// let alpha = model.alpha.clone();
+///
+/// // append a new sample to the dataset and targets, then rebuild the kernel
+/// let kernel = Kernel::gaussian(&extended_dataset, 50.0);
+/// let model = fit_c_warm_start(&params, &kernel, &extended_targets, 1.0, 1.0, &alpha)?;
+/// ```
+///
+/// # Parameters
+///
+/// * `params` - Solver parameters (threshold etc.)
+/// * `kernel` - the kernel matrix `Q`
+/// * `targets` - the ground truth targets `y_i`
+/// * `cpos` - C for positive targets
+/// * `cneg` - C for negative targets
+/// * `alpha` - the signed coefficients of a previous solution, for the first `alpha.len()`
+///   samples
+///
+/// The coefficients have to be feasible for the new problem, `alpha_i` has the sign of the
+/// target, its absolute value is bounded by C and all coefficients sum up to zero. An infeasible
+/// starting point is rejected with `SvmError::InvalidValue`.
+pub fn fit_c_warm_start<'a, A: Float>(
+    params: &'a SolverParams<A>,
+    kernel: &'a Kernel<A>,
+    targets: &[bool],
     cpos: A,
     cneg: A,
-) -> SvmResult<'a, A> {
+    alpha: &[A],
+) -> Result<SvmResult<'a, A>> {
+    check_kernel(kernel, targets.len())?;
+    check_positive("C for positive targets", cpos)?;
+    check_positive("C for negative targets", cneg)?;
+    if alpha.len() > targets.len() {
+        return Err(SvmError::InvalidValue(format!(
+            "{} initial coefficients given, but there are only {} targets",
+            alpha.len(),
+            targets.len()
+        )));
+    }
+
     let bounds = targets
         .iter()
         .map(|x| if *x { cpos } else { cneg })
         .collect::<Vec<_>>();
 
+    // the solver works with unsigned coefficients, which have to lie within the bounds
+    let mut init_alpha = vec![A::zero(); targets.len()];
+    for (i, value) in alpha.iter().enumerate() {
+        let value = if targets[i] { *value } else { -*value };
+        if !(value >= A::zero() && value <= bounds[i]) {
+            return Err(SvmError::InvalidValue(format!(
+                "initial coefficient {} of sample {} is not compatible with its target and C",
+                alpha[i], i
+            )));
+        }
+        init_alpha[i] = value;
+    }
+
+    // SMO keeps the sum of the signed coefficients constant, it has to start at zero
+    let sum = alpha.iter().fold(A::zero(), |sum, x| sum + *x);
+    if sum.abs() > A::epsilon().sqrt() * cpos.max(cneg) {
+        return Err(SvmError::InvalidValue(format!(
+            "initial coefficients have to sum up to zero, but their sum is {}",
+            sum
+        )));
+    }
+
     let kernel = PermutableKernel::new(kernel, targets.to_vec());
 
     let solver = SolverState::new(
-        vec![A::zero(); targets.len()],
+        init_alpha,
         vec![-A::one(); targets.len()],
         targets.to_vec(),
         kernel,
@@ -45,7 +120,7 @@ pub fn fit_c<'a, A: Float>(
         false,
     );
 
-    let mut res = solver.solve();
+    let mut res = solver.solve()?;
 
     res.alpha = res
         .alpha
@@ -54,7 +129,69 @@ pub fn fit_c<'a, A: Float>(
         .map(|(a, b)| if *b { a } else { -a })
         .collect();
 
-    res
+    Ok(res)
+}
+
+/// Multilabel classification with one C-SVC per label
+///
+/// Each sample can belong to any number of labels, the targets are given as a boolean matrix with
+/// shape `(n_samples, n_labels)`. Following the binary relevance approach, a binary classifier
+/// is trained with [fit_c](fn.fit_c.html) for every label independently and all classifiers
+/// share the kernel matrix. Correlations between the labels are not exploited.
+///
+/// # Parameters
+///
+/// * `params` - Solver parameters (threshold etc.)
+/// * `kernel` - the kernel matrix `Q`
+/// * `targets` - the ground truth labels of each sample
+/// * `cpos` - C for positive targets
+/// * `cneg` - C for negative targets
+pub fn fit_c_multilabel<'a, A: Float>(
+    params: &'a SolverParams<A>,
+    kernel: &'a Kernel<A>,
+    targets: ArrayView2<bool>,
+    cpos: A,
+    cneg: A,
+) -> Result<MultiLabelResult<'a, A>> {
+    if targets.ncols() == 0 {
+        return Err(SvmError::InvalidValue(
+            "at least one label is required".into(),
+        ));
+    }
+
+    let models = targets
+        .gencolumns()
+        .into_iter()
+        .map(|column| fit_c(params, kernel, &column.to_vec(), cpos, cneg))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(MultiLabelResult { models })
+}
+
+/// The result of a multilabel classification, containing one model per label
+pub struct MultiLabelResult<'a, A: Float> {
+    models: Vec<SvmResult<'a, A>>,
+}
+
+impl<'a, A: Float> MultiLabelResult<'a, A> {
+    /// Predict the decision value of every label for a single sample
+    pub fn decision_function<S: Data<Elem = A>>(&self, data: ArrayBase<S, Ix1>) -> Array1<A> {
+        self.models
+            .iter()
+            .map(|model| model.predict(data.view()))
+            .collect()
+    }
+
+    /// Predict the labels of a single sample, a label is assigned if its decision value is
+    /// positive
+    pub fn predict<S: Data<Elem = A>>(&self, data: ArrayBase<S, Ix1>) -> Array1<bool> {
+        self.decision_function(data).mapv(|x| x > A::zero())
+    }
+
+    /// Returns the models, one for each label
+    pub fn models(&self) -> &[SvmResult<'a, A>] {
+        &self.models
+    }
 }
 
 /// Support Vector Classification with Nu-penalizing term
@@ -77,7 +214,10 @@ pub fn fit_nu<'a, A: Float>(
     kernel: &'a Kernel<A>,
     targets: &'a [bool],
     nu: A,
-) -> SvmResult<'a, A> {
+) -> Result<SvmResult<'a, A>> {
+    check_kernel(kernel, targets.len())?;
+    check_nu(nu)?;
+
     let mut sum_pos = nu * A::from(targets.len()).unwrap() / A::from(2.0).unwrap();
     let mut sum_neg = nu * A::from(targets.len()).unwrap() / A::from(2.0).unwrap();
     let init_alpha = targets
@@ -107,9 +247,17 @@ pub fn fit_nu<'a, A: Float>(
         true,
     );
 
-    let mut res = solver.solve();
+    let mut res = solver.solve()?;
 
-    let r = res.r.unwrap();
+    let r = match res.r {
+        Some(r) if r > A::zero() => r,
+        _ => {
+            return Err(SvmError::InvalidValue(format!(
+                "the solution is degenerate for Nu = {}",
+                nu
+            )))
+        }
+    };
 
     res.alpha = res
         .alpha
@@ -120,8 +268,9 @@ pub fn fit_nu<'a, A: Float>(
         .collect();
     res.rho /= r;
     res.obj /= r * r;
+    res.update_linear_decision();
 
-    res
+    Ok(res)
 }
 
 /// Support Vector Classification for one-class problems
@@ -138,9 +287,14 @@ pub fn fit_one_class<'a, A: Float + num_traits::ToPrimitive>(
     params: &'a SolverParams<A>,
     kernel: &'a Kernel<A>,
     nu: A,
-) -> SvmResult<'a, A> {
+) -> Result<SvmResult<'a, A>> {
     let size = kernel.size();
-    let n = (nu * A::from(size).unwrap()).to_usize().unwrap();
+    check_kernel(kernel, size)?;
+    check_nu(nu)?;
+
+    let n = (nu * A::from(size).unwrap())
+        .to_usize()
+        .ok_or_else(|| SvmError::InvalidValue(format!("cannot scale Nu = {}", nu)))?;
 
     let init_alpha = (0..size)
         .map(|x| match x.cmp(&n) {
@@ -167,10 +321,13 @@ pub fn fit_one_class<'a, A: Float + num_traits::ToPrimitive>(
 
 #[cfg(test)]
 mod tests {
-    use super::{fit_c, fit_nu, fit_one_class, SolverParams};
-    use linfa::metrics::IntoConfusionMatrix;
+    use super::{fit_c, fit_c_multilabel, fit_c_warm_start, fit_nu, fit_one_class, SolverParams};
+    use crate::error::SvmError;
+    use crate::{check_finite, SvmParams};
+    use approx::assert_abs_diff_eq;
+    use linfa::metrics::{IntoConfusionMatrix, MultiLabelClassification};
     use linfa_kernel::Kernel;
-    use ndarray::{Array, Array2, Axis};
+    use ndarray::{s, Array, Array2, Axis};
     use ndarray_rand::rand_distr::Uniform;
     use ndarray_rand::RandomExt;
 
@@ -213,7 +370,7 @@ mod tests {
         };
 
         // test C Support Vector Classification
-        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0);
+        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0).unwrap();
 
         let pred = entries
             .outer_iter()
@@ -224,8 +381,18 @@ mod tests {
         let cm = pred.into_confusion_matrix(&targets);
         assert_eq!(cm.accuracy(), 1.0);
 
+        // the primal weights reproduce the kernel predictor
+        let (weights, bias) = svc.primal_weights().unwrap();
+        for x in entries.outer_iter() {
+            assert_abs_diff_eq!(
+                weights.dot(&x) + bias,
+                kernel.weighted_sum(&svc.alpha, x) - svc.rho,
+                epsilon = 1e-8
+            );
+        }
+
         // test nu Support Vector Classification
-        let svc = fit_nu(&params, &kernel, &targets, 0.01);
+        let svc = fit_nu(&params, &kernel, &targets, 0.01).unwrap();
         println!("{}", svc);
 
         let pred = entries
@@ -236,6 +403,15 @@ mod tests {
 
         let cm = pred.into_confusion_matrix(&targets);
         assert_eq!(cm.accuracy(), 1.0);
+
+        let (weights, bias) = svc.primal_weights().unwrap();
+        for x in entries.outer_iter() {
+            assert_abs_diff_eq!(
+                weights.dot(&x) + bias,
+                kernel.weighted_sum(&svc.alpha, x) - svc.rho,
+                epsilon = 1e-8
+            );
+        }
     }
 
     #[test]
@@ -253,7 +429,7 @@ mod tests {
         };
 
         // test C Support Vector Classification
-        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0);
+        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0).unwrap();
         println!("C {}", svc);
 
         let pred = dataset
@@ -266,7 +442,7 @@ mod tests {
         assert!(cm.accuracy() > 0.9);
 
         // test nu Support Vector Classification
-        let svc = fit_nu(&params, &kernel, &targets, 0.01);
+        let svc = fit_nu(&params, &kernel, &targets, 0.01).unwrap();
         println!("Nu {}", svc);
 
         let pred = dataset
@@ -290,7 +466,7 @@ mod tests {
             shrinking: false,
         };
 
-        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0);
+        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0).unwrap();
 
         let pred = dataset
             .outer_iter()
@@ -300,8 +476,10 @@ mod tests {
 
         let cm = pred.into_confusion_matrix(&targets);
         assert_eq!(cm.accuracy(), 1.0);
+        // there are no primal weights for a non-linear kernel
+        assert!(svc.primal_weights().is_none());
 
-        let svc = fit_nu(&params, &kernel, &targets, 0.01);
+        let svc = fit_nu(&params, &kernel, &targets, 0.01).unwrap();
 
         let pred = dataset
             .outer_iter()
@@ -313,6 +491,170 @@ mod tests {
         assert_eq!(cm.accuracy(), 1.0);
     }
 
+    #[test]
+    fn test_convoluted_rings_auto_width() {
+        let dataset = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        // no manual tuning of the kernel width
+        let kernel = Kernel::gaussian_auto(&dataset);
+        let eps = kernel.gaussian_eps().unwrap();
+        assert!(eps > 0.0 && eps.is_finite());
+
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+
+        let svc = fit_c(&params, &kernel, &targets, 1.0, 1.0).unwrap();
+
+        let pred = dataset
+            .outer_iter()
+            .map(|x| svc.predict(x))
+            .map(|x| x > 0.0)
+            .collect::<Vec<_>>();
+
+        let cm = pred.into_confusion_matrix(&targets);
+        assert_eq!(cm.accuracy(), 1.0);
+    }
+
+    #[test]
+    fn test_lazy_kernel_matches_dense() {
+        let dataset = generate_convoluted_rings(10);
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+        let dense = Kernel::gaussian(&dataset, 50.0);
+        let lazy = Kernel::gaussian_lazy(&dataset, 50.0);
+
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+
+        // the solver requests the same kernel entries, no matter if they are stored or not
+        let svc_dense = fit_c(&params, &dense, &targets, 1.0, 1.0).unwrap();
+        let svc_lazy = fit_c(&params, &lazy, &targets, 1.0, 1.0).unwrap();
+        assert_abs_diff_eq!(svc_dense.rho, svc_lazy.rho, epsilon = 1e-10);
+        for (a, b) in svc_dense.alpha.iter().zip(svc_lazy.alpha.iter()) {
+            assert_abs_diff_eq!(a, b, epsilon = 1e-10);
+        }
+
+        let svc_dense = fit_one_class(&params, &dense, 0.1).unwrap();
+        let svc_lazy = fit_one_class(&params, &lazy, 0.1).unwrap();
+        assert_abs_diff_eq!(svc_dense.rho, svc_lazy.rho, epsilon = 1e-10);
+        for sample in dataset.outer_iter() {
+            assert_abs_diff_eq!(
+                svc_dense.predict(sample),
+                svc_lazy.predict(sample),
+                epsilon = 1e-10
+            );
+        }
+    }
+
+    #[test]
+    fn test_multilabel_classification() {
+        let entries = Array::random((200, 2), Uniform::new(-1., 1.));
+        // three overlapping linear tags
+        let targets = Array2::from_shape_fn((200, 3), |(i, j)| {
+            let (x, y) = (entries[(i, 0)], entries[(i, 1)]);
+            match j {
+                0 => x > 0.,
+                1 => y > 0.,
+                _ => x + y > 0.5,
+            }
+        });
+
+        let kernel = Kernel::linear(&entries);
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+        let model = fit_c_multilabel(&params, &kernel, targets.view(), 10.0, 10.0).unwrap();
+        assert_eq!(model.models().len(), 3);
+
+        let mut predicted = Array2::from_elem((200, 3), false);
+        for (x, mut row) in entries.outer_iter().zip(predicted.outer_iter_mut()) {
+            row.assign(&model.predict(x));
+        }
+
+        assert!(predicted
+            .label_f1_scores(&targets)
+            .iter()
+            .all(|f1| *f1 > 0.9));
+        // the subset accuracy requires all tags of a sample to be correct
+        let hamming_accuracy = 1.0 - predicted.hamming_loss(&targets);
+        assert!(predicted.subset_accuracy(&targets) <= hamming_accuracy);
+
+        let no_labels = Array2::from_elem((200, 0), false);
+        match fit_c_multilabel(&params, &kernel, no_labels.view(), 1.0, 1.0) {
+            Err(SvmError::InvalidValue(_)) => {}
+            _ => panic!("at least one label must be required"),
+        }
+    }
+
+    #[test]
+    fn test_warm_start_matches_full_refit() {
+        // two noisy, overlapping rings with deterministic positions
+        let dataset = Array2::from_shape_fn((100, 2), |(i, j)| {
+            let radius = if i % 2 == 0 { 1.0 } else { 2.0 } + 0.4 * (7.0 * i as f64).sin();
+            let phi = 2.4 * i as f64;
+            if j == 0 {
+                radius * phi.cos()
+            } else {
+                radius * phi.sin()
+            }
+        });
+        let targets = (0..100).map(|x| x % 2 == 0).collect::<Vec<_>>();
+
+        let params = SolverParams {
+            eps: 1e-6,
+            shrinking: false,
+        };
+
+        // fit all samples but the last one
+        let head = dataset.slice(s![..99, ..]).to_owned();
+        let kernel = Kernel::gaussian(&head, 1.0);
+        let svc = fit_c(&params, &kernel, &targets[..99], 1.0, 1.0).unwrap();
+        let alpha = svc.alpha.clone();
+
+        // add the last sample and resolve from the previous solution
+        let kernel = Kernel::gaussian(&dataset, 1.0);
+        let cold = fit_c(&params, &kernel, &targets, 1.0, 1.0).unwrap();
+        let warm = fit_c_warm_start(&params, &kernel, &targets, 1.0, 1.0, &alpha).unwrap();
+
+        assert!(warm.iterations < cold.iterations);
+        assert_abs_diff_eq!(warm.obj, cold.obj, epsilon = 1e-4);
+        for sample in dataset.outer_iter() {
+            assert_abs_diff_eq!(warm.predict(sample), cold.predict(sample), epsilon = 1e-3);
+        }
+
+        // the coefficients have to be compatible with the targets and the bounds
+        assert!(fit_c_warm_start(&params, &kernel, &targets, 1.0, 1.0, &[-1.0]).is_err());
+        assert!(fit_c_warm_start(&params, &kernel, &targets, 1.0, 1.0, &[2.0]).is_err());
+        assert!(fit_c_warm_start(&params, &kernel, &targets[..50], 1.0, 1.0, &alpha).is_err());
+    }
+
+    #[test]
+    fn test_warm_start_rejects_infeasible_coefficients() {
+        let entries = Array::random((10, 2), Uniform::new(-1., 1.));
+        let kernel = Kernel::linear(&entries);
+        let targets = (0..10).map(|x| x % 2 == 0).collect::<Vec<_>>();
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+
+        // within the bounds, but violating the equality constraint
+        assert!(matches!(
+            fit_c_warm_start(&params, &kernel, &targets, 1.0, 1.0, &[0.5, -0.2]),
+            Err(SvmError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            fit_c_warm_start(&params, &kernel, &targets, 1.0, 1.0, &[f64::NAN]),
+            Err(SvmError::InvalidValue(_))
+        ));
+
+        assert!(fit_c_warm_start(&params, &kernel, &targets, 1.0, 1.0, &[0.5, -0.5]).is_ok());
+    }
+
     #[test]
     fn test_reject_classification() {
         // generate two clusters with 100 samples each
@@ -324,7 +666,7 @@ mod tests {
             shrinking: false,
         };
 
-        let svc = fit_one_class(&params, &kernel, 0.1);
+        let svc = fit_one_class(&params, &kernel, 0.1).unwrap();
         println!("{}", svc);
 
         // now test that points outside the circle are rejected
@@ -350,4 +692,68 @@ mod tests {
         // at least 95% should be correctly rejected
         assert!((rejected as f32) / (total as f32) > 0.95);
     }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+
+        // fitting without any samples returns an error
+        let entries = Array2::<f64>::zeros((0, 2));
+        let kernel = Kernel::linear(&entries);
+        let targets: Vec<bool> = Vec::new();
+        assert!(matches!(
+            fit_c(&params, &kernel, &targets, 1.0, 1.0),
+            Err(SvmError::EmptyDataset)
+        ));
+        assert!(matches!(
+            fit_one_class(&params, &kernel, 0.1),
+            Err(SvmError::EmptyDataset)
+        ));
+
+        // nu has to be in the interval (0, 1)
+        let entries = Array::random((10, 2), Uniform::new(-1., 1.));
+        let kernel = Kernel::linear(&entries);
+        let targets = (0..10).map(|x| x < 5).collect::<Vec<_>>();
+        assert!(matches!(
+            fit_nu(&params, &kernel, &targets, 0.0),
+            Err(SvmError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            fit_nu(&params, &kernel, &targets, 1.0),
+            Err(SvmError::InvalidValue(_))
+        ));
+        assert!(matches!(
+            fit_one_class(&params, &kernel, 0.0),
+            Err(SvmError::InvalidValue(_))
+        ));
+        assert!(fit_c(&params, &kernel, &targets, -1.0, 1.0).is_err());
+
+        // the number of targets has to match the kernel matrix
+        assert!(fit_c(&params, &kernel, &targets[..5], 1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_reject_non_finite() {
+        let mut dataset = generate_convoluted_rings(10);
+        dataset[(3, 1)] = f64::NAN;
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+
+        let kernel = Kernel::gaussian(&dataset, 50.0);
+        let params = SvmParams::new().kernel(&kernel).build().unwrap();
+
+        // the offending sample and feature are reported
+        assert!(matches!(
+            params.fit(&targets),
+            Err(SvmError::NonFiniteData(3, 1))
+        ));
+
+        // the scan can also be run separately from fitting
+        assert!(matches!(
+            check_finite(&kernel),
+            Err(SvmError::NonFiniteData(3, 1))
+        ));
+    }
 }
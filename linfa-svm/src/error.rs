@@ -0,0 +1,46 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+pub type Result<T> = std::result::Result<T, SvmError>;
+
+/// An error when modeling a Support Vector Machine
+#[derive(Debug)]
+pub enum SvmError {
+    /// When any of the hyperparameters are set the wrong value
+    InvalidValue(String),
+    /// When no kernel matrix was passed to the parameter builder
+    MissingKernel,
+    /// When the dataset does not contain any samples
+    EmptyDataset,
+    /// When the kernel matrix contains a non-finite entry for the given sample
+    NonFiniteKernel(usize),
+    /// When the dataset contains a non-finite value for the given sample and feature
+    NonFiniteData(usize, usize),
+    /// When the solver does not converge within the maximal number of iterations
+    NotConverged(usize),
+}
+
+impl Display for SvmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidValue(message) => write!(f, "Invalid value encountered: {}", message),
+            Self::MissingKernel => write!(f, "No kernel matrix was specified"),
+            Self::EmptyDataset => write!(f, "The dataset does not contain any samples"),
+            Self::NonFiniteKernel(idx) => write!(
+                f,
+                "Kernel matrix contains a non-finite value for sample {}",
+                idx
+            ),
+            Self::NonFiniteData(sample, feature) => write!(
+                f,
+                "Dataset contains a non-finite value for sample {} and feature {}",
+                sample, feature
+            ),
+            Self::NotConverged(iterations) => {
+                write!(f, "Solver did not converge after {} iterations", iterations)
+            }
+        }
+    }
+}
+
+impl Error for SvmError {}
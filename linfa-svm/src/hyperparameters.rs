@@ -0,0 +1,249 @@
+use crate::classification::{fit_c, fit_nu};
+use crate::error::{Result, SvmError};
+use crate::permutable_kernel::Kernel;
+use crate::{check_finite, check_nu, check_positive, Float, SolverParams, SvmResult};
+
+/// The penalty used for Support Vector Classification
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Penalty<A> {
+    /// C values for positive and negative targets
+    C(A, A),
+    /// Nu value, controlling the number of support vectors
+    Nu(A),
+}
+
+/// The set of hyperparameters that can be specified for fitting a
+/// [Support Vector Classifier](SVClassify/index.html).
+pub struct SvmParams<'a, A: Float> {
+    solver_params: SolverParams<A>,
+    penalty: Penalty<A>,
+    kernel: &'a Kernel<'a, A>,
+    check_finite: bool,
+}
+
+/// An helper struct used to construct a set of [valid hyperparameters](struct.SvmParams.html) for
+/// Support Vector Classification (using the builder pattern).
+pub struct SvmParamsBuilder<'a, A: Float> {
+    eps: A,
+    shrinking: bool,
+    c: Option<(A, A)>,
+    nu: Option<A>,
+    kernel: Option<&'a Kernel<'a, A>>,
+    check_finite: bool,
+}
+
+impl<'a, A: Float> SvmParamsBuilder<'a, A> {
+    /// Set the stopping condition of the solver
+    pub fn eps(mut self, eps: A) -> Self {
+        self.eps = eps;
+        self
+    }
+
+    /// Set whether the active set of the solver should be shrinked
+    pub fn shrinking(mut self, shrinking: bool) -> Self {
+        self.shrinking = shrinking;
+        self
+    }
+
+    /// Use C-Support Vector Classification with a penalty for positive and negative targets
+    pub fn c(mut self, c_pos: A, c_neg: A) -> Self {
+        self.c = Some((c_pos, c_neg));
+        self
+    }
+
+    /// Use Nu-Support Vector Classification with the given Nu value
+    pub fn nu(mut self, nu: A) -> Self {
+        self.nu = Some(nu);
+        self
+    }
+
+    /// Set whether the dataset and kernel matrix are scanned for NaN or infinite values before
+    /// fitting. The scan can be disabled to avoid its overhead for data known to be clean.
+    pub fn check_finite(mut self, check_finite: bool) -> Self {
+        self.check_finite = check_finite;
+        self
+    }
+
+    /// Set the kernel matrix of the training data
+    pub fn kernel(mut self, kernel: &'a Kernel<'a, A>) -> Self {
+        self.kernel = Some(kernel);
+        self
+    }
+
+    /// Return an instance of `SvmParams` after having performed validation checks on all the
+    /// specified hyperparameters.
+    ///
+    /// Returns an error if any of the validation checks fails.
+    pub fn build(self) -> Result<SvmParams<'a, A>> {
+        let kernel = self.kernel.ok_or(SvmError::MissingKernel)?;
+
+        check_positive("eps", self.eps)?;
+
+        let penalty = match (self.c, self.nu) {
+            (Some(_), Some(_)) => {
+                return Err(SvmError::InvalidValue(
+                    "either C or Nu can be specified, but not both".into(),
+                ))
+            }
+            (Some((c_pos, c_neg)), None) => {
+                check_positive("C for positive targets", c_pos)?;
+                check_positive("C for negative targets", c_neg)?;
+                Penalty::C(c_pos, c_neg)
+            }
+            (None, Some(nu)) => {
+                check_nu(nu)?;
+                Penalty::Nu(nu)
+            }
+            (None, None) => Penalty::C(A::one(), A::one()),
+        };
+
+        Ok(SvmParams {
+            solver_params: SolverParams {
+                eps: self.eps,
+                shrinking: self.shrinking,
+            },
+            penalty,
+            kernel,
+            check_finite: self.check_finite,
+        })
+    }
+}
+
+impl<'a, A: Float> SvmParams<'a, A> {
+    /// Configure a Support Vector Classifier
+    ///
+    /// The kernel matrix is mandatory and has to be set with `kernel`.
+    ///
+    /// Defaults are provided if optional parameters are not specified:
+    /// * `eps = 1e-3`
+    /// * `shrinking = false`
+    /// * `C = (1.0, 1.0)`
+    /// * `check_finite = true`
+    // Violates the convention that new should return a value of type `Self`
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new() -> SvmParamsBuilder<'a, A> {
+        SvmParamsBuilder {
+            eps: A::from(1e-3).unwrap(),
+            shrinking: false,
+            c: None,
+            nu: None,
+            kernel: None,
+            check_finite: true,
+        }
+    }
+
+    /// The solver parameters (stopping condition and shrinking)
+    pub fn solver_params(&self) -> &SolverParams<A> {
+        &self.solver_params
+    }
+
+    /// The penalty used for classification
+    pub fn penalty(&self) -> Penalty<A> {
+        self.penalty
+    }
+
+    /// Fit a Support Vector Classifier to the given targets
+    ///
+    /// Depending on the penalty this uses either [fit_c](SVClassify/fn.fit_c.html) or
+    /// [fit_nu](SVClassify/fn.fit_nu.html).
+    pub fn fit(&'a self, targets: &'a [bool]) -> Result<SvmResult<'a, A>> {
+        if self.check_finite {
+            check_finite(self.kernel)?;
+        }
+
+        match self.penalty {
+            Penalty::C(c_pos, c_neg) => {
+                fit_c(&self.solver_params, self.kernel, targets, c_pos, c_neg)
+            }
+            Penalty::Nu(nu) => fit_nu(&self.solver_params, self.kernel, targets, nu),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Penalty, SvmParams};
+    use crate::error::SvmError;
+    use linfa::metrics::IntoConfusionMatrix;
+    use linfa_kernel::Kernel;
+    use ndarray::{Array, Axis};
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+
+    #[test]
+    fn test_invalid_params() {
+        let entries = Array::random((10, 2), Uniform::new(-1., 1.));
+        let kernel = Kernel::linear(&entries);
+
+        let res = SvmParams::new().kernel(&kernel).nu(1.5).build();
+        assert!(matches_invalid(res.err()));
+
+        // the interval of Nu is open at both ends
+        let res = SvmParams::new().kernel(&kernel).nu(1.0).build();
+        assert!(matches_invalid(res.err()));
+        let res = SvmParams::new().kernel(&kernel).nu(0.0).build();
+        assert!(matches_invalid(res.err()));
+        assert!(SvmParams::new().kernel(&kernel).nu(0.999).build().is_ok());
+
+        let res = SvmParams::new().kernel(&kernel).c(-1.0, 1.0).build();
+        assert!(matches_invalid(res.err()));
+
+        let res = SvmParams::new().kernel(&kernel).eps(0.0).build();
+        assert!(matches_invalid(res.err()));
+
+        let res = SvmParams::new().kernel(&kernel).c(1.0, 1.0).nu(0.5).build();
+        assert!(matches_invalid(res.err()));
+
+        let res = SvmParams::<f64>::new().nu(0.5).build();
+        assert!(matches!(res.err(), Some(SvmError::MissingKernel)));
+    }
+
+    fn matches_invalid(err: Option<SvmError>) -> bool {
+        matches!(err, Some(SvmError::InvalidValue(_)))
+    }
+
+    #[test]
+    fn test_builder_classification() {
+        let entries = ndarray::stack(
+            Axis(0),
+            &[
+                Array::random((10, 2), Uniform::new(-1., -0.5)).view(),
+                Array::random((10, 2), Uniform::new(0.5, 1.)).view(),
+            ],
+        )
+        .unwrap();
+        let targets = (0..20).map(|x| x < 10).collect::<Vec<_>>();
+
+        let kernel = Kernel::linear(&entries);
+
+        // test C Support Vector Classification
+        let params = SvmParams::new()
+            .kernel(&kernel)
+            .c(1.0, 1.0)
+            .build()
+            .unwrap();
+        assert_eq!(params.penalty(), Penalty::C(1.0, 1.0));
+
+        let svc = params.fit(&targets).unwrap();
+        let pred = entries
+            .outer_iter()
+            .map(|x| svc.predict(x) > 0.0)
+            .collect::<Vec<_>>();
+
+        let cm = pred.into_confusion_matrix(&targets);
+        assert_eq!(cm.accuracy(), 1.0);
+
+        // test nu Support Vector Classification
+        let params = SvmParams::new().kernel(&kernel).nu(0.01).build().unwrap();
+        assert_eq!(params.penalty(), Penalty::Nu(0.01));
+
+        let svc = params.fit(&targets).unwrap();
+        let pred = entries
+            .outer_iter()
+            .map(|x| svc.predict(x) > 0.0)
+            .collect::<Vec<_>>();
+
+        let cm = pred.into_confusion_matrix(&targets);
+        assert_eq!(cm.accuracy(), 1.0);
+    }
+}
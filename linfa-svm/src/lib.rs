@@ -15,7 +15,13 @@
 //! For supervised classification tasks the C or Nu values are used to control this balance. In
 //! [fit_c](SVClassify/fn.fit_c) the
 //! C value controls the penalty given to missclassification and should be in the interval (0, inf). In
-//! [fit_nu](SVClassify/fn.fit_nu.html) the Nu value controls the number of support vectors and should be in the interval (0, 1].
+//! [fit_nu](SVClassify/fn.fit_nu.html) the Nu value controls the number of support vectors and should be in the interval (0, 1).
+//!
+//! If samples arrive incrementally, [fit_c_warm_start](SVClassify/fn.fit_c_warm_start.html)
+//! continues from the coefficients of a previous model instead of solving from scratch.
+//!
+//! Samples belonging to several labels at once are classified with
+//! [fit_c_multilabel](SVClassify/fn.fit_c_multilabel.html), which trains one C-SVC per label.
 //!
 //! For supervised classification with just one class of data a special classifier is available in
 //! [fit_one_class](SVClassify/fn.fit_one_class.html). It also accepts a Nu value.
@@ -23,7 +29,14 @@
 //! For support vector regression two flavors are available. With
 //! [fit_epsilon](SVRegress/fn.fit_epsilon.html) a regression task is learned while minimizing deviation
 //! larger than epsilon. In [fit_nu](SVRegress/fn.fit_nu.html) the parameter epsilon is replaced with Nu
-//! again and should be in the interval (0, 1]
+//! again and should be in the interval (0, 1)
+//!
+//! Instead of calling the classification functions directly, the hyperparameters can also be
+//! assembled with [SvmParams](struct.SvmParams.html), which validates them before fitting:
+//! ```rust, ignore
+//! let params = SvmParams::new().kernel(&kernel).c(7.0, 0.6).build()?;
+//! let model = params.fit(&targets)?;
+//! ```
 //!
 //! ## Kernel Methods
 //! Normally the resulting discriminant is linear, but with [Kernel Methods](https://en.wikipedia.org/wiki/Kernel_method) non-linear relations between the input features
@@ -37,6 +50,10 @@
 //! let kernel = Kernel::gaussian_sparse(&dataset, 10);
 //! ```
 //!
+//! For large datasets even a sparse kernel matrix may not fit in memory. A lazy kernel, for
+//! example `Kernel::gaussian_lazy(&dataset, eps)`, only keeps the dataset and evaluates the
+//! columns requested by the solver on demand. This trades computation for memory.
+//!
 //! # The solver
 //! This implementation uses Sequential Minimal Optimization, a widely used optimization tool for
 //! convex problems. It selects in each optimization step two variables and updates the variables.
@@ -66,21 +83,31 @@
 //!
 //! accuracy 0.98818624, MCC 0.9523008
 //! ```
+use linfa_kernel::NonFinite;
 use ndarray::{Array1, ArrayBase, Data, Ix1, NdFloat};
 use std::fmt;
 
 mod classification;
+pub mod error;
+mod hyperparameters;
 mod permutable_kernel;
+mod prediction_cache;
 mod regression;
 pub mod solver_smo;
 
+pub use classification::MultiLabelResult;
+pub use error::{Result, SvmError};
+pub use hyperparameters::{Penalty, SvmParams, SvmParamsBuilder};
 use permutable_kernel::Kernel;
+pub use prediction_cache::CachedPredictor;
 pub use solver_smo::SolverParams;
 
 /// Support Vector Classification
 #[allow(non_snake_case)]
 pub mod SVClassify {
-    pub use crate::classification::{fit_c, fit_nu, fit_one_class};
+    pub use crate::classification::{
+        fit_c, fit_c_multilabel, fit_c_warm_start, fit_nu, fit_one_class,
+    };
 }
 
 /// Support Vector Regression
@@ -95,11 +122,64 @@ pub trait Float: NdFloat + Default + Clone + std::iter::Sum {}
 impl Float for f32 {}
 impl Float for f64 {}
 
-/// SMO can either exit because a threshold is reached or the iterations are maxed out
-#[derive(Debug)]
-pub enum ExitReason {
-    ReachedThreshold,
-    ReachedIterations,
+/// Check that the kernel matrix is non-empty, matches the number of targets and has finite
+/// self-similarities
+fn check_kernel<A: Float>(kernel: &Kernel<A>, ntargets: usize) -> Result<()> {
+    if ntargets == 0 {
+        return Err(SvmError::EmptyDataset);
+    }
+    if kernel.size() != ntargets {
+        return Err(SvmError::InvalidValue(format!(
+            "kernel matrix has {} samples, but there are {} targets",
+            kernel.size(),
+            ntargets
+        )));
+    }
+    if let Some(idx) = kernel.diagonal().iter().position(|x| !x.is_finite()) {
+        return Err(SvmError::NonFiniteKernel(idx));
+    }
+
+    Ok(())
+}
+
+/// Scan the dataset and kernel matrix for NaN or infinite values
+///
+/// The fit functions only check the diagonal of the kernel matrix, this performs a full scan of
+/// a dense or sparse kernel matrix and reports the offending sample. Lazy kernels are not
+/// materialized, only their dataset and diagonal are checked. It is called by
+/// [SvmParams](struct.SvmParams.html) unless disabled with `check_finite(false)`.
+pub fn check_finite<A: Float>(kernel: &Kernel<A>) -> Result<()> {
+    match kernel.find_non_finite() {
+        Some(NonFinite::Dataset { sample, feature }) => {
+            Err(SvmError::NonFiniteData(sample, feature))
+        }
+        Some(NonFinite::Matrix { row, .. }) => Err(SvmError::NonFiniteKernel(row)),
+        None => Ok(()),
+    }
+}
+
+/// Check that a penalty parameter lies in the interval (0, inf)
+fn check_positive<A: Float>(name: &str, value: A) -> Result<()> {
+    if value > A::zero() && value.is_finite() {
+        Ok(())
+    } else {
+        Err(SvmError::InvalidValue(format!(
+            "{} should be positive, but is {}",
+            name, value
+        )))
+    }
+}
+
+/// Check that a Nu value lies in the interval (0, 1)
+fn check_nu<A: Float>(nu: A) -> Result<()> {
+    if nu > A::zero() && nu < A::one() {
+        Ok(())
+    } else {
+        Err(SvmError::InvalidValue(format!(
+            "Nu should be in the interval (0, 1), but is {}",
+            nu
+        )))
+    }
 }
 
 /// The result of the SMO solver
@@ -107,7 +187,6 @@ pub struct SvmResult<'a, A: Float> {
     pub alpha: Vec<A>,
     pub rho: A,
     r: Option<A>,
-    exit_reason: ExitReason,
     iterations: usize,
     obj: A,
     kernel: &'a Kernel<'a, A>,
@@ -126,6 +205,16 @@ impl<'a, A: Float> SvmResult<'a, A> {
         }
     }
 
+    /// Create a predictor for repeated queries
+    ///
+    /// The predictor keeps the support vectors and memoizes the decision values of the most
+    /// recently used queries. This pays off when the same points are predicted many times, for
+    /// example in interactive applications, and returns exactly the same values as
+    /// [predict](#method.predict) unless a quantization step is set.
+    pub fn cached(&self) -> CachedPredictor<'_, 'a, A> {
+        CachedPredictor::new(self)
+    }
+
     /// Returns the number of support vectors
     pub fn nsupport(&self) -> usize {
         self.alpha
@@ -133,25 +222,36 @@ impl<'a, A: Float> SvmResult<'a, A> {
             .filter(|x| x.abs() > A::from(1e-5).unwrap())
             .count()
     }
+
+    /// Returns the weights `w` and the bias `b` of the decision function `w^T x + b`
+    ///
+    /// This is only available for linear kernels, for which the weights `w = sum_i alpha_i x_i`
+    /// can be computed in advance. Predictions then cost a single dot product instead of a
+    /// kernel evaluation with every support vector. Returns `None` for non-linear kernels.
+    pub fn primal_weights(&self) -> Option<(&Array1<A>, A)> {
+        self.linear_decision.as_ref().map(|w| (w, -self.rho))
+    }
+
+    /// Recompute the weights of a linear kernel after the coefficients `alpha` were modified
+    fn update_linear_decision(&mut self) {
+        if self.kernel.is_linear() {
+            let mut weights = Array1::zeros(self.kernel.dataset.ncols());
+            for (alpha, sample) in self.alpha.iter().zip(self.kernel.dataset.outer_iter()) {
+                weights.scaled_add(*alpha, &sample);
+            }
+            self.linear_decision = Some(weights);
+        }
+    }
 }
 
 impl<'a, A: Float> fmt::Display for SvmResult<'a, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.exit_reason {
-            ExitReason::ReachedThreshold => write!(
-                f,
-                "Exited after {} iterations with obj = {} and {} support vectors",
-                self.iterations,
-                self.obj,
-                self.nsupport()
-            ),
-            ExitReason::ReachedIterations => write!(
-                f,
-                "Reached maximal iterations {} with obj = {} and {} support vectors",
-                self.iterations,
-                self.obj,
-                self.nsupport()
-            ),
-        }
+        write!(
+            f,
+            "Exited after {} iterations with obj = {} and {} support vectors",
+            self.iterations,
+            self.obj,
+            self.nsupport()
+        )
     }
 }
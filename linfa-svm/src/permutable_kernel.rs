@@ -214,6 +214,7 @@ mod tests {
             fnc: Box::new(|_, _| 0.0),
             dataset: &dist,
             linear: false,
+            gaussian_eps: None,
         };
 
         let mut kernel = PermutableKernel::new(&dist, targets);
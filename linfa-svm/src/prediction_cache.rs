@@ -0,0 +1,255 @@
+//! Cached prediction for repeated queries
+//!
+//! Without a linear kernel every prediction evaluates the kernel function with all samples of
+//! the training set. The cached predictor keeps only the support vectors, the samples with a
+//! non-zero coefficient, and memoizes the decision values of the most recently used queries.
+use ndarray::{Array1, Array2, ArrayBase, ArrayView1, Axis, Data, Ix1};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use super::error::Result;
+use super::{check_positive, Float, SvmResult};
+
+/// Number of queries kept by a new predictor
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// The mantissa, exponent and sign of every feature, this identifies a query exactly
+type QueryKey = Vec<(u64, i16, i8)>;
+
+/// A predictor which caches support vectors and decision values of an `SvmResult`
+///
+/// Queries are keyed by the exact value of their features. A cached prediction is therefore
+/// identical to the prediction of [SvmResult::predict](struct.SvmResult.html#method.predict),
+/// queries which differ in the last bit are evaluated separately. With a
+/// [quantization](#method.quantization) step nearby queries share their cache entry instead.
+///
+/// At most [capacity](#method.capacity) queries are kept, once the cache is full the least
+/// recently used query is evicted. Finding it scans the cache, which is cheap compared to a
+/// kernel evaluation with every support vector for moderate capacities.
+pub struct CachedPredictor<'b, 'a, A: Float> {
+    model: &'b SvmResult<'a, A>,
+    support_vectors: Array2<A>,
+    coefficients: Vec<A>,
+    capacity: usize,
+    quantization: Option<A>,
+    /// Decision value and time of the last access of every cached query
+    cache: RefCell<HashMap<QueryKey, (A, u64)>>,
+    clock: Cell<u64>,
+}
+
+impl<'b, 'a, A: Float> CachedPredictor<'b, 'a, A> {
+    pub(crate) fn new(model: &'b SvmResult<'a, A>) -> CachedPredictor<'b, 'a, A> {
+        let indices = model
+            .alpha
+            .iter()
+            .enumerate()
+            .filter(|(_, alpha)| **alpha != A::zero())
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+
+        let support_vectors = model.kernel.dataset.select(Axis(0), &indices);
+        let coefficients = indices.iter().map(|idx| model.alpha[*idx]).collect();
+
+        CachedPredictor {
+            model,
+            support_vectors,
+            coefficients,
+            capacity: DEFAULT_CAPACITY,
+            quantization: None,
+            cache: RefCell::new(HashMap::new()),
+            clock: Cell::new(0),
+        }
+    }
+
+    /// Set the maximal number of cached queries, defaults to 1024
+    ///
+    /// A capacity of zero disables the cache. This clears the cache.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self.cache.get_mut().clear();
+        self
+    }
+
+    /// Round every feature of a query to a multiple of `step` before it is evaluated
+    ///
+    /// All queries which round to the same point share a cache entry and get the prediction
+    /// of the rounded point. This trades accuracy for more cache hits, for example with noisy
+    /// sensor readings. Returns an error if `step` is not positive. This clears the cache.
+    pub fn quantization(mut self, step: A) -> Result<Self> {
+        check_positive("quantization step", step)?;
+        self.quantization = Some(step);
+        self.cache.get_mut().clear();
+
+        Ok(self)
+    }
+
+    /// Predict a value for the query `data`, looking it up in the cache first
+    pub fn predict<S: Data<Elem = A>>(&self, data: ArrayBase<S, Ix1>) -> A {
+        let query: Array1<A> = match self.quantization {
+            Some(step) => data.mapv(|x| (x / step).round() * step),
+            None => data.to_owned(),
+        };
+        let key = query
+            .iter()
+            .map(|x| x.integer_decode())
+            .collect::<QueryKey>();
+
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+
+        let mut cache = self.cache.borrow_mut();
+        if let Some((value, last_used)) = cache.get_mut(&key) {
+            *last_used = now;
+            return *value;
+        }
+
+        let value = self.evaluate(query.view());
+        if self.capacity == 0 {
+            return value;
+        }
+        if cache.len() >= self.capacity {
+            let oldest = cache
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                cache.remove(&oldest);
+            }
+        }
+        cache.insert(key, (value, now));
+
+        value
+    }
+
+    /// Evaluate the decision function with the support vectors only
+    ///
+    /// Samples with a zero coefficient do not contribute to the weighted sum, skipping them
+    /// leaves the result unchanged.
+    fn evaluate(&self, data: ArrayView1<A>) -> A {
+        // a linear kernel is already reduced to a single dot product
+        if self.model.primal_weights().is_some() {
+            return self.model.predict(data);
+        }
+
+        let fnc = &self.model.kernel.fnc;
+        let sum: A = self
+            .support_vectors
+            .outer_iter()
+            .zip(self.coefficients.iter())
+            .map(|(x, a)| (*fnc)(x, data) * *a)
+            .sum();
+
+        sum - self.model.rho
+    }
+
+    /// Returns the number of samples with a non-zero coefficient, which are used for prediction
+    pub fn nsupport(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Returns the number of cached queries
+    pub fn cache_size(&self) -> usize {
+        self.cache.borrow().len()
+    }
+
+    /// Remove all cached queries
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SVClassify, SolverParams};
+    use linfa_kernel::Kernel;
+    use ndarray::{array, Array, Array2, Axis};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    /// Samples inside a disc are positive
+    fn disc_targets(dataset: &Array2<f64>) -> Vec<bool> {
+        dataset
+            .outer_iter()
+            .map(|x| x[0] * x[0] + x[1] * x[1] < 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn cached_predictions_equal_uncached() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let dataset = Array::random_using((50, 2), Uniform::new(-1f64, 1.), &mut rng);
+        let kernel = Kernel::gaussian(&dataset, 0.1);
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+        let svc = SVClassify::fit_c(&params, &kernel, &disc_targets(&dataset), 1.0, 1.0).unwrap();
+
+        let cached = svc.cached();
+        assert_eq!(
+            cached.nsupport(),
+            svc.alpha.iter().filter(|a| **a != 0.0).count()
+        );
+
+        let queries = Array::random_using((20, 2), Uniform::new(-1f64, 1.), &mut rng);
+        // evaluate every query twice, the second time from the cache
+        for _ in 0..2 {
+            for x in queries.outer_iter().chain(dataset.outer_iter()) {
+                assert_eq!(cached.predict(x), svc.predict(x));
+            }
+        }
+        assert_eq!(cached.cache_size(), 70);
+
+        cached.clear_cache();
+        assert_eq!(cached.cache_size(), 0);
+        assert_eq!(
+            cached.predict(queries.index_axis(Axis(0), 0)),
+            svc.predict(queries.index_axis(Axis(0), 0))
+        );
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let dataset = Array::random_using((50, 2), Uniform::new(-1f64, 1.), &mut rng);
+        let kernel = Kernel::gaussian(&dataset, 0.1);
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+        let svc = SVClassify::fit_c(&params, &kernel, &disc_targets(&dataset), 1.0, 1.0).unwrap();
+
+        let cached = svc.cached().capacity(10);
+        for x in dataset.outer_iter() {
+            assert_eq!(cached.predict(x), svc.predict(x));
+        }
+        assert_eq!(cached.cache_size(), 10);
+
+        let cached = svc.cached().capacity(0);
+        assert_eq!(cached.predict(dataset.row(0)), svc.predict(dataset.row(0)));
+        assert_eq!(cached.cache_size(), 0);
+    }
+
+    #[test]
+    fn quantized_queries_share_entries() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let dataset = Array::random_using((50, 2), Uniform::new(-1f64, 1.), &mut rng);
+        let kernel = Kernel::gaussian(&dataset, 0.1);
+        let params = SolverParams {
+            eps: 1e-3,
+            shrinking: false,
+        };
+        let svc = SVClassify::fit_c(&params, &kernel, &disc_targets(&dataset), 1.0, 1.0).unwrap();
+
+        let cached = svc.cached().quantization(0.25).unwrap();
+        let expected = svc.predict(array![0.25, -0.5]);
+        assert_eq!(cached.predict(array![0.3, -0.45]), expected);
+        assert_eq!(cached.predict(array![0.2, -0.6]), expected);
+        assert_eq!(cached.cache_size(), 1);
+
+        assert!(svc.cached().quantization(0.0).is_err());
+        assert!(svc.cached().quantization(f64::NAN).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 ///! Support Vector Regression
+use super::error::{Result, SvmError};
 use super::permutable_kernel::{Kernel, PermutableKernelRegression};
 use super::solver_smo::SolverState;
 use super::SolverParams;
-use super::{Float, SvmResult};
+use super::{check_kernel, check_nu, check_positive, Float, SvmResult};
 
 /// Support Vector Regression with epsilon tolerance
 ///
@@ -21,7 +22,16 @@ pub fn fit_epsilon<'a, A: Float>(
     target: &'a [A],
     c: A,
     p: A,
-) -> SvmResult<'a, A> {
+) -> Result<SvmResult<'a, A>> {
+    check_kernel(kernel, target.len())?;
+    check_positive("C", c)?;
+    if p < A::zero() || p.is_nan() {
+        return Err(SvmError::InvalidValue(format!(
+            "epsilon should be non-negative, but is {}",
+            p
+        )));
+    }
+
     let mut linear_term = vec![A::zero(); 2 * target.len()];
     let mut targets = vec![true; 2 * target.len()];
 
@@ -44,15 +54,16 @@ pub fn fit_epsilon<'a, A: Float>(
         false,
     );
 
-    let mut res = solver.solve();
+    let mut res = solver.solve()?;
 
     for i in 0..target.len() {
         let tmp = res.alpha[i + target.len()];
         res.alpha[i] -= tmp;
     }
     res.alpha.truncate(target.len());
+    res.update_linear_decision();
 
-    res
+    Ok(res)
 }
 
 /// Support Vector Regression with nu parameter
@@ -72,7 +83,11 @@ pub fn fit_nu<'a, A: Float>(
     target: &'a [A],
     c: A,
     nu: A,
-) -> SvmResult<'a, A> {
+) -> Result<SvmResult<'a, A>> {
+    check_kernel(kernel, target.len())?;
+    check_positive("C", c)?;
+    check_nu(nu)?;
+
     let mut alpha = vec![A::zero(); 2 * target.len()];
     let mut linear_term = vec![A::zero(); 2 * target.len()];
     let mut targets = vec![true; 2 * target.len()];
@@ -101,24 +116,26 @@ pub fn fit_nu<'a, A: Float>(
         false,
     );
 
-    let mut res = solver.solve();
+    let mut res = solver.solve()?;
 
     for i in 0..target.len() {
         let tmp = res.alpha[i + target.len()];
         res.alpha[i] -= tmp;
     }
     res.alpha.truncate(target.len());
+    res.update_linear_decision();
 
-    res
+    Ok(res)
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::{fit_epsilon, fit_nu, SolverParams};
 
+    use approx::assert_abs_diff_eq;
     use linfa::metrics::Regression;
     use linfa_kernel::Kernel;
-    use ndarray::{Array, Array1};
+    use ndarray::{Array, Array1, Axis};
 
     #[test]
     fn test_linear_epsilon_regression() {
@@ -135,7 +152,7 @@ pub mod tests {
             shrinking: false,
         };
 
-        let svr = fit_epsilon(&params, &kernel, &target, 2.0, 0.01);
+        let svr = fit_epsilon(&params, &kernel, &target, 2.0, 0.01).unwrap();
         println!("{}", svr);
 
         let predicted = sin_curve
@@ -163,7 +180,7 @@ pub mod tests {
             shrinking: false,
         };
 
-        let svr = fit_nu(&params, &kernel, &target, 2.0, 1.0);
+        let svr = fit_nu(&params, &kernel, &target, 2.0, 0.99).unwrap();
         println!("{}", svr);
 
         let predicted = sin_curve
@@ -173,4 +190,32 @@ pub mod tests {
 
         assert!(predicted.mean_squared_error(&target) < 1e-2);
     }
+
+    #[test]
+    fn test_primal_weights_regression() {
+        let samples = Array::linspace(0f64, 10., 50).insert_axis(Axis(1));
+        let target = samples.column(0).mapv(|x| 2. * x + 1.).to_vec();
+        let kernel = Kernel::linear(&samples);
+
+        let params = SolverParams {
+            eps: 1e-8,
+            shrinking: false,
+        };
+
+        let epsilon = fit_epsilon(&params, &kernel, &target, 2.0, 0.01).unwrap();
+        let nu = fit_nu(&params, &kernel, &target, 2.0, 0.5).unwrap();
+        for svr in &[epsilon, nu] {
+            // the weights combine the coefficients of both halves of the dual problem
+            let (weights, bias) = svr.primal_weights().unwrap();
+            assert_abs_diff_eq!(weights[0], 2., epsilon = 0.1);
+
+            for x in samples.outer_iter() {
+                assert_abs_diff_eq!(
+                    weights.dot(&x) + bias,
+                    kernel.weighted_sum(&svr.alpha, x) - svr.rho,
+                    epsilon = 1e-8
+                );
+            }
+        }
+    }
 }
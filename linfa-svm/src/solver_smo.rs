@@ -1,5 +1,6 @@
+use super::error::{Result, SvmError};
 use super::permutable_kernel::Permutable;
-use super::{ExitReason, Float, SvmResult};
+use super::{Float, SvmResult};
 
 use ndarray::{Array1, Axis};
 
@@ -756,7 +757,7 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         (r1 - r2) / A::from(2.0).unwrap()
     }
 
-    pub fn solve(mut self) -> SvmResult<'a, A> {
+    pub fn solve(mut self) -> Result<SvmResult<'a, A>> {
         let mut iter = 0;
         let max_iter = if self.targets.len() > std::usize::MAX / 100 {
             std::usize::MAX
@@ -814,11 +815,9 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
         }
         let obj = v / A::from(2.0).unwrap();
 
-        let exit_reason = if max_iter == iter {
-            ExitReason::ReachedIterations
-        } else {
-            ExitReason::ReachedThreshold
-        };
+        if max_iter == iter {
+            return Err(SvmError::NotConverged(iter));
+        }
 
         // put back the solution
         let alpha: Vec<A> = (0..self.ntotal())
@@ -837,16 +836,15 @@ impl<'a, A: Float, K: 'a + Permutable<'a, A>> SolverState<'a, A, K> {
             None
         };
 
-        SvmResult {
+        Ok(SvmResult {
             alpha,
             rho,
             r,
-            exit_reason,
             obj,
             iterations: iter,
             kernel: self.kernel.inner(),
             linear_decision,
-        }
+        })
     }
 }
 
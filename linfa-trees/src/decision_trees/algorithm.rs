@@ -1,16 +1,21 @@
+use crate::decision_trees::criterion::{ClassificationCriterion, Criterion, VarianceCriterion};
 use crate::decision_trees::hyperparameters::{DecisionTreeParams, SplitQuality};
-use ndarray::{Array1, ArrayBase, Axis, Data, Ix1, Ix2};
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix1, Ix2};
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::{Rng, SeedableRng};
+use std::fmt;
 use std::iter::FromIterator;
 
 /// `RowMask` is used to track which rows are still included up to a particular
 /// node in the tree for one particular feature.
-struct RowMask {
-    mask: Vec<bool>,
-    n_samples: u64,
+pub(crate) struct RowMask {
+    pub mask: Vec<bool>,
+    pub n_samples: u64,
 }
 
 impl RowMask {
-    fn all(n_samples: u64) -> Self {
+    pub fn all(n_samples: u64) -> Self {
         RowMask {
             mask: vec![true; n_samples as usize],
             n_samples,
@@ -36,80 +41,111 @@ impl SortedIndex {
     }
 }
 
-struct TreeNode {
+#[derive(Clone)]
+struct TreeNode<T> {
     feature_idx: usize,
     split_value: f64,
-    left_child: Option<Box<TreeNode>>,
-    right_child: Option<Box<TreeNode>>,
+    left_child: Option<Box<TreeNode<T>>>,
+    right_child: Option<Box<TreeNode<T>>>,
     leaf_node: bool,
-    prediction: u64,
+    prediction: T,
+    /// The class frequencies of the samples in the node, empty for regression
+    class_freq: Vec<u64>,
+    /// The impurity of the samples in the node
+    impurity: f64,
+    n_samples: u64,
 }
 
-impl TreeNode {
-    fn fit(
+impl<T: Copy> TreeNode<T> {
+    #[allow(clippy::too_many_arguments)]
+    fn fit<C: Criterion<Target = T>>(
         x: &ArrayBase<impl Data<Elem = f64>, Ix2>,
-        y: &ArrayBase<impl Data<Elem = u64>, Ix1>,
+        y: &ArrayBase<impl Data<Elem = T>, Ix1>,
         mask: &RowMask,
         hyperparameters: &DecisionTreeParams,
+        criterion: &C,
         sorted_indices: &[SortedIndex],
         depth: u64,
+        seed: u64,
     ) -> Self {
         let mut leaf_node = false;
 
         leaf_node |= mask.n_samples < hyperparameters.min_samples_split;
 
         if let Some(max_depth) = hyperparameters.max_depth {
-            leaf_node |= depth > max_depth;
+            leaf_node |= depth >= max_depth;
         }
 
-        let parent_class_freq = class_frequencies(&y, mask, hyperparameters.n_classes);
-        let prediction = prediction_for_rows(&parent_class_freq);
+        let parent_stats = criterion.stats(y, mask);
+        let prediction = criterion.prediction(&parent_stats);
+        let class_freq = criterion.class_counts(&parent_stats);
+        let impurity = criterion.impurity(&parent_stats);
+
+        // Every node draws its features and the seeds of its children from
+        // its own generator, independent of the order the nodes are fitted
+        let mut rng = StdRng::seed_from_u64(seed);
+        let n_features = sorted_indices.len();
+        let n_examined = hyperparameters.max_features.n_features(n_features);
+        let features = if n_examined < n_features {
+            let mut features = index::sample(&mut rng, n_features, n_examined).into_vec();
+            // Ties between features are broken by the smaller index
+            features.sort_unstable();
+            features
+        } else {
+            (0..n_features).collect()
+        };
+        let (left_seed, right_seed) = (rng.gen(), rng.gen());
 
         let mut best_feature_idx = None;
         let mut best_split_value = None;
         let mut best_score = None;
 
         // Find best split for current level
-        for (feature_idx, sorted_index) in sorted_indices.iter().enumerate() {
-            let mut left_class_freq = parent_class_freq.clone();
-            let mut right_class_freq = vec![0; hyperparameters.n_classes as usize];
+        for feature_idx in features {
+            let sorted_index = &sorted_indices[feature_idx];
+            let mut left_stats = criterion.empty();
+            let mut right_stats = parent_stats.clone();
+
+            // Positions of the rows of this node in the sorted feature column
+            let positions: Vec<usize> = (0..sorted_index.presorted_indices.len())
+                .filter(|i| mask.mask[sorted_index.presorted_indices[*i]])
+                .collect();
 
-            for i in 0..mask.mask.len() - 1 {
-                let split_value = sorted_index.features[i];
+            for pair in positions.windows(2) {
+                let (i, next) = (pair[0], pair[1]);
                 let presorted_index = sorted_index.presorted_indices[i];
 
-                if !mask.mask[presorted_index] {
+                // Move the current sample from the right subset to the left
+                criterion.add(&mut left_stats, y[presorted_index]);
+                criterion.remove(&mut right_stats, y[presorted_index]);
+
+                // Samples with the same feature value cannot be separated
+                if sorted_index.features[i] >= sorted_index.features[next] {
                     continue;
                 }
 
-                // Move the class of the current sample from the left subset to the right
-                left_class_freq[y[presorted_index as usize] as usize] -= 1;
-                right_class_freq[y[presorted_index as usize] as usize] += 1;
-
-                if left_class_freq.iter().sum::<u64>() < hyperparameters.min_samples_split
-                    || right_class_freq.iter().sum::<u64>() < hyperparameters.min_samples_split
+                if criterion.n_samples(&left_stats) < hyperparameters.min_samples_split
+                    || criterion.n_samples(&right_stats) < hyperparameters.min_samples_split
                 {
                     continue;
                 }
 
-                let (left_score, right_score) = match hyperparameters.split_quality {
-                    SplitQuality::Gini => (
-                        gini_impurity(&left_class_freq),
-                        gini_impurity(&right_class_freq),
-                    ),
-                    SplitQuality::Entropy => {
-                        (entropy(&left_class_freq), entropy(&right_class_freq))
-                    }
-                };
+                let left_score = criterion.impurity(&left_stats);
+                let right_score = criterion.impurity(&right_stats);
 
                 let left_weight: f64 =
-                    left_class_freq.iter().sum::<u64>() as f64 / mask.mask.len() as f64;
+                    criterion.n_samples(&left_stats) as f64 / mask.n_samples as f64;
                 let right_weight: f64 =
-                    right_class_freq.iter().sum::<u64>() as f64 / mask.mask.len() as f64;
+                    criterion.n_samples(&right_stats) as f64 / mask.n_samples as f64;
 
                 let score = left_weight * left_score + right_weight * right_score;
 
                 if best_score.is_none() || score < best_score.unwrap() {
+                    // Split halfway between the last sample on the left and
+                    // the first sample on the right
+                    let split_value =
+                        (sorted_index.features[i] + sorted_index.features[next]) / 2.0;
+
                     best_feature_idx = Some(feature_idx);
                     best_split_value = Some(split_value);
                     best_score = Some(score);
@@ -120,12 +156,10 @@ impl TreeNode {
         leaf_node |= best_score.is_none();
 
         if let Some(best_score) = best_score {
-            let parent_score = match hyperparameters.split_quality {
-                SplitQuality::Gini => gini_impurity(&parent_class_freq),
-                SplitQuality::Entropy => entropy(&parent_class_freq),
-            };
-
-            leaf_node |= parent_score - best_score < hyperparameters.min_impurity_decrease;
+            // The decrease is weighted by the fraction of samples in the node
+            let node_weight = mask.n_samples as f64 / mask.mask.len() as f64;
+            leaf_node |=
+                node_weight * (impurity - best_score) < hyperparameters.min_impurity_decrease;
         }
 
         if leaf_node {
@@ -136,6 +170,9 @@ impl TreeNode {
                 right_child: None,
                 leaf_node: true,
                 prediction,
+                class_freq,
+                impurity,
+                n_samples: mask.n_samples,
             };
         }
 
@@ -172,24 +209,28 @@ impl TreeNode {
         // Recurse and refit on left and right subtrees
         let left_child = match left_mask.n_samples {
             l if l > 0 => Some(Box::new(TreeNode::fit(
-                &x,
-                &y,
+                x,
+                y,
                 &left_mask,
-                &hyperparameters,
-                &sorted_indices,
+                hyperparameters,
+                criterion,
+                sorted_indices,
                 depth + 1,
+                left_seed,
             ))),
             _ => None,
         };
 
         let right_child = match right_mask.n_samples {
             l if l > 0 => Some(Box::new(TreeNode::fit(
-                &x,
-                &y,
+                x,
+                y,
                 &right_mask,
-                &hyperparameters,
-                &sorted_indices,
+                hyperparameters,
+                criterion,
+                sorted_indices,
                 depth + 1,
+                right_seed,
             ))),
             _ => None,
         };
@@ -203,39 +244,123 @@ impl TreeNode {
             right_child,
             leaf_node,
             prediction,
+            class_freq,
+            impurity,
+            n_samples: mask.n_samples,
         }
     }
 }
 
 /// A fitted decision tree model.
-pub struct DecisionTree {
+///
+/// A classification tree predicts class labels of type `u64`, a regression
+/// tree predicts continuous targets of type `f64`.
+pub struct DecisionTree<T = u64> {
     hyperparameters: DecisionTreeParams,
-    root_node: TreeNode,
+    root_node: TreeNode<T>,
+    n_features: usize,
 }
 
-impl DecisionTree {
+impl DecisionTree<u64> {
     /// Fit a decision tree using `hyperparamters` on the dataset consisting of
     /// a matrix of features `x` and an array of labels `y`.
     pub fn fit(
         hyperparameters: DecisionTreeParams,
         x: &ArrayBase<impl Data<Elem = f64>, Ix2>,
         y: &ArrayBase<impl Data<Elem = u64>, Ix1>,
+    ) -> Self {
+        let criterion = ClassificationCriterion {
+            n_classes: hyperparameters.n_classes,
+            split_quality: hyperparameters.split_quality,
+        };
+
+        Self::fit_with_criterion(hyperparameters, &criterion, x, y)
+    }
+
+    /// Predict the probabilities of the classes `0..n_classes` for each row of
+    /// a matrix of features `x`.
+    ///
+    /// The probabilities of a sample are the class frequencies of the training
+    /// samples in its leaf, so a pure leaf predicts its class with probability
+    /// one.
+    pub fn predict_proba(&self, x: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> Array2<f64> {
+        let mut probabilities = Array2::zeros((x.nrows(), self.hyperparameters.n_classes as usize));
+
+        for (row, mut probs) in x.genrows().into_iter().zip(probabilities.genrows_mut()) {
+            let class_freq = &find_leaf(&row, &self.root_node).class_freq;
+            let n_samples = class_freq.iter().sum::<u64>() as f64;
+
+            for (prob, freq) in probs.iter_mut().zip(class_freq.iter()) {
+                *prob = *freq as f64 / n_samples;
+            }
+        }
+
+        probabilities
+    }
+}
+
+impl DecisionTree<f64> {
+    /// Fit a regression tree using `hyperparamters` on the dataset consisting
+    /// of a matrix of features `x` and an array of continuous targets `y`.
+    ///
+    /// Splits are chosen by the reduction of the variance of the targets,
+    /// which is the mean squared error of predicting the mean target of a
+    /// node. The leaves predict the mean target of their samples. The minimal
+    /// impurity decrease is relative to the variance of `y`. The number of
+    /// classes and the split quality of `hyperparameters` are ignored.
+    pub fn fit_regression(
+        hyperparameters: DecisionTreeParams,
+        x: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        y: &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    ) -> Self {
+        Self::fit_with_criterion(hyperparameters, &VarianceCriterion, x, y)
+    }
+}
+
+impl<T: Copy> DecisionTree<T> {
+    fn fit_with_criterion<C: Criterion<Target = T>>(
+        hyperparameters: DecisionTreeParams,
+        criterion: &C,
+        x: &ArrayBase<impl Data<Elem = f64>, Ix2>,
+        y: &ArrayBase<impl Data<Elem = T>, Ix1>,
     ) -> Self {
         let all_idxs = RowMask::all(x.nrows() as u64);
         let sorted_indices: Vec<_> = (0..(x.ncols()))
-            .map(|feature_idx| SortedIndex::of_array_column(&x, feature_idx))
+            .map(|feature_idx| SortedIndex::of_array_column(x, feature_idx))
             .collect();
 
-        let root_node = TreeNode::fit(&x, &y, &all_idxs, &hyperparameters, &sorted_indices, 0);
+        // The nodes compare their impurity decrease relative to the scale of
+        // the impurity of all samples
+        let mut node_hyperparameters = hyperparameters;
+        node_hyperparameters.min_impurity_decrease *=
+            criterion.impurity_scale(&criterion.stats(y, &all_idxs));
+
+        let root_node = TreeNode::fit(
+            x,
+            y,
+            &all_idxs,
+            &node_hyperparameters,
+            criterion,
+            &sorted_indices,
+            0,
+            hyperparameters.seed,
+        );
 
-        Self {
+        let tree = Self {
             hyperparameters,
             root_node,
+            n_features: x.ncols(),
+        };
+
+        if hyperparameters.ccp_alpha > 0.0 {
+            tree.prune(hyperparameters.ccp_alpha)
+        } else {
+            tree
         }
     }
 
     /// Make predictions for each row of a matrix of features `x`.
-    pub fn predict(&self, x: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> Array1<u64> {
+    pub fn predict(&self, x: &ArrayBase<impl Data<Elem = f64>, Ix2>) -> Array1<T> {
         Array1::from_iter(
             x.genrows()
                 .into_iter()
@@ -246,22 +371,358 @@ impl DecisionTree {
     pub fn hyperparameters(&self) -> &DecisionTreeParams {
         &self.hyperparameters
     }
+
+    /// Return the number of leaves of the tree.
+    pub fn n_leaves(&self) -> usize {
+        self.root_node.n_leaves()
+    }
+
+    /// Prune the tree by minimal cost-complexity pruning with the complexity
+    /// parameter `ccp_alpha`.
+    ///
+    /// The cost of a tree is the impurity of its leaves, weighted by the
+    /// fraction of training samples in each leaf, plus `ccp_alpha` times the
+    /// number of leaves. The subtree whose collapse into a leaf increases the
+    /// impurity the least per removed leaf is collapsed repeatedly, as long
+    /// as this increase is at most `ccp_alpha`. The fitted tree is kept for
+    /// `ccp_alpha = 0` and larger values give smaller trees, down to a single
+    /// leaf. `ccp_alpha` is recorded in the hyperparameters of the pruned
+    /// tree.
+    pub fn prune(mut self, ccp_alpha: f64) -> Self {
+        let n_total = self.root_node.n_samples as f64;
+
+        while let Some(alpha) = self.root_node.weakest_link(n_total) {
+            if alpha > ccp_alpha {
+                break;
+            }
+            self.root_node.collapse_weakest_links(alpha, n_total);
+        }
+
+        self.hyperparameters.ccp_alpha = ccp_alpha;
+        self
+    }
+
+    /// Return the effective alphas of the minimal cost-complexity pruning
+    /// path, in increasing order.
+    ///
+    /// The first alpha is zero for the fitted tree, every following alpha
+    /// is the smallest `ccp_alpha` for which [prune](#method.prune) removes
+    /// the next subtree, the last one collapses the tree into a single leaf.
+    /// The alphas are candidates to select `ccp_alpha` by cross-validation.
+    pub fn cost_complexity_pruning_path(&self) -> Vec<f64> {
+        let n_total = self.root_node.n_samples as f64;
+        let mut root_node = self.root_node.clone();
+        let mut alphas = vec![0.0];
+
+        while let Some(alpha) = root_node.weakest_link(n_total) {
+            // Guard against rounding, the alphas of the path never decrease
+            alphas.push(alpha.max(*alphas.last().unwrap()));
+            root_node.collapse_weakest_links(alpha, n_total);
+        }
+
+        alphas
+    }
+
+    /// Return the impurity-based importance of each column of the features
+    /// the tree was fitted on.
+    ///
+    /// Every split contributes the decrease from the impurity of its node to
+    /// the impurities of its children, weighted by the number of samples, to
+    /// the importance of its feature. The importances are normalized to sum
+    /// to one, unless the tree consists of a single leaf and all importances
+    /// are zero.
+    pub fn feature_importances(&self) -> Array1<f64> {
+        let mut importances = Array1::zeros(self.n_features);
+        add_impurity_decrease(&self.root_node, &mut importances);
+
+        let total = importances.sum();
+        if total > 0.0 {
+            importances /= total;
+        }
+
+        importances
+    }
+}
+
+impl<T: Copy + fmt::Display> DecisionTree<T> {
+    /// Export the tree as a directed graph in the DOT language of Graphviz.
+    ///
+    /// Every node shows its split, impurity and number of training samples,
+    /// followed by the class frequencies and the predicted class for
+    /// classification or the predicted value for regression. The left child
+    /// of a split holds the samples whose feature is smaller than the
+    /// threshold. The features are named by `feature_names` if it contains
+    /// one name per feature and `x[i]` otherwise. The output can be rendered
+    /// directly, for example with `dot -Tpng tree.dot -o tree.png`.
+    pub fn to_dot(&self, feature_names: Option<&[&str]>) -> String {
+        let names = self.feature_names(feature_names);
+        let mut dot = String::from("digraph Tree {\n");
+        dot.push_str("node [shape=box, style=\"rounded\", fontname=\"helvetica\"];\n");
+        dot.push_str("edge [fontname=\"helvetica\"];\n");
+
+        let mut n_nodes = 0;
+        write_dot_node(
+            &self.root_node,
+            &names,
+            self.impurity_name(),
+            &mut n_nodes,
+            &mut dot,
+        );
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Export the tree as indented text with one line per node.
+    ///
+    /// The nodes show the same information as in [to_dot](#method.to_dot),
+    /// the children of a split are indented below it and marked `true` if
+    /// their samples are smaller than the threshold.
+    pub fn to_text(&self, feature_names: Option<&[&str]>) -> String {
+        let names = self.feature_names(feature_names);
+        let mut text = String::new();
+        write_text_node(
+            &self.root_node,
+            &names,
+            self.impurity_name(),
+            "",
+            0,
+            &mut text,
+        );
+
+        text
+    }
+
+    /// Print the [text representation](#method.to_text) of the tree.
+    pub fn print_tree(&self, feature_names: Option<&[&str]>) {
+        print!("{}", self.to_text(feature_names));
+    }
+
+    fn feature_names(&self, feature_names: Option<&[&str]>) -> Vec<String> {
+        match feature_names {
+            Some(names) if names.len() == self.n_features => {
+                names.iter().map(|name| name.to_string()).collect()
+            }
+            _ => (0..self.n_features).map(|i| format!("x[{}]", i)).collect(),
+        }
+    }
+
+    fn impurity_name(&self) -> &'static str {
+        if self.root_node.class_freq.is_empty() {
+            "variance"
+        } else {
+            match self.hyperparameters.split_quality {
+                SplitQuality::Gini => "gini",
+                SplitQuality::Entropy => "entropy",
+            }
+        }
+    }
+}
+
+/// The properties of `node` shown in the exported trees.
+fn describe_node<T: Copy + fmt::Display>(
+    node: &TreeNode<T>,
+    names: &[String],
+    impurity_name: &str,
+) -> Vec<String> {
+    let mut properties = Vec::new();
+    if !node.leaf_node {
+        properties.push(format!(
+            "{} < {:.3}",
+            names[node.feature_idx], node.split_value
+        ));
+    }
+    properties.push(format!("{} = {:.3}", impurity_name, node.impurity));
+    properties.push(format!("samples = {}", node.n_samples));
+    if node.class_freq.is_empty() {
+        properties.push(format!("value = {:.3}", node.prediction));
+    } else {
+        properties.push(format!("value = {:?}", node.class_freq));
+        properties.push(format!("class = {}", node.prediction));
+    }
+
+    properties
 }
 
-/// Classify a sample &x recursively using the tree node `node`.
-fn make_prediction(x: &ArrayBase<impl Data<Elem = f64>, Ix1>, node: &TreeNode) -> u64 {
+/// Write `node` and its subtree with ids in preorder to `dot` and return the
+/// id of `node`.
+fn write_dot_node<T: Copy + fmt::Display>(
+    node: &TreeNode<T>,
+    names: &[String],
+    impurity_name: &str,
+    n_nodes: &mut usize,
+    dot: &mut String,
+) -> usize {
+    let id = *n_nodes;
+    *n_nodes += 1;
+
+    let label = describe_node(node, names, impurity_name)
+        .iter()
+        .map(|property| property.replace('\\', "\\\\").replace('"', "\\\""))
+        .collect::<Vec<_>>()
+        .join("\\n");
+    dot.push_str(&format!("{} [label=\"{}\"];\n", id, label));
+
+    for (child, branch) in node.children().zip(&[true, false]) {
+        let child_id = write_dot_node(child, names, impurity_name, n_nodes, dot);
+        if id == 0 {
+            // Only the edges of the root are labelled, like in a flow chart
+            let angle = if *branch { 45 } else { -45 };
+            dot.push_str(&format!(
+                "{} -> {} [labeldistance=2.5, labelangle={}, headlabel=\"{}\"];\n",
+                id, child_id, angle, branch
+            ));
+        } else {
+            dot.push_str(&format!("{} -> {};\n", id, child_id));
+        }
+    }
+
+    id
+}
+
+/// Write `node` and its subtree to `text`, indented by `depth` levels.
+fn write_text_node<T: Copy + fmt::Display>(
+    node: &TreeNode<T>,
+    names: &[String],
+    impurity_name: &str,
+    branch: &str,
+    depth: usize,
+    text: &mut String,
+) {
+    if depth > 0 {
+        text.push_str(&"|   ".repeat(depth - 1));
+        text.push_str("|--- ");
+        text.push_str(branch);
+        text.push_str(": ");
+    }
+    text.push_str(&describe_node(node, names, impurity_name).join(", "));
+    text.push('\n');
+
+    for (child, branch) in node.children().zip(&["true", "false"]) {
+        write_text_node(child, names, impurity_name, branch, depth + 1, text);
+    }
+}
+
+/// Predict the target of a sample &x recursively using the tree node `node`.
+fn make_prediction<T: Copy>(x: &ArrayBase<impl Data<Elem = f64>, Ix1>, node: &TreeNode<T>) -> T {
+    find_leaf(x, node).prediction
+}
+
+impl<T> TreeNode<T> {
+    fn n_leaves(&self) -> usize {
+        if self.leaf_node {
+            1
+        } else {
+            self.children().map(|child| child.n_leaves()).sum()
+        }
+    }
+
+    fn children(&self) -> impl Iterator<Item = &TreeNode<T>> {
+        self.left_child
+            .iter()
+            .chain(self.right_child.iter())
+            .map(|child| child.as_ref())
+    }
+
+    /// The impurity of the node weighted by its fraction of the `n_total`
+    /// training samples.
+    fn weighted_impurity(&self, n_total: f64) -> f64 {
+        self.n_samples as f64 / n_total * self.impurity
+    }
+
+    /// The weighted impurity of the leaves below the node.
+    fn leaves_impurity(&self, n_total: f64) -> f64 {
+        if self.leaf_node {
+            self.weighted_impurity(n_total)
+        } else {
+            self.children()
+                .map(|child| child.leaves_impurity(n_total))
+                .sum()
+        }
+    }
+
+    /// The increase of the impurity per removed leaf when the node is
+    /// collapsed into a leaf.
+    fn effective_alpha(&self, n_total: f64) -> f64 {
+        (self.weighted_impurity(n_total) - self.leaves_impurity(n_total))
+            / (self.n_leaves() - 1) as f64
+    }
+
+    /// The smallest effective alpha of the inner nodes of the subtree, `None`
+    /// for a leaf.
+    fn weakest_link(&self, n_total: f64) -> Option<f64> {
+        if self.leaf_node {
+            return None;
+        }
+
+        self.children()
+            .filter_map(|child| child.weakest_link(n_total))
+            .chain(std::iter::once(self.effective_alpha(n_total)))
+            .fold(None, |min: Option<f64>, alpha| match min {
+                Some(min) if min <= alpha => Some(min),
+                _ => Some(alpha),
+            })
+    }
+
+    /// Collapse every inner node with an effective alpha of at most `alpha`
+    /// into a leaf, starting from the root.
+    fn collapse_weakest_links(&mut self, alpha: f64, n_total: f64) {
+        if self.leaf_node {
+            return;
+        }
+
+        // Allow for rounding in the sums of the impurities
+        if self.effective_alpha(n_total) <= alpha + 1e-12 {
+            self.leaf_node = true;
+            self.left_child = None;
+            self.right_child = None;
+        } else {
+            for child in self
+                .left_child
+                .iter_mut()
+                .chain(self.right_child.iter_mut())
+            {
+                child.collapse_weakest_links(alpha, n_total);
+            }
+        }
+    }
+}
+
+/// Add the weighted impurity decrease of the splits below `node` to the
+/// importances of their features.
+fn add_impurity_decrease<T>(node: &TreeNode<T>, importances: &mut Array1<f64>) {
     if node.leaf_node {
-        node.prediction
+        return;
+    }
+
+    let left = node.left_child.as_ref().unwrap();
+    let right = node.right_child.as_ref().unwrap();
+
+    importances[node.feature_idx] += node.n_samples as f64 * node.impurity
+        - left.n_samples as f64 * left.impurity
+        - right.n_samples as f64 * right.impurity;
+
+    add_impurity_decrease(left, importances);
+    add_impurity_decrease(right, importances);
+}
+
+/// Find the leaf of a sample &x recursively starting from the tree node `node`.
+fn find_leaf<'a, T>(
+    x: &ArrayBase<impl Data<Elem = f64>, Ix1>,
+    node: &'a TreeNode<T>,
+) -> &'a TreeNode<T> {
+    if node.leaf_node {
+        node
     } else if x[node.feature_idx] < node.split_value {
-        make_prediction(x, node.left_child.as_ref().unwrap())
+        find_leaf(x, node.left_child.as_ref().unwrap())
     } else {
-        make_prediction(x, node.right_child.as_ref().unwrap())
+        find_leaf(x, node.right_child.as_ref().unwrap())
     }
 }
 
 /// Given an array of labels and a row mask `mask` calculate the frequency of
 /// each class from 0 to `n_classes-1`.
-fn class_frequencies(
+pub(crate) fn class_frequencies(
     labels: &ArrayBase<impl Data<Elem = u64>, Ix1>,
     mask: &RowMask,
     n_classes: u64,
@@ -283,7 +744,7 @@ fn class_frequencies(
 /// Make a point prediction for a subset of rows in the dataset based on the
 /// class that occurs the most frequent. If two classes occur with the same
 /// frequency then the first class is selected.
-fn prediction_for_rows(class_freq: &[u64]) -> u64 {
+pub(crate) fn prediction_for_rows(class_freq: &[u64]) -> u64 {
     class_freq
         .iter()
         .enumerate()
@@ -302,7 +763,7 @@ fn prediction_for_rows(class_freq: &[u64]) -> u64 {
 }
 
 /// Given the class frequencies calculates the gini impurity of the subset.
-fn gini_impurity(class_freq: &[u64]) -> f64 {
+pub(crate) fn gini_impurity(class_freq: &[u64]) -> f64 {
     let n_samples: u64 = class_freq.iter().sum();
     assert!(n_samples > 0);
 
@@ -316,7 +777,7 @@ fn gini_impurity(class_freq: &[u64]) -> f64 {
 }
 
 /// Given the class frequencies calculates the entropy of the subset.
-fn entropy(class_freq: &[u64]) -> f64 {
+pub(crate) fn entropy(class_freq: &[u64]) -> f64 {
     let n_samples: u64 = class_freq.iter().sum();
     assert!(n_samples > 0);
 
@@ -330,8 +791,15 @@ fn entropy(class_freq: &[u64]) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decision_trees::hyperparameters::{MaxFeatures, SplitQuality};
     use approx::assert_abs_diff_eq;
-    use ndarray::Array;
+    use linfa_clustering::generate_blobs;
+    use ndarray::{array, Array, Array2};
+    use ndarray_rand::rand::Rng;
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
 
     fn of_vec(mask: Vec<bool>) -> RowMask {
         RowMask {
@@ -394,4 +862,299 @@ mod tests {
         let perfect_class_freq = vec![8, 0, 0];
         assert_abs_diff_eq!(entropy(&perfect_class_freq), 0.0, epsilon = 1e-5);
     }
+
+    #[test]
+    fn stump_splits_between_samples() {
+        let x = array![[0.0], [1.0], [2.0], [3.0]];
+        let y = array![0, 0, 1, 1];
+        let hyperparams = DecisionTreeParams::new(2).max_depth(Some(1)).build();
+
+        let model = DecisionTree::fit(hyperparams, &x, &y);
+        assert_abs_diff_eq!(model.root_node.split_value, 1.5);
+        assert_eq!(model.predict(&array![[1.4], [1.6]]), array![0, 1]);
+    }
+
+    #[test]
+    fn regression_tree_fits_step_function() {
+        let x = Array::from_shape_fn((20, 1), |(i, _)| i as f64);
+        let y = Array::from_shape_fn(20, |i| [3.0, -1.0, 7.0, 0.0][i / 5]);
+        let hyperparams = DecisionTreeParams::regression().build();
+
+        let model = DecisionTree::fit_regression(hyperparams, &x, &y);
+        let mse = (model.predict(&x) - &y).mapv(|e| e * e).mean().unwrap();
+        assert_abs_diff_eq!(mse, 0.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn regression_stump_finds_best_threshold() {
+        let x = Array::from_shape_fn((10, 1), |(i, _)| i as f64);
+        let y = array![1.0, 1.2, 0.8, 1.0, 1.1, 0.9, 5.0, 5.2, 4.8, 5.0];
+        let hyperparams = DecisionTreeParams::regression().max_depth(Some(1)).build();
+
+        let model = DecisionTree::fit_regression(hyperparams, &x, &y);
+        assert_eq!(model.root_node.feature_idx, 0);
+        assert_abs_diff_eq!(model.root_node.split_value, 5.5);
+        assert!(model.root_node.left_child.as_ref().unwrap().leaf_node);
+        assert!(model.root_node.right_child.as_ref().unwrap().leaf_node);
+
+        // The leaves predict the mean target of each side
+        let pred = model.predict(&array![[0.0], [5.4], [5.6], [9.0]]);
+        assert_abs_diff_eq!(pred, array![1.0, 1.0, 5.0, 5.0], epsilon = 1e-10);
+    }
+
+    #[test]
+    fn regression_split_does_not_depend_on_target_scale() {
+        let x = Array::from_shape_fn((20, 1), |(i, _)| i as f64);
+        let step = Array::from_shape_fn(20, |i| [3.0, -1.0, 7.0, 0.0][i / 5]);
+        let hyperparams = DecisionTreeParams::regression().build();
+
+        // the variance of the targets is far below the default minimal
+        // impurity decrease, and for the large offset far below their squares
+        for y in &[&step * 1e-4, &step + 1e8] {
+            let model = DecisionTree::fit_regression(hyperparams, &x, y);
+            assert_abs_diff_eq!(model.predict(&x), *y, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn predict_proba_returns_leaf_frequencies() {
+        let x = array![[0.0], [1.0], [2.0], [3.0], [4.0], [5.0], [6.0], [7.0]];
+        let y = array![0, 1, 0, 0, 1, 1, 0, 1];
+        let hyperparams = DecisionTreeParams::new(3).max_depth(Some(1)).build();
+
+        // The best split at 3.5 leaves one sample of the other class on each side
+        let model = DecisionTree::fit(hyperparams, &x, &y);
+        assert_abs_diff_eq!(model.root_node.split_value, 3.5);
+        let probs = model.predict_proba(&array![[0.0], [7.0]]);
+        assert_abs_diff_eq!(
+            probs,
+            array![[0.75, 0.25, 0.], [0.25, 0.75, 0.]],
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(model.predict_proba(&x).sum_axis(Axis(1)), Array::ones(8));
+
+        // Pure leaves give one-hot distributions
+        let y = array![0, 0, 0, 0, 2, 2, 2, 2];
+        let model = DecisionTree::fit(hyperparams, &x, &y);
+        assert_abs_diff_eq!(
+            model.predict_proba(&array![[1.0], [4.0]]),
+            array![[1., 0., 0.], [0., 0., 1.]]
+        );
+    }
+
+    #[test]
+    fn importances_concentrate_on_informative_features() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((200, 10), Uniform::new(0., 1.), &mut rng);
+        // Only the features 2 and 7 determine the class
+        let y = Array::from_iter(
+            x.genrows()
+                .into_iter()
+                .map(|row| (row[2] > 0.5) as u64 + (row[7] > 0.3) as u64),
+        );
+        let hyperparams = DecisionTreeParams::new(3).build();
+
+        let model = DecisionTree::fit(hyperparams, &x, &y);
+        let importances = model.feature_importances();
+
+        assert_eq!(importances.len(), 10);
+        assert_abs_diff_eq!(importances.sum(), 1.0, epsilon = 1e-10);
+        assert!(importances[2] + importances[7] > 0.95);
+        assert!(importances[2] > 0.1 && importances[7] > 0.1);
+
+        // A single leaf has no splits
+        let hyperparams = DecisionTreeParams::new(3).max_depth(Some(0)).build();
+        let model = DecisionTree::fit(hyperparams, &x, &y);
+        assert_eq!(model.feature_importances(), Array1::zeros(10));
+    }
+
+    #[test]
+    fn gini_and_entropy_trees_agree_on_blobs() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let centroids = array![[0., 0.], [1., 4.], [-5., 0.], [4., 4.]];
+        let n = 50;
+        let labels = Array::from_iter((0..4).flat_map(|class| vec![class; n]));
+
+        let train_x = generate_blobs(n, &centroids, &mut rng);
+        let test_x = generate_blobs(n, &centroids, &mut rng);
+
+        let mut accuracies = Vec::new();
+        for split_quality in &[SplitQuality::Gini, SplitQuality::Entropy] {
+            let hyperparams = DecisionTreeParams::new(4)
+                .split_quality(*split_quality)
+                .max_depth(Some(5))
+                .build();
+
+            let model = DecisionTree::fit(hyperparams, &train_x, &labels);
+            assert_eq!(model.hyperparameters().split_quality, *split_quality);
+
+            let pred = model.predict(&test_x);
+            let accuracy = pred
+                .iter()
+                .zip(labels.iter())
+                .filter(|(p, l)| p == l)
+                .count() as f64
+                / labels.len() as f64;
+            assert!(accuracy > 0.8);
+            accuracies.push(accuracy);
+        }
+
+        assert!((accuracies[0] - accuracies[1]).abs() < 0.1);
+    }
+
+    /// Two uniform features, the class is determined by the first one but a
+    /// quarter of the labels is flipped.
+    fn noisy_dataset(n_samples: usize, rng: &mut Isaac64Rng) -> (Array2<f64>, Array1<u64>) {
+        let x = Array2::random_using((n_samples, 2), Uniform::new(0., 1.), rng);
+        let y = Array::from_iter(
+            x.genrows()
+                .into_iter()
+                .map(|row| ((row[0] > 0.5) ^ rng.gen_bool(0.25)) as u64),
+        );
+
+        (x, y)
+    }
+
+    fn accuracy(model: &DecisionTree, x: &Array2<f64>, y: &Array1<u64>) -> f64 {
+        let pred = model.predict(x);
+        pred.iter().zip(y.iter()).filter(|(p, y)| p == y).count() as f64 / y.len() as f64
+    }
+
+    #[test]
+    fn pruning_path_ends_in_single_leaf() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let (x, y) = noisy_dataset(200, &mut rng);
+        let hyperparams = DecisionTreeParams::new(2).build();
+        let n_leaves = DecisionTree::fit(hyperparams, &x, &y).n_leaves();
+        assert!(n_leaves > 10);
+
+        // Pruning with zero alpha keeps the tree
+        let pruned = DecisionTree::fit(hyperparams, &x, &y).prune(0.0);
+        assert_eq!(pruned.n_leaves(), n_leaves);
+        assert_eq!(
+            pruned.predict(&x),
+            DecisionTree::fit(hyperparams, &x, &y).predict(&x)
+        );
+
+        let path = DecisionTree::fit(hyperparams, &x, &y).cost_complexity_pruning_path();
+        assert_eq!(path[0], 0.0);
+        assert!(path.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // Larger alphas give smaller trees
+        let mut previous_leaves = n_leaves;
+        for alpha in &path[1..] {
+            let leaves = DecisionTree::fit(hyperparams, &x, &y)
+                .prune(*alpha)
+                .n_leaves();
+            assert!(leaves <= previous_leaves);
+            previous_leaves = leaves;
+        }
+
+        // The last alpha collapses the tree into a single leaf, which predicts
+        // the majority class, ties go to the last class
+        let hyperparams = DecisionTreeParams::new(2)
+            .ccp_alpha(*path.last().unwrap())
+            .build();
+        let stump = DecisionTree::fit(hyperparams, &x, &y);
+        assert_eq!(stump.n_leaves(), 1);
+        assert_eq!(stump.hyperparameters().ccp_alpha, *path.last().unwrap());
+        let majority = (y.sum() * 2 >= y.len() as u64) as u64;
+        assert!(stump.predict(&x).iter().all(|p| *p == majority));
+    }
+
+    #[test]
+    fn selected_alpha_improves_held_out_accuracy() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let (train_x, train_y) = noisy_dataset(300, &mut rng);
+        let (valid_x, valid_y) = noisy_dataset(300, &mut rng);
+        let (test_x, test_y) = noisy_dataset(300, &mut rng);
+        let hyperparams = DecisionTreeParams::new(2).build();
+
+        let full_tree = DecisionTree::fit(hyperparams, &train_x, &train_y);
+        let path = full_tree.cost_complexity_pruning_path();
+
+        // Select the alpha with the best accuracy on the validation set
+        let mut best = (0.0, accuracy(&full_tree, &valid_x, &valid_y));
+        for alpha in &path[1..] {
+            let pruned = DecisionTree::fit(hyperparams, &train_x, &train_y).prune(*alpha);
+            let valid_accuracy = accuracy(&pruned, &valid_x, &valid_y);
+            if valid_accuracy > best.1 {
+                best = (*alpha, valid_accuracy);
+            }
+        }
+
+        let pruned = DecisionTree::fit(hyperparams, &train_x, &train_y).prune(best.0);
+        assert!(pruned.n_leaves() < full_tree.n_leaves());
+        assert!(accuracy(&pruned, &test_x, &test_y) > accuracy(&full_tree, &test_x, &test_y));
+    }
+
+    #[test]
+    fn max_features_is_reproducible_from_the_seed() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let x = Array2::random_using((200, 10), Uniform::new(0., 1.), &mut rng);
+        let y = x.map_axis(Axis(1), |row| ((row[2] > 0.5) ^ (row[7] > 0.3)) as u64);
+
+        assert_eq!(MaxFeatures::Sqrt.n_features(10), 3);
+        assert_eq!(MaxFeatures::Log2.n_features(10), 3);
+        assert_eq!(MaxFeatures::Fraction(0.01).n_features(10), 1);
+        assert_eq!(MaxFeatures::Count(20).n_features(10), 10);
+
+        let fit = |seed| {
+            let hyperparams = DecisionTreeParams::new(2)
+                .max_features(MaxFeatures::Sqrt)
+                .seed(seed)
+                .build();
+            DecisionTree::fit(hyperparams, &x, &y)
+        };
+
+        let tree = fit(7);
+        let same_seed = fit(7);
+        assert_eq!(tree.predict(&x), same_seed.predict(&x));
+        assert_eq!(tree.feature_importances(), same_seed.feature_importances());
+
+        // A different subset of features at the root alone changes the tree
+        let n_different = (0..10)
+            .filter(|seed| fit(*seed).feature_importances() != tree.feature_importances())
+            .count();
+        assert!(n_different >= 5);
+    }
+
+    #[test]
+    fn export_stump() {
+        let x = array![[0.], [1.], [2.], [3.], [4.]];
+        let y = array![0, 0, 0, 1, 1];
+        let tree = DecisionTree::fit(DecisionTreeParams::new(2).build(), &x, &y);
+
+        let expected = r#"digraph Tree {
+node [shape=box, style="rounded", fontname="helvetica"];
+edge [fontname="helvetica"];
+0 [label="width \"cm\" < 2.500\ngini = 0.480\nsamples = 5\nvalue = [3, 2]\nclass = 0"];
+1 [label="gini = 0.000\nsamples = 3\nvalue = [3, 0]\nclass = 0"];
+0 -> 1 [labeldistance=2.5, labelangle=45, headlabel="true"];
+2 [label="gini = 0.000\nsamples = 2\nvalue = [0, 2]\nclass = 1"];
+0 -> 2 [labeldistance=2.5, labelangle=-45, headlabel="false"];
+}
+"#;
+        assert_eq!(tree.to_dot(Some(&["width \"cm\""][..])), expected);
+
+        let expected = "\
+x[0] < 2.500, gini = 0.480, samples = 5, value = [3, 2], class = 0
+|--- true: gini = 0.000, samples = 3, value = [3, 0], class = 0
+|--- false: gini = 0.000, samples = 2, value = [0, 2], class = 1
+";
+        assert_eq!(tree.to_text(None), expected);
+        // Names which do not match the features are not used
+        assert_eq!(tree.to_text(Some(&["width", "height"][..])), expected);
+        assert_eq!(tree.to_text(Some(&[][..])), expected);
+
+        // Regression trees show the variance and the predicted value
+        let y = array![1., 1., 1., 4., 4.];
+        let tree = DecisionTree::fit_regression(DecisionTreeParams::regression().build(), &x, &y);
+        let text = tree.to_text(None);
+        assert_eq!(
+            text.lines().next().unwrap(),
+            "x[0] < 2.500, variance = 2.160, samples = 5, value = 2.200"
+        );
+        assert_eq!(text.lines().count(), 3);
+    }
 }
@@ -0,0 +1,240 @@
+use crate::decision_trees::algorithm::{
+    class_frequencies, entropy, gini_impurity, prediction_for_rows, RowMask,
+};
+use crate::decision_trees::hyperparameters::SplitQuality;
+use ndarray::{ArrayBase, Data, Ix1};
+
+/// A `Criterion` measures the impurity of the targets of the samples in a node.
+///
+/// The split search moves the samples of a node one by one from the right to
+/// the left subset and only updates the statistics of both subsets, so that
+/// classification and regression trees share the same search.
+pub(crate) trait Criterion {
+    /// The type of the targets and of the predictions
+    type Target: Copy;
+    /// The statistics of the targets of a subset of samples
+    type Stats: Clone;
+
+    /// Calculate the statistics of the targets of the rows in `mask`.
+    fn stats(
+        &self,
+        y: &ArrayBase<impl Data<Elem = Self::Target>, Ix1>,
+        mask: &RowMask,
+    ) -> Self::Stats;
+
+    /// The statistics of an empty subset.
+    fn empty(&self) -> Self::Stats;
+
+    /// Add the target of a sample to the subset.
+    fn add(&self, stats: &mut Self::Stats, y: Self::Target);
+
+    /// Remove the target of a sample from the subset.
+    fn remove(&self, stats: &mut Self::Stats, y: Self::Target);
+
+    fn n_samples(&self, stats: &Self::Stats) -> u64;
+
+    fn impurity(&self, stats: &Self::Stats) -> f64;
+
+    /// The scale of the impurity of all samples `stats`. The minimal impurity
+    /// decrease of a split is relative to it.
+    fn impurity_scale(&self, stats: &Self::Stats) -> f64;
+
+    /// The point prediction for the samples of the subset.
+    fn prediction(&self, stats: &Self::Stats) -> Self::Target;
+
+    /// The class frequencies of the subset, empty if the targets are not classes.
+    fn class_counts(&self, stats: &Self::Stats) -> Vec<u64>;
+}
+
+/// Splits classes by the gini impurity or the entropy of their frequencies.
+pub(crate) struct ClassificationCriterion {
+    pub n_classes: u64,
+    pub split_quality: SplitQuality,
+}
+
+impl Criterion for ClassificationCriterion {
+    type Target = u64;
+    type Stats = Vec<u64>;
+
+    fn stats(&self, y: &ArrayBase<impl Data<Elem = u64>, Ix1>, mask: &RowMask) -> Vec<u64> {
+        class_frequencies(y, mask, self.n_classes)
+    }
+
+    fn empty(&self) -> Vec<u64> {
+        vec![0; self.n_classes as usize]
+    }
+
+    fn add(&self, class_freq: &mut Vec<u64>, y: u64) {
+        class_freq[y as usize] += 1;
+    }
+
+    fn remove(&self, class_freq: &mut Vec<u64>, y: u64) {
+        class_freq[y as usize] -= 1;
+    }
+
+    fn n_samples(&self, class_freq: &Vec<u64>) -> u64 {
+        class_freq.iter().sum()
+    }
+
+    fn impurity(&self, class_freq: &Vec<u64>) -> f64 {
+        match self.split_quality {
+            SplitQuality::Gini => gini_impurity(class_freq),
+            SplitQuality::Entropy => entropy(class_freq),
+        }
+    }
+
+    fn impurity_scale(&self, _class_freq: &Vec<u64>) -> f64 {
+        // both impurities are bounded independently of the targets
+        1.0
+    }
+
+    fn prediction(&self, class_freq: &Vec<u64>) -> u64 {
+        prediction_for_rows(class_freq)
+    }
+
+    fn class_counts(&self, class_freq: &Vec<u64>) -> Vec<u64> {
+        class_freq.clone()
+    }
+}
+
+/// Splits continuous targets by the reduction of their variance, which is the
+/// mean squared error of predicting the mean of each subset.
+pub(crate) struct VarianceCriterion;
+
+/// The number of samples, the mean of their targets and the sum of squared
+/// deviations from the mean.
+///
+/// The moments are updated with Welford's method, the sum of squares of the
+/// targets would cancel for targets with a large mean.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Moments {
+    n_samples: u64,
+    mean: f64,
+    sum_sq_dev: f64,
+}
+
+impl Criterion for VarianceCriterion {
+    type Target = f64;
+    type Stats = Moments;
+
+    fn stats(&self, y: &ArrayBase<impl Data<Elem = f64>, Ix1>, mask: &RowMask) -> Moments {
+        assert!(mask.n_samples > 0);
+
+        let mut moments = self.empty();
+        for (idx, included) in mask.mask.iter().enumerate() {
+            if *included {
+                self.add(&mut moments, y[idx]);
+            }
+        }
+
+        moments
+    }
+
+    fn empty(&self) -> Moments {
+        Moments {
+            n_samples: 0,
+            mean: 0.0,
+            sum_sq_dev: 0.0,
+        }
+    }
+
+    fn add(&self, moments: &mut Moments, y: f64) {
+        moments.n_samples += 1;
+        let delta = y - moments.mean;
+        moments.mean += delta / moments.n_samples as f64;
+        moments.sum_sq_dev += delta * (y - moments.mean);
+    }
+
+    fn remove(&self, moments: &mut Moments, y: f64) {
+        if moments.n_samples == 1 {
+            *moments = self.empty();
+            return;
+        }
+
+        // invert the update of `add`
+        moments.n_samples -= 1;
+        let delta = y - moments.mean;
+        moments.mean -= delta / moments.n_samples as f64;
+        moments.sum_sq_dev -= delta * (y - moments.mean);
+    }
+
+    fn n_samples(&self, moments: &Moments) -> u64 {
+        moments.n_samples
+    }
+
+    fn impurity(&self, moments: &Moments) -> f64 {
+        assert!(moments.n_samples > 0);
+
+        // removing samples may leave a tiny negative value for constant targets
+        (moments.sum_sq_dev / moments.n_samples as f64).max(0.0)
+    }
+
+    fn impurity_scale(&self, moments: &Moments) -> f64 {
+        // the decrease of the variance scales with the squared targets, constant
+        // targets are never split
+        let variance = self.impurity(moments);
+        if variance > 0.0 {
+            variance
+        } else {
+            1.0
+        }
+    }
+
+    fn prediction(&self, moments: &Moments) -> f64 {
+        moments.mean
+    }
+
+    fn class_counts(&self, _moments: &Moments) -> Vec<u64> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+
+    #[test]
+    fn variance_example() {
+        let y = array![1.0, 2.0, 3.0, 6.0];
+        let criterion = VarianceCriterion;
+
+        let mut moments = criterion.stats(&y, &RowMask::all(4));
+        // Mean is 3, squared deviations are 4, 1, 0 and 9
+        assert_abs_diff_eq!(criterion.prediction(&moments), 3.0);
+        assert_abs_diff_eq!(criterion.impurity(&moments), 3.5, epsilon = 1e-10);
+
+        criterion.remove(&mut moments, 6.0);
+        assert_eq!(criterion.n_samples(&moments), 3);
+        assert_abs_diff_eq!(criterion.prediction(&moments), 2.0);
+        assert_abs_diff_eq!(criterion.impurity(&moments), 2.0 / 3.0, epsilon = 1e-10);
+
+        // Constant targets are pure
+        let mut moments = criterion.empty();
+        for _ in 0..3 {
+            criterion.add(&mut moments, 0.1);
+        }
+        assert_abs_diff_eq!(criterion.impurity(&moments), 0.0);
+    }
+
+    #[test]
+    fn variance_of_large_targets() {
+        // the squares of the targets differ only in the last bits of a double
+        let y = array![1e8 - 1.0, 1e8 + 1.0, 1e8 - 1.0, 1e8 + 1.0, 1e8 + 3.0];
+        let criterion = VarianceCriterion;
+
+        let mut moments = criterion.stats(&y, &RowMask::all(5));
+        assert_abs_diff_eq!(criterion.prediction(&moments), 1e8 + 0.6, epsilon = 1e-6);
+        assert_abs_diff_eq!(criterion.impurity(&moments), 2.24, epsilon = 1e-6);
+
+        criterion.remove(&mut moments, 1e8 + 3.0);
+        assert_abs_diff_eq!(criterion.impurity(&moments), 1.0, epsilon = 1e-6);
+        criterion.remove(&mut moments, 1e8 + 1.0);
+        criterion.remove(&mut moments, 1e8 + 1.0);
+        assert_abs_diff_eq!(criterion.impurity(&moments), 0.0, epsilon = 1e-6);
+        criterion.remove(&mut moments, 1e8 - 1.0);
+        criterion.remove(&mut moments, 1e8 - 1.0);
+        assert_eq!(moments, criterion.empty());
+    }
+}
@@ -1,10 +1,54 @@
 /// The possible impurity measures for training.
-#[derive(Clone, Copy)]
+///
+/// Both measures are zero for pure nodes and maximal if all classes are
+/// equally frequent. They usually agree on the best split, the entropy
+/// penalizes rare classes slightly more.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SplitQuality {
+    /// The probability of misclassifying a sample which is labeled randomly
+    /// by the class frequencies, `1 - sum_k p_k^2`
     Gini,
+    /// The information content of the class frequencies in bits,
+    /// `-sum_k p_k log2(p_k)`, where empty classes contribute zero
     Entropy,
 }
 
+/// The number of features examined by the split search of each node.
+///
+/// A random subset of the features is drawn for every node, which
+/// decorrelates the trees of an ensemble and regularizes a single tree. The
+/// number of features is at least one and at most the number of features of
+/// the dataset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MaxFeatures {
+    /// Examine all features, the tree does not depend on the seed
+    All,
+    /// Examine a fixed number of features
+    Count(usize),
+    /// Examine a fraction of the features, rounded down
+    Fraction(f64),
+    /// Examine the square root of the number of features, rounded down
+    Sqrt,
+    /// Examine the binary logarithm of the number of features, rounded down
+    Log2,
+}
+
+impl MaxFeatures {
+    /// The number of features examined for a dataset with `n_features`
+    /// features.
+    pub fn n_features(&self, n_features: usize) -> usize {
+        let n_examined = match *self {
+            MaxFeatures::All => n_features,
+            MaxFeatures::Count(count) => count,
+            MaxFeatures::Fraction(fraction) => (fraction * n_features as f64) as usize,
+            MaxFeatures::Sqrt => (n_features as f64).sqrt() as usize,
+            MaxFeatures::Log2 => (n_features as f64).log2() as usize,
+        };
+
+        n_examined.max(1).min(n_features)
+    }
+}
+
 /// The set of hyperparameters that can be specified for fitting a
 /// [decision tree](struct.DecisionTree.html).
 #[derive(Clone, Copy)]
@@ -15,6 +59,9 @@ pub struct DecisionTreeParams {
     pub min_samples_split: u64,
     pub min_samples_leaf: u64,
     pub min_impurity_decrease: f64,
+    pub ccp_alpha: f64,
+    pub max_features: MaxFeatures,
+    pub seed: u64,
 }
 
 /// A helper struct to build the hyperparameters for a decision tree.
@@ -25,6 +72,9 @@ pub struct DecisionTreeParamsBuilder {
     min_samples_split: u64,
     min_samples_leaf: u64,
     min_impurity_decrease: f64,
+    ccp_alpha: f64,
+    max_features: MaxFeatures,
+    seed: u64,
 }
 
 impl DecisionTreeParamsBuilder {
@@ -58,25 +108,47 @@ impl DecisionTreeParamsBuilder {
         self
     }
 
+    pub fn ccp_alpha(mut self, ccp_alpha: f64) -> Self {
+        self.ccp_alpha = ccp_alpha;
+        self
+    }
+
+    pub fn max_features(mut self, max_features: MaxFeatures) -> Self {
+        self.max_features = max_features;
+        self
+    }
+
+    /// Set the seed of the random number generator which draws the features
+    /// examined by each node.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
     pub fn build(&self) -> DecisionTreeParams {
-        DecisionTreeParams::build(
-            self.n_classes,
-            self.split_quality,
-            self.max_depth,
-            self.min_samples_split,
-            self.min_samples_leaf,
-            self.min_impurity_decrease,
-        )
+        DecisionTreeParams::build(self)
     }
 }
 
 impl DecisionTreeParams {
     /// Defaults are provided if the optional parameters are not specified:
     /// * `split_quality = SplitQuality::Gini`
-    /// * `max_depth = None`
+    /// * `max_depth = None`, the root node has depth zero and a tree of
+    ///   `max_depth = Some(1)` consists of a single split
     /// * `min_samples_split = 2`
     /// * `min_samples_leaf = 1`
-    /// * `min_impurity_decrease = 0.00001`
+    /// * `min_impurity_decrease = 0.00001`, a node is only split if the
+    ///   decrease of the impurity, weighted by the fraction of samples in the
+    ///   node, is at least this large. For regression trees the threshold is
+    ///   relative to the variance of all targets, so that it does not depend
+    ///   on their scale
+    /// * `ccp_alpha = 0.0`, the fitted tree is
+    ///   [pruned](struct.DecisionTree.html#method.prune) with this complexity
+    ///   parameter if it is positive
+    /// * `max_features = MaxFeatures::All`
+    /// * `seed = 42`, the features of a node are drawn by a generator seeded
+    ///   from this seed and the path to the node, so that fits with the same
+    ///   seed are identical
     // Violates the convention that new should return a value of type `Self`
     #[allow(clippy::new_ret_no_self)]
     pub fn new(n_classes: u64) -> DecisionTreeParamsBuilder {
@@ -87,26 +159,33 @@ impl DecisionTreeParams {
             min_samples_split: 2,
             min_samples_leaf: 1,
             min_impurity_decrease: 0.00001,
+            ccp_alpha: 0.0,
+            max_features: MaxFeatures::All,
+            seed: 42,
         }
     }
 
-    fn build(
-        n_classes: u64,
-        split_quality: SplitQuality,
-        max_depth: Option<u64>,
-        min_samples_split: u64,
-        min_samples_leaf: u64,
-        min_impurity_decrease: f64,
-    ) -> Self {
+    /// Hyperparameters of a [regression tree](struct.DecisionTree.html#method.fit_regression)
+    ///
+    /// The defaults are the same as for classification, the number of classes
+    /// and the split quality are not used by regression trees.
+    pub fn regression() -> DecisionTreeParamsBuilder {
+        DecisionTreeParams::new(0)
+    }
+
+    fn build(builder: &DecisionTreeParamsBuilder) -> Self {
         // TODO: Check parameters
 
         DecisionTreeParams {
-            n_classes,
-            split_quality,
-            max_depth,
-            min_samples_split,
-            min_samples_leaf,
-            min_impurity_decrease,
+            n_classes: builder.n_classes,
+            split_quality: builder.split_quality,
+            max_depth: builder.max_depth,
+            min_samples_split: builder.min_samples_split,
+            min_samples_leaf: builder.min_samples_leaf,
+            min_impurity_decrease: builder.min_impurity_decrease,
+            ccp_alpha: builder.ccp_alpha,
+            max_features: builder.max_features,
+            seed: builder.seed,
         }
     }
 }
@@ -1,4 +1,5 @@
 mod algorithm;
+mod criterion;
 mod hyperparameters;
 
 pub use algorithm::*;
@@ -0,0 +1,151 @@
+//! Bootstrap resampling
+//!
+//! A bootstrap sample draws as many samples as the dataset contains with replacement. Fitting a
+//! model to many bootstrap samples estimates the sampling uncertainty of its parameters, and the
+//! samples which were not drawn, the out-of-bag samples, can be used for validation.
+use ndarray::{Array1, Array2};
+use rand::Rng;
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+
+/// Generator of bootstrap samples
+///
+/// The random number generator is passed to `new`, a seeded generator gives reproducible samples,
+/// for example
+/// ```rust, ignore
+/// let mut bootstrap = Bootstrap::new(100, Isaac64Rng::seed_from_u64(42));
+/// let slopes = bootstrap
+///     .sample(&dataset)?
+///     .map(|(_, sample)| LinearRegression::new().fit(&sample).map(|model| model.params()[0]))
+///     .collect::<Result<Vec<_>>>()?;
+/// ```
+///
+/// The drawn indices identify the out-of-bag samples of each bootstrap sample
+/// ```rust, ignore
+/// for (indices, sample) in bootstrap.sample(&dataset)? {
+///     let oob = dataset.select_samples(&out_of_bag(&indices, dataset.targets().len()))?;
+///     ...
+/// }
+/// ```
+pub struct Bootstrap<R> {
+    n_bootstrap: usize,
+    rng: R,
+}
+
+impl<R: Rng> Bootstrap<R> {
+    /// Create a generator of `n_bootstrap` samples
+    pub fn new(n_bootstrap: usize, rng: R) -> Bootstrap<R> {
+        Bootstrap { n_bootstrap, rng }
+    }
+
+    /// Return the indices of each bootstrap sample of a dataset with `n_samples` samples
+    ///
+    /// Each sample contains `n_samples` indices drawn with replacement.
+    pub fn indices(&mut self, n_samples: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+        let rng = &mut self.rng;
+        (0..self.n_bootstrap).map(move |_| {
+            (0..n_samples)
+                .map(|_| rng.gen_range(0, n_samples))
+                .collect()
+        })
+    }
+
+    /// Return the drawn indices and the bootstrap sample of `dataset` for each bootstrap sample
+    ///
+    /// The weights, feature and target names are kept. The indices can be passed to
+    /// [out_of_bag](fn.out_of_bag.html) to validate on the samples which were not drawn. Returns an
+    /// error if the dataset is empty.
+    pub fn sample<'a, A: Copy, L: Clone>(
+        &'a mut self,
+        dataset: &'a Dataset<Array2<A>, Array1<L>>,
+    ) -> Result<impl Iterator<Item = (Vec<usize>, Dataset<Array2<A>, Array1<L>>)> + 'a> {
+        let n_samples = dataset.targets().len();
+        if n_samples == 0 {
+            return Err(Error::Parameters(
+                "cannot draw bootstrap samples from an empty dataset".to_string(),
+            ));
+        }
+
+        Ok(self.indices(n_samples).map(move |indices| {
+            let sample = dataset.select_samples_unchecked(&indices);
+            (indices, sample)
+        }))
+    }
+}
+
+/// Return the indices in `0..n` which are not contained in `sampled_indices`
+///
+/// These are the out-of-bag samples of a bootstrap sample, in increasing order.
+pub fn out_of_bag(sampled_indices: &[usize], n: usize) -> Vec<usize> {
+    let mut sampled = vec![false; n];
+    for idx in sampled_indices {
+        if *idx < n {
+            sampled[*idx] = true;
+        }
+    }
+
+    (0..n).filter(|idx| !sampled[*idx]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{out_of_bag, Bootstrap};
+    use crate::dataset::Dataset;
+    use crate::error::Error;
+    use ndarray::{Array1, Array2};
+    use rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn samples_with_replacement() {
+        let dataset = Dataset::new(
+            Array2::from_shape_fn((100, 1), |(i, _)| i as f64),
+            Array1::from((0..100).collect::<Vec<usize>>()),
+        )
+        .with_feature_names(vec!["x".to_string()]);
+
+        let mut bootstrap = Bootstrap::new(50, Isaac64Rng::seed_from_u64(42));
+        let samples = bootstrap.sample(&dataset).unwrap().collect::<Vec<_>>();
+        assert_eq!(samples.len(), 50);
+
+        let mut oob_fraction = 0.;
+        for (indices, sample) in &samples {
+            assert_eq!(sample.targets().len(), 100);
+            assert_eq!(sample.feature_names(), dataset.feature_names());
+            assert_eq!(sample.targets().to_vec(), *indices);
+            for (record, target) in sample.records().outer_iter().zip(sample.targets()) {
+                assert_eq!(record[0], *target as f64);
+            }
+
+            let oob = out_of_bag(indices, 100);
+            assert!(oob.iter().all(|idx| !indices.contains(idx)));
+            oob_fraction += oob.len() as f64 / 100. / 50.;
+        }
+        // on average a fraction of (1 - 1/n)^n ~ 1/e of the samples is out-of-bag
+        assert!((oob_fraction - (-1f64).exp()).abs() < 0.02);
+
+        // the same seed draws the same samples
+        let again = Bootstrap::new(50, Isaac64Rng::seed_from_u64(42))
+            .indices(100)
+            .collect::<Vec<_>>();
+        assert_eq!(again[0], samples[0].0);
+    }
+
+    #[test]
+    fn out_of_bag_indices() {
+        assert_eq!(out_of_bag(&[0, 2, 2, 5], 6), vec![1, 3, 4]);
+        assert_eq!(out_of_bag(&[], 3), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn rejects_empty_dataset() {
+        let dataset = Dataset::new(Array2::<f64>::zeros((0, 2)), Array1::<usize>::zeros(0));
+        let mut bootstrap = Bootstrap::new(10, Isaac64Rng::seed_from_u64(42));
+        let sample = bootstrap.sample(&dataset);
+        match sample {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("an empty dataset must be rejected"),
+        }
+    }
+}
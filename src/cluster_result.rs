@@ -0,0 +1,164 @@
+//! Cluster assignments of density-based clustering algorithms
+//!
+//! Density-based algorithms like DBSCAN and HDBSCAN label observations in sparse regions as
+//! noise. `ClusterResult` wraps their assignments and allows to remove the noise from a dataset,
+//! for example as an outlier removal step before fitting another model.
+use linfa_clustering::{Dbscan, DbscanHyperParams, Hdbscan, HdbscanHyperParams};
+use ndarray::{Array1, Array2, Axis};
+use std::collections::HashSet;
+
+use crate::dataset::Dataset;
+use crate::traits::Predict;
+
+/// The cluster of each observation, `None` for noise
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusterResult {
+    labels: Array1<Option<usize>>,
+}
+
+impl ClusterResult {
+    /// Create a result from the cluster of each observation
+    pub fn new(labels: Array1<Option<usize>>) -> ClusterResult {
+        ClusterResult { labels }
+    }
+
+    /// Return the cluster of each observation, `None` for noise
+    pub fn labels(&self) -> &Array1<Option<usize>> {
+        &self.labels
+    }
+
+    /// Return the number of distinct clusters, noise is not counted as a cluster
+    pub fn n_clusters(&self) -> usize {
+        self.labels.iter().flatten().collect::<HashSet<_>>().len()
+    }
+
+    /// Return the number of observations labelled as noise
+    pub fn noise_count(&self) -> usize {
+        self.labels.iter().filter(|x| x.is_none()).count()
+    }
+
+    /// Return the indices of the observations labelled as noise in increasing order
+    pub fn noise_indices(&self) -> Vec<usize> {
+        self.indices(true)
+    }
+
+    /// Return a new dataset containing only the samples which belong to a cluster
+    ///
+    /// Records, targets and weights of noise samples are removed, the order of the remaining
+    /// samples is preserved.
+    ///
+    /// **Panics** if the number of samples in the dataset differs from the number of labels.
+    pub fn filter_noise<A: Copy, T: Copy>(
+        &self,
+        dataset: &Dataset<Array2<A>, Array1<T>>,
+    ) -> Dataset<Array2<A>, Array1<T>> {
+        assert_eq!(
+            dataset.records.nrows(),
+            self.labels.len(),
+            "dataset and cluster labels must contain the same number of samples"
+        );
+
+        let kept = self.indices(false);
+        let filtered = Dataset::new(
+            dataset.records.select(Axis(0), &kept),
+            dataset.targets.select(Axis(0), &kept),
+        );
+
+        match dataset.weights() {
+            Some(weights) => filtered.with_weights(weights.select(Axis(0), &kept)),
+            None => filtered,
+        }
+    }
+
+    fn indices(&self, noise: bool) -> Vec<usize> {
+        self.labels
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| label.is_none() == noise)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl From<Array1<Option<usize>>> for ClusterResult {
+    fn from(labels: Array1<Option<usize>>) -> ClusterResult {
+        ClusterResult::new(labels)
+    }
+}
+
+/// Records have shape `(n_samples, n_features)` like in all other models
+impl Predict<Array2<f64>, ClusterResult> for DbscanHyperParams {
+    fn predict(&self, x: &Array2<f64>) -> ClusterResult {
+        // DBSCAN expects one observation per column
+        Dbscan::predict(self, &x.t()).into()
+    }
+}
+
+impl Predict<Array2<f64>, ClusterResult> for HdbscanHyperParams {
+    fn predict(&self, x: &Array2<f64>) -> ClusterResult {
+        Hdbscan::predict(self, x).labels().clone().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClusterResult;
+    use crate::traits::Predict;
+    use crate::Dataset;
+    use linfa_clustering::DbscanHyperParams;
+    use ndarray::{arr1, array};
+
+    #[test]
+    fn noise_is_filtered() {
+        let result = ClusterResult::new(arr1(&[Some(0), None, Some(1), None, Some(0)]));
+
+        assert_eq!(result.n_clusters(), 2);
+        assert_eq!(result.noise_count(), 2);
+        assert_eq!(result.noise_indices(), vec![1, 3]);
+
+        let dataset = Dataset::new(
+            array![[0., 0.], [1., 1.], [2., 2.], [3., 3.], [4., 4.]],
+            arr1(&[10, 11, 12, 13, 14]),
+        )
+        .with_weights(arr1(&[1., 2., 3., 4., 5.]));
+        let filtered = result.filter_noise(&dataset);
+
+        assert_eq!(filtered.records(), &array![[0., 0.], [2., 2.], [4., 4.]]);
+        assert_eq!(filtered.targets(), &arr1(&[10, 12, 14]));
+        assert_eq!(filtered.weights(), Some(&arr1(&[1., 3., 5.])));
+    }
+
+    #[test]
+    fn dbscan_outliers_are_removed() {
+        let records = array![[0., 0.], [0.1, 0.], [0., 0.1], [0.1, 0.1], [10., 10.]];
+        let dataset = Dataset::new(records, arr1(&[0, 0, 0, 0, 1]));
+
+        let params = DbscanHyperParams::new(3).tolerance(0.5).build();
+        let result = params.predict(dataset.records());
+        assert_eq!(result.noise_indices(), vec![4]);
+        assert_eq!(result.n_clusters(), 1);
+
+        let filtered = result.filter_noise(&dataset);
+        assert_eq!(filtered.records().nrows(), 4);
+        assert_eq!(filtered.weights(), None);
+        assert!(filtered.targets().iter().all(|x| *x == 0));
+    }
+
+    #[test]
+    fn clusters_are_counted_by_distinct_ids() {
+        // the ids of a result do not have to be contiguous
+        let result = ClusterResult::new(arr1(&[Some(0), Some(5), None, Some(5)]));
+        assert_eq!(result.n_clusters(), 2);
+
+        let result = ClusterResult::new(arr1(&[None, None]));
+        assert_eq!(result.n_clusters(), 0);
+        assert_eq!(result.noise_count(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn filter_noise_wrong_size() {
+        let result = ClusterResult::new(arr1(&[Some(0), None]));
+        result.filter_noise(&Dataset::new(array![[0.]], arr1(&[0])));
+    }
+}
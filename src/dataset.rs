@@ -0,0 +1,1063 @@
+use ndarray::{Array1, Array2, Axis};
+use num_traits::{One, Zero};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+#[cfg(any(feature = "csv", feature = "parquet"))]
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::traits::Transformer;
+
+/// A set of records with corresponding targets
+///
+/// The records are generally stored as a two-dimensional array with shape
+/// `(n_samples, n_features)`, the targets contain one entry (or row) per record.
+/// Optionally each record can be assigned a non-negative weight, models
+/// supporting weights then scale its contribution to the fit accordingly.
+/// The features can be named, which is kept by transformations of the records
+/// and shown when the dataset is printed.
+#[derive(Debug)]
+pub struct Dataset<R, T> {
+    pub records: R,
+    pub targets: T,
+    /// Weight of each record, empty if all records are weighted equally
+    pub weights: Array1<f32>,
+    /// Name of each feature, empty if the features are not named
+    pub feature_names: Vec<String>,
+    /// Name of each class, the class label `k` is named `target_names[k]`, empty if the classes
+    /// are not named
+    pub target_names: Vec<String>,
+}
+
+impl<R, T> Dataset<R, T> {
+    /// Create a new dataset from records and targets
+    pub fn new(records: R, targets: T) -> Dataset<R, T> {
+        Dataset {
+            records,
+            targets,
+            weights: Array1::zeros(0),
+            feature_names: Vec::new(),
+            target_names: Vec::new(),
+        }
+    }
+
+    /// Assign a weight to each record of the dataset
+    pub fn with_weights(mut self, weights: Array1<f32>) -> Dataset<R, T> {
+        self.weights = weights;
+        self
+    }
+
+    /// Return the weights of the records, if any were assigned
+    pub fn weights(&self) -> Option<&Array1<f32>> {
+        if self.weights.is_empty() {
+            None
+        } else {
+            Some(&self.weights)
+        }
+    }
+
+    /// Assign a name to each feature of the records
+    pub fn with_feature_names(mut self, feature_names: Vec<String>) -> Dataset<R, T> {
+        self.feature_names = feature_names;
+        self
+    }
+
+    /// Return the names of the features, if any were assigned
+    pub fn feature_names(&self) -> Option<&[String]> {
+        if self.feature_names.is_empty() {
+            None
+        } else {
+            Some(&self.feature_names)
+        }
+    }
+
+    /// Assign a human-readable name to each class, the class label `k` is named
+    /// `target_names[k]`
+    pub fn with_target_names(mut self, target_names: Vec<String>) -> Dataset<R, T> {
+        self.target_names = target_names;
+        self
+    }
+
+    /// Return the names of the classes, if any were assigned
+    pub fn target_names(&self) -> Option<&[String]> {
+        if self.target_names.is_empty() {
+            None
+        } else {
+            Some(&self.target_names)
+        }
+    }
+
+    /// Transform the records with `transformer`, the targets and weights are kept
+    ///
+    /// The feature names are passed through
+    /// [`Transformer::output_feature_names`](trait.Transformer.html#method.output_feature_names),
+    /// which keeps, renames or drops them depending on the transformer.
+    pub fn transform_records<U, M: Transformer<R, U>>(self, transformer: &M) -> Dataset<U, T> {
+        let feature_names = match self.feature_names() {
+            Some(names) => transformer.output_feature_names(names).unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        Dataset {
+            records: transformer.transform(&self.records),
+            targets: self.targets,
+            weights: self.weights,
+            feature_names,
+            target_names: self.target_names,
+        }
+    }
+
+    /// Return the records of this dataset
+    pub fn records(&self) -> &R {
+        &self.records
+    }
+
+    /// Return the targets of this dataset
+    pub fn targets(&self) -> &T {
+        &self.targets
+    }
+}
+
+#[cfg(feature = "csv")]
+impl Dataset<Array2<f64>, Array1<f64>> {
+    /// Load a dataset from a CSV file, the column `target_column` contains the targets and all
+    /// other columns the features of the records
+    ///
+    /// If `has_header` is set, the first line is skipped. Returns an `Error::InvalidInput` if the
+    /// file cannot be read, the rows have a different number of columns or a value is not a
+    /// number.
+    ///
+    /// Requires the `csv` feature.
+    pub fn from_csv<P: AsRef<Path>>(
+        path: P,
+        target_column: usize,
+        has_header: bool,
+    ) -> Result<Dataset<Array2<f64>, Array1<f64>>> {
+        let path = path.as_ref();
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(has_header)
+            .from_path(path)
+            .map_err(|err| {
+                Error::InvalidInput(format!("cannot read {}: {}", path.display(), err))
+            })?;
+
+        let (mut records, mut targets) = (Vec::new(), Vec::new());
+        let mut n_columns = None;
+        for row in reader.records() {
+            let row = row.map_err(|err| Error::InvalidInput(err.to_string()))?;
+            let line = row.position().map_or(0, |position| position.line());
+
+            if n_columns.is_none() && target_column >= row.len() {
+                return Err(Error::InvalidInput(format!(
+                    "target column {} does not exist, the file has {} columns",
+                    target_column,
+                    row.len()
+                )));
+            }
+            n_columns = Some(row.len());
+
+            for (column, field) in row.iter().enumerate() {
+                let value = field.trim().parse::<f64>().map_err(|_| {
+                    Error::InvalidInput(format!(
+                        "value {:?} in line {}, column {} is not a number",
+                        field, line, column
+                    ))
+                })?;
+                if column == target_column {
+                    targets.push(value);
+                } else {
+                    records.push(value);
+                }
+            }
+        }
+
+        let n_columns = n_columns.ok_or_else(|| {
+            Error::InvalidInput(format!("{} does not contain any rows", path.display()))
+        })?;
+        let records = Array2::from_shape_vec((targets.len(), n_columns - 1), records)
+            .expect("all rows have the same number of columns");
+        let feature_names = if has_header {
+            let header = reader
+                .headers()
+                .map_err(|err| Error::InvalidInput(err.to_string()))?;
+            header
+                .iter()
+                .enumerate()
+                .filter(|(column, _)| *column != target_column)
+                .map(|(_, name)| name.trim().to_string())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Dataset::new(records, Array1::from(targets)).with_feature_names(feature_names))
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl Dataset<Array2<f64>, Array1<f64>> {
+    /// Load a dataset from a Parquet file, the column named `target_column` contains the targets
+    /// and all other columns the features of the records
+    ///
+    /// The columns are taken from the schema in the file metadata, in their order of declaration.
+    /// Integer and boolean columns are converted to `f64`. Returns an `Error::InvalidInput` if the
+    /// file cannot be read, the target column does not exist, a column is nested or not numeric,
+    /// or a value is missing.
+    ///
+    /// Requires the `parquet` feature.
+    pub fn from_parquet<P: AsRef<Path>>(
+        path: P,
+        target_column: &str,
+    ) -> Result<Dataset<Array2<f64>, Array1<f64>>> {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::record::Field;
+
+        let path = path.as_ref();
+        let cannot_read = |err: &dyn std::fmt::Display| {
+            Error::InvalidInput(format!("cannot read {}: {}", path.display(), err))
+        };
+        let file = std::fs::File::open(path).map_err(|err| cannot_read(&err))?;
+        let reader = SerializedFileReader::new(file).map_err(|err| cannot_read(&err))?;
+
+        let schema = reader.metadata().file_metadata().schema_descr();
+        let columns = schema.root_schema().get_fields();
+        if let Some(nested) = columns.iter().find(|column| column.is_group()) {
+            return Err(Error::InvalidInput(format!(
+                "column {} is nested, only flat schemas are supported",
+                nested.name()
+            )));
+        }
+        let target = columns
+            .iter()
+            .position(|column| column.name() == target_column)
+            .ok_or_else(|| {
+                Error::InvalidInput(format!("target column {} does not exist", target_column))
+            })?;
+
+        let (mut records, mut targets) = (Vec::new(), Vec::new());
+        let rows = reader.get_row_iter(None).map_err(|err| cannot_read(&err))?;
+        for (index, row) in rows.enumerate() {
+            for (column, (name, field)) in row.get_column_iter().enumerate() {
+                let value = match field {
+                    Field::Bool(x) => {
+                        if *x {
+                            1.
+                        } else {
+                            0.
+                        }
+                    }
+                    Field::Byte(x) => f64::from(*x),
+                    Field::Short(x) => f64::from(*x),
+                    Field::Int(x) => f64::from(*x),
+                    Field::Long(x) => *x as f64,
+                    Field::UByte(x) => f64::from(*x),
+                    Field::UShort(x) => f64::from(*x),
+                    Field::UInt(x) => f64::from(*x),
+                    Field::ULong(x) => *x as f64,
+                    Field::Float(x) => f64::from(*x),
+                    Field::Double(x) => *x,
+                    Field::Null => {
+                        return Err(Error::InvalidInput(format!(
+                            "value in row {}, column {} is missing",
+                            index, name
+                        )))
+                    }
+                    _ => {
+                        return Err(Error::InvalidInput(format!(
+                            "value {} in row {}, column {} is not a number",
+                            field, index, name
+                        )))
+                    }
+                };
+                if column == target {
+                    targets.push(value);
+                } else {
+                    records.push(value);
+                }
+            }
+        }
+
+        let records = Array2::from_shape_vec((targets.len(), columns.len() - 1), records)
+            .expect("all rows have the same number of columns");
+        let feature_names = columns
+            .iter()
+            .enumerate()
+            .filter(|(column, _)| *column != target)
+            .map(|(_, column)| column.name().to_string())
+            .collect();
+
+        Ok(Dataset::new(records, Array1::from(targets)).with_feature_names(feature_names))
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl Dataset<Array2<f64>, Array1<f64>> {
+    /// Convert an Arrow `RecordBatch` to a dataset, the column named `target_column` contains the
+    /// targets and all other columns the features of the records
+    ///
+    /// Integer and float columns are cast to `f64`, `Float64` columns are used as they are.
+    ///
+    /// The conversion copies the data. Arrow keeps every column in a separate buffer, while the
+    /// records of a dataset are a single matrix, so they cannot be a view into the batch. The
+    /// value buffer of each column is copied once into a column-major records matrix, which
+    /// avoids transposing the data, and the target column is copied as well. Returns an
+    /// `Error::InvalidInput` if the target column does not exist, or a column is not numeric or
+    /// contains missing values.
+    ///
+    /// Requires the `arrow` feature.
+    pub fn from_arrow(
+        batch: &arrow::record_batch::RecordBatch,
+        target_column: &str,
+    ) -> Result<Dataset<Array2<f64>, Array1<f64>>> {
+        use ndarray::ShapeBuilder;
+
+        let schema = batch.schema();
+        let target = schema.index_of(target_column).map_err(|_| {
+            Error::InvalidInput(format!("target column {} does not exist", target_column))
+        })?;
+
+        let mut records = Vec::with_capacity(batch.num_rows() * (batch.num_columns() - 1));
+        let mut targets = Vec::new();
+        let mut feature_names = Vec::with_capacity(batch.num_columns() - 1);
+        for (index, (field, column)) in schema.fields().iter().zip(batch.columns()).enumerate() {
+            let column = arrow_to_f64(field.name(), column)?;
+            let values = column
+                .as_any()
+                .downcast_ref::<arrow::array::Float64Array>()
+                .expect("column was cast to Float64")
+                .values();
+            if index == target {
+                targets.extend_from_slice(values);
+            } else {
+                records.extend_from_slice(values);
+                feature_names.push(field.name().to_string());
+            }
+        }
+        let records =
+            Array2::from_shape_vec((batch.num_rows(), batch.num_columns() - 1).f(), records)
+                .expect("all columns have the same length");
+
+        Ok(Dataset::new(records, Array1::from(targets)).with_feature_names(feature_names))
+    }
+}
+
+/// Cast a numeric Arrow array without missing values to `Float64`
+#[cfg(feature = "arrow")]
+fn arrow_to_f64(name: &str, array: &arrow::array::ArrayRef) -> Result<arrow::array::ArrayRef> {
+    use arrow::datatypes::DataType;
+
+    match array.data_type() {
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64
+        | DataType::Float32
+        | DataType::Float64 => {}
+        dtype => {
+            return Err(Error::InvalidInput(format!(
+                "column {} has non-numeric type {:?}",
+                name, dtype
+            )))
+        }
+    }
+    if array.null_count() > 0 {
+        return Err(Error::InvalidInput(format!(
+            "column {} contains {} missing values",
+            name,
+            array.null_count()
+        )));
+    }
+
+    // casting to the same type only clones the reference to the array
+    arrow::compute::cast(array, &DataType::Float64)
+        .map_err(|err| Error::InvalidInput(err.to_string()))
+}
+
+impl<A: Copy, T: Clone> Dataset<Array2<A>, T> {
+    /// Return a new dataset with the features at `indices`, in the given order
+    ///
+    /// The targets and weights are kept and the feature names are selected alongside the
+    /// records. Returns an error if `indices` is empty or an index is out of range.
+    pub fn select_features(&self, indices: &[usize]) -> Result<Dataset<Array2<A>, T>> {
+        check_indices(indices, self.records.ncols(), "feature")?;
+
+        Ok(Dataset {
+            records: self.records.select(Axis(1), indices),
+            targets: self.targets.clone(),
+            weights: self.weights.clone(),
+            feature_names: match self.feature_names() {
+                Some(names) => indices.iter().map(|i| names[*i].clone()).collect(),
+                None => Vec::new(),
+            },
+            target_names: self.target_names.clone(),
+        })
+    }
+}
+
+impl<A: Copy, L: Clone> Dataset<Array2<A>, Array1<L>> {
+    /// Return a new dataset with the samples at `indices`, in the given order
+    ///
+    /// The records, targets and weights are selected consistently and the feature names are
+    /// kept. Indices may be repeated, for example to draw a bootstrap sample. Returns an error
+    /// if `indices` is empty or an index is out of range.
+    pub fn select_samples(&self, indices: &[usize]) -> Result<Dataset<Array2<A>, Array1<L>>> {
+        check_indices(indices, self.targets.len(), "sample")?;
+
+        Ok(self.select_samples_unchecked(indices))
+    }
+
+    /// Return a new dataset with the samples in random order
+    ///
+    /// The weights, feature and target names are kept. Pass a seeded random number generator to
+    /// get a reproducible order.
+    pub fn shuffle<R: Rng>(&self, rng: &mut R) -> Dataset<Array2<A>, Array1<L>> {
+        let mut indices = (0..self.targets.len()).collect::<Vec<_>>();
+        indices.shuffle(rng);
+
+        self.select_samples_unchecked(&indices)
+    }
+
+    /// Draw `n` random samples, with or without replacement
+    ///
+    /// The weights, feature and target names are kept. Returns an error if `n` is zero, or larger
+    /// than the number of samples when drawing without replacement.
+    pub fn sample<R: Rng>(
+        &self,
+        n: usize,
+        replace: bool,
+        rng: &mut R,
+    ) -> Result<Dataset<Array2<A>, Array1<L>>> {
+        let n_samples = self.targets.len();
+        if n == 0 {
+            return Err(Error::Parameters("cannot draw zero samples".to_string()));
+        }
+        if n_samples == 0 || (!replace && n > n_samples) {
+            return Err(Error::Parameters(format!(
+                "cannot draw {} samples without replacement from {} samples",
+                n, n_samples
+            )));
+        }
+
+        let indices = if replace {
+            (0..n).map(|_| rng.gen_range(0, n_samples)).collect()
+        } else {
+            rand::seq::index::sample(rng, n_samples, n).into_vec()
+        };
+
+        Ok(self.select_samples_unchecked(&indices))
+    }
+
+    /// Select the samples at `indices` without checking them, used when the indices are drawn
+    /// from the range of samples
+    pub(crate) fn select_samples_unchecked(
+        &self,
+        indices: &[usize],
+    ) -> Dataset<Array2<A>, Array1<L>> {
+        Dataset {
+            records: self.records.select(Axis(0), indices),
+            targets: indices.iter().map(|i| self.targets[*i].clone()).collect(),
+            weights: match self.weights() {
+                Some(weights) => weights.select(Axis(0), indices),
+                None => Array1::zeros(0),
+            },
+            feature_names: self.feature_names.clone(),
+            target_names: self.target_names.clone(),
+        }
+    }
+}
+
+impl<A: Copy, T> Dataset<Array2<A>, T> {
+    /// Concatenate the features of `self` and `other`, which must have the same number of samples
+    ///
+    /// The targets, weights and target names are taken from `self`. The feature names are
+    /// concatenated if both datasets name their features, otherwise they are dropped.
+    pub fn hstack<U>(self, other: Dataset<Array2<A>, U>) -> Result<Dataset<Array2<A>, T>> {
+        if self.records.nrows() != other.records.nrows() {
+            return Err(Error::Parameters(format!(
+                "cannot stack features of {} and {} samples",
+                self.records.nrows(),
+                other.records.nrows()
+            )));
+        }
+
+        let feature_names = match (self.feature_names(), other.feature_names()) {
+            (Some(a), Some(b)) => a.iter().chain(b).cloned().collect(),
+            _ => Vec::new(),
+        };
+
+        Ok(Dataset {
+            records: ndarray::stack(Axis(1), &[self.records.view(), other.records.view()]).unwrap(),
+            targets: self.targets,
+            weights: self.weights,
+            feature_names,
+            target_names: self.target_names,
+        })
+    }
+}
+
+impl<A: Copy, L: Clone> Dataset<Array2<A>, Array1<L>> {
+    /// Append the samples of `other` to `self`, both must have the same number of features
+    ///
+    /// Records without weights are weighted with one if the other dataset has weights. The
+    /// feature and target names are kept, but must agree if both datasets have them.
+    pub fn vstack(
+        self,
+        other: Dataset<Array2<A>, Array1<L>>,
+    ) -> Result<Dataset<Array2<A>, Array1<L>>> {
+        if self.records.ncols() != other.records.ncols() {
+            return Err(Error::Parameters(format!(
+                "cannot stack samples with {} and {} features",
+                self.records.ncols(),
+                other.records.ncols()
+            )));
+        }
+        let feature_names = merge_names(self.feature_names, other.feature_names, "feature")?;
+        let target_names = merge_names(self.target_names, other.target_names, "target")?;
+
+        let weights = if self.weights.is_empty() && other.weights.is_empty() {
+            Array1::zeros(0)
+        } else {
+            let weights_or_ones = |weights: Array1<f32>, len: usize| {
+                if weights.is_empty() {
+                    Array1::ones(len)
+                } else {
+                    weights
+                }
+            };
+            let a = weights_or_ones(self.weights, self.targets.len());
+            let b = weights_or_ones(other.weights, other.targets.len());
+            ndarray::stack(Axis(0), &[a.view(), b.view()]).unwrap()
+        };
+
+        Ok(Dataset {
+            records: ndarray::stack(Axis(0), &[self.records.view(), other.records.view()]).unwrap(),
+            targets: self
+                .targets
+                .iter()
+                .chain(other.targets.iter())
+                .cloned()
+                .collect(),
+            weights,
+            feature_names,
+            target_names,
+        })
+    }
+}
+
+/// Keep the names of either dataset, returns an error if both have different names
+fn merge_names(a: Vec<String>, b: Vec<String>, kind: &str) -> Result<Vec<String>> {
+    if a.is_empty() {
+        Ok(b)
+    } else if b.is_empty() || a == b {
+        Ok(a)
+    } else {
+        Err(Error::Parameters(format!(
+            "cannot stack datasets with different {} names",
+            kind
+        )))
+    }
+}
+
+/// Check that `indices` is not empty and all indices are smaller than `len`
+fn check_indices(indices: &[usize], len: usize, kind: &str) -> Result<()> {
+    if indices.is_empty() {
+        return Err(Error::Parameters(format!("cannot select zero {}s", kind)));
+    }
+    if let Some(index) = indices.iter().find(|i| **i >= len) {
+        return Err(Error::Parameters(format!(
+            "{} index {} is out of range, the dataset has {} {}s",
+            kind, index, len, kind
+        )));
+    }
+
+    Ok(())
+}
+
+impl<R, L> Dataset<R, Array1<L>> {
+    /// Transform each target with `f`, the records, weights and feature names are kept
+    ///
+    /// The target names are dropped, as they may not apply to the new targets.
+    pub fn map_targets<S, F: FnMut(&L) -> S>(self, f: F) -> Dataset<R, Array1<S>> {
+        Dataset {
+            records: self.records,
+            targets: self.targets.map(f),
+            weights: self.weights,
+            feature_names: self.feature_names,
+            target_names: Vec::new(),
+        }
+    }
+
+    /// Convert a two-class label vector to boolean targets, `true` for the `positive` class
+    ///
+    /// The target names are dropped. Returns an error if the targets contain more than two
+    /// distinct labels.
+    pub fn to_binary(&self, positive: &L) -> Result<Dataset<R, Array1<bool>>>
+    where
+        R: Clone,
+        L: PartialEq,
+    {
+        let negative = self.targets.iter().find(|label| *label != positive);
+        if let Some(negative) = negative {
+            if self
+                .targets
+                .iter()
+                .any(|label| label != positive && label != negative)
+            {
+                return Err(Error::Parameters(
+                    "Binary targets require at most two distinct labels".into(),
+                ));
+            }
+        }
+
+        Ok(Dataset {
+            records: self.records.clone(),
+            targets: self.targets.map(|label| label == positive),
+            weights: self.weights.clone(),
+            feature_names: self.feature_names.clone(),
+            target_names: Vec::new(),
+        })
+    }
+}
+
+impl<R: Clone> Dataset<R, Array1<usize>> {
+    /// Encode the class labels as one-hot rows with shape `(n_samples, n_classes)`
+    ///
+    /// The number of classes is one more than the largest label, the row of a sample with
+    /// label `k` is one in column `k` and zero otherwise.
+    pub fn to_one_hot<A: Zero + One + Clone>(&self) -> Dataset<R, Array2<A>> {
+        let n_classes = self.targets.iter().max().map(|max| max + 1).unwrap_or(0);
+        let mut targets = Array2::from_elem((self.targets.len(), n_classes), A::zero());
+        for (mut row, label) in targets.genrows_mut().into_iter().zip(self.targets.iter()) {
+            row[*label] = A::one();
+        }
+
+        Dataset {
+            records: self.records.clone(),
+            targets,
+            weights: self.weights.clone(),
+            feature_names: self.feature_names.clone(),
+            target_names: self.target_names.clone(),
+        }
+    }
+}
+
+impl<R> Dataset<R, Array1<usize>> {
+    /// Create a dataset with class labels from one-hot rows, or class scores, by taking the
+    /// index of the largest entry in each row
+    ///
+    /// Returns an error if a row has no entries.
+    pub fn from_one_hot<A: PartialOrd>(
+        dataset: Dataset<R, Array2<A>>,
+    ) -> Result<Dataset<R, Array1<usize>>> {
+        if dataset.targets.ncols() == 0 && dataset.targets.nrows() > 0 {
+            return Err(Error::Parameters(
+                "One-hot targets require at least one class".into(),
+            ));
+        }
+
+        let targets = dataset
+            .targets
+            .axis_iter(Axis(0))
+            .map(|row| {
+                row.iter()
+                    .enumerate()
+                    .fold(0, |best, (i, x)| if *x > row[best] { i } else { best })
+            })
+            .collect::<Array1<_>>();
+
+        Ok(Dataset {
+            records: dataset.records,
+            targets,
+            weights: dataset.weights,
+            feature_names: dataset.feature_names,
+            target_names: dataset.target_names,
+        })
+    }
+}
+
+impl<A, T> fmt::Display for Dataset<Array2<A>, T> {
+    /// Summarize the shape of the records and list the feature names, if any
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Dataset with {} samples and {} features",
+            self.records.nrows(),
+            self.records.ncols()
+        )?;
+        if let Some(names) = self.feature_names() {
+            write!(f, ": {}", names.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial_features::PolynomialFeatures;
+    use ndarray::array;
+    use rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    /// Write `contents` to a file in the temporary directory and return its path
+    #[cfg(feature = "csv")]
+    fn temporary_csv(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("linfa_dataset_{}.csv", name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn from_csv_splits_targets() {
+        let path = temporary_csv("header", "a,target,b\n1.0,0,2.5\n-3,1, 4e2\n");
+        let dataset = Dataset::from_csv(&path, 1, true).unwrap();
+        assert_eq!(dataset.records(), &array![[1.0, 2.5], [-3., 400.]]);
+        assert_eq!(dataset.targets(), &array![0., 1.]);
+        assert_eq!(
+            dataset.feature_names(),
+            Some(&["a".to_string(), "b".to_string()][..])
+        );
+
+        // without header the first line is data as well
+        let path = temporary_csv("no_header", "1,2\n3,4\n");
+        let dataset = Dataset::from_csv(&path, 1, false).unwrap();
+        assert_eq!(dataset.records(), &array![[1.], [3.]]);
+        assert_eq!(dataset.targets(), &array![2., 4.]);
+        assert_eq!(dataset.feature_names(), None);
+    }
+
+    #[test]
+    fn feature_names_follow_transformations() {
+        let names = vec!["x".to_string(), "y".to_string()];
+        let dataset = Dataset::new(array![[1., 2.], [3., 4.]], array![0, 1])
+            .with_feature_names(names.clone());
+        assert_eq!(
+            dataset.to_string(),
+            "Dataset with 2 samples and 2 features: x, y"
+        );
+        assert!(format!("{:?}", dataset).contains("feature_names: [\"x\", \"y\"]"));
+
+        let dataset = dataset.map_targets(|x| *x == 1);
+        assert_eq!(dataset.feature_names(), Some(&names[..]));
+
+        let poly = PolynomialFeatures::new(2).include_bias(false);
+        let dataset = dataset.transform_records(&poly);
+        assert_eq!(dataset.records().ncols(), 5);
+        assert_eq!(
+            dataset.to_string(),
+            "Dataset with 2 samples and 5 features: x, y, x^2, x y, y^2"
+        );
+
+        let unnamed = Dataset::new(array![[1.], [2.]], array![0., 1.]).transform_records(&poly);
+        assert_eq!(unnamed.feature_names(), None);
+        assert_eq!(unnamed.to_string(), "Dataset with 2 samples and 2 features");
+    }
+
+    #[test]
+    fn stack_samples_and_features() {
+        let names = vec!["a".to_string(), "b".to_string()];
+        let first = Dataset::new(array![[1., 2.], [3., 4.]], array![0, 1])
+            .with_weights(array![0.5, 2.])
+            .with_feature_names(names.clone());
+        let second = Dataset::new(array![[5., 6.]], array![1]).with_feature_names(names.clone());
+
+        let samples = first.vstack(second).unwrap();
+        assert_eq!(samples.records(), &array![[1., 2.], [3., 4.], [5., 6.]]);
+        assert_eq!(samples.targets(), &array![0, 1, 1]);
+        assert_eq!(samples.weights(), Some(&array![0.5, 2., 1.]));
+        assert_eq!(samples.feature_names(), Some(&names[..]));
+
+        let extra = Dataset::new(array![[7.], [8.], [9.]], ()).with_feature_names(vec!["c".into()]);
+        let features = samples.hstack(extra).unwrap();
+        assert_eq!(
+            features.records(),
+            &array![[1., 2., 7.], [3., 4., 8.], [5., 6., 9.]]
+        );
+        assert_eq!(features.targets(), &array![0, 1, 1]);
+        assert_eq!(
+            features.to_string(),
+            "Dataset with 3 samples and 3 features: a, b, c"
+        );
+
+        // the shapes must agree
+        let single = Dataset::new(array![[1.]], array![0]);
+        match features.vstack(single) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("samples with a different number of features must be rejected"),
+        }
+        let features = Dataset::new(array![[1.], [2.]], array![0, 1]);
+        match features.hstack(Dataset::new(array![[1.]], ())) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("features with a different number of samples must be rejected"),
+        }
+    }
+
+    #[test]
+    fn shuffle_and_sample() {
+        let dataset = Dataset::new(
+            Array2::from_shape_fn((10, 2), |(i, j)| (i * 2 + j) as f64),
+            Array1::from((0..10).collect::<Vec<_>>()),
+        )
+        .with_feature_names(vec!["a".to_string(), "b".to_string()])
+        .with_target_names((0..10).map(|i| i.to_string()).collect());
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+
+        let shuffled = dataset.shuffle(&mut rng);
+        assert_ne!(shuffled.targets(), dataset.targets());
+        let mut targets = shuffled.targets().to_vec();
+        targets.sort();
+        assert_eq!(targets, dataset.targets().to_vec());
+        // records and targets are permuted together
+        for (record, target) in shuffled.records().outer_iter().zip(shuffled.targets()) {
+            assert_eq!(record[0], (target * 2) as f64);
+        }
+        assert_eq!(shuffled.feature_names(), dataset.feature_names());
+        assert_eq!(shuffled.target_names(), dataset.target_names());
+
+        // the same seed gives the same order
+        let again = dataset.shuffle(&mut Isaac64Rng::seed_from_u64(42));
+        let first = dataset.shuffle(&mut Isaac64Rng::seed_from_u64(42));
+        assert_eq!(again.targets(), first.targets());
+
+        let subset = dataset.sample(5, false, &mut rng).unwrap();
+        let mut targets = subset.targets().to_vec();
+        targets.sort();
+        targets.dedup();
+        assert_eq!(targets.len(), 5);
+        assert_eq!(subset.feature_names(), dataset.feature_names());
+
+        let bootstrap = dataset.sample(20, true, &mut rng).unwrap();
+        assert_eq!(bootstrap.targets().len(), 20);
+        assert_eq!(bootstrap.target_names(), dataset.target_names());
+
+        match dataset.sample(11, false, &mut rng) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("more samples than available must be rejected without replacement"),
+        }
+    }
+
+    #[test]
+    fn select_features_and_samples() {
+        let names = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let dataset = Dataset::new(array![[1., 2., 3.], [4., 5., 6.]], array![0, 1])
+            .with_weights(array![0.5, 2.])
+            .with_feature_names(names);
+
+        let features = dataset.select_features(&[2, 0]).unwrap();
+        assert_eq!(features.records(), &array![[3., 1.], [6., 4.]]);
+        assert_eq!(features.records().ncols(), 2);
+        assert_eq!(
+            features.feature_names(),
+            Some(&["c".to_string(), "a".to_string()][..])
+        );
+        assert_eq!(features.targets(), dataset.targets());
+
+        let samples = dataset.select_samples(&[1, 1]).unwrap();
+        assert_eq!(samples.records(), &array![[4., 5., 6.], [4., 5., 6.]]);
+        assert_eq!(samples.targets(), &array![1, 1]);
+        assert_eq!(samples.weights(), Some(&array![2., 2.]));
+        assert_eq!(samples.feature_names(), dataset.feature_names());
+        assert_eq!(samples.target_names(), None);
+
+        match dataset.select_features(&[]) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("empty selections must be rejected"),
+        }
+        match dataset.select_samples(&[0, 2]) {
+            Err(Error::Parameters(message)) => assert!(message.contains("index 2")),
+            _ => panic!("out of range indices must be rejected"),
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn from_csv_reports_invalid_input() {
+        let path = temporary_csv("not_a_number", "a,b\n1,2\n3,x\n");
+        match Dataset::from_csv(&path, 0, true) {
+            Err(Error::InvalidInput(message)) => {
+                assert!(message.contains("\"x\""));
+                assert!(message.contains("line 3"));
+            }
+            _ => panic!("non-numeric values must be rejected"),
+        }
+
+        let path = temporary_csv("unequal_rows", "1,2\n3,4,5\n");
+        match Dataset::from_csv(&path, 0, false) {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("rows with a different number of columns must be rejected"),
+        }
+
+        let path = temporary_csv("target_column", "1,2\n");
+        match Dataset::from_csv(&path, 2, false) {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("the target column must exist"),
+        }
+
+        match Dataset::from_csv("does/not/exist.csv", 0, false) {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("missing files must be reported"),
+        }
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn from_parquet_upcasts_columns() {
+        use parquet::column::writer::ColumnWriter;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::{FileWriter, RowGroupWriter, SerializedFileWriter};
+        use parquet::schema::parser::parse_message_type;
+        use std::rc::Rc;
+
+        let schema = "message schema {
+            REQUIRED INT32 a;
+            REQUIRED DOUBLE target;
+            REQUIRED FLOAT b;
+            REQUIRED INT64 c;
+        }";
+        let path = std::env::temp_dir().join("linfa_dataset_mixed.parquet");
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = SerializedFileWriter::new(
+            file,
+            Rc::new(parse_message_type(schema).unwrap()),
+            Rc::new(WriterProperties::builder().build()),
+        )
+        .unwrap();
+        let mut row_group = writer.next_row_group().unwrap();
+        while let Some(mut column) = row_group.next_column().unwrap() {
+            match column {
+                ColumnWriter::Int32ColumnWriter(ref mut typed) => {
+                    typed.write_batch(&[1, -3], None, None).unwrap();
+                }
+                ColumnWriter::DoubleColumnWriter(ref mut typed) => {
+                    typed.write_batch(&[0.5, 1.5], None, None).unwrap();
+                }
+                ColumnWriter::FloatColumnWriter(ref mut typed) => {
+                    typed.write_batch(&[2.5, 400.], None, None).unwrap();
+                }
+                ColumnWriter::Int64ColumnWriter(ref mut typed) => {
+                    typed.write_batch(&[7, 1 << 40], None, None).unwrap();
+                }
+                _ => unreachable!(),
+            }
+            row_group.close_column(column).unwrap();
+        }
+        writer.close_row_group(row_group).unwrap();
+        writer.close().unwrap();
+
+        let dataset = Dataset::from_parquet(&path, "target").unwrap();
+        assert_eq!(
+            dataset.records(),
+            &array![[1., 2.5, 7.], [-3., 400., (1u64 << 40) as f64]]
+        );
+        assert_eq!(dataset.targets(), &array![0.5, 1.5]);
+
+        match Dataset::from_parquet(&path, "missing") {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("the target column must exist"),
+        }
+        match Dataset::from_parquet("does/not/exist.parquet", "target") {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("missing files must be reported"),
+        }
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn from_arrow_upcasts_columns() {
+        use arrow::array::{ArrayRef, Float32Array, Float64Array, Int32Array, Int64Array};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use std::sync::Arc;
+
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("target", DataType::Float64, false),
+            Field::new("b", DataType::Float32, false),
+            Field::new("c", DataType::Int64, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1, -3])) as ArrayRef,
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+                Arc::new(Float32Array::from(vec![2.5, 400.])),
+                Arc::new(Int64Array::from(vec![7, 1 << 40])),
+            ],
+        )
+        .unwrap();
+
+        let dataset = Dataset::from_arrow(&batch, "target").unwrap();
+        assert_eq!(
+            dataset.records(),
+            &array![[1., 2.5, 7.], [-3., 400., (1u64 << 40) as f64]]
+        );
+        assert_eq!(dataset.targets(), &array![0.5, 1.5]);
+
+        match Dataset::from_arrow(&batch, "missing") {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("the target column must exist"),
+        }
+
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Float64, true),
+            Field::new("target", DataType::Float64, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Float64Array::from(vec![Some(1.), None])) as ArrayRef,
+                Arc::new(Float64Array::from(vec![0.5, 1.5])),
+            ],
+        )
+        .unwrap();
+        match Dataset::from_arrow(&batch, "target") {
+            Err(Error::InvalidInput(message)) => assert!(message.contains("column a")),
+            _ => panic!("missing values must be rejected"),
+        }
+    }
+
+    #[test]
+    fn one_hot_round_trip() {
+        let labels = array![2, 0, 1, 2, 0];
+        let dataset = Dataset::new(Array2::<f64>::zeros((5, 2)), labels.clone())
+            .with_weights(array![1., 2., 1., 1., 3.]);
+
+        let one_hot = dataset.to_one_hot::<f64>();
+        assert_eq!(
+            one_hot.targets(),
+            &array![
+                [0., 0., 1.],
+                [1., 0., 0.],
+                [0., 1., 0.],
+                [0., 0., 1.],
+                [1., 0., 0.]
+            ]
+        );
+
+        let dataset = Dataset::from_one_hot(one_hot).unwrap();
+        assert_eq!(dataset.targets(), &labels);
+        assert_eq!(dataset.weights(), Some(&array![1., 2., 1., 1., 3.]));
+    }
+
+    #[test]
+    fn map_and_binary_targets() {
+        let dataset = Dataset::new(Array2::<f64>::zeros((4, 2)), array![1, 3, 3, 1]);
+        let mapped = dataset.map_targets(|x| x * 2);
+        assert_eq!(mapped.targets(), &array![2, 6, 6, 2]);
+
+        let binary = mapped.to_binary(&6).unwrap();
+        assert_eq!(binary.targets(), &array![false, true, true, false]);
+
+        let dataset = Dataset::new(Array2::<f64>::zeros((3, 2)), array!["a", "b", "c"]);
+        match dataset.to_binary(&"a") {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("more than two labels must be rejected"),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use linfa_linear::error::LinearError;
+use std::error::Error as StdError;
+use std::fmt::{self, Display};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error when fitting a model through the common traits
+#[derive(Debug)]
+pub enum Error {
+    /// When the parameters or the shape of the dataset are invalid
+    Parameters(String),
+    /// When input data, for example a file, cannot be read or parsed
+    InvalidInput(String),
+    /// Errors encountered when fitting a linear model
+    Linear(LinearError),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Parameters(message) => write!(f, "Invalid parameter: {}", message),
+            Self::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+            Self::Linear(error) => write!(f, "Linear model error: {}", error),
+        }
+    }
+}
+
+impl StdError for Error {}
+
+impl From<LinearError> for Error {
+    fn from(error: LinearError) -> Error {
+        Error::Linear(error)
+    }
+}
@@ -0,0 +1,197 @@
+//! Cross-validation of grouped samples
+//!
+//! Samples of the same group, for example several measurements of one patient, are correlated.
+//! If a group is split between training and validation, the model is validated on samples it
+//! has partially seen, which inflates the score. Group-aware folds keep every group together.
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use ndarray::{Array1, Array2};
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+
+/// The training and the test set of a split
+type TrainTest<A, L> = (Dataset<Array2<A>, Array1<L>>, Dataset<Array2<A>, Array1<L>>);
+
+/// Splitter of grouped samples into `n_folds` folds which never share a group
+///
+/// Every fold is the validation set of one split and the other folds are its training set. The
+/// groups are assigned to the folds from the largest to the smallest, each to a fold with the
+/// fewest groups and among those the fewest samples. The number of groups in the folds differs
+/// by at most one and the number of samples is balanced as far as the group sizes allow. The
+/// assignment is deterministic, shuffle the group ids for a random one.
+pub struct GroupKFold {
+    n_folds: usize,
+}
+
+impl GroupKFold {
+    /// Create a splitter into `n_folds` folds
+    pub fn new(n_folds: usize) -> GroupKFold {
+        GroupKFold { n_folds }
+    }
+
+    /// Return the training and validation indices of each fold, where `groups` contains the
+    /// group of each sample
+    ///
+    /// No group appears in both the training and the validation indices of a fold. Returns an
+    /// error if there are fewer than two folds or fewer groups than folds.
+    pub fn indices<G: Eq + Hash>(&self, groups: &[G]) -> Result<Vec<(Vec<usize>, Vec<usize>)>> {
+        if self.n_folds < 2 {
+            return Err(Error::Parameters(format!(
+                "the number of folds ({}) must be at least two",
+                self.n_folds
+            )));
+        }
+
+        // the samples of each group, in the order of their first appearance
+        let mut group_idx = HashMap::new();
+        let mut members: Vec<Vec<usize>> = Vec::new();
+        for (i, group) in groups.iter().enumerate() {
+            let idx = *group_idx.entry(group).or_insert_with(|| {
+                members.push(Vec::new());
+                members.len() - 1
+            });
+            members[idx].push(i);
+        }
+
+        if members.len() < self.n_folds {
+            return Err(Error::Parameters(format!(
+                "cannot split {} groups into {} folds",
+                members.len(),
+                self.n_folds
+            )));
+        }
+
+        // the sort is stable, groups of the same size keep their order
+        members.sort_by_key(|group| Reverse(group.len()));
+
+        let mut folds = vec![Vec::new(); self.n_folds];
+        let mut n_groups = vec![0; self.n_folds];
+        for group in members {
+            let fold = (0..self.n_folds)
+                .min_by_key(|fold| (n_groups[*fold], folds[*fold].len()))
+                .unwrap();
+            n_groups[fold] += 1;
+            folds[fold].extend(group);
+        }
+
+        Ok((0..self.n_folds)
+            .map(|fold| {
+                let mut train = folds
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, _)| *other != fold)
+                    .flat_map(|(_, indices)| indices.iter().cloned())
+                    .collect::<Vec<_>>();
+                let mut validation = folds[fold].clone();
+                train.sort_unstable();
+                validation.sort_unstable();
+
+                (train, validation)
+            })
+            .collect())
+    }
+
+    /// Return the training and validation datasets of each fold of `dataset`, where `groups`
+    /// contains the group of each sample
+    ///
+    /// The weights, feature and target names are kept. Returns an error if `groups` does not
+    /// have one entry per sample and the same errors as [indices](#method.indices).
+    pub fn split<'a, A: Copy, L: Clone, G: Eq + Hash + 'a>(
+        &self,
+        dataset: &'a Dataset<Array2<A>, Array1<L>>,
+        groups: &[G],
+    ) -> Result<impl Iterator<Item = TrainTest<A, L>> + 'a> {
+        if groups.len() != dataset.targets().len() {
+            return Err(Error::Parameters(format!(
+                "number of groups ({}) and samples ({}) differ",
+                groups.len(),
+                dataset.targets().len()
+            )));
+        }
+
+        let folds = self.indices(groups)?;
+
+        Ok(folds.into_iter().map(move |(train, validation)| {
+            (
+                dataset.select_samples_unchecked(&train),
+                dataset.select_samples_unchecked(&validation),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GroupKFold;
+    use crate::dataset::Dataset;
+    use crate::error::Error;
+    use ndarray::{Array1, Array2};
+
+    #[test]
+    fn keeps_groups_together() {
+        // 12 patients with three or four samples each
+        let groups = (0..40)
+            .map(|i| ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l"][(i * 7) % 12])
+            .collect::<Vec<_>>();
+        let dataset = Dataset::new(
+            Array2::from_shape_fn((40, 1), |(i, _)| i as f64),
+            Array1::from((0..40).collect::<Vec<usize>>()),
+        );
+
+        let folds = GroupKFold::new(5)
+            .split(&dataset, &groups)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(folds.len(), 5);
+
+        let mut validated = [0; 40];
+        for (train, validation) in &folds {
+            assert_eq!(train.targets().len() + validation.targets().len(), 40);
+            for i in validation.targets() {
+                validated[*i] += 1;
+                assert!(train.targets().iter().all(|j| groups[*j] != groups[*i]));
+            }
+        }
+        // every sample is validated exactly once
+        assert!(validated.iter().all(|n| *n == 1));
+    }
+
+    #[test]
+    fn balances_groups() {
+        let groups = [0, 0, 0, 0, 1, 1, 1, 2, 2, 3, 3, 4, 5];
+        let folds = GroupKFold::new(3).indices(&groups).unwrap();
+
+        // the groups of sizes 4, 3, 2, 2, 1, 1 fill the folds to 5, 4 and 4 samples
+        let mut sizes = folds.iter().map(|(_, v)| v.len()).collect::<Vec<_>>();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![4, 4, 5]);
+        for (_, validation) in &folds {
+            let mut fold_groups = validation.iter().map(|i| groups[*i]).collect::<Vec<_>>();
+            fold_groups.dedup();
+            assert_eq!(fold_groups.len(), 2);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_folds() {
+        let groups = [0, 0, 1, 1, 2];
+        let dataset = Dataset::new(Array2::<f64>::zeros((4, 1)), Array1::<usize>::zeros(4));
+
+        match GroupKFold::new(1).indices(&groups) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("a single fold must be rejected"),
+        }
+        match GroupKFold::new(4).indices(&groups) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("more folds than groups must be rejected"),
+        }
+        let split = GroupKFold::new(2).split(&dataset, &groups);
+        match split {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("a group per sample is required"),
+        }
+    }
+}
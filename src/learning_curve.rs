@@ -0,0 +1,353 @@
+//! Learning curves
+//!
+//! A learning curve shows the training and validation score of a model as a function of the
+//! number of training samples. A large gap between both scores which closes with more samples
+//! indicates that the model overfits and benefits from more data, two low scores which converge
+//! quickly indicate that the model underfits.
+use ndarray::{Array1, Array2, Axis};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::cmp::Ordering;
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+use crate::traits::{Fit, Predict};
+
+/// Training and validation scores of a learning curve
+///
+/// The scores are stored with shape `(n_sizes, n_folds)`, one row per training set size.
+pub struct LearningCurve {
+    train_sizes: Vec<usize>,
+    train_scores: Array2<f64>,
+    validation_scores: Array2<f64>,
+}
+
+impl LearningCurve {
+    /// Return the absolute number of training samples of each size, averaged over the folds
+    pub fn train_sizes(&self) -> &[usize] {
+        &self.train_sizes
+    }
+
+    /// Return the training scores with shape `(n_sizes, n_folds)`
+    pub fn train_scores(&self) -> &Array2<f64> {
+        &self.train_scores
+    }
+
+    /// Return the validation scores with shape `(n_sizes, n_folds)`
+    pub fn validation_scores(&self) -> &Array2<f64> {
+        &self.validation_scores
+    }
+
+    /// Return the mean training score of each size over the folds
+    pub fn train_scores_mean(&self) -> Array1<f64> {
+        self.train_scores.mean_axis(Axis(1)).unwrap()
+    }
+
+    /// Return the standard deviation of the training score of each size over the folds
+    pub fn train_scores_std(&self) -> Array1<f64> {
+        self.train_scores.std_axis(Axis(1), 0.)
+    }
+
+    /// Return the mean validation score of each size over the folds
+    pub fn validation_scores_mean(&self) -> Array1<f64> {
+        self.validation_scores.mean_axis(Axis(1)).unwrap()
+    }
+
+    /// Return the standard deviation of the validation score of each size over the folds
+    pub fn validation_scores_std(&self) -> Array1<f64> {
+        self.validation_scores.std_axis(Axis(1), 0.)
+    }
+}
+
+/// Compute a learning curve with stratified k-fold cross-validation
+///
+/// The dataset is shuffled with `rng` and split into `cv_folds` stratified folds. For every fold and every fraction in
+/// `train_sizes` a new estimator is created with `estimator_factory` and fitted on a stratified
+/// subsample of this fraction of the remaining folds. The model is then scored on the subsample
+/// it was trained on and on the held-out fold with `scorer(prediction, ground_truth)`, for
+/// example
+/// ```rust, ignore
+/// let curve = learning_curve(
+///     LinearRegression::new,
+///     &dataset,
+///     &[0.1, 0.5, 1.0],
+///     5,
+///     |prediction, truth| truth.r2(prediction),
+///     &mut Isaac64Rng::seed_from_u64(42),
+/// )?;
+/// ```
+///
+/// Samples are stratified by their target if the targets are discrete, with at least two samples
+/// per distinct target on average. Otherwise, for example for continuous targets, the folds and
+/// subsamples are drawn uniformly at random. A seeded generator returns the same curve on
+/// repeated calls.
+pub fn learning_curve<A, L, E, F, S, R>(
+    estimator_factory: F,
+    dataset: &Dataset<Array2<A>, Array1<L>>,
+    train_sizes: &[f64],
+    cv_folds: usize,
+    scorer: S,
+    rng: &mut R,
+) -> Result<LearningCurve>
+where
+    A: Copy,
+    L: Clone + PartialOrd,
+    F: Fn() -> E,
+    E: Fit<Array2<A>, Array1<L>>,
+    E::Object: Predict<Array2<A>, Array1<L>>,
+    S: Fn(&Array1<L>, &Array1<L>) -> f64,
+    R: Rng,
+{
+    check_folds(dataset, cv_folds)?;
+    if let Some(size) = train_sizes.iter().find(|x| !(**x > 0. && **x <= 1.)) {
+        return Err(Error::Parameters(format!(
+            "train sizes must be fractions in (0, 1], got: {}",
+            size
+        )));
+    }
+
+    let folds = stratified_folds(dataset.targets(), cv_folds, rng);
+
+    let mut train_scores = Array2::zeros((train_sizes.len(), cv_folds));
+    let mut validation_scores = Array2::zeros((train_sizes.len(), cv_folds));
+    let mut total_sizes = vec![0; train_sizes.len()];
+    for (fold, (train, validation)) in folds.into_iter().enumerate() {
+        let validation_set = dataset.select_samples(&validation)?;
+        let train_order = stratified_order(dataset.targets(), &train, rng);
+
+        for (size_idx, size) in train_sizes.iter().enumerate() {
+            let n_train = ((size * train.len() as f64).round() as usize).max(1);
+            let train_set = dataset.select_samples(&train_order[..n_train])?;
+            let model = estimator_factory().fit(&train_set)?;
+
+            train_scores[(size_idx, fold)] =
+                scorer(&model.predict(train_set.records()), train_set.targets());
+            validation_scores[(size_idx, fold)] = scorer(
+                &model.predict(validation_set.records()),
+                validation_set.targets(),
+            );
+            total_sizes[size_idx] += n_train;
+        }
+    }
+
+    Ok(LearningCurve {
+        train_sizes: total_sizes
+            .into_iter()
+            .map(|total| total / cv_folds)
+            .collect(),
+        train_scores,
+        validation_scores,
+    })
+}
+
+/// Check that records and targets have the same length and that there are enough samples for
+/// `cv_folds` folds
+pub(crate) fn check_folds<A, L>(
+    dataset: &Dataset<Array2<A>, Array1<L>>,
+    cv_folds: usize,
+) -> Result<()> {
+    let n_samples = dataset.targets().len();
+    if dataset.records().nrows() != n_samples {
+        return Err(Error::Parameters(format!(
+            "number of records ({}) and targets ({}) differ",
+            dataset.records().nrows(),
+            n_samples
+        )));
+    }
+    if cv_folds < 2 || cv_folds > n_samples {
+        return Err(Error::Parameters(format!(
+            "cv_folds ({}) must be between 2 and the number of samples ({})",
+            cv_folds, n_samples
+        )));
+    }
+
+    Ok(())
+}
+
+/// Split the samples into `cv_folds` stratified folds, return the training and validation
+/// indices of each fold
+pub(crate) fn stratified_folds<L: PartialOrd, R: Rng>(
+    targets: &Array1<L>,
+    cv_folds: usize,
+    rng: &mut R,
+) -> Vec<(Vec<usize>, Vec<usize>)> {
+    // every prefix of the stratified order contains the classes in their overall proportions,
+    // dealing it out round-robin results in stratified folds
+    let all_indices = (0..targets.len()).collect::<Vec<_>>();
+    let order = stratified_order(targets, &all_indices, rng);
+
+    (0..cv_folds)
+        .map(|fold| {
+            let (validation, train): (Vec<_>, Vec<_>) = order
+                .iter()
+                .enumerate()
+                .partition(|(position, _)| position % cv_folds == fold);
+            (
+                train.into_iter().map(|(_, i)| *i).collect(),
+                validation.into_iter().map(|(_, i)| *i).collect(),
+            )
+        })
+        .collect()
+}
+
+/// Order `indices` such that every prefix contains the classes of `targets` in their overall
+/// proportions, the samples of each class are shuffled
+///
+/// Targets with fewer than two samples per distinct value on average are not discrete, their
+/// order is a uniform shuffle.
+fn stratified_order<L: PartialOrd, R: Rng>(
+    targets: &Array1<L>,
+    indices: &[usize],
+    rng: &mut R,
+) -> Vec<usize> {
+    let mut indices = indices.to_vec();
+    indices.shuffle(rng);
+
+    // incomparable targets like NaN cannot be grouped into classes
+    if indices
+        .iter()
+        .any(|i| targets[*i].partial_cmp(&targets[*i]).is_none())
+    {
+        return indices;
+    }
+
+    // group the positions in the shuffled order by class, the sort is stable to keep the
+    // shuffled order within a class
+    let mut by_class = (0..indices.len()).collect::<Vec<_>>();
+    by_class.sort_by(|a, b| {
+        targets[indices[*a]]
+            .partial_cmp(&targets[indices[*b]])
+            .unwrap_or(Ordering::Equal)
+    });
+    let mut classes = Vec::new();
+    let mut start = 0;
+    for end in 1..=by_class.len() {
+        if end == by_class.len()
+            || targets[indices[by_class[end]]] != targets[indices[by_class[start]]]
+        {
+            classes.push(&by_class[start..end]);
+            start = end;
+        }
+    }
+    if 2 * classes.len() > indices.len() {
+        return indices;
+    }
+
+    // the relative position of each sample within its class, ties keep the shuffled order
+    let mut keyed = classes
+        .iter()
+        .flat_map(|class| {
+            class
+                .iter()
+                .enumerate()
+                .map(move |(rank, position)| ((rank as f64 + 0.5) / class.len() as f64, *position))
+        })
+        .collect::<Vec<_>>();
+    keyed.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .unwrap_or(Ordering::Equal)
+            .then(a.1.cmp(&b.1))
+    });
+
+    keyed
+        .into_iter()
+        .map(|(_, position)| indices[position])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::Regression;
+    use linfa_linear::LinearRegression;
+    use ndarray::array;
+    use ndarray_rand::rand_distr::Normal;
+    use ndarray_rand::RandomExt;
+    use rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn validation_score_rises_with_more_data() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array2::random_using((100, 5), Normal::new(0., 1.).unwrap(), &mut rng);
+        let noise = Array1::random_using(100, Normal::new(0., 0.5).unwrap(), &mut rng);
+        let targets = records.dot(&array![1., -2., 0.5, 3., -1.]) + noise;
+        let dataset = Dataset::new(records, targets);
+
+        let curve = learning_curve(
+            LinearRegression::new,
+            &dataset,
+            &[0.1, 0.3, 1.0],
+            5,
+            |prediction: &Array1<f64>, truth: &Array1<f64>| truth.r2(prediction),
+            &mut Isaac64Rng::seed_from_u64(42),
+        )
+        .unwrap();
+
+        assert_eq!(curve.train_sizes(), &[8, 24, 80]);
+        assert_eq!(curve.train_scores().dim(), (3, 5));
+        assert!(curve.train_scores_mean().iter().all(|score| *score > 0.9));
+        let validation = curve.validation_scores_mean();
+        assert!(validation[0] < validation[1] && validation[1] < validation[2]);
+        assert!(validation[2] > 0.9);
+        // with few samples the scores vary strongly between the folds
+        assert!(curve.validation_scores_std()[0] > curve.validation_scores_std()[2]);
+    }
+
+    #[test]
+    fn stratified_order_keeps_class_proportions() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let targets = Array1::from(vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 2, 2, 2]);
+        let order = stratified_order(&targets, &(0..12).collect::<Vec<_>>(), &mut rng);
+
+        let count =
+            |prefix: &[usize], class| prefix.iter().filter(|i| targets[**i] == class).count();
+        assert_eq!(count(&order[..4], 0), 2);
+        assert_eq!(count(&order[..4], 1), 1);
+        assert_eq!(count(&order[..4], 2), 1);
+
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn continuous_targets_are_not_stratified() {
+        let indices = (0..1000).collect::<Vec<_>>();
+        let mut shuffled = indices.clone();
+        shuffled.shuffle(&mut Isaac64Rng::seed_from_u64(42));
+
+        // mostly distinct values, and values which cannot be compared
+        let mut targets = Array1::from_shape_fn(1000, |i| i as f64 / 7.);
+        targets[0] = targets[2];
+        let order = stratified_order(&targets, &indices, &mut Isaac64Rng::seed_from_u64(42));
+        assert_eq!(order, shuffled);
+
+        targets[10] = std::f64::NAN;
+        let order = stratified_order(&targets, &indices, &mut Isaac64Rng::seed_from_u64(42));
+        assert_eq!(order, shuffled);
+    }
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        let dataset = Dataset::new(array![[0.], [1.], [2.]], array![0., 1., 2.]);
+        let scorer = |prediction: &Array1<f64>, truth: &Array1<f64>| truth.r2(prediction);
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+
+        match learning_curve(LinearRegression::new, &dataset, &[1.0], 4, scorer, &mut rng) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("more folds than samples must be rejected"),
+        }
+        match learning_curve(
+            LinearRegression::new,
+            &dataset,
+            &[0., 1.0],
+            2,
+            scorer,
+            &mut rng,
+        ) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("empty training sets must be rejected"),
+        }
+    }
+}
@@ -31,18 +31,86 @@
 /// Clustering algorithms for unlabeled data.
 
 pub mod clustering {
+    pub use crate::cluster_result::ClusterResult;
     pub use linfa_clustering::*;
 }
 
+mod bootstrap;
+mod cluster_result;
+mod dataset;
+mod error;
+mod group_k_fold;
+mod learning_curve;
+mod linear;
+mod logistic;
 mod metrics_classification;
+mod metrics_distribution;
+mod metrics_multilabel;
 mod metrics_regression;
+mod multi_output;
+mod polynomial_features;
+mod ransac;
+mod reduction;
+mod standard_scaler;
+mod time_series_split;
+mod traits;
+mod transformed_target;
+mod validation_curve;
+
+pub use dataset::Dataset;
+pub use error::{Error, Result};
+pub use traits::{BatchedFit, Fit, InverseTransformer, Predict, PredictProba, Transformer};
+
+/// Meta-estimators combining a model with transformations
+
+pub mod compose {
+    pub use crate::transformed_target::{
+        FittedTransformedTargetRegressor, TransformedTargetRegressor,
+    };
+}
 
 /// Common metrics functions for classification and regression
 
 pub mod metrics {
     pub use crate::metrics_classification::{
-        BinaryClassification, ConfusionMatrix, IntoConfusionMatrix, Modify,
-        ReceiverOperatingCharacteristic,
+        kappa_score, log_loss, log_loss_multiclass, matthews_corrcoef,
+        multiclass_matthews_corrcoef, tune_threshold, weighted_kappa_score, BinaryClassification,
+        ConfusionMatrix, IntoConfusionMatrix, KappaWeighting, Modify,
+        ReceiverOperatingCharacteristic, ThresholdCurve, ThresholdMetric, LOG_LOSS_EPS,
     };
-    pub use crate::metrics_regression::Regression;
+    pub use crate::metrics_distribution::ks_2samp;
+    pub use crate::metrics_multilabel::MultiLabelClassification;
+    pub use crate::metrics_regression::{
+        explained_variance_score, huber_loss, mean_absolute_percentage_error, quantile_loss,
+        symmetric_mape, Regression,
+    };
+}
+
+/// Tools to evaluate and compare models
+
+pub mod model_selection {
+    pub use crate::bootstrap::{out_of_bag, Bootstrap};
+    pub use crate::group_k_fold::GroupKFold;
+    pub use crate::learning_curve::{learning_curve, LearningCurve};
+    pub use crate::time_series_split::TimeSeriesSplit;
+    pub use crate::validation_curve::{validation_curve, ValidationCurve};
+}
+
+/// Meta-estimators for regression with multiple targets
+
+pub mod multioutput {
+    pub use crate::multi_output::{FittedMultiOutputRegressor, MultiOutputRegressor};
+}
+
+/// Transformers to preprocess records before fitting a model
+
+pub mod preprocessing {
+    pub use crate::polynomial_features::PolynomialFeatures;
+    pub use crate::standard_scaler::StandardScaler;
+}
+
+/// Robust meta-estimators for regression with outliers
+
+pub mod robust {
+    pub use crate::ransac::{FittedRansac, Ransac};
 }
@@ -0,0 +1,129 @@
+//! Implementation of the common traits for the models of `linfa-linear`
+use linfa_linear::error::LinearError;
+use linfa_linear::ols::FittedLinearRegression;
+use linfa_linear::ridge::FittedRidgeRegression;
+use linfa_linear::{LinearRegression, RidgeRegression};
+use ndarray::{Array1, Array2};
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+use crate::traits::{BatchedFit, Fit, Predict};
+
+macro_rules! impl_linear {
+    ($($float:ty),*) => {
+        $(
+            impl Fit<Array2<$float>, Array1<$float>> for LinearRegression {
+                type Object = FittedLinearRegression<$float>;
+
+                fn fit(
+                    &self,
+                    dataset: &Dataset<Array2<$float>, Array1<$float>>,
+                ) -> Result<Self::Object> {
+                    match dataset.weights() {
+                        Some(weights) => LinearRegression::fit_weighted(
+                            self,
+                            &dataset.records,
+                            &dataset.targets,
+                            &weights.mapv(|x| x as $float),
+                        ),
+                        None => LinearRegression::fit(self, &dataset.records, &dataset.targets),
+                    }
+                    .map_err(|err| LinearError::InvalidValue(err).into())
+                }
+            }
+
+            impl BatchedFit<Array2<$float>, Array1<$float>> for LinearRegression {
+                type Object = FittedLinearRegression<$float>;
+
+                fn fit_batched<I>(&self, batches: I) -> Result<Self::Object>
+                where
+                    I: IntoIterator<Item = Dataset<Array2<$float>, Array1<$float>>>,
+                {
+                    // the normal equations are not weighted, stop at the first weighted batch
+                    let mut weighted = false;
+                    let batches = batches.into_iter().scan((), |_, dataset| {
+                        weighted = dataset.weights().is_some();
+                        if weighted {
+                            None
+                        } else {
+                            Some((dataset.records, dataset.targets))
+                        }
+                    });
+                    let model = LinearRegression::fit_batches(self, batches)
+                        .map_err(|err| Error::from(LinearError::InvalidValue(err)));
+
+                    if weighted {
+                        return Err(Error::Parameters(
+                            "weighted batches are not supported".into(),
+                        ));
+                    }
+
+                    model
+                }
+            }
+
+            impl Predict<Array2<$float>, Array1<$float>> for FittedLinearRegression<$float> {
+                fn predict(&self, x: &Array2<$float>) -> Array1<$float> {
+                    FittedLinearRegression::predict(self, x)
+                }
+            }
+
+            impl Fit<Array2<$float>, Array1<$float>> for RidgeRegression {
+                type Object = FittedRidgeRegression<$float>;
+
+                fn fit(
+                    &self,
+                    dataset: &Dataset<Array2<$float>, Array1<$float>>,
+                ) -> Result<Self::Object> {
+                    RidgeRegression::fit(self, &dataset.records, &dataset.targets)
+                        .map_err(Into::into)
+                }
+            }
+
+            impl Predict<Array2<$float>, Array1<$float>> for FittedRidgeRegression<$float> {
+                fn predict(&self, x: &Array2<$float>) -> Array1<$float> {
+                    FittedRidgeRegression::predict(self, x)
+                }
+            }
+        )*
+    };
+}
+
+impl_linear!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::dataset::Dataset;
+    use crate::error::Error;
+    use crate::traits::{BatchedFit, Fit};
+    use approx::assert_abs_diff_eq;
+    use linfa_linear::ols::FittedLinearRegression;
+    use linfa_linear::LinearRegression;
+    use ndarray::{array, s};
+
+    #[test]
+    fn batched_fit_matches_fit() {
+        let records = array![[1., 2.], [3., -1.], [0., 4.], [2., 2.], [5., 1.], [-1., 3.]];
+        let targets = array![3., 1., 5., 4., 2., 6.];
+        let dataset = Dataset::new(records.clone(), targets.clone());
+        let batches = (0..3).map(|i| {
+            Dataset::new(
+                records.slice(s![2 * i..2 * i + 2, ..]).to_owned(),
+                targets.slice(s![2 * i..2 * i + 2]).to_owned(),
+            )
+        });
+
+        let model: FittedLinearRegression<f64> =
+            Fit::fit(&LinearRegression::new(), &dataset).unwrap();
+        let batched: FittedLinearRegression<f64> =
+            LinearRegression::new().fit_batched(batches).unwrap();
+        assert_abs_diff_eq!(batched.params(), model.params(), epsilon = 1e-10);
+        assert_abs_diff_eq!(batched.intercept(), model.intercept(), epsilon = 1e-10);
+
+        let weighted = vec![dataset.with_weights(array![1., 1., 1., 2., 2., 2.])];
+        match LinearRegression::new().fit_batched(weighted) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("weighted batches must be rejected"),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Implementation of the common traits for the models of `linfa-logistic`
+use linfa_logistic::{
+    FittedLogisticRegression, FittedMultiLogisticRegression, LogisticRegression,
+    MultiLogisticRegression,
+};
+use ndarray::{Array1, Array2};
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+use crate::traits::{Fit, PredictProba};
+
+macro_rules! impl_logistic {
+    ($($float:ty),*) => {
+        $(
+            /// The weights of the dataset are multiplied with the class weights
+            impl<C: PartialOrd + Clone> Fit<Array2<$float>, Array1<C>>
+                for LogisticRegression<$float>
+            {
+                type Object = FittedLogisticRegression<$float, C>;
+
+                fn fit(
+                    &self,
+                    dataset: &Dataset<Array2<$float>, Array1<C>>,
+                ) -> Result<Self::Object> {
+                    match dataset.weights() {
+                        Some(weights) => LogisticRegression::fit_weighted(
+                            self,
+                            &dataset.records,
+                            dataset.targets.iter(),
+                            &weights.mapv(|x| x as $float),
+                        ),
+                        None => {
+                            LogisticRegression::fit(self, &dataset.records, dataset.targets.iter())
+                        }
+                    }
+                    .map_err(|err| Error::Parameters(err.to_string()))
+                }
+            }
+
+            impl<C: PartialOrd + Clone> Fit<Array2<$float>, Array1<C>>
+                for MultiLogisticRegression<$float>
+            {
+                type Object = FittedMultiLogisticRegression<$float, C>;
+
+                fn fit(
+                    &self,
+                    dataset: &Dataset<Array2<$float>, Array1<C>>,
+                ) -> Result<Self::Object> {
+                    match dataset.weights() {
+                        Some(weights) => MultiLogisticRegression::fit_weighted(
+                            self,
+                            &dataset.records,
+                            dataset.targets.iter(),
+                            &weights.mapv(|x| x as $float),
+                        ),
+                        None => MultiLogisticRegression::fit(
+                            self,
+                            &dataset.records,
+                            dataset.targets.iter(),
+                        ),
+                    }
+                    .map_err(Error::Parameters)
+                }
+            }
+
+            impl<C: PartialOrd + Clone> PredictProba<Array2<$float>, Array2<$float>>
+                for FittedLogisticRegression<$float, C>
+            {
+                fn predict_proba(&self, x: &Array2<$float>) -> Array2<$float> {
+                    FittedLogisticRegression::predict_proba(self, x)
+                }
+            }
+
+            impl<C: PartialOrd + Clone> PredictProba<Array2<$float>, Array2<$float>>
+                for FittedMultiLogisticRegression<$float, C>
+            {
+                fn predict_proba(&self, x: &Array2<$float>) -> Array2<$float> {
+                    self.predict_probabilities(x)
+                }
+            }
+        )*
+    };
+}
+
+impl_logistic!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use crate::traits::PredictProba;
+    use crate::Dataset;
+    use approx::assert_abs_diff_eq;
+    use linfa_logistic::{LogisticRegression, MultiLogisticRegression};
+    use ndarray::{array, Array1, Array2, Axis};
+
+    #[test]
+    fn fit_uses_dataset_weights() {
+        let records = array![[-2.0], [-1.0], [-0.5], [0.5], [1.0], [3.0]];
+        let weighted = Dataset::new(records.clone(), array![0, 0, 1, 0, 1, 1])
+            .with_weights(array![1., 1., 3., 1., 1., 1.]);
+        // the third sample is repeated three times
+        let repeated = Dataset::new(
+            array![[-2.0], [-1.0], [-0.5], [-0.5], [-0.5], [0.5], [1.0], [3.0]],
+            array![0, 0, 1, 1, 1, 0, 1, 1],
+        );
+
+        let binary = LogisticRegression::<f64>::default().gradient_tolerance(1e-8);
+        let (a, b) = (
+            crate::traits::Fit::fit(&binary, &weighted).unwrap(),
+            crate::traits::Fit::fit(&binary, &repeated).unwrap(),
+        );
+        assert_abs_diff_eq!(a.params(), b.params(), epsilon = 1e-6);
+        assert_abs_diff_eq!(a.intercept(), b.intercept(), epsilon = 1e-6);
+
+        let unweighted =
+            crate::traits::Fit::fit(&binary, &Dataset::new(records, array![0, 0, 1, 0, 1, 1]))
+                .unwrap();
+        assert!(unweighted.intercept() < a.intercept());
+
+        let multi = MultiLogisticRegression::<f64>::default().gradient_tolerance(1e-8);
+        let (a, b) = (
+            crate::traits::Fit::fit(&multi, &weighted).unwrap(),
+            crate::traits::Fit::fit(&multi, &repeated).unwrap(),
+        );
+        assert_abs_diff_eq!(a.params(), b.params(), epsilon = 1e-6);
+        assert_abs_diff_eq!(a.intercept(), b.intercept(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn probabilities_sum_to_one() {
+        let x = array![[-2.0], [-1.0], [0.0], [1.0], [2.0], [3.0]];
+        let binary = LogisticRegression::default()
+            .fit(&x, &[0, 0, 0, 1, 1, 1])
+            .unwrap();
+        let multi = MultiLogisticRegression::default()
+            .fit(&x, &[0, 0, 1, 1, 2, 2])
+            .unwrap();
+
+        let probs = binary.predict_proba(&x);
+        assert_eq!(probs.dim(), (6, 2));
+        assert_abs_diff_eq!(probs.sum_axis(Axis(1)), Array1::ones(6), epsilon = 1e-12);
+
+        let probs: Array2<f64> = multi.predict_proba(&x);
+        assert_eq!(probs.dim(), (6, 3));
+        assert_abs_diff_eq!(probs.sum_axis(Axis(1)), Array1::ones(6), epsilon = 1e-12);
+    }
+}
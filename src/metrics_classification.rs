@@ -11,6 +11,8 @@ use ndarray::prelude::*;
 use ndarray::Data;
 use ndarray::IntoNdProducer;
 
+use crate::error::{Error, Result};
+
 /// Return tuple of class index for each element of prediction and ground_truth
 fn map_prediction_to_idx<A: Eq + Hash, C: Data<Elem = A>, D: Data<Elem = A>>(
     prediction: &ArrayBase<C, Ix1>,
@@ -198,6 +200,28 @@ impl<A> ConfusionMatrix<A> {
         self.matrix.diag().sum() / self.matrix.sum()
     }
 
+    /// Balanced accuracy score
+    ///
+    /// The balanced accuracy is the mean over all classes of the ratio of correct classifications
+    /// to the number of samples of this class. Unlike the accuracy it is not dominated by the
+    /// majority class of an imbalanced dataset, always predicting the majority class scores
+    /// `1 / n_classes`. Classes without any samples in the ground truth are ignored.
+    pub fn balanced_accuracy(&self) -> f32 {
+        // the columns contain the ground truth
+        let support = self.matrix.sum_axis(Axis(0));
+        let (sum, count) = self
+            .matrix
+            .diag()
+            .iter()
+            .zip(support.iter())
+            .filter(|(_, support)| **support > 0.0)
+            .fold((0.0, 0), |(sum, count), (correct, support)| {
+                (sum + correct / support, count + 1)
+            });
+
+        sum / count as f32
+    }
+
     /// F-beta-score
     ///
     /// The F-beta-score averages between precision and recall. It is defined as
@@ -222,29 +246,43 @@ impl<A> ConfusionMatrix<A> {
     /// Estimates the normalized cross-correlation between target and predicted variable. The MCC
     /// is more significant than precision or recall, because all four quadrants are included in
     /// the evaluation. A generalized evaluation for multiple labels is also included.
+    ///
+    /// If all targets or all predictions belong to a single class the correlation is undefined,
+    /// in this case zero is returned.
     pub fn mcc(&self) -> f32 {
-        let mut cov_xy = 0.0;
-        for k in 0..self.members.len() {
-            for l in 0..self.members.len() {
-                for m in 0..self.members.len() {
-                    cov_xy += self.matrix[(k, k)] * self.matrix[(l, m)];
-                    cov_xy -= self.matrix[(k, l)] * self.matrix[(m, k)];
-                }
-            }
-        }
+        mcc(&self.matrix)
+    }
 
-        let sum = self.matrix.sum();
-        let sum_over_cols = self.matrix.sum_axis(Axis(0));
-        let sum_over_rows = self.matrix.sum_axis(Axis(1));
-
-        let mut cov_xx: f32 = 0.0;
-        let mut cov_yy: f32 = 0.0;
-        for k in 0..self.members.len() {
-            cov_xx += sum_over_rows[k] * (sum - sum_over_rows[k]);
-            cov_yy += sum_over_cols[k] * (sum - sum_over_cols[k]);
-        }
+    /// Cohen's kappa, the agreement between predictions and ground truth corrected for chance
+    ///
+    /// The observed disagreement is compared to the disagreement expected if both labelings were
+    /// independent with the same class frequencies
+    /// ```ignore
+    /// 1 - sum(weights * observed) / sum(weights * expected)
+    /// ```
+    /// Perfect agreement scores 1, agreement by chance 0. For ordinal labels the disagreements can
+    /// be weighted by the distance of the classes with [KappaWeighting](enum.KappaWeighting.html),
+    /// in the order of the classes of the confusion matrix. The score is symmetric in both
+    /// labelings, it can also compare two raters.
+    ///
+    /// If both labelings only contain the same single class, the agreement by chance is perfect
+    /// and the score undefined, in this case zero is returned.
+    pub fn cohen_kappa(&self, weighting: KappaWeighting) -> f32 {
+        kappa(&self.matrix, weighting)
+    }
 
-        cov_xy / cov_xx.sqrt() / cov_yy.sqrt()
+    /// Support of each class, the number of samples of this class in the ground truth
+    ///
+    /// The support is returned in the order of the classes of the confusion matrix and helps to
+    /// judge per-class scores on imbalanced datasets. For a weighted confusion matrix the
+    /// weights of the samples are summed and rounded.
+    pub fn support(&self) -> Vec<usize> {
+        // the columns contain the ground truth
+        self.matrix
+            .sum_axis(Axis(0))
+            .iter()
+            .map(|support| support.round() as usize)
+            .collect()
     }
 
     /// Split confusion matrix in N one-vs-all binary confusion matrices
@@ -289,6 +327,129 @@ impl<A> ConfusionMatrix<A> {
     }
 }
 
+impl ConfusionMatrix<usize> {
+    /// Label the classes with human-readable names, the class `k` is named `target_names[k]`
+    ///
+    /// This is typically used with the [target names](../struct.Dataset.html#method.target_names)
+    /// of a dataset, classes without a name keep their number.
+    pub fn with_target_names(&self, target_names: &[String]) -> ConfusionMatrix<String> {
+        ConfusionMatrix {
+            matrix: self.matrix.clone(),
+            members: self.members.map(|class| {
+                target_names
+                    .get(*class)
+                    .cloned()
+                    .unwrap_or_else(|| class.to_string())
+            }),
+        }
+    }
+}
+
+/// Print a confusion matrix followed by a classification report
+///
+/// The report lists precision, recall, f1-score and support of each class in a one-vs-all
+/// evaluation, together with the accuracy and the macro and support-weighted averages. Undefined
+/// scores of classes which are never predicted or never present are shown as zero.
+impl<A: fmt::Display> fmt::Display for ConfusionMatrix<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)?;
+        writeln!(f)?;
+
+        let names = self
+            .members
+            .iter()
+            .map(|class| class.to_string())
+            .collect::<Vec<_>>();
+        let width = names
+            .iter()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("weighted avg".len());
+
+        let zero_if_nan = |x: f32| if x.is_nan() { 0.0 } else { x };
+        let scores = self
+            .split_one_vs_all()
+            .into_iter()
+            .map(|cm| {
+                (
+                    zero_if_nan(cm.precision()),
+                    zero_if_nan(cm.recall()),
+                    zero_if_nan(cm.f1_score()),
+                )
+            })
+            .collect::<Vec<_>>();
+        let support = self.support();
+        let total = support.iter().sum::<usize>();
+
+        writeln!(
+            f,
+            "{:>width$} {:>10} {:>10} {:>10} {:>10}",
+            "",
+            "precision",
+            "recall",
+            "f1-score",
+            "support",
+            width = width
+        )?;
+        writeln!(f)?;
+        for ((name, (p, r, f1)), support) in names.iter().zip(&scores).zip(&support) {
+            writeln!(
+                f,
+                "{:>width$} {:>10.2} {:>10.2} {:>10.2} {:>10}",
+                name,
+                p,
+                r,
+                f1,
+                support,
+                width = width
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>width$} {:>10} {:>10} {:>10.2} {:>10}",
+            "accuracy",
+            "",
+            "",
+            self.accuracy(),
+            total,
+            width = width
+        )?;
+
+        let n_classes = scores.len() as f32;
+        let macro_avg = scores.iter().fold((0.0, 0.0, 0.0), |acc, (p, r, f1)| {
+            (
+                acc.0 + p / n_classes,
+                acc.1 + r / n_classes,
+                acc.2 + f1 / n_classes,
+            )
+        });
+        let weighted_avg =
+            scores
+                .iter()
+                .zip(&support)
+                .fold((0.0, 0.0, 0.0), |acc, ((p, r, f1), support)| {
+                    let weight = *support as f32 / total as f32;
+                    (acc.0 + p * weight, acc.1 + r * weight, acc.2 + f1 * weight)
+                });
+        for (name, (p, r, f1)) in &[("macro avg", macro_avg), ("weighted avg", weighted_avg)] {
+            writeln!(
+                f,
+                "{:>width$} {:>10.2} {:>10.2} {:>10.2} {:>10}",
+                name,
+                p,
+                r,
+                f1,
+                total,
+                width = width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Print a confusion matrix
 impl<A: fmt::Display> fmt::Debug for ConfusionMatrix<A> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -313,6 +474,237 @@ impl<A: fmt::Display> fmt::Debug for ConfusionMatrix<A> {
     }
 }
 
+/// Weighting of disagreements in [cohen_kappa](struct.ConfusionMatrix.html#method.cohen_kappa)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KappaWeighting {
+    /// All disagreements are weighted equally
+    Unweighted,
+    /// Disagreements are weighted by the distance of the classes
+    Linear,
+    /// Disagreements are weighted by the squared distance of the classes, this penalizes
+    /// disagreements between distant classes more strongly
+    Quadratic,
+}
+
+/// Count the pairs of labels in double precision
+///
+/// The classes are the sorted labels which occur in `y_true` or `y_pred`, as in a confusion
+/// matrix the rows contain the predictions and the columns the ground truth.
+fn count_matrix<L: Ord + Copy>(y_true: &[L], y_pred: &[L]) -> Array2<f64> {
+    let mut classes = y_true.iter().chain(y_pred).copied().collect::<Vec<_>>();
+    classes.sort_unstable();
+    classes.dedup();
+    let index = |label: &L| classes.binary_search(label).unwrap();
+
+    let mut matrix = Array2::zeros((classes.len(), classes.len()));
+    for (truth, prediction) in y_true.iter().zip(y_pred) {
+        matrix[(index(prediction), index(truth))] += 1.0;
+    }
+
+    matrix
+}
+
+/// Cohen's kappa of a confusion matrix, the classes are weighted by the distance of their indices
+fn kappa<F: NdFloat>(matrix: &Array2<F>, weighting: KappaWeighting) -> F {
+    let sum = matrix.sum();
+    let sum_over_cols = matrix.sum_axis(Axis(0));
+    let sum_over_rows = matrix.sum_axis(Axis(1));
+
+    let (mut observed, mut expected) = (F::zero(), F::zero());
+    for ((i, j), count) in matrix.indexed_iter() {
+        let distance = F::from(if i > j { i - j } else { j - i }).unwrap();
+        let weight = match weighting {
+            KappaWeighting::Unweighted => distance.min(F::one()),
+            KappaWeighting::Linear => distance,
+            KappaWeighting::Quadratic => distance * distance,
+        };
+
+        observed += weight * *count;
+        expected += weight * sum_over_rows[i] * sum_over_cols[j] / sum;
+    }
+
+    if expected == F::zero() {
+        return F::zero();
+    }
+
+    F::one() - observed / expected
+}
+
+/// Matthews correlation coefficient of a confusion matrix, zero if it is undefined
+fn mcc<F: NdFloat>(matrix: &Array2<F>) -> F {
+    let n_classes = matrix.nrows();
+    let mut cov_xy = F::zero();
+    for k in 0..n_classes {
+        for l in 0..n_classes {
+            for m in 0..n_classes {
+                cov_xy += matrix[(k, k)] * matrix[(l, m)];
+                cov_xy -= matrix[(k, l)] * matrix[(m, k)];
+            }
+        }
+    }
+
+    let sum = matrix.sum();
+    let sum_over_cols = matrix.sum_axis(Axis(0));
+    let sum_over_rows = matrix.sum_axis(Axis(1));
+
+    let (mut cov_xx, mut cov_yy) = (F::zero(), F::zero());
+    for k in 0..n_classes {
+        cov_xx += sum_over_rows[k] * (sum - sum_over_rows[k]);
+        cov_yy += sum_over_cols[k] * (sum - sum_over_cols[k]);
+    }
+
+    if cov_xx == F::zero() || cov_yy == F::zero() {
+        return F::zero();
+    }
+
+    cov_xy / cov_xx.sqrt() / cov_yy.sqrt()
+}
+
+/// Cohen's kappa of the predicted class labels `y_pred` against `y_true`
+///
+/// This is the unweighted [weighted_kappa_score](fn.weighted_kappa_score.html), see
+/// [cohen_kappa](struct.ConfusionMatrix.html#method.cohen_kappa) for details.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length.
+pub fn kappa_score(y_true: &[usize], y_pred: &[usize]) -> f64 {
+    weighted_kappa_score(y_true, y_pred, KappaWeighting::Unweighted)
+}
+
+/// Weighted Cohen's kappa of the predicted class labels `y_pred` against `y_true`
+///
+/// The distance of two labels is the distance of their positions among the sorted labels which
+/// occur in either labeling, the same as for
+/// [cohen_kappa](struct.ConfusionMatrix.html#method.cohen_kappa) of a confusion matrix with these
+/// classes. Labels which occur in neither labeling do not count towards the distance, for example
+/// the labels `1` and `3` are neighbours if `2` does not occur. The score is computed in double
+/// precision.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length.
+pub fn weighted_kappa_score(y_true: &[usize], y_pred: &[usize], weighting: KappaWeighting) -> f64 {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+
+    kappa(&count_matrix(y_true, y_pred), weighting)
+}
+
+/// Matthews correlation coefficient of the binary predictions `y_pred` against `y_true`
+///
+/// With the cells of the binary confusion matrix this is
+/// ```ignore
+/// (tp * tn - fp * fn) / sqrt((tp + fp) * (tp + fn) * (tn + fp) * (tn + fn))
+/// ```
+/// which ranges from -1 for inverted predictions over 0 for random predictions to 1 for perfect
+/// predictions. Unlike the accuracy it is not inflated by predicting the majority class of an
+/// imbalanced dataset. If all targets or all predictions belong to one class, the denominator is
+/// zero and the score undefined, in this case zero is returned. The counts and the score are
+/// computed in double precision, unlike [mcc](struct.ConfusionMatrix.html#method.mcc).
+///
+/// **Panics** if `y_true` and `y_pred` differ in length.
+pub fn matthews_corrcoef(y_true: &[bool], y_pred: &[bool]) -> f64 {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+
+    mcc(&count_matrix(y_true, y_pred))
+}
+
+/// Matthews correlation coefficient of the predicted class labels `y_pred` against `y_true`
+///
+/// This is the multiclass generalisation of [matthews_corrcoef](fn.matthews_corrcoef.html), see
+/// [mcc](struct.ConfusionMatrix.html#method.mcc). It coincides with the binary score for the
+/// labels `0` and `1` and returns zero in the same degenerate cases.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length.
+pub fn multiclass_matthews_corrcoef(y_true: &[usize], y_pred: &[usize]) -> f64 {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+
+    mcc(&count_matrix(y_true, y_pred))
+}
+
+/// Default clipping of the probabilities in [log_loss](fn.log_loss.html)
+pub const LOG_LOSS_EPS: f64 = 1e-15;
+
+/// Logistic loss, or cross-entropy, of the predicted probabilities `y_prob` of the positive class
+///
+/// This is the mean negative log-likelihood of the targets `y_true`
+/// ```ignore
+/// -1/n * sum_i (y_i * ln(p_i) + (1 - y_i) * ln(1 - p_i))
+/// ```
+/// and penalizes confident but wrong predictions heavily. The probabilities are clipped to
+/// `[eps, 1 - eps]` first, such that a probability of exactly zero or one for the wrong class
+/// yields a finite loss. A sensible choice for `eps` is [LOG_LOSS_EPS](constant.LOG_LOSS_EPS.html).
+///
+/// **Panics** if `y_true` and `y_prob` differ in length or are empty.
+pub fn log_loss<A: NdFloat>(y_true: &[bool], y_prob: &[A], eps: A) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_prob.len(),
+        "`y_true` and `y_prob` must have the same length"
+    );
+    assert!(!y_true.is_empty(), "`y_true` must not be empty");
+
+    let loss = y_true
+        .iter()
+        .zip(y_prob.iter())
+        .map(|(y, p)| {
+            let p = p.max(eps).min(A::one() - eps);
+            if *y {
+                -p.ln()
+            } else {
+                -(A::one() - p).ln()
+            }
+        })
+        .fold(A::zero(), |sum, loss| sum + loss);
+
+    loss / A::from(y_true.len()).unwrap()
+}
+
+/// Logistic loss, or cross-entropy, of the predicted class probabilities `y_prob`
+///
+/// The row `i` of `y_prob` holds the probabilities of sample `i` for the classes `0..n_classes`,
+/// as returned by [PredictProba](../trait.PredictProba.html), and `y_true[i]` is its class
+/// label. The loss is the mean negative log-probability of the true classes. The probabilities
+/// are clipped by [LOG_LOSS_EPS](constant.LOG_LOSS_EPS.html) and every row is renormalized to sum
+/// to one afterwards.
+///
+/// **Panics** if the number of rows of `y_prob` differs from the length of `y_true`, if they are
+/// empty or if a label is not smaller than the number of columns of `y_prob`.
+pub fn log_loss_multiclass<A: NdFloat>(y_true: &[usize], y_prob: ArrayView2<A>) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_prob.nrows(),
+        "`y_prob` must have one row per entry of `y_true`"
+    );
+    assert!(!y_true.is_empty(), "`y_true` must not be empty");
+
+    let eps = A::from(LOG_LOSS_EPS).unwrap();
+    let loss = y_true
+        .iter()
+        .zip(y_prob.genrows())
+        .map(|(y, probs)| {
+            assert!(
+                *y < probs.len(),
+                "label {} has no column in `y_prob` with {} classes",
+                y,
+                probs.len()
+            );
+            let probs = probs.mapv(|p| p.max(eps).min(A::one() - eps));
+            -(probs[*y] / probs.sum()).ln()
+        })
+        .fold(A::zero(), |sum, loss| sum + loss);
+
+    loss / A::from(y_true.len()).unwrap()
+}
+
 /// Classification for multi-label evaluation
 ///
 /// Contains a routine to calculate the confusion matrix, all other scores are derived form it.
@@ -351,7 +743,7 @@ impl<A: Clone + Ord + Hash, D: Data<Elem = A>> IntoConfusionMatrix<A> for Modifi
 
         // count each index tuple in the confusion matrix
         let mut confusion_matrix = Array2::zeros((classes.len(), classes.len()));
-        for (i1, i2) in indices.into_iter().filter_map(|x| x) {
+        for (i1, i2) in indices.into_iter().flatten() {
             confusion_matrix[(i1, i2)] += *self.weights.get(i1).unwrap_or(&1.0);
         }
 
@@ -513,12 +905,166 @@ impl<A: NdFloat, D: Data<Elem = A>> BinaryClassification<A> for ArrayBase<D, Ix1
         }
     }
 }
+
+/// Score of a binary confusion matrix, which lists the positive class first
+type ScoreFn = dyn Fn(&ConfusionMatrix<bool>) -> f32;
+
+/// Metric to maximize when tuning the decision threshold of a binary classifier
+pub enum ThresholdMetric {
+    /// F1-score of the positive class
+    F1,
+    /// Balanced accuracy, the mean of the true positive and true negative rates
+    BalancedAccuracy,
+    /// Mean misclassification cost, with a cost per false positive and per false negative
+    ///
+    /// The negated cost is maximized, so the curve contains non-positive values.
+    Cost {
+        false_positive: f32,
+        false_negative: f32,
+    },
+    /// Custom score of the binary confusion matrix, which lists the positive class first
+    Custom(Box<ScoreFn>),
+}
+
+/// The scores of a metric for the decision thresholds of a binary classifier
+///
+/// Returned by [tune_threshold](fn.tune_threshold.html).
+pub struct ThresholdCurve<A> {
+    thresholds: Vec<A>,
+    scores: Vec<f32>,
+    best: usize,
+}
+
+impl<A: NdFloat> ThresholdCurve<A> {
+    /// Returns the thresholds in increasing order
+    pub fn get_thresholds(&self) -> Vec<A> {
+        self.thresholds.clone()
+    }
+
+    /// Returns the score of the metric for each threshold
+    pub fn get_scores(&self) -> Vec<f32> {
+        self.scores.clone()
+    }
+
+    /// Returns the threshold with the best score
+    pub fn best_threshold(&self) -> A {
+        self.thresholds[self.best]
+    }
+
+    /// Returns the best score
+    pub fn best_score(&self) -> f32 {
+        self.scores[self.best]
+    }
+}
+
+/// Sweep the decision threshold of the predicted `scores` against the binary `targets` and
+/// find the threshold which maximizes `metric`
+///
+/// A sample is predicted positive if its score is at least the threshold. The default threshold
+/// of one half is rarely optimal for imbalanced data or uneven misclassification costs. Every
+/// distinct score is a candidate threshold, undefined scores of the metric, for example the
+/// F1-score without any positive prediction, are skipped in the search for the best threshold.
+/// Of several thresholds with the best score the smallest one is chosen.
+///
+/// Returns an `Error::InvalidInput` if `scores` and `targets` differ in length, are empty or a
+/// score is NaN.
+pub fn tune_threshold<A: NdFloat>(
+    scores: &[A],
+    targets: &[bool],
+    metric: &ThresholdMetric,
+) -> Result<ThresholdCurve<A>> {
+    if scores.len() != targets.len() {
+        return Err(Error::InvalidInput(format!(
+            "number of scores ({}) and targets ({}) differ",
+            scores.len(),
+            targets.len()
+        )));
+    }
+    if scores.is_empty() {
+        return Err(Error::InvalidInput("scores must not be empty".into()));
+    }
+    if let Some(idx) = scores.iter().position(|x| x.is_nan()) {
+        return Err(Error::InvalidInput(format!("score {} is NaN", idx)));
+    }
+
+    let mut pairs = scores
+        .iter()
+        .cloned()
+        .zip(targets.iter().cloned())
+        .collect::<Vec<_>>();
+    // there is no NaN left, the scores are totally ordered
+    pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let n_positives = targets.iter().filter(|t| **t).count() as f32;
+    let n_negatives = targets.len() as f32 - n_positives;
+
+    // the samples below the threshold are predicted negative, starting with all samples
+    // predicted positive at the smallest score
+    let (mut false_negatives, mut true_negatives) = (0., 0.);
+    let mut thresholds = Vec::new();
+    let mut curve = Vec::new();
+    let mut i = 0;
+    while i < pairs.len() {
+        let threshold = pairs[i].0;
+        let cm = ConfusionMatrix {
+            matrix: array![
+                [n_positives - false_negatives, n_negatives - true_negatives],
+                [false_negatives, true_negatives]
+            ],
+            members: Array1::from(vec![true, false]),
+        };
+
+        thresholds.push(threshold);
+        curve.push(match metric {
+            ThresholdMetric::F1 => cm.f1_score(),
+            ThresholdMetric::BalancedAccuracy => cm.balanced_accuracy(),
+            ThresholdMetric::Cost {
+                false_positive,
+                false_negative,
+            } => {
+                -(false_positive * cm.matrix[(0, 1)] + false_negative * cm.matrix[(1, 0)])
+                    / targets.len() as f32
+            }
+            ThresholdMetric::Custom(score) => score(&cm),
+        });
+
+        while i < pairs.len() && pairs[i].0 == threshold {
+            if pairs[i].1 {
+                false_negatives += 1.;
+            } else {
+                true_negatives += 1.;
+            }
+            i += 1;
+        }
+    }
+
+    let mut best = 0;
+    for (idx, score) in curve.iter().enumerate() {
+        if !curve[best].is_finite() || (score.is_finite() && *score > curve[best]) {
+            best = idx;
+        }
+    }
+
+    Ok(ThresholdCurve {
+        thresholds,
+        scores: curve,
+        best,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{BinaryClassification, IntoConfusionMatrix, Modify};
-    use approx::{abs_diff_eq, AbsDiffEq};
-    use ndarray::{array, Array1, ArrayBase, ArrayView1, Data, Dimension};
-    use rand::{distributions::Uniform, Rng};
+    use super::{
+        kappa_score, log_loss, log_loss_multiclass, matthews_corrcoef,
+        multiclass_matthews_corrcoef, tune_threshold, weighted_kappa_score, BinaryClassification,
+        IntoConfusionMatrix, KappaWeighting, Modify, ThresholdMetric, LOG_LOSS_EPS,
+    };
+    use crate::dataset::Dataset;
+    use crate::error::Error;
+    use approx::{abs_diff_eq, assert_abs_diff_eq, AbsDiffEq};
+    use ndarray::{array, Array1, Array2, ArrayBase, ArrayView1, Data, Dimension};
+    use rand::{distributions::Uniform, Rng, SeedableRng};
+    use rand_isaac::Isaac64Rng;
     use std::borrow::Borrow;
 
     fn assert_eq_slice<
@@ -562,6 +1108,56 @@ mod tests {
         assert_eq_slice(cm.matrix, &[2., 1., 0., 3.]);
     }
 
+    #[test]
+    fn test_cm_target_names() {
+        let dataset = Dataset::new(Array2::<f64>::zeros((4, 1)), array![0usize, 1, 2, 2])
+            .with_target_names(vec!["cat".to_string(), "dog".to_string()]);
+        let predicted = array![0usize, 1, 1, 2];
+
+        let cm = predicted
+            .into_confusion_matrix(dataset.targets())
+            .with_target_names(dataset.target_names().unwrap());
+        let printed = cm.to_string();
+
+        assert!(printed.contains("| cat") && printed.contains("| dog"));
+        // the third class has no name and keeps its number
+        assert!(printed.contains("| 2"));
+        // the matrix is followed by the classification report
+        assert!(printed.starts_with(&format!("{:?}", cm)));
+        assert!(printed.contains("weighted avg"));
+    }
+
+    #[test]
+    fn test_cm_support_and_report() {
+        let predicted = array![0, 0, 0, 0, 1, 0, 0, 2, 1, 0];
+        let ground_truth = array![0, 0, 0, 0, 0, 0, 1, 1, 1, 2];
+
+        let cm = predicted.into_confusion_matrix(&ground_truth);
+        assert_eq!(cm.support(), vec![6, 3, 1]);
+
+        let report = cm.to_string();
+        let rows = report
+            .lines()
+            .skip_while(|line| !line.contains("precision"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows[0].split_whitespace().collect::<Vec<_>>(),
+            vec!["precision", "recall", "f1-score", "support"]
+        );
+        // one row per class, the last columns are the f1-score and the support
+        for (row, (class, support)) in rows[2..5].iter().zip(&[("0", "6"), ("1", "3"), ("2", "1")])
+        {
+            let columns = row.split_whitespace().collect::<Vec<_>>();
+            assert_eq!(columns.len(), 5);
+            assert_eq!((columns[0], columns[4]), (*class, *support));
+        }
+        // the third class is never predicted correctly, its scores are zero
+        assert!(rows[4].contains("0.00       0.00       0.00"));
+        assert!(rows[6].starts_with("    accuracy") && rows[6].ends_with(" 0.60         10"));
+        assert!(rows[7].trim_start().starts_with("macro avg"));
+        assert!(rows[8].starts_with("weighted avg") && rows[8].ends_with(" 10"));
+    }
+
     #[test]
     fn test_cm_metrices() {
         let predicted = Array1::from(vec![0, 1, 0, 1, 0, 1]);
@@ -589,6 +1185,195 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_majority_predictor() {
+        // an imbalanced dataset where always the majority class is predicted
+        let ground_truth = (0..100).map(|i| i < 10).collect::<Vec<_>>();
+        let predicted = vec![false; 100];
+
+        let cm = predicted.into_confusion_matrix(&ground_truth);
+
+        assert_abs_diff_eq!(cm.accuracy(), 0.9);
+        assert_abs_diff_eq!(cm.balanced_accuracy(), 0.5);
+        assert_abs_diff_eq!(cm.mcc(), 0.0);
+
+        // with one of three classes missing the balanced accuracy averages the two others
+        let predicted = array![0, 0, 1, 2, 1, 1];
+        let ground_truth = array![0, 0, 0, 1, 1, 1];
+
+        let cm = predicted.into_confusion_matrix(&ground_truth);
+        assert_abs_diff_eq!(cm.balanced_accuracy(), 2. / 3., epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_cohen_kappa() {
+        let a = array![0, 1, 2, 3, 3, 2, 1, 0, 2, 3];
+        let b = array![0, 2, 2, 3, 2, 2, 1, 1, 3, 3];
+
+        let cm = a.clone().into_confusion_matrix(&a);
+        assert_abs_diff_eq!(cm.cohen_kappa(KappaWeighting::Unweighted), 1.0);
+        assert_abs_diff_eq!(cm.cohen_kappa(KappaWeighting::Quadratic), 1.0);
+
+        // all disagreements are between neighbouring classes, which weighting forgives partially
+        let cm = a.into_confusion_matrix(&b);
+        let unweighted = cm.cohen_kappa(KappaWeighting::Unweighted);
+        let linear = cm.cohen_kappa(KappaWeighting::Linear);
+        let quadratic = cm.cohen_kappa(KappaWeighting::Quadratic);
+        assert_abs_diff_eq!(unweighted, 0.452_054_8, epsilon = 1e-6);
+        assert_abs_diff_eq!(linear, 0.649_122_8, epsilon = 1e-6);
+        assert_abs_diff_eq!(quadratic, 0.813_084_1, epsilon = 1e-6);
+
+        // independent labelings agree only by chance
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let range = Uniform::new(0, 4);
+        let a = (0..2000).map(|_| rng.sample(range)).collect::<Vec<_>>();
+        let b = (0..2000).map(|_| rng.sample(range)).collect::<Array1<_>>();
+        let kappa = a
+            .into_confusion_matrix(&b)
+            .cohen_kappa(KappaWeighting::Unweighted);
+        assert!(kappa.abs() < 0.05);
+
+        // a single shared class has no chance-corrected agreement
+        let cm = array![1, 1, 1].into_confusion_matrix(&array![1, 1, 1]);
+        assert_abs_diff_eq!(cm.cohen_kappa(KappaWeighting::Linear), 0.0);
+    }
+
+    #[test]
+    fn test_kappa_score() {
+        let a = [0, 1, 2, 3, 3, 2, 1, 0, 2, 3];
+        let b = [0, 2, 2, 3, 2, 2, 1, 1, 3, 3];
+        assert_abs_diff_eq!(kappa_score(&a, &b), 0.452_054_8, epsilon = 1e-6);
+        assert_abs_diff_eq!(
+            weighted_kappa_score(&a, &b, KappaWeighting::Quadratic),
+            0.813_084_1,
+            epsilon = 1e-6
+        );
+
+        // binary labels, observed agreement 3/4 against 1/2 by chance
+        assert_abs_diff_eq!(
+            kappa_score(&[0, 0, 1, 1], &[0, 1, 1, 1]),
+            0.5,
+            epsilon = 1e-6
+        );
+
+        // the missing label 2 does not separate 1 and 3
+        assert_abs_diff_eq!(
+            weighted_kappa_score(&[0, 1, 3], &[0, 3, 3], KappaWeighting::Linear),
+            2. / 3.,
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            weighted_kappa_score(&[0, 1, 3], &[0, 3, 3], KappaWeighting::Linear),
+            weighted_kappa_score(&[0, 1, 2], &[0, 2, 2], KappaWeighting::Linear),
+            epsilon = 1e-12
+        );
+
+        // only the labels which occur are allocated
+        assert_abs_diff_eq!(
+            kappa_score(&[0, 1_000_000_000, 0], &[0, 1_000_000_000, 1_000_000_000]),
+            kappa_score(&[0, 1, 0], &[0, 1, 1]),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_matthews_corrcoef() {
+        let y_true = [true, true, false, true, false, true];
+        let y_pred = [false, true, false, true, false, true];
+        // tp = 3, tn = 2, fp = 0, fn = 1
+        assert_abs_diff_eq!(
+            matthews_corrcoef(&y_true, &y_pred),
+            6. / 72f64.sqrt(),
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(matthews_corrcoef(&y_true, &y_true), 1.0, epsilon = 1e-6);
+        let inverted = y_true.iter().map(|y| !y).collect::<Vec<_>>();
+        assert_abs_diff_eq!(matthews_corrcoef(&y_true, &inverted), -1.0, epsilon = 1e-6);
+
+        // a constant prediction has a zero denominator
+        assert_abs_diff_eq!(matthews_corrcoef(&y_true, &[true; 6]), 0.0);
+
+        // the multiclass score agrees with the binary score on two classes
+        let to_labels = |y: &[bool]| y.iter().map(|y| *y as usize).collect::<Vec<_>>();
+        assert_abs_diff_eq!(
+            multiclass_matthews_corrcoef(&to_labels(&y_true), &to_labels(&y_pred)),
+            matthews_corrcoef(&y_true, &y_pred),
+            epsilon = 1e-6
+        );
+
+        let a = [0, 1, 2, 3, 3, 2, 1, 0, 2, 3];
+        assert_abs_diff_eq!(multiclass_matthews_corrcoef(&a, &a), 1.0, epsilon = 1e-6);
+        let b = [0, 2, 2, 3, 2, 2, 1, 1, 3, 3];
+        let mcc = multiclass_matthews_corrcoef(&a, &b);
+        assert!(mcc > 0.0 && mcc < 1.0);
+
+        // the covariance terms are products of large counts, which single precision rounds
+        let n = 1_000_000;
+        let y_true = (0..n).map(|i| i % 2 == 0).collect::<Vec<_>>();
+        let y_pred = (0..n)
+            .map(|i| i % 2 == 0 || i % 1000 == 1)
+            .collect::<Vec<_>>();
+        // tp = n / 2, tn = n / 2 - n / 1000, fp = n / 1000, fn = 0
+        let (tp, tn, fp) = (500_000f64, 499_000f64, 1_000f64);
+        let expected = tp * tn / ((tp + fp) * tp * tn * (tn + fp)).sqrt();
+        assert_abs_diff_eq!(
+            matthews_corrcoef(&y_true, &y_pred),
+            expected,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_log_loss() {
+        let y_true = [true, false, true, true];
+        let y_prob = [0.9, 0.2, 0.6, 1.0];
+        let expected = -(0.9f64.ln() + 0.8f64.ln() + 0.6f64.ln() + (1. - 1e-15f64).ln()) / 4.;
+        assert_abs_diff_eq!(
+            log_loss(&y_true, &y_prob, LOG_LOSS_EPS),
+            expected,
+            epsilon = 1e-10
+        );
+
+        // a certain but wrong prediction is clipped to a finite loss
+        let loss = log_loss(&[false], &[1.0], LOG_LOSS_EPS);
+        assert!(loss.is_finite());
+        // the probability is clipped to `1 - eps`, in floating point `1 - (1 - eps)` differs
+        // from `eps`
+        assert_abs_diff_eq!(loss, -(1. - (1. - LOG_LOSS_EPS)).ln(), epsilon = 1e-10);
+        assert_abs_diff_eq!(
+            log_loss(&[true], &[0.5f32], 1e-7),
+            2f32.ln(),
+            epsilon = 1e-6
+        );
+
+        // the multiclass loss agrees with the binary loss on two classes
+        let y_prob = array![[0.1, 0.9], [0.8, 0.2], [0.4, 0.6], [0.0, 1.0]];
+        assert_abs_diff_eq!(
+            log_loss_multiclass(&[1, 0, 1, 1], y_prob.view()),
+            expected,
+            epsilon = 1e-10
+        );
+
+        let y_prob = array![[0.2, 0.7, 0.1], [0.6, 0.2, 0.2], [0.6, 0.1, 0.3]];
+        assert_abs_diff_eq!(
+            log_loss_multiclass(&[1, 0, 2], y_prob.view()),
+            -(0.7f64.ln() + 0.6f64.ln() + 0.3f64.ln()) / 3.,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_log_loss_multiclass_missing_class() {
+        log_loss_multiclass(&[0, 2], array![[0.5, 0.5], [0.5, 0.5]].view());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_kappa_score_length_mismatch() {
+        kappa_score(&[0, 1], &[0]);
+    }
+
     #[test]
     fn test_modification() {
         let predicted = array![0, 3, 2, 0, 1, 1, 1, 3, 2, 3];
@@ -671,4 +1456,74 @@ mod tests {
             .zip(result.iter())
             .for_each(|(x, r)| assert_eq_slice(x.matrix, r))
     }
+
+    #[test]
+    fn test_tune_threshold() {
+        // 90 negatives with scores in [0, 0.4) and 10 positives with scores in [0.25, 0.61], the
+        // scores of the positive class are too low
+        let mut scores = (0..90).map(|i| i as f64 * 0.4 / 90.).collect::<Vec<_>>();
+        scores.extend((0..10).map(|i| 0.25 + i as f64 * 0.04));
+        let targets = (0..100).map(|i| i >= 90).collect::<Vec<_>>();
+
+        // at one half there are 3 true positives, 7 false negatives and no false positives
+        let default_f1 = 6. / 13.;
+
+        let curve = tune_threshold(&scores, &targets, &ThresholdMetric::F1).unwrap();
+        assert_eq!(curve.get_thresholds().len(), curve.get_scores().len());
+        assert!(curve
+            .get_thresholds()
+            .windows(2)
+            .all(|pair| pair[0] < pair[1]));
+        assert!(curve.best_threshold() < 0.5);
+        assert!(curve.best_score() > default_f1 + 0.1);
+
+        // the best threshold is reproduced by the confusion matrix of its predictions
+        let predictions = scores
+            .iter()
+            .map(|s| (*s >= curve.best_threshold()) as usize)
+            .collect::<Vec<_>>();
+        let labels = targets.iter().map(|t| *t as usize).collect::<Vec<_>>();
+        let cm = ArrayView1::from(&predictions[..])
+            .with_classes(&[1, 0])
+            .into_confusion_matrix(ArrayView1::from(&labels[..]));
+        assert_abs_diff_eq!(cm.f1_score(), curve.best_score(), epsilon = 1e-6);
+
+        // without costs for false positives everything is predicted positive
+        let cost = ThresholdMetric::Cost {
+            false_positive: 0.,
+            false_negative: 1.,
+        };
+        let curve = tune_threshold(&scores, &targets, &cost).unwrap();
+        assert_abs_diff_eq!(curve.best_threshold(), 0.);
+        assert_abs_diff_eq!(curve.best_score(), 0.);
+
+        let accuracy = ThresholdMetric::Custom(Box::new(|cm| cm.accuracy()));
+        let curve = tune_threshold(&scores, &targets, &accuracy).unwrap();
+        assert!(curve.best_score() >= 0.9);
+
+        let curve = tune_threshold(&scores, &targets, &ThresholdMetric::BalancedAccuracy).unwrap();
+        assert!(curve.best_score() > 0.5);
+    }
+
+    #[test]
+    fn test_tune_threshold_rejects_invalid_scores() {
+        let targets = [false, true, true];
+        match tune_threshold(&[0.1, f64::NAN, 0.8], &targets, &ThresholdMetric::F1) {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("NaN scores must be rejected"),
+        }
+        match tune_threshold(&[0.1, 0.8], &targets, &ThresholdMetric::F1) {
+            Err(Error::InvalidInput(_)) => {}
+            _ => panic!("scores and targets of different length must be rejected"),
+        }
+
+        // infinite scores are ordered as usual
+        let curve = tune_threshold(
+            &[f64::NEG_INFINITY, 0.5, f64::INFINITY],
+            &targets,
+            &ThresholdMetric::F1,
+        )
+        .unwrap();
+        assert_eq!(curve.best_threshold(), 0.5);
+    }
 }
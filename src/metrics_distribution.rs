@@ -0,0 +1,270 @@
+//! Comparison of distributions
+//!
+//! This module implements tests whether two samples are drawn from the same distribution, for
+//! example to detect a drift of the features between the training data and production data.
+
+use ndarray::NdFloat;
+
+/// Two-sample Kolmogorov-Smirnov test
+///
+/// Returns the statistic, the largest distance between the empirical distribution functions of
+/// `a` and `b`, and the p-value of the hypothesis that both samples are drawn from the same
+/// continuous distribution. The p-value uses the asymptotic Kolmogorov distribution with the
+/// effective sample size `n_a * n_b / (n_a + n_b)` and the correction of Stephens for small
+/// samples, it is accurate for effective sample sizes of about four and larger.
+///
+/// Tied values are handled in the standard way: the distribution functions jump by all samples
+/// of a value at once and are only compared between distinct values, so ties within or across
+/// the samples never inflate the statistic and the result does not depend on the order of the
+/// samples. The asymptotic p-value is conservative for tied samples, so for those the p-value
+/// follows Conover's approach for discontinuous distributions: it is the exact probability of a
+/// statistic at least as large when the pooled values are assigned to two samples of sizes `n_a`
+/// and `n_b` at random, with the distance again only compared between distinct values. The exact
+/// computation takes `O(n_a * n_b)` steps and is used up to `n_a * n_b` of ten million, larger
+/// tied samples fall back to the asymptotic p-value.
+///
+/// **Panics** if one of the samples is empty or contains NaN.
+pub fn ks_2samp<A: NdFloat>(a: &[A], b: &[A]) -> (A, f64) {
+    assert!(
+        !a.is_empty() && !b.is_empty(),
+        "both samples must be non-empty"
+    );
+
+    let sorted = |sample: &[A]| {
+        let mut sample = sample.to_vec();
+        sample.sort_by(|x, y| {
+            x.partial_cmp(y)
+                .expect("samples must not contain NaN values")
+        });
+        sample
+    };
+    let (a, b) = (sorted(a), sorted(b));
+    let (n_a, n_b) = (a.len(), b.len());
+
+    let (mut i, mut j) = (0, 0);
+    let mut statistic = A::zero();
+    // the statistic scaled by `n_a * n_b`, compared exactly in the p-value for ties
+    let mut scaled_statistic = 0;
+    while i < n_a && j < n_b {
+        // step over all samples with the smallest remaining value in both samples
+        let value = if a[i] < b[j] { a[i] } else { b[j] };
+        while i < n_a && a[i] <= value {
+            i += 1;
+        }
+        while j < n_b && b[j] <= value {
+            j += 1;
+        }
+
+        let distance = (A::from(i).unwrap() / A::from(n_a).unwrap()
+            - A::from(j).unwrap() / A::from(n_b).unwrap())
+        .abs();
+        if distance > statistic {
+            statistic = distance;
+        }
+        scaled_statistic = scaled_statistic.max(scaled_distance(i, j, n_a, n_b));
+    }
+
+    let mut pooled = a.iter().chain(b.iter()).cloned().collect::<Vec<_>>();
+    pooled.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    if pooled.windows(2).any(|pair| pair[0] == pair[1]) && n_a * n_b <= EXACT_TIES_LIMIT {
+        let p_value = exact_survival_with_ties(&pooled, n_a, n_b, scaled_statistic);
+        return (statistic, p_value);
+    }
+
+    let n_eff = ((n_a * n_b) as f64 / (n_a + n_b) as f64).sqrt();
+    let lambda = (n_eff + 0.12 + 0.11 / n_eff) * statistic.to_f64().unwrap();
+
+    (statistic, kolmogorov_survival(lambda))
+}
+
+/// Largest `n_a * n_b` for which the p-value of tied samples is computed exactly
+const EXACT_TIES_LIMIT: usize = 10_000_000;
+
+/// The distance `|i / n_a - j / n_b|` scaled by `n_a * n_b`
+fn scaled_distance(i: usize, j: usize, n_a: usize, n_b: usize) -> usize {
+    let (x, y) = (i * n_b, j * n_a);
+    if x > y {
+        x - y
+    } else {
+        y - x
+    }
+}
+
+/// The probability that a random split of the sorted `pooled` values into samples of sizes `n_a`
+/// and `n_b` has a scaled statistic of at least `scaled_statistic`
+///
+/// A split is a lattice path from `(0, 0)` to `(n_a, n_b)`, where the point `(i, j)` means that
+/// the `i + j` smallest values contain `i` values of the first sample. The distance is only
+/// checked at points between distinct values. For each point where a path first reaches the
+/// statistic, the fraction of paths reaching it without doing so before is multiplied by the
+/// hypergeometric probability of the point, which keeps small p-values accurate.
+fn exact_survival_with_ties<A: NdFloat>(
+    pooled: &[A],
+    n_a: usize,
+    n_b: usize,
+    scaled_statistic: usize,
+) -> f64 {
+    if scaled_statistic == 0 {
+        return 1.0;
+    }
+
+    let n = n_a + n_b;
+    let ln_factorial = std::iter::once(0.0)
+        .chain((1..=n).scan(0.0, |sum, k| {
+            *sum += (k as f64).ln();
+            Some(*sum)
+        }))
+        .collect::<Vec<f64>>();
+    let ln_binomial = |n: usize, k: usize| ln_factorial[n] - ln_factorial[k] - ln_factorial[n - k];
+
+    // fraction of the paths to `(i, j)` which did not reach the statistic before, the row `i` is
+    // updated in place from the row `i - 1`
+    let mut fraction = vec![0.0; n_b + 1];
+    let mut p_value = 0.0;
+    for i in 0..=n_a {
+        for j in 0..=n_b {
+            let k = i + j;
+            if k == 0 {
+                fraction[0] = 1.0;
+                continue;
+            }
+
+            let mut reached = 0.0;
+            if i > 0 {
+                reached += fraction[j] * i as f64 / k as f64;
+            }
+            if j > 0 {
+                reached += fraction[j - 1] * j as f64 / k as f64;
+            }
+
+            let between_values = k == n || pooled[k - 1] < pooled[k];
+            if between_values && scaled_distance(i, j, n_a, n_b) >= scaled_statistic {
+                let probability = ln_binomial(n_a, i) + ln_binomial(n_b, j) - ln_binomial(n, k);
+                p_value += reached * probability.exp();
+                fraction[j] = 0.0;
+            } else {
+                fraction[j] = reached;
+            }
+        }
+    }
+
+    p_value.clamp(0.0, 1.0)
+}
+
+/// The probability that a variable of the Kolmogorov distribution exceeds `lambda`
+fn kolmogorov_survival(lambda: f64) -> f64 {
+    if lambda <= 0.0 {
+        return 1.0;
+    }
+
+    // both series converge for all positive values, but each only quickly on one side
+    let p = if lambda < 1.18 {
+        let factor = -std::f64::consts::PI.powi(2) / (8.0 * lambda * lambda);
+        let sum = (1..=10)
+            .map(|k| ((2 * k - 1) as f64).powi(2) * factor)
+            .map(f64::exp)
+            .sum::<f64>();
+
+        1.0 - (2.0 * std::f64::consts::PI).sqrt() / lambda * sum
+    } else {
+        let sum = (1..=100)
+            .map(|k| {
+                let sign = if k % 2 == 1 { 1.0 } else { -1.0 };
+                sign * (-2.0 * (k * k) as f64 * lambda * lambda).exp()
+            })
+            .sum::<f64>();
+
+        2.0 * sum
+    };
+
+    p.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{kolmogorov_survival, ks_2samp};
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_kolmogorov_survival() {
+        assert_abs_diff_eq!(kolmogorov_survival(0.0), 1.0);
+        assert_abs_diff_eq!(kolmogorov_survival(0.5), 0.963_945, epsilon = 1e-6);
+        assert_abs_diff_eq!(kolmogorov_survival(1.0), 0.270_000, epsilon = 1e-6);
+        assert_abs_diff_eq!(kolmogorov_survival(1.36), 0.049_486, epsilon = 1e-6);
+        assert_abs_diff_eq!(kolmogorov_survival(2.0), 0.000_671, epsilon = 1e-6);
+        // both series agree at the switch
+        assert_abs_diff_eq!(kolmogorov_survival(1.18), 0.123_454, epsilon = 1e-6);
+        assert_abs_diff_eq!(kolmogorov_survival(1.1799), 0.123_454, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_ks_2samp() {
+        // the same sample in a different order
+        let a = (0..50).map(|x| x as f64).collect::<Vec<_>>();
+        let b = a.iter().rev().cloned().collect::<Vec<_>>();
+        let (statistic, p_value) = ks_2samp(&a, &b);
+        assert_abs_diff_eq!(statistic, 0.0);
+        assert_abs_diff_eq!(p_value, 1.0);
+
+        // disjoint samples
+        let b = (100..140).map(|x| x as f64).collect::<Vec<_>>();
+        let (statistic, p_value) = ks_2samp(&a, &b);
+        assert_abs_diff_eq!(statistic, 1.0);
+        assert!(p_value < 1e-10);
+
+        // a shift of half the range without ties, the distributions differ by 1/2 below 25
+        let b = (25..75).map(|x| x as f64 - 0.5).collect::<Vec<_>>();
+        let (statistic, p_value) = ks_2samp(&a, &b);
+        assert_abs_diff_eq!(statistic, 0.5);
+        assert_abs_diff_eq!(p_value, kolmogorov_survival((5.0 + 0.12 + 0.022) * 0.5));
+    }
+
+    #[test]
+    fn test_ks_2samp_ties() {
+        // every value is tied, the distribution functions only differ after the value 1
+        let a = [1.0f32, 1.0, 1.0, 2.0, 2.0, 2.0];
+        let b = [1.0f32, 2.0, 2.0, 2.0, 2.0, 2.0];
+        let (statistic, p_value) = ks_2samp(&a, &b);
+        assert_abs_diff_eq!(statistic, 2.0 / 6.0, epsilon = 1e-6);
+        // 504 of the 924 splits of the pooled values have a distance of at least 2/6 at the value 1
+        assert_abs_diff_eq!(p_value, 6.0 / 11.0, epsilon = 1e-12);
+
+        // tied samples are never separated, even if the runs have different lengths
+        let a = [3.0, 3.0, 3.0, 3.0];
+        let b = [3.0, 3.0];
+        assert_abs_diff_eq!(ks_2samp(&a, &b).0, 0.0);
+        assert_abs_diff_eq!(ks_2samp(&a, &b).1, 1.0);
+    }
+
+    #[test]
+    fn test_ks_2samp_ties_p_value() {
+        // the p-values are the fractions of all splits of the pooled values, counted by enumeration
+        let a = [0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0];
+        let b = [1.0, 2.0, 2.0, 3.0, 3.0, 3.0];
+        let (statistic, p_value) = ks_2samp(&a, &b);
+        assert_abs_diff_eq!(statistic, 17.0 / 42.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(p_value, 43.0 / 143.0, epsilon = 1e-12);
+
+        // the second sample is tied with the largest value of the first
+        let a = [0.0, 0.0, 0.0, 1.0];
+        let b = [1.0, 1.0, 1.0, 1.0, 1.0];
+        let (statistic, p_value) = ks_2samp(&a, &b);
+        assert_abs_diff_eq!(statistic, 0.75, epsilon = 1e-12);
+        assert_abs_diff_eq!(p_value, 1.0 / 21.0, epsilon = 1e-12);
+
+        // without ties the asymptotic p-value is used
+        let a = [0.0, 0.1, 0.2, 1.0];
+        let b = [1.1, 1.2, 1.3, 1.4, 1.5];
+        let n_eff = (20.0f64 / 9.0).sqrt();
+        assert_abs_diff_eq!(
+            ks_2samp(&a, &b).1,
+            kolmogorov_survival(n_eff + 0.12 + 0.11 / n_eff)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_ks_2samp_empty() {
+        ks_2samp::<f64>(&[1.0, 2.0], &[]);
+    }
+}
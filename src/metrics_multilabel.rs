@@ -0,0 +1,133 @@
+//! Common metrics for multilabel classification
+//!
+//! In multilabel classification each sample can belong to any number of labels. Predictions and
+//! ground truth are boolean matrices with shape `(n_samples, n_labels)`.
+
+use ndarray::prelude::*;
+use ndarray::Data;
+
+/// Multilabel classification metrics trait
+pub trait MultiLabelClassification {
+    /// Hamming loss, the fraction of wrongly predicted labels over all samples and labels
+    fn hamming_loss<D: Data<Elem = bool>>(&self, ground_truth: &ArrayBase<D, Ix2>) -> f32;
+    /// Subset accuracy, the fraction of samples whose labels are all predicted correctly
+    ///
+    /// This is much stricter than the Hamming loss, a single wrong label counts the whole sample
+    /// as misclassified.
+    fn subset_accuracy<D: Data<Elem = bool>>(&self, ground_truth: &ArrayBase<D, Ix2>) -> f32;
+    /// F1-score of each label, treating the presence of the label as the positive class
+    ///
+    /// Labels which are neither present in the ground truth nor predicted score zero.
+    fn label_f1_scores<D: Data<Elem = bool>>(
+        &self,
+        ground_truth: &ArrayBase<D, Ix2>,
+    ) -> Array1<f32>;
+}
+
+impl<S: Data<Elem = bool>> MultiLabelClassification for ArrayBase<S, Ix2> {
+    fn hamming_loss<D: Data<Elem = bool>>(&self, ground_truth: &ArrayBase<D, Ix2>) -> f32 {
+        check_shapes(self, ground_truth);
+
+        let wrong = self
+            .iter()
+            .zip(ground_truth.iter())
+            .filter(|(a, b)| a != b)
+            .count();
+
+        wrong as f32 / self.len() as f32
+    }
+
+    fn subset_accuracy<D: Data<Elem = bool>>(&self, ground_truth: &ArrayBase<D, Ix2>) -> f32 {
+        check_shapes(self, ground_truth);
+
+        let correct = self
+            .genrows()
+            .into_iter()
+            .zip(ground_truth.genrows())
+            .filter(|(a, b)| a == b)
+            .count();
+
+        correct as f32 / self.nrows() as f32
+    }
+
+    fn label_f1_scores<D: Data<Elem = bool>>(
+        &self,
+        ground_truth: &ArrayBase<D, Ix2>,
+    ) -> Array1<f32> {
+        check_shapes(self, ground_truth);
+
+        self.gencolumns()
+            .into_iter()
+            .zip(ground_truth.gencolumns())
+            .map(|(predicted, truth)| {
+                let (mut tp, mut fp, mut fn_) = (0, 0, 0);
+                for (p, t) in predicted.iter().zip(truth.iter()) {
+                    match (p, t) {
+                        (true, true) => tp += 1,
+                        (true, false) => fp += 1,
+                        (false, true) => fn_ += 1,
+                        (false, false) => {}
+                    }
+                }
+
+                if tp == 0 {
+                    0.0
+                } else {
+                    2.0 * tp as f32 / (2 * tp + fp + fn_) as f32
+                }
+            })
+            .collect()
+    }
+}
+
+fn check_shapes<S: Data<Elem = bool>, D: Data<Elem = bool>>(
+    predicted: &ArrayBase<S, Ix2>,
+    ground_truth: &ArrayBase<D, Ix2>,
+) {
+    assert_eq!(
+        predicted.shape(),
+        ground_truth.shape(),
+        "predictions and ground truth must have the same shape"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiLabelClassification;
+    use approx::assert_abs_diff_eq;
+    use ndarray::array;
+
+    #[test]
+    fn test_multilabel_metrics() {
+        let ground_truth = array![
+            [true, false, true],
+            [false, true, false],
+            [true, true, false],
+            [false, false, false]
+        ];
+        let predicted = array![
+            [true, false, true],
+            [false, true, true],
+            [true, false, false],
+            [false, false, false]
+        ];
+
+        assert_abs_diff_eq!(predicted.hamming_loss(&ground_truth), 2. / 12.);
+        // only two samples have all labels correct, although most labels are right
+        assert_abs_diff_eq!(predicted.subset_accuracy(&ground_truth), 0.5);
+        assert_abs_diff_eq!(
+            predicted.label_f1_scores(&ground_truth),
+            array![1.0f32, 2. / 3., 2. / 3.],
+            epsilon = 1e-6
+        );
+
+        assert_abs_diff_eq!(ground_truth.hamming_loss(&ground_truth), 0.0);
+        assert_abs_diff_eq!(ground_truth.subset_accuracy(&ground_truth), 1.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shape_mismatch() {
+        array![[true, false]].hamming_loss(&array![[true], [false]]);
+    }
+}
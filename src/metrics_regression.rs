@@ -103,10 +103,187 @@ impl<
     }
 }
 
+/// Mean absolute percentage error of the predictions `y_pred` against `y_true`
+///
+/// The relative errors `|y_true - y_pred| / |y_true|` are averaged and returned as a fraction,
+/// not multiplied by 100. The error is independent of the scale of the targets, but undefined
+/// for targets of zero. These samples are skipped, and if all targets are zero `NaN` is returned.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length.
+pub fn mean_absolute_percentage_error<A: NdFloat>(
+    y_true: ArrayView1<A>,
+    y_pred: ArrayView1<A>,
+) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+
+    let (sum, n_samples) = y_true
+        .iter()
+        .zip(y_pred.iter())
+        .filter(|(y, _)| **y != A::zero())
+        .fold((A::zero(), 0), |(sum, n), (y, p)| {
+            (sum + ((*y - *p) / *y).abs(), n + 1)
+        });
+
+    if n_samples == 0 {
+        A::nan()
+    } else {
+        sum / A::from(n_samples).unwrap()
+    }
+}
+
+/// Symmetric mean absolute percentage error of the predictions `y_pred` against `y_true`
+///
+/// The absolute errors are relative to the mean magnitude of target and prediction
+/// `2 |y_true - y_pred| / (|y_true| + |y_pred|)`, which bounds each term by two and treats over-
+/// and under-prediction alike. The result is a fraction between zero and two. Terms where
+/// both the target and the prediction are zero are perfect predictions and count as zero.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length.
+pub fn symmetric_mape<A: NdFloat>(y_true: ArrayView1<A>, y_pred: ArrayView1<A>) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+
+    let two = A::from(2.0).unwrap();
+    let sum = y_true
+        .iter()
+        .zip(y_pred.iter())
+        .map(|(y, p)| {
+            let magnitude = y.abs() + p.abs();
+            if magnitude == A::zero() {
+                A::zero()
+            } else {
+                two * (*y - *p).abs() / magnitude
+            }
+        })
+        .fold(A::zero(), |sum, x| sum + x);
+
+    sum / A::from(y_true.len()).unwrap()
+}
+
+/// Explained variance score of the predictions `y_pred` against `y_true`
+///
+/// This is the fraction of the variance of the targets which is explained by the predictions
+/// ```ignore
+/// 1 - Var(y_true - y_pred) / Var(y_true)
+/// ```
+/// Unlike the R squared coefficient it ignores the mean of the residuals, so predictions which
+/// are off by a constant bias still score one. If the targets are constant, the score is one
+/// for perfect predictions and zero otherwise.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length or are empty.
+pub fn explained_variance_score<A: NdFloat + FromPrimitive>(
+    y_true: ArrayView1<A>,
+    y_pred: ArrayView1<A>,
+) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+    assert!(!y_true.is_empty(), "`y_true` must not be empty");
+
+    let residual_variance = (&y_true - &y_pred)
+        .var_axis(Axis(0), A::zero())
+        .into_scalar();
+    let target_variance = y_true.var_axis(Axis(0), A::zero())[()];
+
+    if target_variance > A::zero() {
+        A::one() - residual_variance / target_variance
+    } else if residual_variance > A::zero() {
+        A::zero()
+    } else {
+        A::one()
+    }
+}
+
+/// Mean Huber loss of the predictions `y_pred` against `y_true`
+///
+/// The loss of a residual `r` is quadratic for small residuals and linear for large ones
+/// ```ignore
+/// r^2 / 2                  if |r| <= delta
+/// delta * (|r| - delta / 2) otherwise
+/// ```
+/// which makes it less sensitive to outliers than the squared error, while staying smooth
+/// around zero. For large `delta` it approaches half the mean squared error.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length, are empty or if `delta` is not
+/// positive.
+pub fn huber_loss<A: NdFloat>(y_true: ArrayView1<A>, y_pred: ArrayView1<A>, delta: A) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+    assert!(!y_true.is_empty(), "`y_true` must not be empty");
+    assert!(delta > A::zero(), "`delta` must be positive");
+
+    let half = A::from(0.5).unwrap();
+    let sum = y_true
+        .iter()
+        .zip(y_pred.iter())
+        .map(|(y, p)| {
+            let residual = (*y - *p).abs();
+            if residual <= delta {
+                half * residual * residual
+            } else {
+                delta * (residual - half * delta)
+            }
+        })
+        .fold(A::zero(), |sum, x| sum + x);
+
+    sum / A::from(y_true.len()).unwrap()
+}
+
+/// Mean quantile loss, or pinball loss, of the predictions `y_pred` against `y_true`
+///
+/// Underestimating a target by `r` costs `quantile * r` and overestimating it costs
+/// `(1 - quantile) * r`, so the loss is minimized by the `quantile` of the targets. A `quantile`
+/// of one half gives half the mean absolute error.
+///
+/// **Panics** if `y_true` and `y_pred` differ in length, are empty or if `quantile` is not
+/// between zero and one.
+pub fn quantile_loss<A: NdFloat>(y_true: ArrayView1<A>, y_pred: ArrayView1<A>, quantile: A) -> A {
+    assert_eq!(
+        y_true.len(),
+        y_pred.len(),
+        "`y_true` and `y_pred` must have the same length"
+    );
+    assert!(!y_true.is_empty(), "`y_true` must not be empty");
+    assert!(
+        quantile >= A::zero() && quantile <= A::one(),
+        "`quantile` must be between zero and one"
+    );
+
+    let sum = y_true
+        .iter()
+        .zip(y_pred.iter())
+        .map(|(y, p)| {
+            let residual = *y - *p;
+            if residual >= A::zero() {
+                quantile * residual
+            } else {
+                (quantile - A::one()) * residual
+            }
+        })
+        .fold(A::zero(), |sum, x| sum + x);
+
+    sum / A::from(y_true.len()).unwrap()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Regression;
-    use approx::abs_diff_eq;
+    use super::{
+        explained_variance_score, huber_loss, mean_absolute_percentage_error, quantile_loss,
+        symmetric_mape, Regression,
+    };
+    use approx::{abs_diff_eq, assert_abs_diff_eq};
     use ndarray::prelude::*;
 
     #[test]
@@ -146,4 +323,125 @@ mod tests {
 
         abs_diff_eq!(a.mean_squared_error(&b), 0.1, epsilon = 1e-5);
     }
+
+    #[test]
+    fn test_mean_absolute_percentage_error() {
+        let y_true = array![1.0, -2.0, 4.0, 0.5];
+        let y_pred = array![1.5, -1.0, 4.0, 0.25];
+        // 0.5, 0.5, 0.0, 0.5
+        assert_abs_diff_eq!(
+            mean_absolute_percentage_error(y_true.view(), y_pred.view()),
+            0.375,
+            epsilon = 1e-10
+        );
+
+        // targets of zero are skipped
+        let y_true = array![0.0, 2.0, 0.0, 10.0];
+        let y_pred = array![3.0, 1.0, -1.0, 11.0];
+        assert_abs_diff_eq!(
+            mean_absolute_percentage_error(y_true.view(), y_pred.view()),
+            0.3,
+            epsilon = 1e-10
+        );
+        let single: f64 =
+            mean_absolute_percentage_error(y_true.slice(s![..1]), y_pred.slice(s![..1]));
+        assert!(single.is_nan());
+
+        // the error does not depend on the scale
+        assert_abs_diff_eq!(
+            mean_absolute_percentage_error((&y_true * 100.).view(), (&y_pred * 100.).view()),
+            0.3,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_symmetric_mape() {
+        let y_true = array![1.0f32, 2.0, 0.0, 0.0, -1.0];
+        let y_pred = array![3.0f32, 2.0, 0.0, 5.0, 1.0];
+        // 2 * 2 / 4, 0, 0, 2 * 5 / 5, 2 * 2 / 2
+        assert_abs_diff_eq!(
+            symmetric_mape(y_true.view(), y_pred.view()),
+            1.0,
+            epsilon = 1e-6
+        );
+
+        // swapping targets and predictions gives the same error
+        assert_abs_diff_eq!(
+            symmetric_mape(y_true.view(), y_pred.view()),
+            symmetric_mape(y_pred.view(), y_true.view()),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn test_explained_variance_score() {
+        let y_true = array![1.0, 2.0, 3.0, 4.0, 5.0];
+        let y_pred = array![1.5, 1.5, 3.5, 3.5, 5.0];
+        // residuals -0.5, 0.5, -0.5, 0.5, 0 with variance 0.2, the targets have variance 2
+        assert_abs_diff_eq!(
+            explained_variance_score(y_true.view(), y_pred.view()),
+            0.9,
+            epsilon = 1e-10
+        );
+
+        // a constant bias is not penalized, unlike in R squared
+        let biased = &y_true + 1.0;
+        let score = explained_variance_score(y_true.view(), biased.view());
+        assert_abs_diff_eq!(score, 1.0, epsilon = 1e-10);
+        assert!(y_true.r2(&biased) < score - 0.1);
+
+        // constant targets
+        let constant = array![2.0f32, 2.0, 2.0];
+        assert_abs_diff_eq!(
+            explained_variance_score(constant.view(), constant.view()),
+            1.0
+        );
+        assert_abs_diff_eq!(
+            explained_variance_score(constant.view(), array![1.0f32, 2.0, 3.0].view()),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_huber_loss() {
+        let y_true = array![0.0, 1.0, 2.0, 10.0];
+        let y_pred = array![0.5, 1.0, 4.0, 0.0];
+        // 0.125, 0, 1 * (2 - 0.5), 1 * (10 - 0.5)
+        assert_abs_diff_eq!(
+            huber_loss(y_true.view(), y_pred.view(), 1.0),
+            11.125 / 4.,
+            epsilon = 1e-10
+        );
+
+        // a large delta gives half the squared error
+        assert_abs_diff_eq!(
+            huber_loss(y_true.view(), y_pred.view(), 100.0),
+            y_true.mean_squared_error(&y_pred) / 2.,
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_quantile_loss() {
+        let y_true = array![1.0f32, 2.0, 3.0, 4.0];
+        let y_pred = array![2.0f32, 2.0, 2.0, 2.0];
+        // overestimated by 1, exact, underestimated by 1 and 2
+        assert_abs_diff_eq!(
+            quantile_loss(y_true.view(), y_pred.view(), 0.9),
+            (0.1 + 0.9 + 1.8) / 4.,
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            quantile_loss(y_true.view(), y_pred.view(), 0.5),
+            y_true.mean_absolute_error(&y_pred) / 2.,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_huber_loss_non_positive_delta() {
+        huber_loss(array![1.0].view(), array![2.0].view(), 0.0);
+    }
 }
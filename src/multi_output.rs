@@ -0,0 +1,141 @@
+//! Multi-output regression
+//!
+//! Fits one regressor per target column, which extends any single-output regressor to
+//! vector-valued targets. Correlations between the outputs are not exploited.
+use ndarray::{Array1, Array2, Axis, NdFloat};
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+use crate::traits::{Fit, Predict};
+
+/// Meta-estimator training one regressor per output column
+///
+/// The regressors are created with the factory passed to `new`, for example
+/// ```rust, ignore
+/// let model = MultiOutputRegressor::new(|| RidgeRegression::new().alpha(0.1))
+///     .fit(&dataset)?;
+/// ```
+pub struct MultiOutputRegressor<F> {
+    estimator_factory: F,
+}
+
+impl<F> MultiOutputRegressor<F> {
+    /// Create a new multi-output regressor, `estimator_factory` is called once per output
+    pub fn new(estimator_factory: F) -> MultiOutputRegressor<F> {
+        MultiOutputRegressor { estimator_factory }
+    }
+}
+
+/// A fitted multi-output regressor, containing one model per output
+pub struct FittedMultiOutputRegressor<M> {
+    models: Vec<M>,
+}
+
+impl<M> FittedMultiOutputRegressor<M> {
+    /// Return the fitted models, one for each output column
+    pub fn models(&self) -> &[M] {
+        &self.models
+    }
+}
+
+impl<A, E, F> Fit<Array2<A>, Array2<A>> for MultiOutputRegressor<F>
+where
+    A: NdFloat,
+    F: Fn() -> E,
+    E: Fit<Array2<A>, Array1<A>>,
+    E::Object: Predict<Array2<A>, Array1<A>>,
+{
+    type Object = FittedMultiOutputRegressor<E::Object>;
+
+    fn fit(&self, dataset: &Dataset<Array2<A>, Array2<A>>) -> Result<Self::Object> {
+        let (records, targets) = (dataset.records(), dataset.targets());
+
+        if records.len_of(Axis(0)) != targets.len_of(Axis(0)) {
+            return Err(Error::Parameters(format!(
+                "number of records ({}) and targets ({}) differ",
+                records.len_of(Axis(0)),
+                targets.len_of(Axis(0))
+            )));
+        }
+        if targets.len_of(Axis(1)) == 0 {
+            return Err(Error::Parameters(
+                "at least one output column is required".into(),
+            ));
+        }
+
+        // the records are shared by all outputs, only the targets are swapped
+        let mut single = Dataset::new(records.clone(), targets.column(0).to_owned());
+        let models = targets
+            .gencolumns()
+            .into_iter()
+            .map(|column| {
+                single.targets.assign(&column);
+                (self.estimator_factory)().fit(&single)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(FittedMultiOutputRegressor { models })
+    }
+}
+
+impl<A, M> Predict<Array2<A>, Array2<A>> for FittedMultiOutputRegressor<M>
+where
+    A: NdFloat,
+    M: Predict<Array2<A>, Array1<A>>,
+{
+    fn predict(&self, x: &Array2<A>) -> Array2<A> {
+        let mut predictions = Array2::zeros((x.len_of(Axis(0)), self.models.len()));
+        for (mut column, model) in predictions.gencolumns_mut().into_iter().zip(&self.models) {
+            column.assign(&model.predict(x));
+        }
+
+        predictions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiOutputRegressor;
+    use crate::traits::{Fit, Predict};
+    use crate::Dataset;
+    use approx::assert_abs_diff_eq;
+    use linfa_linear::{LinearRegression, RidgeRegression};
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn fits_one_model_per_output() {
+        let records = array![[0., 1.], [1., 0.], [2., 3.], [3., 1.], [4., 5.]];
+        let targets = ndarray::stack(
+            ndarray::Axis(1),
+            &[
+                records.dot(&array![[1.], [2.]]).view(),
+                (records.dot(&array![[-1.], [0.5]]) + 3.).view(),
+            ],
+        )
+        .unwrap();
+        let dataset = Dataset::new(records, targets);
+
+        let model = MultiOutputRegressor::new(LinearRegression::new)
+            .fit(&dataset)
+            .unwrap();
+        assert_eq!(model.models().len(), 2);
+
+        let pred: Array2<f64> = model.predict(dataset.records());
+        assert_abs_diff_eq!(&pred, dataset.targets(), epsilon = 1e-8);
+
+        let model = MultiOutputRegressor::new(|| RidgeRegression::new().alpha(1e-10))
+            .fit(&dataset)
+            .unwrap();
+        let pred: Array2<f64> = model.predict(dataset.records());
+        assert_abs_diff_eq!(&pred, dataset.targets(), epsilon = 1e-6);
+    }
+
+    #[test]
+    fn rejects_mismatching_shapes() {
+        let dataset = Dataset::new(array![[0.], [1.]], array![[0., 1.]]);
+
+        assert!(MultiOutputRegressor::new(LinearRegression::new)
+            .fit(&dataset)
+            .is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Polynomial feature expansion
+//!
+//! Generates all products of the input features up to a given degree, which allows linear
+//! models to fit polynomial relationships. With features `[a, b]` and degree 2 the output columns
+//! are `[1, a, b, a^2, a b, b^2]`.
+use ndarray::{s, Array2, Axis, NdFloat};
+
+use crate::traits::{InverseTransformer, Transformer};
+
+/// Transformer generating polynomial and interaction features
+///
+/// ## Column ordering
+///
+/// Each output column is the product of a sorted combination of input features, given by
+/// [`powers`](#method.powers). The columns are ordered by degree, starting with the bias column
+/// of degree zero, and within a degree in lexicographic order of the combinations. For three
+/// features `[a, b, c]` and degree 2 this gives
+/// ```ignore
+/// 1, a, b, c, a^2, a b, a c, b^2, b c, c^2
+/// ```
+/// which is the ordering of scikit-learn's `PolynomialFeatures`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolynomialFeatures {
+    degree: usize,
+    interaction_only: bool,
+    include_bias: bool,
+}
+
+impl PolynomialFeatures {
+    /// Create a new transformer with all terms up to `degree`, including the bias column
+    pub fn new(degree: usize) -> PolynomialFeatures {
+        PolynomialFeatures {
+            degree,
+            interaction_only: false,
+            include_bias: true,
+        }
+    }
+
+    /// Only generate products of distinct features, no feature is raised to a higher power
+    pub fn interaction_only(mut self, interaction_only: bool) -> Self {
+        self.interaction_only = interaction_only;
+        self
+    }
+
+    /// Include the bias column of ones, the term of degree zero
+    pub fn include_bias(mut self, include_bias: bool) -> Self {
+        self.include_bias = include_bias;
+        self
+    }
+
+    /// Return the combination of input features multiplied in each output column
+    ///
+    /// A feature appears once for every power, `[0, 0, 2]` stands for `x_0^2 x_2` and the empty
+    /// combination for the bias column.
+    pub fn powers(&self, n_features: usize) -> Vec<Vec<usize>> {
+        let min_degree = if self.include_bias { 0 } else { 1 };
+        let mut combinations = Vec::new();
+
+        for degree in min_degree..=self.degree {
+            // the first combination of a degree, it is advanced like an odometer
+            let mut combination = if self.interaction_only {
+                (0..degree).collect::<Vec<_>>()
+            } else {
+                vec![0; degree]
+            };
+            if let Some(&last) = combination.last() {
+                if last >= n_features {
+                    continue;
+                }
+            }
+
+            loop {
+                combinations.push(combination.clone());
+                if !self.advance(&mut combination, n_features) {
+                    break;
+                }
+            }
+        }
+
+        combinations
+    }
+
+    /// Advance `combination` to its lexicographic successor, return false if it was the last
+    fn advance(&self, combination: &mut [usize], n_features: usize) -> bool {
+        let degree = combination.len();
+        // the largest value at position `i`, interaction terms need distinct features
+        let max_value = |i: usize| {
+            if self.interaction_only {
+                n_features - (degree - i)
+            } else {
+                n_features - 1
+            }
+        };
+
+        let position = match (0..degree).rev().find(|i| combination[*i] < max_value(*i)) {
+            Some(position) => position,
+            None => return false,
+        };
+        combination[position] += 1;
+        for i in position + 1..degree {
+            combination[i] = if self.interaction_only {
+                combination[i - 1] + 1
+            } else {
+                combination[i - 1]
+            };
+        }
+
+        true
+    }
+
+    /// Return the number of output columns for `n_features` input features
+    pub fn n_output_features(&self, n_features: usize) -> usize {
+        self.powers(n_features).len()
+    }
+
+    /// Return the name of each output column given the names of the input features
+    ///
+    /// Repeated features are written as powers, for example `a^2 b` or `1` for the bias column.
+    pub fn feature_names<S: AsRef<str>>(&self, input_names: &[S]) -> Vec<String> {
+        self.powers(input_names.len())
+            .iter()
+            .map(|combination| {
+                if combination.is_empty() {
+                    return "1".to_string();
+                }
+
+                let mut terms = Vec::new();
+                let mut start = 0;
+                while start < combination.len() {
+                    let feature = combination[start];
+                    let count = combination[start..]
+                        .iter()
+                        .take_while(|x| **x == feature)
+                        .count();
+                    let name = input_names[feature].as_ref();
+                    terms.push(if count == 1 {
+                        name.to_string()
+                    } else {
+                        format!("{}^{}", name, count)
+                    });
+                    start += count;
+                }
+                terms.join(" ")
+            })
+            .collect()
+    }
+}
+
+impl<A: NdFloat> Transformer<Array2<A>, Array2<A>> for PolynomialFeatures {
+    /// Expand records with shape `(n_samples, n_features)` to the polynomial features with
+    /// shape `(n_samples, n_output_features)`
+    fn transform(&self, x: &Array2<A>) -> Array2<A> {
+        let powers = self.powers(x.len_of(Axis(1)));
+        let mut output = Array2::ones((x.len_of(Axis(0)), powers.len()));
+
+        for (mut column, combination) in output.gencolumns_mut().into_iter().zip(&powers) {
+            for feature in combination {
+                column *= &x.column(*feature);
+            }
+        }
+
+        output
+    }
+
+    fn output_feature_names(&self, input_names: &[String]) -> Option<Vec<String>> {
+        Some(self.feature_names(input_names))
+    }
+}
+
+impl<A: NdFloat> InverseTransformer<Array2<A>, Array2<A>> for PolynomialFeatures {
+    /// Recover the input features from the columns of degree one
+    ///
+    /// # Panics
+    ///
+    /// If the degree is zero, as the expansion contains no input feature, or if the number of
+    /// columns is not the output of any number of input features.
+    fn inverse_transform(&self, x: &Array2<A>) -> Array2<A> {
+        assert!(
+            self.degree > 0,
+            "an expansion of degree zero cannot be inverted"
+        );
+        let n_columns = x.len_of(Axis(1));
+        let n_features = (0..=n_columns)
+            .find(|n| self.n_output_features(*n) >= n_columns)
+            .filter(|n| self.n_output_features(*n) == n_columns)
+            .expect("the number of columns does not match a polynomial expansion");
+
+        // the columns of degree one follow the bias column, in the order of the input features
+        let offset = if self.include_bias { 1 } else { 0 };
+        x.slice(s![.., offset..offset + n_features]).to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolynomialFeatures;
+    use crate::traits::{InverseTransformer, Transformer};
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn degree_two_on_three_features() {
+        let x = array![[1., 2., 3.], [-1., 0.5, 2.]];
+        let poly = PolynomialFeatures::new(2);
+
+        assert_eq!(
+            poly.powers(3),
+            vec![
+                vec![],
+                vec![0],
+                vec![1],
+                vec![2],
+                vec![0, 0],
+                vec![0, 1],
+                vec![0, 2],
+                vec![1, 1],
+                vec![1, 2],
+                vec![2, 2]
+            ]
+        );
+
+        let expanded = poly.transform(&x);
+        assert_abs_diff_eq!(
+            expanded,
+            array![
+                [1., 1., 2., 3., 1., 2., 3., 4., 6., 9.],
+                [1., -1., 0.5, 2., 1., -0.5, -2., 0.25, 1., 4.]
+            ]
+        );
+        assert_eq!(
+            poly.feature_names(&["a", "b", "c"]),
+            vec!["1", "a", "b", "c", "a^2", "a b", "a c", "b^2", "b c", "c^2"]
+        );
+    }
+
+    #[test]
+    fn interaction_only_without_bias() {
+        let x = array![[2., 3., 5.]];
+        let poly = PolynomialFeatures::new(3)
+            .interaction_only(true)
+            .include_bias(false);
+
+        assert_eq!(poly.n_output_features(3), 7);
+        let expanded: Array2<f64> = poly.transform(&x);
+        assert_abs_diff_eq!(expanded, array![[2., 3., 5., 6., 10., 15., 30.]]);
+        assert_eq!(
+            poly.feature_names(&["a", "b", "c"]),
+            vec!["a", "b", "c", "a b", "a c", "b c", "a b c"]
+        );
+
+        // there are no interactions of more features than available
+        assert_eq!(
+            PolynomialFeatures::new(3)
+                .interaction_only(true)
+                .powers(2)
+                .len(),
+            4
+        );
+    }
+
+    #[test]
+    fn higher_degree_powers() {
+        let poly = PolynomialFeatures::new(3).include_bias(false);
+
+        assert_eq!(poly.n_output_features(2), 9);
+        assert_eq!(
+            poly.feature_names(&["x", "y"]),
+            vec!["x", "y", "x^2", "x y", "y^2", "x^3", "x^2 y", "x y^2", "y^3"]
+        );
+    }
+
+    #[test]
+    fn inverse_recovers_input_features() {
+        let x = array![[1., 2., 3.], [-1., 0.5, 2.]];
+        for poly in &[
+            PolynomialFeatures::new(2),
+            PolynomialFeatures::new(3).include_bias(false),
+            PolynomialFeatures::new(2).interaction_only(true),
+        ] {
+            let expanded: Array2<f64> = poly.transform(&x);
+            assert_eq!(poly.inverse_transform(&expanded), x);
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Random sample consensus (RANSAC)
+//!
+//! Robust regression with a large fraction of outliers. Repeatedly a minimal random subset of
+//! the samples is fitted with a base regressor and the samples whose absolute residual is below
+//! a threshold form the consensus set of this trial. The base regressor is finally refitted on
+//! the largest consensus set, which ignores the outliers entirely instead of only reducing their
+//! influence.
+use ndarray::{Array1, Array2, Axis, NdFloat};
+use rand::Rng;
+use std::cmp::Ordering;
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+use crate::traits::{Fit, Predict};
+
+/// Meta-estimator fitting a base regressor on the consensus set of random trials
+///
+/// The regressors are created with the factory passed to `new`. The subsets are drawn from the
+/// random number generator passed to `new`, a seeded generator gives reproducible fits, for
+/// example
+/// ```rust, ignore
+/// let model = Ransac::new(LinearRegression::new, Isaac64Rng::seed_from_u64(42))
+///     .residual_threshold(1.0)
+///     .fit(&dataset)?;
+/// let outliers = model.inlier_mask().iter().filter(|x| !**x).count();
+/// ```
+pub struct Ransac<F, R> {
+    estimator_factory: F,
+    rng: R,
+    min_samples: Option<usize>,
+    residual_threshold: Option<f64>,
+    max_trials: usize,
+    stop_probability: f64,
+}
+
+impl<F, R: Rng + Clone> Ransac<F, R> {
+    /// Create a new RANSAC regressor, `estimator_factory` is called once per trial
+    ///
+    /// Every call to `fit` starts from a copy of `rng`. Defaults are provided for the remaining
+    /// parameters:
+    /// * `min_samples = n_features + 1`, the size of a minimal subset for a linear model with
+    ///   intercept;
+    /// * `residual_threshold` is the median absolute deviation of the targets;
+    /// * `max_trials = 100`;
+    /// * `stop_probability = 0.99`.
+    pub fn new(estimator_factory: F, rng: R) -> Ransac<F, R> {
+        Ransac {
+            estimator_factory,
+            rng,
+            min_samples: None,
+            residual_threshold: None,
+            max_trials: 100,
+            stop_probability: 0.99,
+        }
+    }
+
+    /// Number of samples drawn in each trial
+    pub fn min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = Some(min_samples);
+        self
+    }
+
+    /// Maximal absolute residual of a sample to be counted as inlier
+    pub fn residual_threshold(mut self, residual_threshold: f64) -> Self {
+        self.residual_threshold = Some(residual_threshold);
+        self
+    }
+
+    /// Maximal number of random trials
+    pub fn max_trials(mut self, max_trials: usize) -> Self {
+        self.max_trials = max_trials;
+        self
+    }
+
+    /// Stop early once at least one trial without outliers has been drawn with this
+    /// probability, estimated from the inlier ratio of the best consensus set so far
+    pub fn stop_probability(mut self, stop_probability: f64) -> Self {
+        self.stop_probability = stop_probability;
+        self
+    }
+}
+
+/// A fitted RANSAC regressor, containing the base model fitted on the consensus set
+pub struct FittedRansac<M> {
+    model: M,
+    inlier_mask: Array1<bool>,
+    n_trials: usize,
+}
+
+impl<M> FittedRansac<M> {
+    /// Return the base model fitted on the inliers
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Return for each training sample whether it belongs to the consensus set
+    pub fn inlier_mask(&self) -> &Array1<bool> {
+        &self.inlier_mask
+    }
+
+    /// Return the number of random trials performed
+    pub fn n_trials(&self) -> usize {
+        self.n_trials
+    }
+}
+
+impl<A, E, F, R> Fit<Array2<A>, Array1<A>> for Ransac<F, R>
+where
+    A: NdFloat,
+    F: Fn() -> E,
+    R: Rng + Clone,
+    E: Fit<Array2<A>, Array1<A>>,
+    E::Object: Predict<Array2<A>, Array1<A>>,
+{
+    type Object = FittedRansac<E::Object>;
+
+    fn fit(&self, dataset: &Dataset<Array2<A>, Array1<A>>) -> Result<Self::Object> {
+        let (records, targets) = (dataset.records(), dataset.targets());
+        let n_samples = records.len_of(Axis(0));
+        let min_samples = self
+            .min_samples
+            .unwrap_or_else(|| records.len_of(Axis(1)) + 1);
+
+        if n_samples != targets.len() {
+            return Err(Error::Parameters(format!(
+                "number of records ({}) and targets ({}) differ",
+                n_samples,
+                targets.len()
+            )));
+        }
+        if min_samples == 0 || min_samples > n_samples {
+            return Err(Error::Parameters(format!(
+                "min_samples ({}) must be between 1 and the number of samples ({})",
+                min_samples, n_samples
+            )));
+        }
+        if self.max_trials == 0 {
+            return Err(Error::Parameters("max_trials cannot be 0".into()));
+        }
+        if self.stop_probability.is_nan()
+            || self.stop_probability <= 0.
+            || self.stop_probability > 1.
+        {
+            return Err(Error::Parameters(format!(
+                "stop_probability must be in (0, 1], got: {}",
+                self.stop_probability
+            )));
+        }
+        let threshold = match self.residual_threshold {
+            Some(threshold) if threshold >= 0. => A::from(threshold).unwrap(),
+            Some(threshold) => {
+                return Err(Error::Parameters(format!(
+                    "residual_threshold must be non-negative, got: {}",
+                    threshold
+                )))
+            }
+            None => {
+                let center = median(targets.to_vec());
+                median(targets.iter().map(|y| (*y - center).abs()).collect())
+            }
+        };
+
+        let mut rng = self.rng.clone();
+        // the number of inliers, their summed absolute residuals and the inlier mask
+        let mut best: Option<(usize, A, Array1<bool>)> = None;
+        let mut max_trials = self.max_trials;
+        let mut n_trials = 0;
+        while n_trials < max_trials {
+            n_trials += 1;
+
+            let indices = rand::seq::index::sample(&mut rng, n_samples, min_samples).into_vec();
+            // degenerate subsets may fail to fit, for example with duplicated records
+            let model = match (self.estimator_factory)().fit(&dataset.select_samples(&indices)?) {
+                Ok(model) => model,
+                Err(_) => continue,
+            };
+
+            let residuals = (model.predict(records) - targets).mapv(A::abs);
+            let inlier_mask = residuals.mapv(|r| r <= threshold);
+            let n_inliers = inlier_mask.iter().filter(|x| **x).count();
+            let loss = residuals
+                .iter()
+                .zip(inlier_mask.iter())
+                .filter(|(_, inlier)| **inlier)
+                .fold(A::zero(), |sum, (r, _)| sum + *r);
+
+            let is_better = match &best {
+                Some((best_inliers, best_loss, _)) => {
+                    n_inliers > *best_inliers || (n_inliers == *best_inliers && loss < *best_loss)
+                }
+                None => true,
+            };
+            if is_better {
+                max_trials = max_trials.min(required_trials(
+                    n_inliers as f64 / n_samples as f64,
+                    min_samples,
+                    self.stop_probability,
+                ));
+                best = Some((n_inliers, loss, inlier_mask));
+            }
+        }
+
+        let inlier_mask = match best {
+            Some((n_inliers, _, inlier_mask)) if n_inliers >= min_samples => inlier_mask,
+            _ => {
+                return Err(Error::Parameters(
+                    "no trial found a consensus set with at least min_samples inliers".into(),
+                ))
+            }
+        };
+        let inliers = inlier_mask
+            .iter()
+            .enumerate()
+            .filter(|(_, inlier)| **inlier)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let model = (self.estimator_factory)().fit(&dataset.select_samples(&inliers)?)?;
+
+        Ok(FittedRansac {
+            model,
+            inlier_mask,
+            n_trials,
+        })
+    }
+}
+
+impl<A, M> Predict<Array2<A>, Array1<A>> for FittedRansac<M>
+where
+    A: NdFloat,
+    M: Predict<Array2<A>, Array1<A>>,
+{
+    fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        self.model.predict(x)
+    }
+}
+
+/// The number of trials to draw at least one subset without outliers with probability
+/// `stop_probability`, given the ratio of inliers
+fn required_trials(inlier_ratio: f64, min_samples: usize, stop_probability: f64) -> usize {
+    let clean_subset = inlier_ratio.powi(min_samples as i32);
+    if clean_subset >= 1. {
+        0
+    } else if clean_subset <= 0. || stop_probability >= 1. {
+        usize::MAX
+    } else {
+        ((1. - stop_probability).ln() / (1. - clean_subset).ln()).ceil() as usize
+    }
+}
+
+fn median<A: NdFloat>(mut values: Vec<A>) -> A {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / A::from(2.).unwrap()
+    } else {
+        values[mid]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ransac;
+    use crate::traits::{Fit, Predict};
+    use crate::Dataset;
+    use approx::assert_abs_diff_eq;
+    use linfa_linear::LinearRegression;
+    use ndarray::{Array1, Array2, Axis};
+    use rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    /// A line `y = 2x + 1` with small noise, the first and third of every five samples are
+    /// replaced by gross outliers on the line `y = 120 - 3x`
+    fn line_with_outliers() -> (Dataset<Array2<f64>, Array1<f64>>, Array1<bool>) {
+        let x = Array1::range(0., 50., 0.5);
+        let outliers = Array1::from_shape_fn(100, |i| i % 5 == 0 || i % 5 == 2);
+        let y = Array1::from_shape_fn(100, |i| {
+            if outliers[i] {
+                120. - 3. * x[i] + 2. * (i as f64).sin()
+            } else {
+                2. * x[i] + 1. + 0.1 * (3. * i as f64).sin()
+            }
+        });
+
+        (Dataset::new(x.insert_axis(Axis(1)), y), outliers)
+    }
+
+    #[test]
+    fn recovers_line_through_outliers() {
+        let (dataset, outliers) = line_with_outliers();
+
+        // the inherent `fit` of `LinearRegression` takes the records and targets separately
+        let ols = Fit::fit(&LinearRegression::new(), &dataset).unwrap();
+        assert!((ols.params()[0] - 2.).abs() > 1.);
+
+        let model = Ransac::new(LinearRegression::new, Isaac64Rng::seed_from_u64(42))
+            .residual_threshold(1.)
+            .fit(&dataset)
+            .unwrap();
+        assert_abs_diff_eq!(model.model().params()[0], 2., epsilon = 0.04);
+        assert_eq!(model.inlier_mask(), &outliers.mapv(|x| !x));
+        assert!(model.n_trials() < 100);
+
+        let x = Array2::from_shape_vec((2, 1), vec![0., 100.]).unwrap();
+        let pred: Array1<f64> = model.predict(&x);
+        assert_abs_diff_eq!(pred[1] - pred[0], 200., epsilon = 4.);
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let (dataset, _) = line_with_outliers();
+
+        assert!(
+            Ransac::new(LinearRegression::new, Isaac64Rng::seed_from_u64(42))
+                .min_samples(101)
+                .fit(&dataset)
+                .is_err()
+        );
+        assert!(
+            Ransac::new(LinearRegression::new, Isaac64Rng::seed_from_u64(42))
+                .max_trials(0)
+                .fit(&dataset)
+                .is_err()
+        );
+        assert!(
+            Ransac::new(LinearRegression::new, Isaac64Rng::seed_from_u64(42))
+                .stop_probability(0.)
+                .fit(&dataset)
+                .is_err()
+        );
+        assert!(
+            Ransac::new(LinearRegression::new, Isaac64Rng::seed_from_u64(42))
+                .residual_threshold(-1.)
+                .fit(&dataset)
+                .is_err()
+        );
+    }
+}
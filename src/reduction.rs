@@ -0,0 +1,45 @@
+//! Implementation of the common traits for the models of `linfa-reduction`
+use linfa_reduction::PrincipalComponentAnalysis;
+use ndarray::Array2;
+
+use crate::traits::{InverseTransformer, Transformer};
+
+impl Transformer<Array2<f64>, Array2<f64>> for PrincipalComponentAnalysis {
+    /// Project the records onto the principal components
+    fn transform(&self, x: &Array2<f64>) -> Array2<f64> {
+        self.predict(x)
+    }
+}
+
+impl InverseTransformer<Array2<f64>, Array2<f64>> for PrincipalComponentAnalysis {
+    /// Reconstruct records from their projection onto the principal components
+    fn inverse_transform(&self, x: &Array2<f64>) -> Array2<f64> {
+        PrincipalComponentAnalysis::inverse_transform(self, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::traits::{InverseTransformer, Transformer};
+    use approx::assert_abs_diff_eq;
+    use linfa_reduction::PrincipalComponentAnalysis;
+    use ndarray::{array, Array2};
+
+    #[test]
+    fn pca_reconstructs_low_rank_records() {
+        // the third feature is the sum of the first two, the centered records have rank two
+        let x: Array2<f64> = array![
+            [1., 2., 3.],
+            [-1., 0.5, -0.5],
+            [2., -1., 1.],
+            [0., 3., 3.],
+            [-2., -2., -4.]
+        ];
+        let pca = PrincipalComponentAnalysis::fit(x.clone(), 2);
+
+        let projected = pca.transform(&x);
+        assert_eq!(projected.dim(), (5, 2));
+        let reconstructed = InverseTransformer::inverse_transform(&pca, &projected);
+        assert_abs_diff_eq!(reconstructed, x, epsilon = 1e-6);
+    }
+}
@@ -0,0 +1,270 @@
+//! Standardization of features with running statistics
+//!
+//! The scaler keeps the number of samples and the sum and sum of squares of each feature, so
+//! batches can be added and removed in any order. A sliding window over a stream is scaled by
+//! adding each new batch and removing the batch which leaves the window.
+use ndarray::{Array1, Array2, ArrayBase, Axis, Data, Ix2, NdFloat};
+
+use crate::error::{Error, Result};
+use crate::traits::{InverseTransformer, Transformer};
+
+/// Transformer centering each feature to zero mean and scaling it to unit variance
+///
+/// The mean and the variance are computed from the running statistics of all batches passed to
+/// [partial_fit](#method.partial_fit) and not yet removed by
+/// [partial_unfit](#method.partial_unfit). Features with zero variance are only centered. The
+/// variance is the population variance, computed as `sum_sq / n - mean^2`, which loses precision
+/// for features with a large mean compared to their spread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StandardScaler<A> {
+    n_samples: usize,
+    sum: Array1<A>,
+    sum_sq: Array1<A>,
+}
+
+impl<A: NdFloat> StandardScaler<A> {
+    /// Create a scaler without any samples
+    pub fn new() -> StandardScaler<A> {
+        StandardScaler {
+            n_samples: 0,
+            sum: Array1::zeros(0),
+            sum_sq: Array1::zeros(0),
+        }
+    }
+
+    /// Add the samples of a batch with shape `(n_samples, n_features)` to the statistics
+    ///
+    /// The first batch of an empty scaler sets the number of features. Returns an error if the
+    /// number of features differs from the previous batches.
+    pub fn partial_fit(&mut self, x: &ArrayBase<impl Data<Elem = A>, Ix2>) -> Result<()> {
+        if self.n_samples == 0 {
+            self.sum = Array1::zeros(x.ncols());
+            self.sum_sq = Array1::zeros(x.ncols());
+        }
+        self.check_features(x)?;
+
+        self.n_samples += x.nrows();
+        self.sum += &x.sum_axis(Axis(0));
+        self.sum_sq += &x.mapv(|x| x * x).sum_axis(Axis(0));
+
+        Ok(())
+    }
+
+    /// Remove the samples of a batch, which was added before, from the statistics
+    ///
+    /// Removing all remaining samples resets the scaler to an empty one, which accepts batches
+    /// with any number of features. Returns an error if the number of features differs or if the
+    /// batch has more samples than the scaler.
+    pub fn partial_unfit(&mut self, x: &ArrayBase<impl Data<Elem = A>, Ix2>) -> Result<()> {
+        self.check_features(x)?;
+        if x.nrows() > self.n_samples {
+            return Err(Error::Parameters(format!(
+                "cannot remove {} samples from a scaler with {} samples",
+                x.nrows(),
+                self.n_samples
+            )));
+        }
+
+        if x.nrows() == self.n_samples {
+            // drop the rounding errors of the sums together with the last batch
+            *self = StandardScaler::new();
+            return Ok(());
+        }
+
+        self.n_samples -= x.nrows();
+        self.sum -= &x.sum_axis(Axis(0));
+        self.sum_sq -= &x.mapv(|x| x * x).sum_axis(Axis(0));
+
+        Ok(())
+    }
+
+    /// Return the number of samples in the statistics
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+
+    /// Return the mean of each feature, `None` for an empty scaler
+    pub fn mean(&self) -> Option<Array1<A>> {
+        if self.n_samples == 0 {
+            return None;
+        }
+
+        Some(&self.sum / A::from(self.n_samples).unwrap())
+    }
+
+    /// Return the population variance of each feature, `None` for an empty scaler
+    pub fn variance(&self) -> Option<Array1<A>> {
+        let mean = self.mean()?;
+        let n_samples = A::from(self.n_samples).unwrap();
+
+        // cancellation may leave a tiny negative value for constant features
+        Some((&self.sum_sq / n_samples - &mean * &mean).mapv(|var| var.max(A::zero())))
+    }
+
+    fn check_features(&self, x: &ArrayBase<impl Data<Elem = A>, Ix2>) -> Result<()> {
+        if x.ncols() != self.sum.len() && self.n_samples > 0 {
+            return Err(Error::Parameters(format!(
+                "batch has {} features, but the scaler has {}",
+                x.ncols(),
+                self.sum.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// The mean and the standard deviation of each feature, a zero deviation is replaced by one
+    fn moments(&self) -> (Array1<A>, Array1<A>) {
+        let mean = self.mean().expect("the scaler must contain samples");
+        let std = self.variance().unwrap().mapv(|var| {
+            if var > A::zero() {
+                var.sqrt()
+            } else {
+                A::one()
+            }
+        });
+
+        (mean, std)
+    }
+}
+
+impl<A: NdFloat> Default for StandardScaler<A> {
+    fn default() -> StandardScaler<A> {
+        StandardScaler::new()
+    }
+}
+
+impl<A: NdFloat> Transformer<Array2<A>, Array2<A>> for StandardScaler<A> {
+    /// Standardize records with shape `(n_samples, n_features)`
+    ///
+    /// # Panics
+    ///
+    /// If the scaler is empty or the number of features differs from the fitted batches.
+    fn transform(&self, x: &Array2<A>) -> Array2<A> {
+        let (mean, std) = self.moments();
+
+        (x - &mean) / &std
+    }
+
+    fn output_feature_names(&self, input_names: &[String]) -> Option<Vec<String>> {
+        Some(input_names.to_vec())
+    }
+}
+
+impl<A: NdFloat> InverseTransformer<Array2<A>, Array2<A>> for StandardScaler<A> {
+    /// Map standardized records back to the scale of the fitted batches
+    ///
+    /// # Panics
+    ///
+    /// If the scaler is empty or the number of features differs from the fitted batches.
+    fn inverse_transform(&self, x: &Array2<A>) -> Array2<A> {
+        let (mean, std) = self.moments();
+
+        x * &std + &mean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StandardScaler;
+    use crate::error::Error;
+    use crate::traits::{InverseTransformer, Transformer};
+    use approx::assert_abs_diff_eq;
+    use ndarray::{array, s, Array2, Axis};
+
+    #[test]
+    fn batches_match_whole_dataset() {
+        let x = Array2::from_shape_fn((30, 3), |(i, j)| (i * (j + 1)) as f64 + (j * 10) as f64);
+
+        let mut whole = StandardScaler::new();
+        whole.partial_fit(&x).unwrap();
+        let mut batched = StandardScaler::new();
+        for batch in x.axis_chunks_iter(Axis(0), 7) {
+            batched.partial_fit(&batch).unwrap();
+        }
+
+        assert_eq!(batched.n_samples(), 30);
+        assert_abs_diff_eq!(
+            batched.mean().unwrap(),
+            x.mean_axis(Axis(0)).unwrap(),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            batched.variance().unwrap(),
+            x.var_axis(Axis(0), 0.),
+            epsilon = 1e-9
+        );
+
+        let scaled = batched.transform(&x);
+        assert_abs_diff_eq!(scaled, whole.transform(&x), epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            scaled.mean_axis(Axis(0)).unwrap(),
+            array![0., 0., 0.],
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            scaled.var_axis(Axis(0), 0.),
+            array![1., 1., 1.],
+            epsilon = 1e-9
+        );
+        assert_abs_diff_eq!(batched.inverse_transform(&scaled), x, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn unfit_reverts_fit() {
+        let old = array![[1., 10.], [2., 20.], [3., 30.], [4., 42.]];
+        let new = array![[100., -5.], [200., -6.]];
+
+        let mut scaler = StandardScaler::new();
+        scaler.partial_fit(&old).unwrap();
+        let before = scaler.clone();
+
+        scaler.partial_fit(&new).unwrap();
+        scaler.partial_unfit(&new).unwrap();
+        assert_eq!(scaler.n_samples(), before.n_samples());
+        assert_abs_diff_eq!(
+            scaler.mean().unwrap(),
+            before.mean().unwrap(),
+            epsilon = 1e-10
+        );
+        assert_abs_diff_eq!(
+            scaler.variance().unwrap(),
+            before.variance().unwrap(),
+            epsilon = 1e-8
+        );
+
+        // sliding the window by one batch keeps only the newer samples
+        scaler.partial_fit(&new).unwrap();
+        scaler.partial_unfit(&old.slice(s![..2, ..])).unwrap();
+        assert_abs_diff_eq!(
+            scaler.mean().unwrap(),
+            array![(3. + 4. + 100. + 200.) / 4., (30. + 42. - 5. - 6.) / 4.],
+            epsilon = 1e-10
+        );
+
+        // removing the only batch leaves an empty scaler for any number of features
+        let mut scaler = StandardScaler::new();
+        scaler.partial_fit(&new).unwrap();
+        scaler.partial_unfit(&new).unwrap();
+        assert_eq!(scaler, StandardScaler::new());
+        assert_eq!(scaler.mean(), None);
+        scaler.partial_fit(&array![[1., 2., 3.]]).unwrap();
+        assert_eq!(scaler.mean(), Some(array![1., 2., 3.]));
+    }
+
+    #[test]
+    fn rejects_invalid_batches() {
+        let mut scaler = StandardScaler::new();
+        scaler.partial_fit(&array![[1., 2.], [3., 4.]]).unwrap();
+
+        match scaler.partial_fit(&array![[1., 2., 3.]]) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("a batch with a different number of features must be rejected"),
+        }
+        match scaler.partial_unfit(&Array2::zeros((3, 2))) {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("more samples than fitted cannot be removed"),
+        }
+        assert_eq!(scaler.n_samples(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+//! Cross-validation of time series
+//!
+//! Random folds use future samples to predict past ones, which overestimates the performance of
+//! a model on temporal data. A forward-chaining split always trains on samples which precede the
+//! test samples, the samples of the dataset have to be in temporal order.
+use ndarray::{Array1, Array2};
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+
+/// The training and the test set of a split
+type TrainTest<A, L> = (Dataset<Array2<A>, Array1<L>>, Dataset<Array2<A>, Array1<L>>);
+
+/// Splitter of ordered samples into training and test sets
+///
+/// The samples are divided into `n_splits + 1` consecutive blocks of equal size, the remainder
+/// is added to the first block. The `k`-th split tests on the block `k + 1` and trains on the
+/// samples before it, so the training windows expand from split to split, for example
+/// ```ignore
+/// split 0: train [0, 1, 2, 3], test [4, 5]
+/// split 1: train [0, 1, 2, 3, 4, 5], test [6, 7]
+/// split 2: train [0, 1, 2, 3, 4, 5, 6, 7], test [8, 9]
+/// ```
+/// for ten samples and three splits. A maximal training size slides a window of fixed size
+/// instead, and a gap leaves out the samples right before each test block, for example when the
+/// targets are computed from a rolling window which overlaps the test block.
+pub struct TimeSeriesSplit {
+    n_splits: usize,
+    max_train_size: Option<usize>,
+    gap: usize,
+}
+
+impl TimeSeriesSplit {
+    /// Create a splitter into `n_splits` pairs of training and test sets with expanding training
+    /// windows and without a gap
+    pub fn new(n_splits: usize) -> TimeSeriesSplit {
+        TimeSeriesSplit {
+            n_splits,
+            max_train_size: None,
+            gap: 0,
+        }
+    }
+
+    /// Limit the training window to the last `max_train_size` samples before the gap
+    pub fn max_train_size(mut self, max_train_size: Option<usize>) -> Self {
+        self.max_train_size = max_train_size;
+        self
+    }
+
+    /// Exclude the `gap` samples before each test block from training
+    pub fn gap(mut self, gap: usize) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Return the training and test indices of each split of `n_samples` samples
+    ///
+    /// All training indices are smaller than all test indices of the same split. Returns an
+    /// error if there are fewer samples than blocks or if the gap leaves no training samples for
+    /// the first split.
+    pub fn indices(&self, n_samples: usize) -> Result<Vec<(Vec<usize>, Vec<usize>)>> {
+        if self.n_splits == 0 {
+            return Err(Error::Parameters(
+                "the number of splits must be positive".to_string(),
+            ));
+        }
+        if self.max_train_size == Some(0) {
+            return Err(Error::Parameters(
+                "the maximal training size must be positive".to_string(),
+            ));
+        }
+
+        let test_size = n_samples / (self.n_splits + 1);
+        if test_size == 0 {
+            return Err(Error::Parameters(format!(
+                "cannot split {} samples into {} test blocks and a training block",
+                n_samples, self.n_splits
+            )));
+        }
+
+        let first_test_start = n_samples - self.n_splits * test_size;
+        if first_test_start <= self.gap {
+            return Err(Error::Parameters(format!(
+                "the gap ({}) leaves no training samples before the first test block at {}",
+                self.gap, first_test_start
+            )));
+        }
+
+        Ok((0..self.n_splits)
+            .map(|split| {
+                let test_start = first_test_start + split * test_size;
+                let train_end = test_start - self.gap;
+                let train_start = match self.max_train_size {
+                    Some(max_train_size) => train_end.saturating_sub(max_train_size),
+                    None => 0,
+                };
+
+                (
+                    (train_start..train_end).collect(),
+                    (test_start..test_start + test_size).collect(),
+                )
+            })
+            .collect())
+    }
+
+    /// Return the training and test datasets of each split of `dataset`
+    ///
+    /// The weights, feature and target names are kept. Returns the same errors as
+    /// [indices](#method.indices).
+    pub fn split<'a, A: Copy, L: Clone>(
+        &self,
+        dataset: &'a Dataset<Array2<A>, Array1<L>>,
+    ) -> Result<impl Iterator<Item = TrainTest<A, L>> + 'a> {
+        let splits = self.indices(dataset.targets().len())?;
+
+        Ok(splits.into_iter().map(move |(train, test)| {
+            (
+                dataset.select_samples_unchecked(&train),
+                dataset.select_samples_unchecked(&test),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TimeSeriesSplit;
+    use crate::dataset::Dataset;
+    use crate::error::Error;
+    use ndarray::{Array1, Array2};
+
+    #[test]
+    fn expanding_windows() {
+        let splits = TimeSeriesSplit::new(3).indices(10).unwrap();
+        assert_eq!(
+            splits,
+            vec![
+                (vec![0, 1, 2, 3], vec![4, 5]),
+                (vec![0, 1, 2, 3, 4, 5], vec![6, 7]),
+                (vec![0, 1, 2, 3, 4, 5, 6, 7], vec![8, 9]),
+            ]
+        );
+    }
+
+    #[test]
+    fn sliding_windows_with_gap() {
+        let splits = TimeSeriesSplit::new(3)
+            .max_train_size(Some(3))
+            .gap(1)
+            .indices(10)
+            .unwrap();
+        assert_eq!(
+            splits,
+            vec![
+                (vec![0, 1, 2], vec![4, 5]),
+                (vec![2, 3, 4], vec![6, 7]),
+                (vec![4, 5, 6], vec![8, 9]),
+            ]
+        );
+    }
+
+    #[test]
+    fn never_trains_on_the_future() {
+        let dataset = Dataset::new(
+            Array2::from_shape_fn((103, 2), |(i, j)| (i * 2 + j) as f64),
+            Array1::from((0..103).collect::<Vec<usize>>()),
+        );
+
+        for splitter in &[
+            TimeSeriesSplit::new(5),
+            TimeSeriesSplit::new(4).max_train_size(Some(20)),
+            TimeSeriesSplit::new(7).gap(3),
+        ] {
+            let mut n_splits = 0;
+            for (train, test) in splitter.split(&dataset).unwrap() {
+                let last_train = train.targets().iter().max().unwrap();
+                assert!(test.targets().iter().all(|idx| idx > last_train));
+                // the records are selected alongside the targets
+                assert_eq!(test.records()[(0, 0)], test.targets()[0] as f64 * 2.);
+                n_splits += 1;
+            }
+            assert_eq!(n_splits, splitter.n_splits);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_splits() {
+        for result in &[
+            TimeSeriesSplit::new(0).indices(10),
+            TimeSeriesSplit::new(10).indices(10),
+            TimeSeriesSplit::new(3).gap(4).indices(10),
+            TimeSeriesSplit::new(3).max_train_size(Some(0)).indices(10),
+        ] {
+            match result {
+                Err(Error::Parameters(_)) => {}
+                _ => panic!("invalid splits must be rejected"),
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Common traits shared by the estimators of the `linfa` ecosystem
+use crate::dataset::Dataset;
+use crate::error::Result;
+
+/// Fit a model to a dataset
+///
+/// The returned object contains the learned parameters and can, for supervised models, be used
+/// for prediction.
+pub trait Fit<R, T> {
+    type Object;
+
+    fn fit(&self, dataset: &Dataset<R, T>) -> Result<Self::Object>;
+}
+
+/// Fit a model to a sequence of batches, for datasets which do not fit into memory
+///
+/// Only a single batch has to be kept in memory at a time, for example while reading a large file
+/// in chunks. On data which fits into memory, passing it in batches results in the same model
+/// (up to the tolerance of the solver) as [Fit](trait.Fit.html).
+pub trait BatchedFit<R, T> {
+    type Object;
+
+    fn fit_batched<I>(&self, batches: I) -> Result<Self::Object>
+    where
+        I: IntoIterator<Item = Dataset<R, T>>;
+}
+
+/// Predict targets for a set of records
+pub trait Predict<R, T> {
+    fn predict(&self, x: &R) -> T;
+}
+
+/// Predict the probability of each class for a set of records
+///
+/// The probabilities have shape `(n_samples, n_classes)` and each row sums to one.
+pub trait PredictProba<R, P> {
+    fn predict_proba(&self, x: &R) -> P;
+}
+
+/// Transform records into a new representation, for example a preprocessing step
+pub trait Transformer<R, T> {
+    fn transform(&self, x: &R) -> T;
+
+    /// Return the names of the output features given the names of the input features
+    ///
+    /// Transformers which keep the features, or derive new features from them, should return
+    /// their names. The default returns `None`, which drops the names.
+    fn output_feature_names(&self, _input_names: &[String]) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// Map transformed records back to their original representation
+///
+/// For a transformer which keeps all information `inverse_transform(&transform(&x))` reproduces
+/// `x`, a lossy transformer, for example a PCA with fewer components than features, returns the
+/// closest reconstruction.
+pub trait InverseTransformer<R, T>: Transformer<R, T> {
+    fn inverse_transform(&self, x: &T) -> R;
+}
@@ -0,0 +1,171 @@
+//! Regression on transformed targets
+//!
+//! Skewed targets, for example prices or counts, are often better modelled in a transformed
+//! space. The targets are mapped with a function before fitting a base regressor and its
+//! predictions are mapped back with the inverse function.
+use ndarray::{Array1, Array2, NdFloat};
+
+use crate::dataset::Dataset;
+use crate::error::{Error, Result};
+use crate::traits::{Fit, Predict};
+
+/// A transformation of the targets as a function pointer
+type TargetFn<A> = fn(A) -> A;
+
+/// Meta-estimator fitting a base regressor on transformed targets
+///
+/// The targets are mapped with `func` before fitting and the predictions are mapped back with
+/// `inverse_func`, for example
+/// ```rust, ignore
+/// let model = TransformedTargetRegressor::new(LinearRegression::new(), f64::ln, f64::exp)
+///     .fit(&dataset)?;
+/// ```
+/// fits a linear model to the logarithm of the targets.
+pub struct TransformedTargetRegressor<E, T, I> {
+    estimator: E,
+    func: T,
+    inverse_func: I,
+}
+
+impl<E, T, I> TransformedTargetRegressor<E, T, I> {
+    /// Create a new regressor from a base estimator, the target transformation and its inverse
+    pub fn new(estimator: E, func: T, inverse_func: I) -> TransformedTargetRegressor<E, T, I> {
+        TransformedTargetRegressor {
+            estimator,
+            func,
+            inverse_func,
+        }
+    }
+}
+
+impl<E> TransformedTargetRegressor<E, (), ()> {
+    /// Create a new regressor fitting `estimator` to `ln(1 + y)`
+    ///
+    /// This is a common choice for non-negative targets spanning several orders of magnitude.
+    pub fn log1p<A: NdFloat>(
+        estimator: E,
+    ) -> TransformedTargetRegressor<E, TargetFn<A>, TargetFn<A>> {
+        TransformedTargetRegressor::new(estimator, |y: A| y.ln_1p(), |y: A| y.exp_m1())
+    }
+}
+
+/// A fitted regressor on transformed targets
+pub struct FittedTransformedTargetRegressor<M, I> {
+    model: M,
+    inverse_func: I,
+}
+
+impl<M, I> FittedTransformedTargetRegressor<M, I> {
+    /// Return the base model, which predicts the transformed targets
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+}
+
+impl<A, E, T, I> Fit<Array2<A>, Array1<A>> for TransformedTargetRegressor<E, T, I>
+where
+    A: NdFloat,
+    E: Fit<Array2<A>, Array1<A>>,
+    T: Fn(A) -> A,
+    I: Fn(A) -> A + Clone,
+{
+    type Object = FittedTransformedTargetRegressor<E::Object, I>;
+
+    fn fit(&self, dataset: &Dataset<Array2<A>, Array1<A>>) -> Result<Self::Object> {
+        let targets = dataset.targets().mapv(&self.func);
+        if let Some(idx) = targets.iter().position(|y| !y.is_finite()) {
+            return Err(Error::Parameters(format!(
+                "the target of sample {} is not finite after the transformation",
+                idx
+            )));
+        }
+
+        // the weights and feature names are kept for the base estimator
+        let transformed = Dataset {
+            records: dataset.records().clone(),
+            targets,
+            weights: dataset.weights.clone(),
+            feature_names: dataset.feature_names.clone(),
+            target_names: Vec::new(),
+        };
+
+        Ok(FittedTransformedTargetRegressor {
+            model: self.estimator.fit(&transformed)?,
+            inverse_func: self.inverse_func.clone(),
+        })
+    }
+}
+
+impl<A, M, I> Predict<Array2<A>, Array1<A>> for FittedTransformedTargetRegressor<M, I>
+where
+    A: NdFloat,
+    M: Predict<Array2<A>, Array1<A>>,
+    I: Fn(A) -> A,
+{
+    fn predict(&self, x: &Array2<A>) -> Array1<A> {
+        self.model.predict(x).mapv(&self.inverse_func)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TransformedTargetRegressor;
+    use crate::error::Error;
+    use crate::metrics::Regression;
+    use crate::traits::{Fit, Predict};
+    use crate::Dataset;
+    use linfa_linear::LinearRegression;
+    use ndarray::{array, s, Array1, Array2};
+    use ndarray_rand::rand::SeedableRng;
+    use ndarray_rand::rand_distr::{Normal, Uniform};
+    use ndarray_rand::RandomExt;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn log_transform_fits_log_normal_targets() {
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array2::random_using((200, 1), Uniform::new(0., 2.), &mut rng);
+        let noise = Array1::random_using(200, Normal::new(0., 0.1).unwrap(), &mut rng);
+        let targets = (records.column(0).mapv(|x| 2. * x) + noise).mapv(f64::exp);
+        let (train, test) = (
+            Dataset::new(
+                records.slice(s![..150, ..]).to_owned(),
+                targets.slice(s![..150]).to_owned(),
+            ),
+            Dataset::new(
+                records.slice(s![150.., ..]).to_owned(),
+                targets.slice(s![150..]).to_owned(),
+            ),
+        );
+
+        let direct = Fit::fit(&LinearRegression::new(), &train).unwrap();
+        let log = TransformedTargetRegressor::new(LinearRegression::new(), f64::ln, f64::exp)
+            .fit(&train)
+            .unwrap();
+
+        let rmse = |prediction: Array1<f64>| test.targets().mean_squared_error(&prediction).sqrt();
+        let (direct, log) = (
+            rmse(direct.predict(test.records())),
+            rmse(log.predict(test.records())),
+        );
+        assert!(log < direct / 2.);
+
+        // the shifted logarithm is close to the logarithm for large targets
+        let log1p = TransformedTargetRegressor::log1p(LinearRegression::new())
+            .fit(&train)
+            .unwrap();
+        assert!(rmse(log1p.predict(test.records())) < direct);
+    }
+
+    #[test]
+    fn rejects_non_finite_transformed_targets() {
+        let dataset = Dataset::new(array![[0.], [1.]], array![1., -1.]);
+        let model = TransformedTargetRegressor::new(LinearRegression::new(), f64::ln, f64::exp)
+            .fit(&dataset);
+
+        match model {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("the logarithm of a negative target must be rejected"),
+        }
+    }
+}
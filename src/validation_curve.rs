@@ -0,0 +1,186 @@
+//! Validation curves
+//!
+//! A validation curve shows the training and validation score of a model as a function of a
+//! single hyperparameter. Where both scores are low the model underfits, where the training
+//! score keeps improving while the validation score drops it overfits.
+use ndarray::{Array1, Array2, Axis};
+use rand::Rng;
+
+use crate::dataset::Dataset;
+use crate::error::Result;
+use crate::learning_curve::{check_folds, stratified_folds};
+use crate::traits::{Fit, Predict};
+
+/// Training and validation scores of a validation curve
+///
+/// The scores are stored with shape `(n_values, n_folds)`, one row per parameter value.
+pub struct ValidationCurve {
+    train_scores: Array2<f64>,
+    validation_scores: Array2<f64>,
+}
+
+impl ValidationCurve {
+    /// Return the training scores with shape `(n_values, n_folds)`
+    pub fn train_scores(&self) -> &Array2<f64> {
+        &self.train_scores
+    }
+
+    /// Return the validation scores with shape `(n_values, n_folds)`
+    pub fn validation_scores(&self) -> &Array2<f64> {
+        &self.validation_scores
+    }
+
+    /// Return the mean training score of each parameter value over the folds
+    pub fn train_scores_mean(&self) -> Array1<f64> {
+        self.train_scores.mean_axis(Axis(1)).unwrap()
+    }
+
+    /// Return the standard deviation of the training score of each parameter value over the
+    /// folds
+    pub fn train_scores_std(&self) -> Array1<f64> {
+        self.train_scores.std_axis(Axis(1), 0.)
+    }
+
+    /// Return the mean validation score of each parameter value over the folds
+    pub fn validation_scores_mean(&self) -> Array1<f64> {
+        self.validation_scores.mean_axis(Axis(1)).unwrap()
+    }
+
+    /// Return the standard deviation of the validation score of each parameter value over the
+    /// folds
+    pub fn validation_scores_std(&self) -> Array1<f64> {
+        self.validation_scores.std_axis(Axis(1), 0.)
+    }
+}
+
+/// Compute a validation curve over the values of a single hyperparameter
+///
+/// The dataset is shuffled with `rng` and split into `cv_folds` stratified folds, the same folds
+/// as in [`learning_curve`](fn.learning_curve.html) for an equally seeded generator. For every fold and every value in `param_values`
+/// the estimator returned by `estimator_factory(value)` is fitted on the remaining folds and
+/// scored on them and on the held-out fold with `scorer(prediction, ground_truth)`, for example
+/// ```rust, ignore
+/// let curve = validation_curve(
+///     |alpha| RidgeRegression::new().alpha(*alpha),
+///     &dataset,
+///     &[0.01, 0.1, 1.0, 10.0],
+///     5,
+///     |prediction, truth| truth.r2(prediction),
+///     &mut Isaac64Rng::seed_from_u64(42),
+/// )?;
+/// ```
+pub fn validation_curve<A, L, P, E, F, S, R>(
+    estimator_factory: F,
+    dataset: &Dataset<Array2<A>, Array1<L>>,
+    param_values: &[P],
+    cv_folds: usize,
+    scorer: S,
+    rng: &mut R,
+) -> Result<ValidationCurve>
+where
+    A: Copy,
+    L: Clone + PartialOrd,
+    F: Fn(&P) -> E,
+    E: Fit<Array2<A>, Array1<L>>,
+    E::Object: Predict<Array2<A>, Array1<L>>,
+    S: Fn(&Array1<L>, &Array1<L>) -> f64,
+    R: Rng,
+{
+    check_folds(dataset, cv_folds)?;
+
+    let folds = stratified_folds(dataset.targets(), cv_folds, rng);
+
+    let mut train_scores = Array2::zeros((param_values.len(), cv_folds));
+    let mut validation_scores = Array2::zeros((param_values.len(), cv_folds));
+    for (fold, (train, validation)) in folds.into_iter().enumerate() {
+        let train_set = dataset.select_samples(&train)?;
+        let validation_set = dataset.select_samples(&validation)?;
+
+        for (value_idx, value) in param_values.iter().enumerate() {
+            let model = estimator_factory(value).fit(&train_set)?;
+
+            train_scores[(value_idx, fold)] =
+                scorer(&model.predict(train_set.records()), train_set.targets());
+            validation_scores[(value_idx, fold)] = scorer(
+                &model.predict(validation_set.records()),
+                validation_set.targets(),
+            );
+        }
+    }
+
+    Ok(ValidationCurve {
+        train_scores,
+        validation_scores,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::metrics::Regression;
+    use linfa_linear::RidgeRegression;
+    use ndarray::array;
+    use ndarray_rand::rand_distr::Normal;
+    use ndarray_rand::RandomExt;
+    use rand::SeedableRng;
+    use rand_isaac::Isaac64Rng;
+
+    #[test]
+    fn ridge_overfits_without_penalty() {
+        // few samples and many irrelevant features, the unpenalized model overfits
+        let mut rng = Isaac64Rng::seed_from_u64(42);
+        let records = Array2::random_using((30, 20), Normal::new(0., 1.).unwrap(), &mut rng);
+        let noise = Array1::random_using(30, Normal::new(0., 1.).unwrap(), &mut rng);
+        let targets =
+            records.column(0).mapv(|x| 3. * x) - records.column(1).mapv(|x| 2. * x) + noise;
+        let dataset = Dataset::new(records, targets);
+
+        let alphas = [1e-6, 1e-2, 1., 10., 1e6];
+        let curve = validation_curve(
+            |alpha| RidgeRegression::new().alpha(*alpha),
+            &dataset,
+            &alphas,
+            5,
+            |prediction: &Array1<f64>, truth: &Array1<f64>| truth.r2(prediction),
+            &mut Isaac64Rng::seed_from_u64(42),
+        )
+        .unwrap();
+
+        assert_eq!(curve.train_scores().dim(), (5, 5));
+        // a larger penalty can only reduce the fit on the training data
+        let train = curve.train_scores_mean();
+        assert!(train.as_slice().unwrap().windows(2).all(|w| w[0] >= w[1]));
+        assert!(train[0] > 0.95);
+
+        let validation = curve.validation_scores_mean();
+        let best = (0..alphas.len()).fold(0, |best, i| {
+            if validation[i] > validation[best] {
+                i
+            } else {
+                best
+            }
+        });
+        assert!(best > 0 && best < alphas.len() - 1);
+        assert!(validation[best] > 0.7);
+        assert!(validation[alphas.len() - 1] < 0.1);
+    }
+
+    #[test]
+    fn rejects_too_many_folds() {
+        let dataset = Dataset::new(array![[0.], [1.], [2.]], array![0., 1., 2.]);
+        let curve = validation_curve(
+            |alpha| RidgeRegression::new().alpha(*alpha),
+            &dataset,
+            &[1.0],
+            4,
+            |prediction: &Array1<f64>, truth: &Array1<f64>| truth.r2(prediction),
+            &mut Isaac64Rng::seed_from_u64(42),
+        );
+
+        match curve {
+            Err(Error::Parameters(_)) => {}
+            _ => panic!("more folds than samples must be rejected"),
+        }
+    }
+}